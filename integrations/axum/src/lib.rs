@@ -1626,6 +1626,11 @@ where
 
         // register server functions first to allow for wildcard router path
         for (path, method) in server_fn::axum::server_fn_paths() {
+            // datagram server functions open WebTransport sessions, which need an
+            // HTTP/3 server rather than this router
+            if method == Method::CONNECT {
+                continue;
+            }
             let cx_with_state = cx_with_state.clone();
             let handler = move |req: Request<Body>| async move {
                 handle_server_fns_with_context(cx_with_state, req).await
@@ -1633,18 +1638,20 @@ where
             router = router.route(
                 path,
                 match method {
-                    Method::GET => get(handler),
-                    Method::POST => post(handler),
-                    Method::PUT => put(handler),
-                    Method::DELETE => delete(handler),
-                    Method::PATCH => patch(handler),
+                    Method::GET => get(handler.clone()),
+                    Method::POST => post(handler.clone()),
+                    Method::PUT => put(handler.clone()),
+                    Method::DELETE => delete(handler.clone()),
+                    Method::PATCH => patch(handler.clone()),
                     _ => {
                         panic!(
                             "Unsupported server function HTTP method: \
                              {method:?}"
                         );
                     }
-                },
+                }
+                // server functions answer `OPTIONS` with their allowed methods
+                .options(handler),
             );
         }
 
@@ -873,6 +873,9 @@ pub fn slot(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 ///    your prefix must begin with `/`. Otherwise your function won't be found.
 /// - `endpoint`: specifies the exact path at which the server function handler will be mounted,
 ///   relative to the prefix (defaults to the function name followed by unique hash)
+/// - `version`: a version, like `"v2"`, added to the path after the prefix. Several versions of
+///   a function can share an `endpoint`, and a request for the path without the version is sent
+///   to the one named by its `Accept-Version` header
 /// - `input`: the encoding for the arguments (defaults to `PostUrl`)
 /// - `output`: the encoding for the response (defaults to `Json`)
 /// - `client`: a custom `Client` implementation that will be used for this server fn
@@ -882,6 +885,10 @@ pub fn slot(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 ///     - `"GetUrl"`: `GET` request with URL-encoded arguments and JSON response
 ///     - `"Cbor"`: `POST` request with CBOR-encoded arguments and response
 ///     - `"GetCbor"`: `GET` request with URL-encoded arguments and CBOR response
+///     - `"Flatbuffers"`: `POST` request with FlatBuffers arguments and response (requires
+///       the `flatbuffers` feature)
+///     - `"Protobuf"`: `POST` request with a single Protocol Buffers message as the argument,
+///       and a message as the response (requires the `protobuf` feature)
 /// - `req` and `res` specify the HTTP request and response types to be used on the server (these
 ///   should usually only be necessary if you are integrating with a server other than Actix/Axum)
 /// - `impl_from`: specifies whether to implement trait `From` for server function's type or not.
@@ -889,6 +896,9 @@ pub fn slot(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 ///   to convert from the argument type to the server function type, and vice versa, allowing you to convert
 ///   between them easily. Setting `impl_from` to `false` disables this, which can be necessary for argument types
 ///   for which this would create a conflicting implementation. (defaults to `true`)
+/// - `middleware`: a list of [`Layer`](../server_fn/middleware/trait.Layer.html)s that wrap this server
+///   function only, like `middleware = [Auth::bearer(validate), Timeout::new(duration)]`. They are
+///   applied in the order listed, before any `#[middleware]` attributes on the function.
 ///
 /// ```rust,ignore
 /// #[server(
@@ -75,7 +75,7 @@ cfg_if! {
         #[test]
         fn server_encoding() {
             #[server(encoding = "GetJson")]
-            pub async fn my_server_action() -> Result<(), ServerFnError> {
+            pub async fn my_server_action(id: u32) -> Result<(), ServerFnError> {
                 Ok(())
             }
             assert_eq!(
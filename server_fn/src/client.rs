@@ -1,8 +1,21 @@
 use crate::{error::ServerFnError, request::ClientReq, response::ClientRes};
-use std::{future::Future, sync::OnceLock};
+use futures::future;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use std::{
+    future::Future,
+    sync::{OnceLock, RwLock},
+    time::Duration,
+};
 
 static ROOT_URL: OnceLock<&'static str> = OnceLock::new();
 
+static CLIENT_CONFIG: RwLock<ClientConfig> = RwLock::new(ClientConfig::new());
+
+/// A function that adds headers to every request sent by the built-in clients.
+pub type HeadersHook = Box<dyn Fn(&mut HeaderMap) + Send + Sync>;
+
+static HEADERS_HOOK: OnceLock<HeadersHook> = OnceLock::new();
+
 /// Set the root server URL that all server function paths are relative to for the client.
 ///
 /// If this is not set, it defaults to the origin.
@@ -15,6 +28,375 @@ pub fn get_server_url() -> &'static str {
     ROOT_URL.get().copied().unwrap_or("")
 }
 
+/// The URL that a request to the server function at `path` is sent to.
+///
+/// This is `path` appended to the [`ClientConfig::with_base_url`] URL if one is
+/// set, or to the [`set_server_url`] root otherwise.
+pub fn server_fn_url(path: &str) -> String {
+    let config = CLIENT_CONFIG.read().unwrap_or_else(|e| e.into_inner());
+    // joined while the lock is held, since the base URL borrows from it
+    let base = config.base_url().unwrap_or(get_server_url());
+    join_url(base, path)
+}
+
+/// Joins a base URL and a path with exactly one `/` between them.
+fn join_url(base: &str, path: &str) -> String {
+    if base.is_empty() {
+        return path.to_string();
+    }
+    let base = base.trim_end_matches('/');
+    let path = path.trim_start_matches('/');
+    format!("{base}/{path}")
+}
+
+/// Options that apply to every server function call made by the built-in clients.
+///
+/// ```rust,ignore
+/// set_client_config(
+///     ClientConfig::new()
+///         .with_base_url("https://api.example.com")
+///         .with_timeout(Duration::from_secs(10))
+///         .with_retry(RetryPolicy::new(3)),
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    base_url: Option<String>,
+    credentials: bool,
+    timeout: Option<Duration>,
+    retry: Option<RetryPolicy>,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    max_url_length: Option<usize>,
+}
+
+impl ClientConfig {
+    /// Creates the default configuration, which sends requests to the
+    /// [`set_server_url`] root, has no timeout, never retries, and adds no headers.
+    pub const fn new() -> Self {
+        Self {
+            base_url: None,
+            credentials: false,
+            timeout: None,
+            retry: None,
+            headers: Vec::new(),
+            max_url_length: None,
+        }
+    }
+
+    /// Sends calls to the server functions hosted at `url`, such as
+    /// `https://api.example.com`, rather than to the [`set_server_url`] root.
+    ///
+    /// A browser only lets a page call a server on another origin if the server
+    /// allows it, e.g. with the `Cors` middleware.
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = Some(url.into());
+        self
+    }
+
+    /// The URL set with [`ClientConfig::with_base_url`], if any.
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_deref()
+    }
+
+    /// Makes the browser send cookies with calls to a server on another origin,
+    /// which the server has to allow with `Access-Control-Allow-Credentials`.
+    ///
+    /// This has no effect on other clients.
+    pub fn with_credentials(mut self, include: bool) -> Self {
+        self.credentials = include;
+        self
+    }
+
+    /// Whether cookies are sent with cross-origin calls from the browser.
+    pub fn credentials(&self) -> bool {
+        self.credentials
+    }
+
+    /// Fails calls that have not completed within `timeout` with
+    /// `ServerFnError::Request("timeout")`, cancelling the underlying request.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The timeout set with [`ClientConfig::with_timeout`], if any.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Retries calls that fail with `ServerFnError::Request` according to `policy`.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// The policy set with [`ClientConfig::with_retry`], if any.
+    pub fn retry(&self) -> Option<&RetryPolicy> {
+        self.retry.as_ref()
+    }
+
+    /// Adds a header with a fixed value to every request.
+    ///
+    /// For values that change between calls, use [`set_headers_hook`].
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+
+    /// The headers added with [`ClientConfig::with_header`].
+    pub fn headers(&self) -> &[(HeaderName, HeaderValue)] {
+        &self.headers
+    }
+
+    /// Sends the arguments of a [`GetUrl`](crate::codec::GetUrl) server function
+    /// in the body of a `POST` request instead, whenever the URL with them in its
+    /// query string would be longer than `len`.
+    ///
+    /// This keeps calls with large arguments below the URL limits of servers and
+    /// proxies. The server accepts these requests like a `GET` request, but they
+    /// are not cached by the browser.
+    pub fn with_max_url_length(mut self, len: usize) -> Self {
+        self.max_url_length = Some(len);
+        self
+    }
+
+    /// The length set with [`ClientConfig::with_max_url_length`], if any.
+    pub fn max_url_length(&self) -> Option<usize> {
+        self.max_url_length
+    }
+}
+
+/// Describes when and how often a failed call is sent again.
+///
+/// Only calls that fail with `ServerFnError::Request`, i.e., before any response was
+/// received, are retried, so a streaming response is never restarted once it has
+/// begun. By default, only idempotent requests (such as those made with `GetUrl`)
+/// are retried.
+///
+/// The delay before each retry doubles, starting at the base delay and capped at
+/// the maximum delay, and is randomized by up to half its length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    /// Retries up to `max_retries` times, starting with a 100ms delay that grows to
+    /// at most 10s.
+    pub const fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            non_idempotent: false,
+        }
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn with_base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Sets the longest delay between two attempts.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Also retries non-idempotent requests, such as `POST`s.
+    ///
+    /// Only enable this if every server function can safely run more than once.
+    pub fn retry_non_idempotent(mut self, retry: bool) -> Self {
+        self.non_idempotent = retry;
+        self
+    }
+
+    /// The largest number of times a call is sent again.
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Whether a request with the given idempotency may be retried at all.
+    pub fn applies_to(&self, idempotent: bool) -> bool {
+        idempotent || self.non_idempotent
+    }
+
+    /// The delay before retry number `retry`, counting from zero.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        delay.mul_f64(1.0 - jitter() / 2.0)
+    }
+}
+
+/// A random number in `[0, 1)`.
+fn jitter() -> f64 {
+    #[cfg(feature = "browser")]
+    {
+        js_sys::Math::random()
+    }
+    #[cfg(not(feature = "browser"))]
+    {
+        use std::{
+            collections::hash_map::RandomState,
+            hash::{BuildHasher, Hasher},
+        };
+
+        let bits = RandomState::new().build_hasher().finish() >> 11;
+        bits as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Sets the options used by the built-in clients for all later server function calls.
+pub fn set_client_config(config: ClientConfig) {
+    *CLIENT_CONFIG.write().unwrap_or_else(|e| e.into_inner()) = config;
+}
+
+/// Returns the options used by the built-in clients.
+pub fn get_client_config() -> ClientConfig {
+    CLIENT_CONFIG
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Sets a function that is called before every request is sent, and can add
+/// headers such as `Authorization` to it. Returns `Err(_)` if the hook has already
+/// been set.
+///
+/// Headers set here replace those with the same name from
+/// [`ClientConfig::with_header`].
+pub fn set_headers_hook(
+    hook: impl Fn(&mut HeaderMap) + Send + Sync + 'static,
+) -> Result<(), HeadersHook> {
+    HEADERS_HOOK.set(Box::new(hook))
+}
+
+/// The headers to add to the next request, from both the [`ClientConfig`] and the
+/// [`HeadersHook`].
+pub fn extra_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in get_client_config().headers() {
+        headers.append(name.clone(), value.clone());
+    }
+    if let Some(hook) = HEADERS_HOOK.get() {
+        hook(&mut headers);
+    }
+    headers
+}
+
+/// The error returned when a call does not complete within the configured timeout.
+#[cfg(any(feature = "browser", feature = "reqwest"))]
+fn timeout_error<CustErr>() -> ServerFnError<CustErr> {
+    ServerFnError::Request("timeout".to_string())
+}
+
+/// Wraps a server function call so that it can be cancelled with the returned
+/// [`AbortHandle`], e.g. when the component that started it is unmounted.
+///
+/// Once aborted, the call resolves to `ServerFnError::Request("aborted")` and the
+/// future making the request is dropped, which cancels the request: the browser
+/// client aborts the `fetch`, and the `reqwest` client closes the connection.
+/// Dropping the call without aborting it cancels the request in the same way.
+///
+/// ```rust,ignore
+/// let (orders, handle) = abortable(list_orders());
+/// on_cleanup(move || handle.abort());
+/// spawn_local(async move {
+///     match orders.await {
+///         Ok(orders) => set_orders(orders),
+///         Err(e) if is_aborted(&e) => {}
+///         Err(e) => show_error(e),
+///     }
+/// });
+/// ```
+pub fn abortable<T, CustErr>(
+    call: impl Future<Output = Result<T, ServerFnError<CustErr>>>,
+) -> (
+    impl Future<Output = Result<T, ServerFnError<CustErr>>>,
+    AbortHandle,
+) {
+    let (call, handle) = future::abortable(call);
+    let call =
+        async move { call.await.unwrap_or_else(|_| Err(aborted_error())) };
+    (call, AbortHandle(handle))
+}
+
+/// Cancels a server function call wrapped with [`abortable`].
+///
+/// The handle can be cloned, and aborting a call that has already completed has no
+/// effect.
+#[derive(Debug, Clone)]
+pub struct AbortHandle(future::AbortHandle);
+
+impl AbortHandle {
+    /// Aborts the call, if it is still running.
+    pub fn abort(&self) {
+        self.0.abort();
+    }
+
+    /// Whether [`AbortHandle::abort`] has been called.
+    pub fn is_aborted(&self) -> bool {
+        self.0.is_aborted()
+    }
+}
+
+/// The error returned by a call that was cancelled with an [`AbortHandle`].
+fn aborted_error<CustErr>() -> ServerFnError<CustErr> {
+    ServerFnError::Request("aborted".to_string())
+}
+
+/// Whether `err` is the error of a call that was cancelled with an
+/// [`AbortHandle`].
+pub fn is_aborted<CustErr>(err: &ServerFnError<CustErr>) -> bool {
+    matches!(err, ServerFnError::Request(msg) if msg == "aborted")
+}
+
+/// Sends `req`, sending a copy of it again according to the configured
+/// [`RetryPolicy`] for as long as `send` fails with `ServerFnError::Request`.
+///
+/// A request that `try_clone` cannot copy, e.g. one with a streaming body, is
+/// only sent once.
+#[cfg(any(feature = "browser", feature = "reqwest"))]
+async fn send_with_retry<Req, Res, CustErr, SendFut, SleepFut>(
+    mut req: Req,
+    idempotent: bool,
+    try_clone: impl Fn(&Req) -> Option<Req>,
+    send: impl Fn(Req) -> SendFut,
+    sleep: impl Fn(Duration) -> SleepFut,
+) -> Result<Res, ServerFnError<CustErr>>
+where
+    SendFut: Future<Output = Result<Res, ServerFnError<CustErr>>>,
+    SleepFut: Future<Output = ()>,
+{
+    let policy = match get_client_config().retry() {
+        Some(policy) if policy.applies_to(idempotent) => policy.clone(),
+        _ => return send(req).await,
+    };
+    let mut retry = 0;
+    loop {
+        let next = if retry < policy.max_retries() {
+            try_clone(&req)
+        } else {
+            None
+        };
+        // the failed response is dropped before waiting, since the custom error
+        // type it may hold isn't necessarily `Send`
+        req = match (send(req).await, next) {
+            (Err(ServerFnError::Request(_)), Some(next)) => next,
+            (res, _) => return res,
+        };
+        sleep(policy.backoff(retry)).await;
+        retry += 1;
+    }
+}
+
 /// A client defines a pair of request/response types and the logic to send
 /// and receive them.
 ///
@@ -36,13 +418,87 @@ pub trait Client<CustErr> {
 #[cfg(feature = "browser")]
 /// Implements [`Client`] for a `fetch` request in the browser.
 pub mod browser {
-    use super::Client;
+    use super::{
+        extra_headers, get_client_config, send_with_retry, timeout_error,
+        Client,
+    };
     use crate::{
-        error::ServerFnError, request::browser::BrowserRequest,
-        response::browser::BrowserResponse,
+        error::ServerFnError,
+        request::browser::{BrowserRequest, Request},
+        response::browser::{BrowserResponse, Response},
     };
+    use futures::future::{self, Either};
+    use gloo_timers::future::sleep;
+    use http::Method;
     use send_wrapper::SendWrapper;
     use std::future::Future;
+    use wasm_bindgen::JsValue;
+    use web_sys::{AbortController, RequestCredentials, RequestInit};
+
+    /// Copies the request, letting it send cookies to other origins.
+    fn with_credentials(
+        req: &web_sys::Request,
+    ) -> Result<web_sys::Request, JsValue> {
+        let mut init = RequestInit::new();
+        init.credentials(RequestCredentials::Include);
+        web_sys::Request::new_with_request_and_init(req, &init)
+    }
+
+    /// Copies the request, adding a signal that can abort it.
+    fn with_abort_signal(
+        req: &web_sys::Request,
+    ) -> Result<(Request, AbortController), JsValue> {
+        let controller = AbortController::new()?;
+        let mut init = RequestInit::new();
+        init.signal(Some(&controller.signal()));
+        let req = web_sys::Request::new_with_request_and_init(req, &init)?;
+        Ok((Request::from(req), controller))
+    }
+
+    /// Aborts the `fetch` when dropped, unless it has completed.
+    struct AbortOnDrop(Option<AbortController>);
+
+    impl AbortOnDrop {
+        fn abort(&mut self) {
+            if let Some(controller) = self.0.take() {
+                controller.abort();
+            }
+        }
+
+        fn disarm(&mut self) {
+            self.0 = None;
+        }
+    }
+
+    impl Drop for AbortOnDrop {
+        fn drop(&mut self) {
+            self.abort();
+        }
+    }
+
+    async fn send_once<CustErr>(
+        req: web_sys::Request,
+    ) -> Result<Response, ServerFnError<CustErr>> {
+        // dropping the call, e.g. through an `AbortHandle`, cancels the request
+        let (req, controller) = with_abort_signal(&req)
+            .map_err(|e| ServerFnError::Request(format!("{e:?}")))?;
+        let mut guard = AbortOnDrop(Some(controller));
+        let res = match get_client_config().timeout() {
+            None => req.send().await,
+            Some(timeout) => {
+                let send = Box::pin(req.send());
+                match future::select(send, Box::pin(sleep(timeout))).await {
+                    Either::Left((res, _)) => res,
+                    Either::Right(_) => {
+                        guard.abort();
+                        return Err(timeout_error());
+                    }
+                }
+            }
+        };
+        guard.disarm();
+        res.map_err(|e| ServerFnError::Request(e.to_string()))
+    }
 
     /// Implements [`Client`] for a `fetch` request in the browser.    
     pub struct BrowserClient;
@@ -56,12 +512,34 @@ pub mod browser {
         ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>>
                + Send {
             SendWrapper::new(async move {
-                req.0
-                    .take()
-                    .send()
-                    .await
-                    .map(|res| BrowserResponse(SendWrapper::new(res)))
-                    .map_err(|e| ServerFnError::Request(e.to_string()))
+                let mut req = web_sys::Request::from(req);
+                if get_client_config().credentials() {
+                    req = with_credentials(&req).map_err(|e| {
+                        ServerFnError::Request(format!("{e:?}"))
+                    })?;
+                }
+                let headers = req.headers();
+                for (name, value) in extra_headers().iter() {
+                    let value = value
+                        .to_str()
+                        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+                    headers.set(name.as_str(), value).map_err(|e| {
+                        ServerFnError::Request(format!("{e:?}"))
+                    })?;
+                }
+                let idempotent = Method::from_bytes(req.method().as_bytes())
+                    .map(|method| method.is_idempotent())
+                    .unwrap_or(false);
+                send_with_retry(
+                    req,
+                    idempotent,
+                    // `Request.clone()` in JS, which copies the body as well
+                    |req| web_sys::Request::clone(req).ok(),
+                    send_once,
+                    sleep,
+                )
+                .await
+                .map(BrowserResponse::from)
             })
         }
     }
@@ -70,11 +548,64 @@ pub mod browser {
 #[cfg(feature = "reqwest")]
 /// Implements [`Client`] for a request made by [`reqwest`].
 pub mod reqwest {
-    use super::Client;
-    use crate::{error::ServerFnError, request::reqwest::CLIENT};
-    use futures::TryFutureExt;
-    use reqwest::{Request, Response};
-    use std::future::Future;
+    use super::{
+        extra_headers, get_client_config, send_with_retry, timeout_error,
+        Client,
+    };
+    #[cfg(feature = "debug-wire")]
+    use crate::inspect::Wire;
+    use crate::{
+        error::ServerFnError,
+        request::reqwest::{client, CLIENT},
+    };
+    use reqwest::{
+        header::{HeaderName, HeaderValue},
+        Request, Response,
+    };
+    use std::{future::Future, sync::PoisonError};
+
+    /// Builds and sends all later server function calls with `client`, instead of
+    /// a default [`reqwest::Client`].
+    ///
+    /// This lets the calls share a connection pool with the rest of the
+    /// application, and use its proxy, TLS, and cookie settings.
+    ///
+    /// ```rust,ignore
+    /// set_client(
+    ///     reqwest::Client::builder()
+    ///         .cookie_store(true)
+    ///         .proxy(reqwest::Proxy::all("http://proxy.internal:8080")?)
+    ///         .build()?,
+    /// );
+    /// ```
+    pub fn set_client(client: reqwest::Client) {
+        *CLIENT.write().unwrap_or_else(PoisonError::into_inner) = client;
+    }
+
+    async fn send_once<CustErr>(
+        mut req: Request,
+    ) -> Result<Response, ServerFnError<CustErr>> {
+        if let Some(timeout) = get_client_config().timeout() {
+            *req.timeout_mut() = Some(timeout);
+        }
+        #[cfg(feature = "debug-wire")]
+        if let Some(body) = req.body().and_then(|body| body.as_bytes()) {
+            let content_type = req
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .map(|value| {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                });
+            Wire::client_request(req.url().path(), content_type).log(body);
+        }
+        client().execute(req).await.map_err(|e| {
+            if e.is_timeout() {
+                timeout_error()
+            } else {
+                ServerFnError::Request(e.to_string())
+            }
+        })
+    }
 
     /// Implements [`Client`] for a request made by [`reqwest`].
     pub struct ReqwestClient;
@@ -84,12 +615,26 @@ pub mod reqwest {
         type Response = Response;
 
         fn send(
-            req: Self::Request,
+            mut req: Self::Request,
         ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>>
                + Send {
-            CLIENT
-                .execute(req)
-                .map_err(|e| ServerFnError::Request(e.to_string()))
+            // `reqwest` still uses `http` 0.2, so the headers are converted by hand
+            for (name, value) in extra_headers().iter() {
+                if let (Ok(name), Ok(value)) = (
+                    HeaderName::from_bytes(name.as_str().as_bytes()),
+                    HeaderValue::from_bytes(value.as_bytes()),
+                ) {
+                    req.headers_mut().append(name, value);
+                }
+            }
+            let idempotent = req.method().is_idempotent();
+            send_with_retry(
+                req,
+                idempotent,
+                Request::try_clone,
+                send_once,
+                tokio::time::sleep,
+            )
         }
     }
 }
@@ -0,0 +1,106 @@
+use super::{Encoding, FromRes, IntoRes};
+use crate::{
+    error::ServerFnError,
+    response::{ClientRes, Res},
+};
+use http::Method;
+use std::marker::PhantomData;
+
+/// A `Content-Type` to send in place of the one of an encoding, for use with
+/// [`WithContentType`].
+pub trait MediaType {
+    /// The value of the `Content-Type` header.
+    const CONTENT_TYPE: &'static str;
+}
+
+/// An output encoding that serializes like `E`, but labels the response with the
+/// content type of `M`, like a vendor type that an API gateway requires.
+///
+/// The client asks for the same content type in its `Accept` header, and decodes the
+/// response like `E` would.
+///
+/// ```rust,ignore
+/// pub struct VendorJson;
+///
+/// impl MediaType for VendorJson {
+///     const CONTENT_TYPE: &'static str = "application/vnd.myapp+json";
+/// }
+///
+/// #[server(output = WithContentType<Json, VendorJson>)]
+/// pub async fn list_orders() -> Result<Vec<Order>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+pub struct WithContentType<E, M>(PhantomData<(E, M)>);
+
+impl<E: Encoding, M: MediaType> Encoding for WithContentType<E, M> {
+    const CONTENT_TYPE: &'static str = M::CONTENT_TYPE;
+    const METHOD: Method = E::METHOD;
+}
+
+impl<E, M, T, Response, Err> IntoRes<WithContentType<E, M>, Response, Err> for T
+where
+    M: MediaType,
+    Response: Res<Err>,
+    T: IntoRes<E, Response, Err> + Send,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<Err>> {
+        let mut res = IntoRes::<E, Response, Err>::into_res(self).await?;
+        res.insert_header("content-type", M::CONTENT_TYPE);
+        Ok(res)
+    }
+}
+
+impl<E, M, T, Response, Err> FromRes<WithContentType<E, M>, Response, Err> for T
+where
+    Response: ClientRes<Err> + Send,
+    T: FromRes<E, Response, Err>,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<Err>> {
+        <T as FromRes<E, Response, Err>>::from_res(res).await
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default", feature = "json"))]
+mod tests {
+    use super::{MediaType, WithContentType};
+    use crate::{
+        codec::{FromRes, IntoRes, Json},
+        error::NoCustomError,
+    };
+    use axum::body::Body;
+    use http::{header, Response};
+    use serde::{Deserialize, Serialize};
+
+    struct VendorJson;
+
+    impl MediaType for VendorJson {
+        const CONTENT_TYPE: &'static str = "application/vnd.myapp+json";
+    }
+
+    type Vendor = WithContentType<Json, VendorJson>;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Order {
+        id: u32,
+        total: u64,
+    }
+
+    #[tokio::test]
+    async fn labels_json_with_custom_content_type() {
+        let order = Order { id: 7, total: 1250 };
+        let res: Response<Body> =
+            <Order as IntoRes<Vendor, _, NoCustomError>>::into_res(order)
+                .await
+                .unwrap();
+        assert_eq!(
+            res.headers()[header::CONTENT_TYPE],
+            "application/vnd.myapp+json"
+        );
+
+        let order = <Order as FromRes<Vendor, _, NoCustomError>>::from_res(res)
+            .await
+            .unwrap();
+        assert_eq!(order, Order { id: 7, total: 1250 });
+    }
+}
@@ -0,0 +1,426 @@
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    error::ServerFnError,
+    request::{BrowserMockReq, ClientReq},
+    response::ClientRes,
+};
+use bytes::Bytes;
+use futures::{channel::oneshot, Future, FutureExt, Sink, Stream};
+use http::Method;
+use std::{
+    any::Any,
+    fmt::Debug,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// An experimental encoding that exchanges unreliable datagrams with a server
+/// function, over a WebTransport session on an HTTP/3 connection.
+///
+/// The server function takes a [`DatagramDuplex`] as its only argument and returns a
+/// [`DatagramResponse`], which accepts the session. Datagrams are raw [`Bytes`]: they
+/// may be lost or arrive out of order, and each one must fit in a single QUIC packet.
+///
+/// ```rust,ignore
+/// #[server(input = Datagram, output = Datagram)]
+/// pub async fn positions(
+///     datagrams: DatagramDuplex,
+/// ) -> Result<DatagramResponse, ServerFnError> {
+///     Ok(datagrams.accept(|mut incoming, mut outgoing| async move {
+///         while let Some(datagram) = incoming.next().await {
+///             outgoing.send(datagram?).await?;
+///         }
+///         Ok(())
+///     }))
+/// }
+/// ```
+///
+/// The session is opened with an extended `CONNECT` request. Only connections that
+/// provide a [`DatagramConnection`] support datagrams; on any other, the server
+/// function returns an error. Calling it through the HTTP client returns an error as
+/// well.
+pub struct Datagram;
+
+impl Encoding for Datagram {
+    const CONTENT_TYPE: &'static str = "application/octet-stream";
+    const METHOD: Method = Method::CONNECT;
+}
+
+/// The datagrams received over a WebTransport session.
+pub type DatagramReceiver =
+    Pin<Box<dyn Stream<Item = Result<Bytes, ServerFnError>> + Send>>;
+
+/// Sends datagrams over a WebTransport session.
+pub type DatagramSender =
+    Pin<Box<dyn Sink<Bytes, Error = ServerFnError> + Send>>;
+
+/// Runs a WebTransport session, once it has been accepted.
+pub type OnDatagrams = Box<
+    dyn FnOnce(
+            DatagramReceiver,
+            DatagramSender,
+        ) -> Pin<Box<dyn Future<Output = ()> + Send>>
+        + Send,
+>;
+
+/// The connection of a request that opens a WebTransport session, if it supports
+/// datagrams.
+///
+/// None of the built-in server integrations speak HTTP/3, so an HTTP/3 server
+/// inserts one into the extensions of each request that opens a session, and runs
+/// the session once the response has been sent:
+///
+/// ```rust,ignore
+/// let (connection, session) = DatagramConnection::new();
+/// req.extensions_mut().insert(connection);
+/// let res = handle_server_fn(req).await;
+/// // ... send the response, then
+/// if let Some(session) = session.await {
+///     session(incoming, outgoing).await;
+/// }
+/// ```
+#[derive(Clone)]
+pub struct DatagramConnection(Arc<Mutex<Option<oneshot::Sender<OnDatagrams>>>>);
+
+impl DatagramConnection {
+    /// Creates a new connection, and a future that resolves to the session once the
+    /// server function has accepted it, or to `None` if it never does.
+    pub fn new() -> (Self, impl Future<Output = Option<OnDatagrams>> + Send) {
+        let (start, session) = oneshot::channel();
+        (
+            Self(Arc::new(Mutex::new(Some(start)))),
+            session.map(Result::ok),
+        )
+    }
+
+    /// Starts the session, which only happens once per connection.
+    fn start(&self, session: OnDatagrams) -> bool {
+        let start =
+            self.0.lock().unwrap_or_else(PoisonError::into_inner).take();
+        start.is_some_and(|start| start.send(session).is_ok())
+    }
+}
+
+impl Debug for DatagramConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatagramConnection").finish_non_exhaustive()
+    }
+}
+
+/// A server request that can open a WebTransport session.
+///
+/// This is the boundary between the [`Datagram`] encoding and a server integration.
+pub trait DatagramReq<CustErr>
+where
+    Self: Sized,
+{
+    /// The response that accepts the session.
+    type Response: Send + 'static;
+
+    /// Checks that the connection supports datagrams, and returns the response that
+    /// accepts the session.
+    ///
+    /// Once the response has been sent, the integration calls `on_datagrams` with
+    /// the datagrams of the session.
+    fn try_into_datagrams(
+        self,
+        on_datagrams: OnDatagrams,
+    ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send;
+}
+
+impl<CustErr> DatagramReq<CustErr> for BrowserMockReq {
+    type Response = ();
+
+    async fn try_into_datagrams(
+        self,
+        _on_datagrams: OnDatagrams,
+    ) -> Result<Self::Response, ServerFnError<CustErr>> {
+        unreachable!()
+    }
+}
+
+/// A WebTransport session that has not been accepted yet.
+///
+/// A server function using the [`Datagram`] encoding receives this as its argument.
+pub struct DatagramDuplex {
+    response: Box<dyn Any + Send>,
+    session: oneshot::Sender<OnDatagrams>,
+}
+
+impl Debug for DatagramDuplex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatagramDuplex").finish_non_exhaustive()
+    }
+}
+
+impl DatagramDuplex {
+    /// Accepts the session, returning the response that completes the `CONNECT`
+    /// request.
+    ///
+    /// Once the response has been sent, `session` is called with a stream of the
+    /// datagrams from the client and a sink for the datagrams to the client. The
+    /// session is closed when it returns. An error also ends the session.
+    pub fn accept<F, Fut>(self, session: F) -> DatagramResponse
+    where
+        F: FnOnce(DatagramReceiver, DatagramSender) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), ServerFnError>> + Send + 'static,
+    {
+        let Self {
+            response,
+            session: start,
+        } = self;
+        let session: OnDatagrams = Box::new(move |receiver, sender| {
+            Box::pin(async move {
+                // the error has nowhere to go, but the session closes either way
+                _ = session(receiver, sender).await;
+            })
+        });
+        // if this fails, the session was never opened
+        _ = start.send(session);
+        DatagramResponse { response }
+    }
+}
+
+/// The response that accepts a WebTransport session, created by
+/// [`DatagramDuplex::accept`].
+pub struct DatagramResponse {
+    response: Box<dyn Any + Send>,
+}
+
+impl Debug for DatagramResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatagramResponse").finish_non_exhaustive()
+    }
+}
+
+fn client_error<CustErr>() -> ServerFnError<CustErr> {
+    ServerFnError::Request(
+        "datagram server functions can't be called with an HTTP client; \
+         connect to them with WebTransport instead"
+            .into(),
+    )
+}
+
+fn unsupported_error<CustErr>() -> ServerFnError<CustErr> {
+    ServerFnError::Request(
+        "datagrams are not supported by this connection".into(),
+    )
+}
+
+impl<T, Request, CustErr> IntoReq<Datagram, Request, CustErr> for T
+where
+    Request: ClientReq<CustErr>,
+    T: Into<DatagramDuplex>,
+{
+    fn into_req(
+        self,
+        _path: &str,
+        _accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        Err(client_error())
+    }
+}
+
+impl<T, Request, CustErr> FromReq<Datagram, Request, CustErr> for T
+where
+    Request: DatagramReq<CustErr> + Send,
+    T: From<DatagramDuplex>,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        let (start, session) = oneshot::channel::<OnDatagrams>();
+        let on_datagrams: OnDatagrams = Box::new(move |receiver, sender| {
+            Box::pin(async move {
+                // the server function may fail instead of accepting the session
+                if let Ok(session) = session.await {
+                    session(receiver, sender).await;
+                }
+            })
+        });
+        let response = req.try_into_datagrams(on_datagrams).await?;
+        Ok(DatagramDuplex {
+            response: Box::new(response),
+            session: start,
+        }
+        .into())
+    }
+}
+
+impl<Response, CustErr> IntoRes<Datagram, Response, CustErr>
+    for DatagramResponse
+where
+    Response: Send + 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        self.response
+            .downcast::<Response>()
+            .map(|response| *response)
+            .map_err(|_| {
+                ServerFnError::Response(
+                    "the session was accepted for a different response type"
+                        .into(),
+                )
+            })
+    }
+}
+
+impl<Response, CustErr> FromRes<Datagram, Response, CustErr>
+    for DatagramResponse
+where
+    Response: ClientRes<CustErr> + Send,
+{
+    async fn from_res(_res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        Err(client_error())
+    }
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{
+        unsupported_error, DatagramConnection, DatagramReq, OnDatagrams,
+    };
+    use crate::error::ServerFnError;
+    use axum::body::Body;
+    use http::{Request, Response};
+
+    impl<CustErr> DatagramReq<CustErr> for Request<Body> {
+        type Response = Response<Body>;
+
+        async fn try_into_datagrams(
+            self,
+            on_datagrams: OnDatagrams,
+        ) -> Result<Self::Response, ServerFnError<CustErr>> {
+            let started = self
+                .extensions()
+                .get::<DatagramConnection>()
+                .is_some_and(|connection| connection.start(on_datagrams));
+            if !started {
+                return Err(unsupported_error());
+            }
+            Ok(Response::new(Body::empty()))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{
+        Datagram, DatagramConnection, DatagramDuplex, DatagramResponse,
+    };
+    use crate::{
+        codec::{FromReq, IntoRes},
+        error::{NoCustomError, ServerFnError},
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use futures::{channel::mpsc, SinkExt, StreamExt};
+    use http::{Method, Request, Response, StatusCode};
+
+    fn connect() -> Request<Body> {
+        Request::builder()
+            .method(Method::CONNECT)
+            .uri("/api/positions")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    /// The body of an echo server function.
+    async fn echo(
+        datagrams: DatagramDuplex,
+    ) -> Result<DatagramResponse, ServerFnError> {
+        Ok(datagrams.accept(|mut incoming, mut outgoing| async move {
+            while let Some(datagram) = incoming.next().await {
+                outgoing.send(datagram?).await?;
+            }
+            Ok(())
+        }))
+    }
+
+    #[tokio::test]
+    async fn echoes_datagrams_over_an_in_memory_transport() {
+        let (connection, session) = DatagramConnection::new();
+        let mut req = connect();
+        req.extensions_mut().insert(connection);
+        let datagrams =
+            <DatagramDuplex as FromReq<Datagram, _, NoCustomError>>::from_req(
+                req,
+            )
+            .await
+            .unwrap();
+        let res = echo(datagrams).await.unwrap();
+        let res: Response<Body> = <DatagramResponse as IntoRes<
+            Datagram,
+            _,
+            NoCustomError,
+        >>::into_res(res)
+        .await
+        .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // the response has been sent, so start exchanging datagrams
+        let (mut client_tx, server_rx) = mpsc::unbounded::<Bytes>();
+        let (server_tx, mut client_rx) = mpsc::unbounded::<Bytes>();
+        let session = session.await.unwrap();
+        let transport = tokio::spawn(session(
+            Box::pin(server_rx.map(Ok)),
+            Box::pin(server_tx.sink_map_err(|e| -> ServerFnError {
+                ServerFnError::Response(e.to_string())
+            })),
+        ));
+
+        for datagram in [&[1, 2, 3][..], &[], &[0xff; 1200]] {
+            client_tx
+                .send(Bytes::copy_from_slice(datagram))
+                .await
+                .unwrap();
+            assert_eq!(client_rx.next().await.as_deref(), Some(datagram));
+        }
+
+        // closing the transport ends the session
+        drop(client_tx);
+        transport.await.unwrap();
+        assert_eq!(client_rx.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn fails_without_datagram_support() {
+        let err =
+            <DatagramDuplex as FromReq<Datagram, _, NoCustomError>>::from_req(
+                connect(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ServerFnError::Request(
+                "datagrams are not supported by this connection".into()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn ends_the_session_if_the_server_function_fails() {
+        let (connection, session) = DatagramConnection::new();
+        let mut req = connect();
+        req.extensions_mut().insert(connection);
+        let datagrams =
+            <DatagramDuplex as FromReq<Datagram, _, NoCustomError>>::from_req(
+                req,
+            )
+            .await
+            .unwrap();
+        // the server function returns an error without accepting the session
+        drop(datagrams);
+
+        let (_client_tx, server_rx) = mpsc::unbounded::<Bytes>();
+        let (server_tx, mut client_rx) = mpsc::unbounded::<Bytes>();
+        let session = session.await.unwrap();
+        session(
+            Box::pin(server_rx.map(Ok)),
+            Box::pin(server_tx.sink_map_err(|e| -> ServerFnError {
+                ServerFnError::Response(e.to_string())
+            })),
+        )
+        .await;
+        assert_eq!(client_rx.next().await, None);
+    }
+}
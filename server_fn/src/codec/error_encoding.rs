@@ -0,0 +1,373 @@
+use super::negotiate::essence;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+use super::{negotiate::quality, request_headers::RequestHeaders};
+use crate::error::{ServerFnError, ServerFnErrorSerde};
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+use bytes::Bytes;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    str::FromStr,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+static GLOBAL_ENCODER: RwLock<Option<Arc<dyn ErrorEncoder>>> =
+    RwLock::new(None);
+static ENCODERS: Lazy<DashMap<String, Arc<dyn ErrorEncoder>>> =
+    Lazy::new(DashMap::new);
+
+/// Controls how a [`ServerFnError`] is written to the body of an error response, and
+/// read back by the client.
+///
+/// By default, errors are encoded as the `Accept` header of the request prefers: as
+/// `{"kind":"ServerError","message":"..."}` in JSON or CBOR, or as `Kind|message` in
+/// plain text for clients that accept neither, or send no `Accept` header. Only the
+/// errors of a running server function are negotiated, while those of its middleware
+/// are always plain text. An encoder is given the same two parts, and can lay them out however it likes, for example as
+/// `{ "error": { "code": "ServerError", "message": "..." } }` for an API that is
+/// also used by other clients.
+///
+/// Encoders are registered for every server function with [`set_error_encoder`], or
+/// for a single one with [`set_error_encoder_for`]. The server and the client must
+/// register the same encoder.
+pub trait ErrorEncoder: Send + Sync + 'static {
+    /// The `Content-Type` of encoded errors.
+    fn content_type(&self) -> &'static str;
+
+    /// Encodes an error, given the name of its [`ServerFnError`] variant and its
+    /// message.
+    fn encode(&self, kind: &str, message: &str) -> String;
+
+    /// Decodes an error encoded by [`encode`](ErrorEncoder::encode), returning its
+    /// kind and message, or `None` if `body` is not a valid encoded error.
+    fn decode(&self, body: &str) -> Option<(String, String)>;
+}
+
+/// Uses `encoder` for the errors of all server functions that don't have an encoder
+/// of their own.
+pub fn set_error_encoder(encoder: impl ErrorEncoder) {
+    *GLOBAL_ENCODER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(encoder));
+}
+
+/// Uses `encoder` for the errors of the server function at `path`, which is usually
+/// its [`ServerFn::PATH`](crate::ServerFn::PATH).
+pub fn set_error_encoder_for(path: &str, encoder: impl ErrorEncoder) {
+    ENCODERS.insert(path.to_string(), Arc::new(encoder));
+}
+
+fn encoder_for(path: &str) -> Option<Arc<dyn ErrorEncoder>> {
+    ENCODERS
+        .get(path)
+        .map(|encoder| Arc::clone(&encoder))
+        .or_else(|| {
+            GLOBAL_ENCODER
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone()
+        })
+}
+
+/// Encodes the error of the server function at `path`, returning the content type,
+/// unless the error is plain text, and the body.
+///
+/// The error is sanitized first, if an [`ErrorSanitizer`](super::ErrorSanitizer)
+/// applies to it.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub(crate) fn encode_error<CustErr>(
+    path: &str,
+    err: &ServerFnError<CustErr>,
+) -> (Option<&'static str>, Bytes)
+where
+    CustErr: FromStr + Display,
+{
+    let sanitized = super::sanitize_error(path, err);
+    #[cfg(feature = "tracing")]
+    err.trace_source(path, sanitized.as_ref().map(|(id, _)| id.as_str()));
+    let err = sanitized.as_ref().map_or(err, |(_, sanitized)| sanitized);
+
+    let serialized = err.ser().unwrap_or_else(|_| err.to_string());
+    let (kind, message) = serialized
+        .split_once('|')
+        .unwrap_or(("ServerError", &serialized));
+    if let Some(encoder) = encoder_for(path) {
+        return (
+            Some(encoder.content_type()),
+            encoder.encode(kind, message).into(),
+        );
+    }
+    let error = ErrorBody {
+        kind: kind.into(),
+        message: message.into(),
+    };
+    let accept = RequestHeaders::with_current(|headers| headers.accept.clone());
+    let accept = accept.as_deref().unwrap_or_default();
+    let json = quality(accept, JSON);
+    let text = quality(accept, "text/plain");
+    #[cfg(feature = "cbor")]
+    if quality(accept, CBOR) > json.max(text) {
+        let mut body = Vec::new();
+        if ciborium::ser::into_writer(&error, &mut body).is_ok() {
+            return (Some(CBOR), body.into());
+        }
+    }
+    if json > 0.0 && json >= text {
+        if let Ok(body) = serde_json::to_vec(&error) {
+            return (Some(JSON), body.into());
+        }
+    }
+    (None, serialized.into())
+}
+
+const JSON: &str = "application/json";
+#[cfg(feature = "cbor")]
+const CBOR: &str = "application/cbor";
+
+/// An error as encoded for a client that accepts JSON or CBOR.
+#[derive(Serialize, Deserialize)]
+struct ErrorBody<'a> {
+    kind: Cow<'a, str>,
+    message: Cow<'a, str>,
+}
+
+/// Decodes the body of an error response from the server function at `path`, with
+/// the given `Content-Type`.
+pub(crate) fn decode_error<CustErr>(
+    path: &str,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> ServerFnError<CustErr>
+where
+    CustErr: FromStr + Display,
+{
+    let text = String::from_utf8_lossy(body);
+    if let Some((kind, message)) =
+        encoder_for(path).and_then(|encoder| encoder.decode(&text))
+    {
+        return ServerFnError::de(&format!("{kind}|{message}"));
+    }
+    let error: Option<ErrorBody> = match content_type.map(essence) {
+        Some(JSON) => serde_json::from_slice(body).ok(),
+        #[cfg(feature = "cbor")]
+        Some(CBOR) => ciborium::de::from_reader(body).ok(),
+        _ => None,
+    };
+    match error {
+        Some(error) => {
+            ServerFnError::de(&format!("{}|{}", error.kind, error.message))
+        }
+        None => ServerFnError::de(&text),
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{decode_error, set_error_encoder_for, ErrorEncoder};
+    use crate::{
+        client::Client,
+        codec::{test_client::ServerOnly, Encoding, IntoReq, Json},
+        error::NoCustomError,
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+
+    /// Encodes errors as `{ "error": { "code", "message" } }`.
+    struct JsonErrors;
+
+    #[derive(Serialize, Deserialize)]
+    struct Envelope {
+        error: ErrorBody,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct ErrorBody {
+        code: String,
+        message: String,
+    }
+
+    impl ErrorEncoder for JsonErrors {
+        fn content_type(&self) -> &'static str {
+            "application/json"
+        }
+
+        fn encode(&self, kind: &str, message: &str) -> String {
+            serde_json::to_string(&Envelope {
+                error: ErrorBody {
+                    code: kind.to_string(),
+                    message: message.to_string(),
+                },
+            })
+            .unwrap()
+        }
+
+        fn decode(&self, body: &str) -> Option<(String, String)> {
+            let envelope: Envelope = serde_json::from_str(body).ok()?;
+            Some((envelope.error.code, envelope.error.message))
+        }
+    }
+
+    /// Sends requests straight to [`Fail`] on the server.
+    struct Loopback;
+
+    impl Client<NoCustomError> for Loopback {
+        type Request = Request<Bytes>;
+        type Response = Response<Body>;
+
+        async fn send(
+            req: Self::Request,
+        ) -> Result<Self::Response, ServerFnError> {
+            Ok(Fail::run_on_server(req.map(Body::from)).await)
+        }
+    }
+
+    /// Always fails with `message`.
+    #[derive(Serialize, Deserialize)]
+    struct Fail {
+        message: String,
+    }
+
+    impl ServerFn for Fail {
+        const PATH: &'static str = "/api/fail_with_json_error";
+
+        type Client = Loopback;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = ();
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<(), ServerFnError> {
+            Err(ServerFnError::ServerError(self.message))
+        }
+    }
+
+    fn request(message: &str) -> Request<Bytes> {
+        <Fail as IntoReq<Json, _, NoCustomError>>::into_req(
+            Fail {
+                message: message.into(),
+            },
+            Fail::PATH,
+            Json::CONTENT_TYPE,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn custom_encoder_round_trips() {
+        set_error_encoder_for(Fail::PATH, JsonErrors);
+
+        let res =
+            Fail::run_on_server(request("no | luck").map(Body::from)).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "application/json");
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(
+            body,
+            r#"{"error":{"code":"ServerError","message":"no | luck"}}"#
+        );
+
+        let err = Fail::run_on_client_with_req(request("no | luck"), None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.without_status(),
+            ServerFnError::ServerError("no | luck".into())
+        );
+    }
+
+    /// Always fails, without an encoder of its own.
+    #[derive(Serialize, Deserialize)]
+    struct FailNegotiated {}
+
+    impl ServerFn for FailNegotiated {
+        const PATH: &'static str = "/api/fail_negotiated";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = ();
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<(), ServerFnError> {
+            Err(ServerFnError::ServerError("no luck".into()))
+        }
+    }
+
+    /// Calls [`FailNegotiated`] with `accept`, returning the content type and body
+    /// of the error.
+    async fn fail_with(accept: Option<&str>) -> (Option<String>, Bytes) {
+        let mut req = Request::post(FailNegotiated::PATH)
+            .header(header::CONTENT_TYPE, Json::CONTENT_TYPE);
+        if let Some(accept) = accept {
+            req = req.header(header::ACCEPT, accept);
+        }
+        let res =
+            FailNegotiated::run_on_server(req.body(Body::from("{}")).unwrap())
+                .await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let content_type = res
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (content_type, body)
+    }
+
+    fn decode(content_type: Option<&str>, body: &[u8]) -> ServerFnError {
+        decode_error::<NoCustomError>(FailNegotiated::PATH, content_type, body)
+    }
+
+    #[cfg(feature = "cbor")]
+    #[tokio::test]
+    async fn cbor_clients_get_cbor_errors() {
+        let (content_type, body) = fail_with(Some("application/cbor")).await;
+        assert_eq!(content_type.as_deref(), Some("application/cbor"));
+        let error: super::ErrorBody =
+            ciborium::de::from_reader(body.as_ref()).unwrap();
+        assert_eq!(error.kind, "ServerError");
+        assert_eq!(error.message, "no luck");
+        assert_eq!(
+            decode(content_type.as_deref(), &body),
+            ServerFnError::ServerError("no luck".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn browsers_get_json_errors() {
+        let (content_type, body) = fail_with(Some(
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+        ))
+        .await;
+        assert_eq!(content_type.as_deref(), Some("application/json"));
+        assert_eq!(body, r#"{"kind":"ServerError","message":"no luck"}"#);
+        assert_eq!(
+            decode(content_type.as_deref(), &body),
+            ServerFnError::ServerError("no luck".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn other_clients_get_plain_text_errors() {
+        for accept in [None, Some("text/plain"), Some("application/x-postcard")]
+        {
+            let (content_type, body) = fail_with(accept).await;
+            assert_eq!(content_type, None);
+            assert_eq!(body, "ServerError|no luck");
+            assert_eq!(
+                decode(None, &body),
+                ServerFnError::ServerError("no luck".into())
+            );
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{error::ServerFnError, middleware::RequestIdValue};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use std::{
+    borrow::Cow,
+    sync::{PoisonError, RwLock},
+};
+
+static GLOBAL_SANITIZER: Lazy<RwLock<ErrorSanitizer>> = Lazy::new(|| {
+    RwLock::new(if cfg!(debug_assertions) {
+        ErrorSanitizer::disabled()
+    } else {
+        ErrorSanitizer::new()
+    })
+});
+static SANITIZERS: Lazy<DashMap<String, ErrorSanitizer>> =
+    Lazy::new(DashMap::new);
+
+/// Hides the details of server errors from clients.
+///
+/// The message of a [`ServerFnError::ServerError`], or of any error converted into a
+/// [`ServerFnError`] with `?`, can contain details like SQL errors or file paths that
+/// should not leave the server. A sanitizer replaces it in the response with a
+/// generic message and a correlation ID, and logs the full error with the same ID
+/// through `tracing`, if that feature is enabled. Errors meant for the client, like
+/// invalid arguments or custom error types, are sent as they are.
+///
+/// The correlation ID is the [`RequestIdValue`] of the request, if a
+/// [`RequestId`](crate::middleware::RequestId) layer gave it one, or a new UUID
+/// otherwise.
+///
+/// Sanitizers are registered for every server function with [`set_error_sanitizer`],
+/// or for a single one with [`set_error_sanitizer_for`]. Unless one is registered,
+/// errors are sanitized in release builds, and sent in full in debug builds.
+///
+/// ```rust,ignore
+/// set_error_sanitizer(ErrorSanitizer::new().message("something went wrong"));
+/// // the client receives
+/// // "something went wrong (correlation ID: 0b5e0c1a-...)"
+/// ```
+#[derive(Debug, Clone)]
+pub struct ErrorSanitizer {
+    message: Option<Cow<'static, str>>,
+}
+
+impl Default for ErrorSanitizer {
+    fn default() -> Self {
+        Self {
+            message: Some("internal server error".into()),
+        }
+    }
+}
+
+impl ErrorSanitizer {
+    /// Creates a sanitizer that replaces server errors with
+    /// `internal server error`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a sanitizer that sends every error in full.
+    pub fn disabled() -> Self {
+        Self { message: None }
+    }
+
+    /// Replaces server errors with `message` instead.
+    pub fn message(mut self, message: impl Into<Cow<'static, str>>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Returns the error to send instead of `err`, and its correlation ID, if `err`
+    /// should be hidden from the client.
+    fn sanitize<CustErr>(
+        &self,
+        err: &ServerFnError<CustErr>,
+    ) -> Option<(String, ServerFnError<CustErr>)> {
+        // received from another server function, and sent on without its status
+        if let ServerFnError::WithStatus { error, .. } = err {
+            return self.sanitize(error);
+        }
+        let message = self.message.as_ref()?;
+        let hidden = matches!(
+            err,
+            ServerFnError::ServerError(_)
+                | ServerFnError::ServerErrorSource { .. }
+        );
+        if !hidden {
+            return None;
+        }
+        let id = RequestIdValue::current()
+            .map(|id| id.as_str().to_owned())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let sanitized = ServerFnError::ServerError(format!(
+            "{message} (correlation ID: {id})"
+        ));
+        Some((id, sanitized))
+    }
+}
+
+/// Uses `sanitizer` for the errors of all server functions that don't have a
+/// sanitizer of their own.
+pub fn set_error_sanitizer(sanitizer: ErrorSanitizer) {
+    *GLOBAL_SANITIZER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = sanitizer;
+}
+
+/// Uses `sanitizer` for the errors of the server function at `path`, which is
+/// usually its [`ServerFn::PATH`](crate::ServerFn::PATH).
+pub fn set_error_sanitizer_for(path: &str, sanitizer: ErrorSanitizer) {
+    SANITIZERS.insert(path.to_string(), sanitizer);
+}
+
+/// Sanitizes the error of the server function at `path`, returning the error to send
+/// and its correlation ID, or `None` if it can be sent as it is.
+pub(crate) fn sanitize_error<CustErr>(
+    path: &str,
+    err: &ServerFnError<CustErr>,
+) -> Option<(String, ServerFnError<CustErr>)> {
+    match SANITIZERS.get(path) {
+        Some(sanitizer) => sanitizer.sanitize(err),
+        None => GLOBAL_SANITIZER
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .sanitize(err),
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{set_error_sanitizer_for, ErrorSanitizer};
+    use crate::{
+        codec::{test_client::ServerOnly, Json},
+        error::NoCustomError,
+        middleware::{service_fn, Layer, RequestId},
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+    use std::io;
+
+    /// A database error whose message should not reach the client.
+    #[derive(Debug, thiserror::Error)]
+    #[error("relation \"users\" does not exist at /var/lib/app/queries.sql:12")]
+    struct DbError;
+
+    /// What the `#[server]` macro generates for
+    /// `async fn find_user(id: u32) -> Result<String, ServerFnError>`.
+    #[derive(Serialize, Deserialize)]
+    struct FindUser {
+        id: u32,
+    }
+
+    impl ServerFn for FindUser {
+        const PATH: &'static str = "/api/find_user";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = String;
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<String, ServerFnError> {
+            Err(ServerFnError::from_source(io::Error::other(DbError)))
+        }
+    }
+
+    fn request(body: &'static str) -> Request<Body> {
+        Request::post(FindUser::PATH)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("x-request-id", "req-42")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    async fn body(res: Response<Body>) -> String {
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn database_errors_become_generic() {
+        set_error_sanitizer_for(FindUser::PATH, ErrorSanitizer::new());
+
+        let res = FindUser::run_on_server(request(r#"{"id":1}"#)).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let first = body(res).await;
+        assert!(
+            first.starts_with(
+                "ServerError|internal server error (correlation ID: "
+            ),
+            "{first}"
+        );
+        assert!(!first.contains("users"));
+
+        // a new ID is generated for each error
+        let other =
+            body(FindUser::run_on_server(request(r#"{"id":1}"#)).await).await;
+        assert_ne!(first, other);
+    }
+
+    #[tokio::test]
+    async fn request_id_is_the_correlation_id() {
+        set_error_sanitizer_for(FindUser::PATH, ErrorSanitizer::new());
+
+        let mut service =
+            RequestId::new().layer(service_fn(FindUser::run_on_server));
+        let res = service.0.run(request(r#"{"id":1}"#)).await;
+        assert_eq!(
+            body(res).await,
+            "ServerError|internal server error (correlation ID: req-42)"
+        );
+    }
+
+    #[tokio::test]
+    async fn client_errors_stay_intact() {
+        set_error_sanitizer_for(FindUser::PATH, ErrorSanitizer::new());
+
+        let res = FindUser::run_on_server(request("{}")).await;
+        assert!(body(res).await.starts_with("Deserialization|"));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn full_error_is_logged_with_correlation_id() {
+        use std::{
+            fmt::Debug,
+            sync::{Arc, Mutex},
+        };
+        use tracing::{
+            field::{Field, Visit},
+            Event, Subscriber,
+        };
+        use tracing_subscriber::{layer::Context, prelude::*, Registry};
+
+        #[derive(Clone, Default)]
+        struct Capture(Arc<Mutex<Vec<String>>>);
+
+        impl Visit for Capture {
+            fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{}={value:?}", field.name()));
+            }
+        }
+
+        impl<S: Subscriber> tracing_subscriber::Layer<S> for Capture {
+            fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+                event.record(&mut self.clone());
+            }
+        }
+
+        set_error_sanitizer_for(
+            "/api/find_user_logged",
+            ErrorSanitizer::new().message("oops"),
+        );
+        let capture = Capture::default();
+        let subscriber = Registry::default().with(capture.clone());
+        let (_, body) = tracing::subscriber::with_default(subscriber, || {
+            let err = ServerFnError::from_source(io::Error::other(DbError));
+            crate::codec::encode_error("/api/find_user_logged", &err)
+        });
+
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let id = body
+            .strip_prefix("ServerError|oops (correlation ID: ")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .unwrap();
+        let fields = capture.0.lock().unwrap();
+        assert!(fields.contains(&format!("correlation_id={id:?}")));
+        assert!(fields
+            .iter()
+            .any(|field| field.contains(r#"relation "users" does not exist"#)));
+    }
+}
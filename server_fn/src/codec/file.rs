@@ -0,0 +1,403 @@
+use super::{
+    range::{ranged_response, slice_stream},
+    ByteStream, Encoding, FromRes, IntoRes, Streaming,
+};
+use crate::{
+    error::ServerFnError,
+    response::{ClientRes, Res},
+};
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use std::{fmt::Write, pin::Pin};
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+use {
+    bytes::BytesMut,
+    std::{io, io::SeekFrom, path::Path},
+    tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt},
+};
+
+/// How many bytes are read from a file at a time.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A file that a server function sends to be downloaded, streamed from disk or any
+/// [`AsyncRead`](tokio::io::AsyncRead) rather than read into memory.
+///
+/// A server function can return this type if its output encoding is [`Streaming`].
+/// The response has a `Content-Disposition: attachment` header, so that browsers
+/// save it under its file name, and a `Content-Type` guessed from the extension of
+/// that name. When the length of the file is known, the response also has a
+/// `Content-Length`, and answers a request for a single byte range with
+/// `206 Partial Content`, so that downloads can be resumed.
+///
+/// Using [`GetUrl`](super::GetUrl) as the input encoding lets a plain link download
+/// the file.
+///
+/// ```rust,ignore
+/// #[server(input = GetUrl, output = Streaming)]
+/// pub async fn download_report(id: u32) -> Result<FileResponse, ServerFnError> {
+///     let file = FileResponse::open(format!("reports/{id}.pdf")).await?;
+///     Ok(file.with_filename(format!("report-{id}.pdf")))
+/// }
+/// ```
+///
+/// On the client, the file can be read with [`FileResponse::into_stream`].
+pub struct FileResponse {
+    content_type: String,
+    filename: Option<String>,
+    content_length: Option<u64>,
+    source: Source,
+}
+
+enum Source {
+    #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+    File(tokio::fs::File),
+    #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+    Reader(Pin<Box<dyn AsyncRead + Send>>),
+    Stream(ByteStream),
+}
+
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+impl FileResponse {
+    /// Opens the file at `path`, named after its last component.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let file = tokio::fs::File::open(path).await?;
+        let len = file.metadata().await?.len();
+        let response = Self {
+            content_type: Streaming::CONTENT_TYPE.to_string(),
+            filename: None,
+            content_length: Some(len),
+            source: Source::File(file),
+        };
+        Ok(match path.file_name() {
+            Some(name) => response.with_filename(name.to_string_lossy()),
+            None => response,
+        })
+    }
+
+    /// Sends the bytes read from `reader`.
+    ///
+    /// Its length is unknown, so byte ranges are only supported if it is set with
+    /// [`FileResponse::with_content_length`].
+    pub fn from_reader(reader: impl AsyncRead + Send + 'static) -> Self {
+        Self {
+            content_type: Streaming::CONTENT_TYPE.to_string(),
+            filename: None,
+            content_length: None,
+            source: Source::Reader(Box::pin(reader)),
+        }
+    }
+}
+
+impl FileResponse {
+    /// Sets the name the file is saved under, and its content type if a known
+    /// extension tells what it is.
+    pub fn with_filename(mut self, filename: impl Into<String>) -> Self {
+        let filename = filename.into();
+        if let Some(content_type) = guess_content_type(&filename) {
+            self.content_type = content_type.to_string();
+        }
+        self.filename = Some(filename);
+        self
+    }
+
+    /// Sets the content type, instead of guessing it from the file name.
+    pub fn with_content_type(
+        mut self,
+        content_type: impl Into<String>,
+    ) -> Self {
+        self.content_type = content_type.into();
+        self
+    }
+
+    /// Sets the length of the file in bytes.
+    pub fn with_content_length(mut self, len: u64) -> Self {
+        self.content_length = Some(len);
+        self
+    }
+
+    /// The MIME type of the file.
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// The name the file is saved under, if it has one.
+    ///
+    /// This is always `None` on the client.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// The length of the file in bytes, if it is known.
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Consumes the response, returning the contents of the file.
+    pub fn into_stream(self) -> ByteStream {
+        let stream = ByteStream::new(self.source.into_chunks(0, None));
+        match self.content_length {
+            Some(len) => stream.with_content_length(len),
+            None => stream,
+        }
+    }
+
+    /// The `Content-Disposition` header, with an ASCII fallback for names that
+    /// need to be percent-encoded.
+    fn disposition(&self) -> String {
+        let Some(filename) = &self.filename else {
+            return "attachment".to_string();
+        };
+        let quoted = filename
+            .chars()
+            .map(|c| match c {
+                '"' | '\\' => '_',
+                c if c.is_ascii() && !c.is_ascii_control() => c,
+                _ => '_',
+            })
+            .collect::<String>();
+        let mut disposition = format!("attachment; filename=\"{quoted}\"");
+        if quoted != *filename {
+            disposition.push_str("; filename*=UTF-8''");
+            for byte in filename.bytes() {
+                if byte.is_ascii_alphanumeric()
+                    || b"!#$&+-.^_`|~".contains(&byte)
+                {
+                    disposition.push(byte as char);
+                } else {
+                    _ = write!(disposition, "%{byte:02X}");
+                }
+            }
+        }
+        disposition
+    }
+}
+
+impl Source {
+    /// The bytes from `start` on, at most `len` of them.
+    fn into_chunks(
+        self,
+        start: u64,
+        len: Option<u64>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Bytes, ServerFnError>> + Send>> {
+        match self {
+            #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+            Source::File(mut file) => Box::pin(read_chunks(
+                async move {
+                    file.seek(SeekFrom::Start(start)).await?;
+                    Ok(file)
+                },
+                len,
+            )),
+            #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+            Source::Reader(mut reader) => Box::pin(read_chunks(
+                async move {
+                    let mut skipped = (&mut reader).take(start);
+                    tokio::io::copy(&mut skipped, &mut tokio::io::sink())
+                        .await?;
+                    Ok(reader)
+                },
+                len,
+            )),
+            Source::Stream(stream) => {
+                Box::pin(slice_stream(stream.into_inner(), start, len))
+            }
+        }
+    }
+}
+
+/// Reads the reader that `open` resolves to in chunks, stopping after `len` bytes.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+fn read_chunks<R>(
+    open: impl std::future::Future<Output = io::Result<R>> + Send + 'static,
+    len: Option<u64>,
+) -> impl Stream<Item = Result<Bytes, ServerFnError>> + Send
+where
+    R: AsyncRead + Send + Unpin + 'static,
+{
+    futures::stream::once(open)
+        .map(move |reader| {
+            let reader =
+                reader.map(|reader| reader.take(len.unwrap_or(u64::MAX)));
+            futures::stream::unfold(Some(reader), |reader| async move {
+                let mut reader = match reader? {
+                    Ok(reader) => reader,
+                    Err(e) => return Some((Err(e.into()), None)),
+                };
+                let mut buf = BytesMut::with_capacity(CHUNK_SIZE);
+                match reader.read_buf(&mut buf).await {
+                    Ok(0) => None,
+                    Ok(_) => Some((Ok(buf.freeze()), Some(Ok(reader)))),
+                    Err(e) => Some((Err(e.into()), None)),
+                }
+            })
+        })
+        .flatten()
+}
+
+/// The content type of a file, from the extension of its name.
+fn guess_content_type(filename: &str) -> Option<&'static str> {
+    let (_, ext) = filename.rsplit_once('.')?;
+    Some(match ext.to_ascii_lowercase().as_str() {
+        "txt" => "text/plain; charset=utf-8",
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "wasm" => "application/wasm",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => return None,
+    })
+}
+
+impl<CustErr, Response> IntoRes<Streaming, Response, CustErr> for FileResponse
+where
+    Response: Res<CustErr>,
+    CustErr: 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        let disposition = self.disposition();
+        let source = self.source;
+        let mut res: Response = ranged_response(
+            &self.content_type,
+            self.content_length,
+            |start, len| {
+                source.into_chunks(start, len).map(|chunk| {
+                    chunk.map_err(|e| ServerFnError::Response(e.to_string()))
+                })
+            },
+        )?;
+        res.insert_header("content-disposition", &disposition);
+        Ok(res)
+    }
+}
+
+impl<CustErr, Response> FromRes<Streaming, Response, CustErr> for FileResponse
+where
+    Response: ClientRes<CustErr> + Send,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let content_type = res
+            .content_type()
+            .unwrap_or_else(|| Streaming::CONTENT_TYPE.to_string());
+        let stream = res.try_into_stream()?;
+        Ok(Self {
+            content_type,
+            filename: None,
+            content_length: None,
+            source: Source::Stream(ByteStream::new(stream)),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default", feature = "url"))]
+mod tests {
+    use super::FileResponse;
+    use crate::{
+        codec::{test_client::ServerOnly, GetUrl, Streaming},
+        error::NoCustomError,
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+
+    const REPORT: &str = "quarterly numbers: up and to the right";
+
+    /// What the `#[server]` macro generates for
+    /// `#[server(input = GetUrl, output = Streaming)] async fn download(name: String)`.
+    #[derive(Serialize, Deserialize)]
+    struct Download {
+        name: String,
+    }
+
+    impl ServerFn for Download {
+        const PATH: &'static str = "/api/download";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = FileResponse;
+        type InputEncoding = GetUrl;
+        type OutputEncoding = Streaming;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<FileResponse, ServerFnError> {
+            let path = std::env::temp_dir().join(self.name);
+            Ok(FileResponse::open(path).await?)
+        }
+    }
+
+    /// Writes the report under a name of its own, and requests it.
+    async fn download(name: &str, range: Option<&str>) -> Response<Body> {
+        tokio::fs::write(std::env::temp_dir().join(name), REPORT)
+            .await
+            .unwrap();
+        let mut req = Request::get(format!("{}?name={name}", Download::PATH));
+        if let Some(range) = range {
+            req = req.header(header::RANGE, range);
+        }
+        Download::run_on_server(req.body(Body::empty()).unwrap()).await
+    }
+
+    async fn body(res: Response<Body>) -> String {
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn downloads_whole_file() {
+        let res = download("server_fn_full_report.txt", None).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let headers = res.headers();
+        assert_eq!(headers[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+        assert_eq!(
+            headers[header::CONTENT_DISPOSITION],
+            r#"attachment; filename="server_fn_full_report.txt""#
+        );
+        assert_eq!(headers[header::CONTENT_LENGTH], REPORT.len().to_string());
+        assert_eq!(headers[header::ACCEPT_RANGES], "bytes");
+        assert_eq!(body(res).await, REPORT);
+    }
+
+    #[tokio::test]
+    async fn downloads_requested_range() {
+        let res =
+            download("server_fn_ranged_report.txt", Some("bytes=19-24")).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        let headers = res.headers();
+        assert_eq!(
+            headers[header::CONTENT_RANGE],
+            format!("bytes 19-24/{}", REPORT.len())
+        );
+        assert_eq!(headers[header::CONTENT_LENGTH], "6");
+        assert_eq!(body(res).await, "up and");
+    }
+
+    #[tokio::test]
+    async fn rejects_range_past_the_end() {
+        let res =
+            download("server_fn_short_report.txt", Some("bytes=1000-")).await;
+        assert_eq!(res.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            res.headers()[header::CONTENT_RANGE],
+            format!("bytes */{}", REPORT.len())
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    error::ServerFnError,
+    request::{ClientReq, Req},
+    response::{ClientRes, Res},
+};
+use bytes::Bytes;
+use flatbuffers::{FlatBufferBuilder, Follow, InvalidFlatbuffer, Verifiable};
+use http::Method;
+
+/// Pass arguments and receive responses as [FlatBuffers](https://flatbuffers.dev) in a
+/// `POST` request.
+///
+/// The argument and the return value are both a [`Flatbuffer`], which holds the raw
+/// buffer. Tables are read from it in place with [`Flatbuffer::root`], using the
+/// accessors generated by `flatc`, so nothing is copied or deserialized up front.
+///
+/// ```rust,ignore
+/// #[server(input = Flatbuffers, output = Flatbuffers)]
+/// pub async fn greet(person: Flatbuffer) -> Result<Flatbuffer, ServerFnError> {
+///     let person = person.root::<Person>()?;
+///
+///     let mut builder = FlatBufferBuilder::new();
+///     let text = builder.create_string(&format!("Hello, {}!", person.name()));
+///     let greeting = Greeting::create(&mut builder, &GreetingArgs { text: Some(text) });
+///     builder.finish(greeting, None);
+///     Ok(Flatbuffer::from_builder(builder))
+/// }
+/// ```
+pub struct Flatbuffers;
+
+impl Encoding for Flatbuffers {
+    const CONTENT_TYPE: &'static str = "application/x-flatbuffers";
+    const METHOD: Method = Method::POST;
+}
+
+/// A finished FlatBuffers buffer, sent or received with the [`Flatbuffers`] encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flatbuffer(Bytes);
+
+impl Flatbuffer {
+    /// Wraps a finished buffer.
+    pub fn new(data: impl Into<Bytes>) -> Self {
+        Self(data.into())
+    }
+
+    /// Takes the finished buffer out of a [`FlatBufferBuilder`], without copying it.
+    ///
+    /// ## Panics
+    /// Panics if [`FlatBufferBuilder::finish`] has not been called yet.
+    pub fn from_builder(builder: FlatBufferBuilder<'_>) -> Self {
+        let (data, head) = builder.collapse();
+        Self(Bytes::from(data).slice(head..))
+    }
+
+    /// Verifies the buffer and returns its root table `T`.
+    ///
+    /// The buffer comes from the other side of the connection, so it is checked every
+    /// time this is called. Read the tables you need through the returned accessor,
+    /// rather than calling this repeatedly.
+    pub fn root<'buf, T>(&'buf self) -> Result<T::Inner, InvalidFlatbuffer>
+    where
+        T: Follow<'buf> + Verifiable + 'buf,
+    {
+        flatbuffers::root::<T>(&self.0)
+    }
+
+    /// The raw bytes of the buffer.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Consumes the wrapper, returning the raw bytes of the buffer.
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
+}
+
+impl<T, Request, Err> IntoReq<Flatbuffers, Request, Err> for T
+where
+    Request: ClientReq<Err>,
+    T: Into<Flatbuffer>,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<Err>> {
+        Request::try_new_post_bytes(
+            path,
+            accepts,
+            Flatbuffers::CONTENT_TYPE,
+            self.into().into_bytes(),
+        )
+    }
+}
+
+impl<T, Request, Err> FromReq<Flatbuffers, Request, Err> for T
+where
+    Request: Req<Err> + Send,
+    T: From<Flatbuffer>,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<Err>> {
+        let data = req.try_into_bytes().await?;
+        Ok(Flatbuffer(data).into())
+    }
+}
+
+impl<Response, Err> IntoRes<Flatbuffers, Response, Err> for Flatbuffer
+where
+    Response: Res<Err>,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<Err>> {
+        Response::try_from_bytes(Flatbuffers::CONTENT_TYPE, self.0)
+    }
+}
+
+impl<Response, Err> FromRes<Flatbuffers, Response, Err> for Flatbuffer
+where
+    Response: ClientRes<Err> + Send,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<Err>> {
+        let data = res.try_into_bytes().await?;
+        Ok(Flatbuffer(data))
+    }
+}
@@ -8,6 +8,10 @@ use crate::{
 use http::Method;
 use serde::{de::DeserializeOwned, Serialize};
 /// Pass arguments and receive responses as JSON in the body of a `POST` request.
+///
+/// Arguments that can't be deserialized are rejected with a
+/// [`ServerFnError::Deserialization`] that names the path of the field at fault, like
+/// `post.tags[2]`, which the server sends with `422 Unprocessable Entity`.
 pub struct Json;
 
 impl Encoding for Json {
@@ -38,8 +42,15 @@ where
 {
     async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
         let string_data = req.try_into_string().await?;
-        serde_json::from_str::<Self>(&string_data)
-            .map_err(|e| ServerFnError::Args(e.to_string()))
+        let de = &mut serde_json::Deserializer::from_str(&string_data);
+        serde_path_to_error::deserialize(de).map_err(|e| {
+            // errors in the arguments themselves, like a missing one, are at the root
+            let message = match e.path().to_string().as_str() {
+                "." => e.inner().to_string(),
+                path => format!("{path}: {}", e.inner()),
+            };
+            ServerFnError::Deserialization(message)
+        })
     }
 }
 
@@ -66,3 +77,78 @@ where
             .map_err(|e| ServerFnError::Deserialization(e.to_string()))
     }
 }
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Json;
+    use crate::{
+        codec::test_client::ServerOnly, error::NoCustomError, ServerFn,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Post {
+        title: String,
+        tags: Vec<String>,
+    }
+
+    /// What the `#[server]` macro generates for
+    /// `async fn create_post(post: Post) -> Result<String, ServerFnError>`.
+    #[derive(Serialize, Deserialize)]
+    struct CreatePost {
+        post: Post,
+    }
+
+    impl ServerFn for CreatePost {
+        const PATH: &'static str = "/api/create_post";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = String;
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<String, ServerFnError> {
+            Ok(self.post.title)
+        }
+    }
+
+    async fn call(body: &'static str) -> (StatusCode, String) {
+        let req = Request::post(CreatePost::PATH)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let res = CreatePost::run_on_server(req).await;
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments_name_the_field() {
+        let (status, body) = call(r#"{"post":{"tags":[]}}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body.starts_with("Deserialization|post: missing field `title`"));
+
+        let (status, body) =
+            call(r#"{"post":{"title":"Hello","tags":["a",2]}}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body.starts_with("Deserialization|post.tags[1]: invalid type"));
+
+        let (status, body) = call("{}").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body.starts_with("Deserialization|missing field `post`"));
+    }
+
+    #[tokio::test]
+    async fn valid_arguments_run_the_function() {
+        let res = call(r#"{"post":{"title":"Hello","tags":["a"]}}"#).await;
+        assert_eq!(res, (StatusCode::OK, r#""Hello""#.to_string()));
+    }
+}
@@ -44,16 +44,76 @@ mod multipart;
 #[cfg(feature = "multipart")]
 pub use multipart::*;
 
+#[cfg(feature = "flatbuffers")]
+mod flatbuffers;
+#[cfg(feature = "flatbuffers")]
+pub use flatbuffers::*;
+
+#[cfg(feature = "json")]
+mod ndjson;
+#[cfg(feature = "json")]
+pub use ndjson::*;
+
 #[cfg(feature = "msgpack")]
 mod msgpack;
 #[cfg(feature = "msgpack")]
 pub use msgpack::*;
 
+#[cfg(feature = "postcard")]
+mod postcard;
+#[cfg(feature = "postcard")]
+pub use postcard::*;
+
+#[cfg(feature = "protobuf")]
+mod protobuf;
+#[cfg(feature = "protobuf")]
+pub use protobuf::*;
+
+mod content_type;
+#[cfg(feature = "datagram")]
+mod datagram;
+mod error_encoding;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod error_sanitizer;
+mod file;
+mod multipart_response;
+mod negotiate;
+mod range;
+mod raw;
+mod request_headers;
+mod sse;
 mod stream;
+#[cfg(all(test, feature = "axum-no-default"))]
+pub(crate) mod test_client;
+#[cfg(feature = "websocket")]
+mod websocket;
 use crate::error::ServerFnError;
+pub use content_type::*;
+#[cfg(feature = "datagram")]
+pub use datagram::*;
+pub(crate) use error_encoding::decode_error;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub(crate) use error_encoding::encode_error;
+pub use error_encoding::{
+    set_error_encoder, set_error_encoder_for, ErrorEncoder,
+};
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub(crate) use error_sanitizer::sanitize_error;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use error_sanitizer::{
+    set_error_sanitizer, set_error_sanitizer_for, ErrorSanitizer,
+};
+pub use file::*;
 use futures::Future;
 use http::Method;
+pub use multipart_response::*;
+pub use negotiate::Negotiate;
+pub use raw::*;
+pub(crate) use request_headers::RequestHeaders;
+pub use sse::*;
 pub use stream::*;
+#[cfg(feature = "websocket")]
+pub use websocket::*;
 
 /// Serializes a data type into an HTTP request, on the client.
 ///
@@ -170,7 +230,7 @@ pub trait IntoRes<Encoding, Response, CustErr> {
 /// data from a response. They are often quite short, usually consisting
 /// of just two steps:
 /// 1. Extracting a [`String`], [`Bytes`](bytes::Bytes), or a [`Stream`](futures::Stream)
-/// from the response body.
+///    from the response body.
 /// 2. Deserializing the data type from that value.
 ///
 /// For example, here’s the implementation for [`Json`].
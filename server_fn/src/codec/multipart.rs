@@ -4,9 +4,15 @@ use crate::{
     request::{browser::BrowserFormData, ClientReq, Req},
     IntoReq,
 };
-use futures::StreamExt;
+use bytes::Bytes;
+use futures::{stream, Stream, StreamExt};
 use http::Method;
-use multer::Multipart;
+use multer::{Field, Multipart};
+use std::{
+    fmt::{self, Debug},
+    pin::Pin,
+    task::{Context, Poll},
+};
 use web_sys::FormData;
 
 /// Encodes multipart form data.
@@ -94,3 +100,179 @@ where
         Ok(MultipartData::Server(data).into())
     }
 }
+
+/// Multipart form data that the server reads one part at a time, as it arrives.
+///
+/// Unlike [`MultipartData`], which hands over the [`multer`] parser itself, this
+/// yields each field as a [`FormPart`], whose data can be streamed in turn. Each part
+/// must be dropped before the next one is read, so that large uploads with many files
+/// never need to be held in memory at once. Since it is read from the body of the
+/// request, this works with both Axum and Actix.
+///
+/// ```rust,ignore
+/// #[server(input = MultipartFormData)]
+/// pub async fn upload(data: MultipartStream) -> Result<usize, ServerFnError> {
+///     let mut parts = data.into_stream();
+///     let mut count = 0;
+///     while let Some(part) = parts.next().await {
+///         let mut part = part?;
+///         let mut file = create_file(part.file_name().unwrap_or("upload")).await?;
+///         while let Some(chunk) = part.next().await {
+///             file.write_all(&chunk?).await?;
+///         }
+///         count += 1;
+///     }
+///     Ok(count)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MultipartStream(MultipartData);
+
+impl MultipartStream {
+    /// Consumes the wrapper, returning a stream of the parts of the form.
+    ///
+    /// On the client side, the stream is always empty.
+    pub fn into_stream(
+        self,
+    ) -> impl Stream<Item = Result<FormPart, ServerFnError>> + Send {
+        stream::unfold(self.0.into_inner(), |multipart| async move {
+            let mut multipart = multipart?;
+            match multipart.next_field().await {
+                Ok(Some(field)) => Some((Ok(FormPart(field)), Some(multipart))),
+                Ok(None) => None,
+                Err(e) => Some((Err(invalid(e)), None)),
+            }
+        })
+    }
+}
+
+impl From<MultipartData> for MultipartStream {
+    fn from(value: MultipartData) -> Self {
+        Self(value)
+    }
+}
+
+impl From<MultipartStream> for MultipartData {
+    fn from(value: MultipartStream) -> Self {
+        value.0
+    }
+}
+
+impl From<FormData> for MultipartStream {
+    fn from(value: FormData) -> Self {
+        Self(value.into())
+    }
+}
+
+/// A single field of a [`MultipartStream`].
+///
+/// This is a [`Stream`] of the chunks of its data, as they arrive.
+pub struct FormPart(Field<'static>);
+
+impl FormPart {
+    /// The name of the field, from its `Content-Disposition` header.
+    pub fn name(&self) -> Option<&str> {
+        self.0.name()
+    }
+
+    /// The name of the uploaded file, if the field is a file.
+    pub fn file_name(&self) -> Option<&str> {
+        self.0.file_name()
+    }
+
+    /// The MIME type of the data, from the `Content-Type` header of the field.
+    pub fn content_type(&self) -> Option<&str> {
+        self.0.content_type().map(|mime| mime.as_ref())
+    }
+
+    /// Reads the rest of the data of the field.
+    pub async fn bytes(self) -> Result<Bytes, ServerFnError> {
+        self.0.bytes().await.map_err(invalid)
+    }
+}
+
+impl Debug for FormPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FormPart")
+            .field("name", &self.name())
+            .field("file_name", &self.file_name())
+            .field("content_type", &self.content_type())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for FormPart {
+    type Item = Result<Bytes, ServerFnError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.0.poll_next_unpin(cx).map_err(invalid)
+    }
+}
+
+fn invalid(err: multer::Error) -> ServerFnError {
+    ServerFnError::Deserialization(err.to_string())
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{MultipartFormData, MultipartStream};
+    use crate::{codec::FromReq, error::NoCustomError};
+    use axum::body::Body;
+    use bytes::Bytes;
+    use futures::{channel::mpsc, FutureExt, StreamExt};
+    use http::{header, Request};
+    use std::convert::Infallible;
+
+    /// A file field, followed by the boundary that ends it.
+    fn part(name: &str, file_name: &str, data: &str) -> String {
+        format!(
+            "\r\ncontent-disposition: form-data; name=\"{name}\"; \
+             filename=\"{file_name}\"\r\ncontent-type: text/plain\r\n\r\n\
+             {data}\r\n--boundary"
+        )
+    }
+
+    #[tokio::test]
+    async fn yields_parts_as_they_arrive() {
+        let (tx, rx) = mpsc::unbounded::<Result<Bytes, Infallible>>();
+        let req = Request::post("/api/upload")
+            .header(
+                header::CONTENT_TYPE,
+                "multipart/form-data; boundary=boundary",
+            )
+            .body(Body::from_stream(rx))
+            .unwrap();
+        let data = <MultipartStream as FromReq<
+            MultipartFormData,
+            _,
+            NoCustomError,
+        >>::from_req(req)
+        .await
+        .unwrap();
+        let mut parts = Box::pin(data.into_stream());
+
+        // only the first file has been sent so far
+        let first = format!("--boundary{}", part("first", "a.txt", "aaaa"));
+        tx.unbounded_send(Ok(first.into())).unwrap();
+        let first = parts.next().await.unwrap().unwrap();
+        assert_eq!(first.name(), Some("first"));
+        assert_eq!(first.file_name(), Some("a.txt"));
+        assert_eq!(first.content_type(), Some("text/plain"));
+        assert_eq!(first.bytes().await.unwrap(), "aaaa");
+        assert!(parts.next().now_or_never().is_none());
+
+        let second = format!("{}--\r\n", part("second", "b.txt", "bbbb"));
+        tx.unbounded_send(Ok(second.into())).unwrap();
+        let mut second = parts.next().await.unwrap().unwrap();
+        assert_eq!(second.name(), Some("second"));
+        assert_eq!(second.file_name(), Some("b.txt"));
+        assert_eq!(second.next().await.unwrap().unwrap(), "bbbb");
+        assert!(second.next().await.is_none());
+        drop(second);
+
+        assert!(parts.next().await.is_none());
+    }
+}
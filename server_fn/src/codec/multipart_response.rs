@@ -0,0 +1,483 @@
+use super::{Encoding, FromRes};
+use crate::{
+    error::{NoCustomError, ServerFnError},
+    response::{ClientRes, Res},
+    IntoRes,
+};
+use bytes::{Bytes, BytesMut};
+use futures::{stream, Stream, StreamExt};
+use http::Method;
+use std::{fmt::Debug, pin::Pin};
+
+/// An encoding that represents a response made of several named parts, each with its
+/// own content type, sent as `multipart/mixed`.
+///
+/// A server function that uses this as its output encoding should return
+/// [`MultipartParts`]. Each part is sent as soon as it is produced, and the client
+/// yields each part once it has been received in full.
+///
+/// ```rust,ignore
+/// #[server(output = MultipartResponse)]
+/// pub async fn photo(id: u32) -> Result<MultipartParts, ServerFnError> {
+///     let photo = load_photo(id).await?;
+///     Ok(MultipartParts::from(vec![
+///         Part::new("thumbnail", "image/png", photo.thumbnail),
+///         Part::new("metadata", "application/json", photo.metadata_json),
+///     ]))
+/// }
+///
+/// let mut parts = photo(1).await?.into_inner();
+/// while let Some(part) = parts.next().await {
+///     let part = part?;
+///     // ...
+/// }
+/// ```
+pub struct MultipartResponse;
+
+impl Encoding for MultipartResponse {
+    const CONTENT_TYPE: &'static str = "multipart/mixed";
+    const METHOD: Method = Method::POST;
+}
+
+/// A single part of a [`MultipartResponse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    /// The name of the part, sent in its `Content-Disposition` header.
+    pub name: String,
+    /// The MIME type of the part's data.
+    pub content_type: String,
+    /// The data itself.
+    pub data: Bytes,
+}
+
+impl Part {
+    /// Creates a new part.
+    pub fn new(
+        name: impl Into<String>,
+        content_type: impl Into<String>,
+        data: impl Into<Bytes>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            content_type: content_type.into(),
+            data: data.into(),
+        }
+    }
+
+    fn encode(&self, boundary: &str) -> Bytes {
+        let head = format!(
+            "--{boundary}\r\ncontent-disposition: inline; \
+             name=\"{}\"\r\ncontent-type: {}\r\ncontent-length: {}\r\n\r\n",
+            self.name.replace('\\', "\\\\").replace('"', "\\\""),
+            self.content_type,
+            self.data.len()
+        );
+        let mut buf = BytesMut::with_capacity(head.len() + self.data.len() + 2);
+        buf.extend_from_slice(head.as_bytes());
+        buf.extend_from_slice(&self.data);
+        buf.extend_from_slice(b"\r\n");
+        buf.freeze()
+    }
+}
+
+impl<N, C, D> From<(N, C, D)> for Part
+where
+    N: Into<String>,
+    C: Into<String>,
+    D: Into<Bytes>,
+{
+    fn from((name, content_type, data): (N, C, D)) -> Self {
+        Part::new(name, content_type, data)
+    }
+}
+
+/// A stream of [`Part`]s, encoded as `multipart/mixed`.
+///
+/// A server function can return this type if its output encoding is
+/// [`MultipartResponse`].
+pub struct MultipartParts<CustErr = NoCustomError>(
+    Pin<Box<dyn Stream<Item = Result<Part, ServerFnError<CustErr>>> + Send>>,
+);
+
+impl<CustErr> Debug for MultipartParts<CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("MultipartParts").finish()
+    }
+}
+
+impl MultipartParts {
+    /// Creates a new `MultipartParts` from the given stream.
+    pub fn new(
+        value: impl Stream<Item = Result<Part, ServerFnError>> + Send + 'static,
+    ) -> Self {
+        Self(Box::pin(value))
+    }
+}
+
+impl<CustErr> MultipartParts<CustErr> {
+    /// Consumes the wrapper, returning a stream of parts.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<Part, ServerFnError<CustErr>>> + Send {
+        self.0
+    }
+}
+
+impl<P> From<Vec<P>> for MultipartParts
+where
+    P: Into<Part>,
+{
+    fn from(value: Vec<P>) -> Self {
+        let parts = value.into_iter().map(|part| Ok(part.into()));
+        Self(Box::pin(stream::iter(parts.collect::<Vec<_>>())))
+    }
+}
+
+impl<CustErr, Response> IntoRes<MultipartResponse, Response, CustErr>
+    for MultipartParts<CustErr>
+where
+    Response: Res<CustErr>,
+    CustErr: 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        let boundary = boundary();
+        let content_type =
+            format!("{}; boundary={boundary}", MultipartResponse::CONTENT_TYPE);
+        let closing = Bytes::from(format!("--{boundary}--\r\n"));
+        let parts = self
+            .into_inner()
+            .map(move |part| part.map(|part| part.encode(&boundary)));
+        Response::try_from_stream(
+            &content_type,
+            parts.chain(stream::once(async { Ok(closing) })),
+        )
+    }
+}
+
+impl<CustErr, Response> FromRes<MultipartResponse, Response, CustErr>
+    for MultipartParts
+where
+    Response: ClientRes<CustErr> + Send,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let decoder = PartsDecoder {
+            chunks: Box::pin(res.try_into_stream()?),
+            buf: BytesMut::new(),
+            delimiter: None,
+            done: false,
+        };
+        Ok(MultipartParts(Box::pin(stream::unfold(
+            decoder,
+            |mut decoder| async move {
+                match decoder.next_part().await {
+                    Ok(part) => part.map(|part| (Ok(part), decoder)),
+                    Err(e) => {
+                        decoder.done = true;
+                        Some((Err(e), decoder))
+                    }
+                }
+            },
+        ))))
+    }
+}
+
+/// A random boundary, which is very unlikely to appear in any part.
+fn boundary() -> String {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+    };
+
+    let a = RandomState::new().build_hasher().finish();
+    let b = RandomState::new().build_hasher().finish();
+    format!("{a:016x}{b:016x}")
+}
+
+type Chunks = Pin<Box<dyn Stream<Item = Result<Bytes, ServerFnError>> + Send>>;
+
+/// Splits a `multipart/mixed` body into parts, whatever the chunk boundaries are.
+///
+/// The boundary is read from the first line of the body, so the `Content-Type`
+/// header of the response is not needed.
+struct PartsDecoder {
+    chunks: Chunks,
+    buf: BytesMut,
+    /// `\r\n--<boundary>`, which follows the data of every part.
+    delimiter: Option<Bytes>,
+    done: bool,
+}
+
+impl PartsDecoder {
+    async fn next_part(&mut self) -> Result<Option<Part>, ServerFnError> {
+        if self.done {
+            return Ok(None);
+        }
+        let delimiter = match &self.delimiter {
+            Some(delimiter) => delimiter.clone(),
+            None => {
+                let end = self.find(b"\r\n").await?;
+                let line = self.buf.split_to(end + 2);
+                let boundary = line[..end]
+                    .strip_prefix(b"--")
+                    .ok_or_else(|| invalid("missing boundary"))?;
+                // a response without any parts only has the closing boundary
+                if boundary.ends_with(b"--") {
+                    self.done = true;
+                    return Ok(None);
+                }
+                let delimiter =
+                    Bytes::from([&b"\r\n--"[..], boundary].concat());
+                self.delimiter = Some(delimiter.clone());
+                delimiter
+            }
+        };
+
+        // a part without headers starts with an empty line right away
+        self.fill(2).await?;
+        let end = if self.buf.starts_with(b"\r\n") {
+            0
+        } else {
+            self.find(b"\r\n\r\n").await? + 2
+        };
+        let head = self.buf.split_to(end + 2);
+        let head = std::str::from_utf8(&head)
+            .map_err(|_| invalid("part headers are not UTF-8"))?;
+        let mut name = None;
+        let mut content_type = None;
+        let mut content_length = None;
+        for line in head.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "content-disposition" => name = disposition_name(value),
+                "content-type" => content_type = Some(value.to_owned()),
+                "content-length" => content_length = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        let data = match content_length {
+            Some(len) => {
+                self.fill(len).await?;
+                self.buf.split_to(len).freeze()
+            }
+            None => {
+                let end = self.find(&delimiter).await?;
+                self.buf.split_to(end).freeze()
+            }
+        };
+
+        // the delimiter is followed by `\r\n` before the next part, or `--` at the end
+        self.fill(delimiter.len() + 2).await?;
+        if !self.buf.starts_with(&delimiter) {
+            return Err(invalid("missing boundary after part"));
+        }
+        match &self.buf.split_to(delimiter.len() + 2)[delimiter.len()..] {
+            b"\r\n" => {}
+            b"--" => self.done = true,
+            _ => return Err(invalid("malformed boundary")),
+        }
+
+        Ok(Some(Part {
+            name: name.unwrap_or_default(),
+            content_type: content_type
+                .unwrap_or_else(|| "text/plain".to_owned()),
+            data,
+        }))
+    }
+
+    /// Reads the next chunk into the buffer, returning an error at the end of the body.
+    async fn read_more(&mut self) -> Result<(), ServerFnError> {
+        match self.chunks.next().await {
+            Some(Ok(chunk)) => {
+                self.buf.extend_from_slice(&chunk);
+                Ok(())
+            }
+            Some(Err(e)) => Err(e),
+            None => Err(invalid("unexpected end of body")),
+        }
+    }
+
+    /// Reads until the buffer holds at least `len` bytes.
+    async fn fill(&mut self, len: usize) -> Result<(), ServerFnError> {
+        while self.buf.len() < len {
+            self.read_more().await?;
+        }
+        Ok(())
+    }
+
+    /// Reads until the buffer contains `needle`, returning its position.
+    async fn find(&mut self, needle: &[u8]) -> Result<usize, ServerFnError> {
+        let mut from = 0;
+        loop {
+            if let Some(pos) = self.buf[from..]
+                .windows(needle.len())
+                .position(|window| window == needle)
+            {
+                return Ok(from + pos);
+            }
+            // the needle may begin in what has already been searched
+            from = self.buf.len().saturating_sub(needle.len() - 1);
+            self.read_more().await?;
+        }
+    }
+}
+
+/// The `name` parameter of a `Content-Disposition` header.
+fn disposition_name(value: &str) -> Option<String> {
+    // skip other parameters that end in `name`, like `filename`
+    let (start, _) = value.match_indices("name=").find(|(i, _)| {
+        let before = value[..*i].trim_end();
+        before.is_empty() || before.ends_with(';')
+    })?;
+    let rest = &value[start + "name=".len()..];
+    match rest.strip_prefix('"') {
+        Some(quoted) => {
+            let mut name = String::new();
+            let mut chars = quoted.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => return Some(name),
+                    '\\' => name.extend(chars.next()),
+                    c => name.push(c),
+                }
+            }
+            None
+        }
+        None => Some(rest.split(';').next()?.trim().to_owned()),
+    }
+}
+
+fn invalid(msg: &str) -> ServerFnError {
+    ServerFnError::Deserialization(format!("invalid multipart response: {msg}"))
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{MultipartParts, MultipartResponse, Part};
+    use crate::{
+        codec::{FromRes, IntoRes},
+        error::NoCustomError,
+    };
+    use axum::body::Body;
+    use futures::{stream, StreamExt, TryStreamExt};
+    use http::{header, Response};
+    use http_body_util::BodyExt;
+    use std::convert::Infallible;
+
+    fn parts() -> Vec<Part> {
+        vec![
+            // binary data that happens to contain something like a boundary
+            Part::new(
+                "thumbnail",
+                "image/png",
+                &b"\x89PNG\r\n--\r\n\x00\x01"[..],
+            ),
+            Part::new(
+                "meta \"data\"",
+                "application/json",
+                r#"{"width":1,"height":1}"#,
+            ),
+        ]
+    }
+
+    async fn decode(res: Response<Body>) -> Vec<Part> {
+        <MultipartParts as FromRes<MultipartResponse, _, NoCustomError>>::from_res(
+            res,
+        )
+        .await
+        .unwrap()
+        .into_inner()
+        .try_collect()
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_trips_two_parts() {
+        let res: Response<Body> = <MultipartParts as IntoRes<
+            MultipartResponse,
+            _,
+            NoCustomError,
+        >>::into_res(MultipartParts::from(
+            parts(),
+        ))
+        .await
+        .unwrap();
+        let content_type =
+            res.headers()[header::CONTENT_TYPE].to_str().unwrap();
+        assert!(content_type.starts_with("multipart/mixed; boundary="));
+
+        assert_eq!(decode(res).await, parts());
+    }
+
+    #[tokio::test]
+    async fn decodes_parts_split_across_chunks() {
+        let res: Response<Body> = <MultipartParts as IntoRes<
+            MultipartResponse,
+            _,
+            NoCustomError,
+        >>::into_res(MultipartParts::from(
+            parts(),
+        ))
+        .await
+        .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let bytes = (0..body.len())
+            .map(|i| Ok::<_, Infallible>(body.slice(i..i + 1)))
+            .collect::<Vec<_>>();
+        let res = Response::new(Body::from_stream(stream::iter(bytes)));
+
+        assert_eq!(decode(res).await, parts());
+    }
+
+    #[tokio::test]
+    async fn decodes_parts_without_content_length() {
+        let body = "--b\r\ncontent-disposition: inline; name=a\r\n\r\n\
+                    first\r\n--b\r\ncontent-type: text/html\r\n\r\n\
+                    <p>second</p>\r\n--b--\r\n";
+        let res = Response::new(Body::from(body));
+
+        assert_eq!(
+            decode(res).await,
+            [
+                Part::new("a", "text/plain", "first"),
+                Part::new("", "text/html", "<p>second</p>"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn round_trips_no_parts() {
+        let res: Response<Body> = <MultipartParts as IntoRes<
+            MultipartResponse,
+            _,
+            NoCustomError,
+        >>::into_res(MultipartParts::from(
+            Vec::<Part>::new(),
+        ))
+        .await
+        .unwrap();
+
+        assert!(decode(res).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn truncated_body_is_an_error() {
+        let res =
+            Response::new(Body::from("--b\r\ncontent-length: 10\r\n\r\nshort"));
+        let mut parts = <MultipartParts as FromRes<
+            MultipartResponse,
+            _,
+            NoCustomError,
+        >>::from_res(res)
+        .await
+        .unwrap()
+        .into_inner();
+
+        assert!(parts.next().await.unwrap().is_err());
+        assert!(parts.next().await.is_none());
+    }
+}
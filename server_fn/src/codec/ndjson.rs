@@ -0,0 +1,255 @@
+use super::{Encoding, FromRes};
+use crate::{
+    error::{NoCustomError, ServerFnError},
+    response::{ClientRes, Res},
+    IntoRes,
+};
+use bytes::{Bytes, BytesMut};
+use futures::{stream, Stream, StreamExt};
+use http::Method;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt::Debug, pin::Pin};
+
+/// An encoding that represents a stream of JSON values, one per line
+/// ([newline-delimited JSON](https://github.com/ndjson/ndjson-spec)).
+///
+/// A server function that uses this as its output encoding should return [`JsonStream`].
+/// Each item is sent as soon as the stream yields it, so the client can handle rows
+/// while the server is still producing the rest.
+///
+/// ```rust,ignore
+/// #[server(output = NdJson)]
+/// pub async fn recent_orders() -> Result<JsonStream<Order>, ServerFnError> {
+///     Ok(JsonStream::from(orders_since(yesterday())))
+/// }
+///
+/// let mut orders = recent_orders().await?.into_inner();
+/// while let Some(order) = orders.next().await {
+///     // ...
+/// }
+/// ```
+pub struct NdJson;
+
+impl Encoding for NdJson {
+    const CONTENT_TYPE: &'static str = "application/x-ndjson";
+    const METHOD: Method = Method::POST;
+}
+
+/// A stream of values, encoded as newline-delimited JSON.
+///
+/// A server function can return this type if its output encoding is [`NdJson`].
+pub struct JsonStream<T, CustErr = NoCustomError>(
+    Pin<Box<dyn Stream<Item = Result<T, ServerFnError<CustErr>>> + Send>>,
+);
+
+impl<T, CustErr> Debug for JsonStream<T, CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("JsonStream").finish()
+    }
+}
+
+impl<T> JsonStream<T> {
+    /// Creates a new `JsonStream` from the given stream.
+    pub fn new(
+        value: impl Stream<Item = Result<T, ServerFnError>> + Send + 'static,
+    ) -> Self {
+        Self(Box::pin(value))
+    }
+}
+
+impl<T, CustErr> JsonStream<T, CustErr> {
+    /// Consumes the wrapper, returning a stream of values.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<T, ServerFnError<CustErr>>> + Send {
+        self.0
+    }
+}
+
+impl<S, T> From<S> for JsonStream<T>
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: 'static,
+{
+    fn from(value: S) -> Self {
+        Self(Box::pin(value.map(Ok)))
+    }
+}
+
+impl<CustErr, T, Response> IntoRes<NdJson, Response, CustErr>
+    for JsonStream<T, CustErr>
+where
+    Response: Res<CustErr>,
+    T: Serialize + Send + 'static,
+    CustErr: 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        Response::try_from_stream(
+            NdJson::CONTENT_TYPE,
+            self.into_inner().map(|item| {
+                let mut line = serde_json::to_vec(&item?)
+                    .map_err(|e| ServerFnError::Serialization(e.to_string()))?;
+                line.push(b'\n');
+                Ok(Bytes::from(line))
+            }),
+        )
+    }
+}
+
+impl<CustErr, T, Response> FromRes<NdJson, Response, CustErr> for JsonStream<T>
+where
+    Response: ClientRes<CustErr> + Send,
+    T: DeserializeOwned + Send + 'static,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let chunks = res.try_into_stream()?;
+        Ok(JsonStream(Box::pin(lines(chunks).map(|line| {
+            line.and_then(|line| {
+                serde_json::from_slice(&line)
+                    .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+            })
+        }))))
+    }
+}
+
+/// Splits a stream of chunks into non-empty lines, whatever the chunk boundaries are.
+fn lines(
+    chunks: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+) -> impl Stream<Item = Result<Bytes, ServerFnError>> + Send {
+    let chunks = Box::pin(chunks);
+    stream::unfold(
+        (chunks, BytesMut::new(), false),
+        |(mut chunks, mut buf, mut done)| async move {
+            loop {
+                if let Some(end) = buf.iter().position(|b| *b == b'\n') {
+                    let line = buf.split_to(end + 1).freeze();
+                    if !is_blank(&line) {
+                        return Some((Ok(line), (chunks, buf, done)));
+                    }
+                } else if done {
+                    // the last line doesn't have to end with a newline
+                    let rest = buf.split().freeze();
+                    return (!is_blank(&rest))
+                        .then(|| (Ok(rest), (chunks, buf, done)));
+                } else {
+                    match chunks.next().await {
+                        Some(Ok(chunk)) => buf.extend_from_slice(&chunk),
+                        Some(Err(e)) => {
+                            return Some((Err(e), (chunks, buf, done)))
+                        }
+                        None => done = true,
+                    }
+                }
+            }
+        },
+    )
+}
+
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(u8::is_ascii_whitespace)
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{JsonStream, NdJson};
+    use crate::{
+        codec::{FromRes, IntoRes},
+        error::NoCustomError,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use futures::{channel::mpsc, stream, StreamExt};
+    use http::{header, Response};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+    use std::{convert::Infallible, time::Duration};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Row {
+        id: u32,
+        label: String,
+    }
+
+    fn row(id: u32) -> Row {
+        Row {
+            id,
+            label: format!("row {id}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_one_line_per_item() {
+        let (tx, rx) = mpsc::unbounded();
+        let res: Response<Body> =
+            <JsonStream<Row> as IntoRes<NdJson, _, NoCustomError>>::into_res(
+                JsonStream::from(rx),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+
+        let mut body = res.into_body();
+        for id in 0..3 {
+            tx.unbounded_send(row(id)).unwrap();
+            let frame = body.frame().await.unwrap().unwrap();
+            assert_eq!(
+                frame.into_data().unwrap(),
+                format!("{{\"id\":{id},\"label\":\"row {id}\"}}\n")
+            );
+        }
+        drop(tx);
+        assert!(body.frame().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn client_observes_rows_as_they_are_produced() {
+        let (tx, rx) = mpsc::unbounded();
+        let res: Response<Body> =
+            <JsonStream<Row> as IntoRes<NdJson, _, NoCustomError>>::into_res(
+                JsonStream::from(rx),
+            )
+            .await
+            .unwrap();
+        let mut rows =
+            <JsonStream<Row> as FromRes<NdJson, _, NoCustomError>>::from_res(
+                res,
+            )
+            .await
+            .unwrap()
+            .into_inner();
+
+        for id in 0..3 {
+            // nothing arrives until the server produces the next row
+            let early =
+                tokio::time::timeout(Duration::from_millis(10), rows.next());
+            assert!(early.await.is_err());
+            tx.unbounded_send(row(id)).unwrap();
+            assert_eq!(rows.next().await.unwrap().unwrap(), row(id));
+        }
+        drop(tx);
+        assert!(rows.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn decodes_lines_split_across_chunks() {
+        let chunks = [
+            "{\"id\":0,\"label\":\"row 0\"}\n{\"id\":1,",
+            "\"label\":\"row 1\"}\n\n",
+            "{\"id\":2,\"label\":\"row 2\"}",
+        ];
+        let body = Body::from_stream(stream::iter(
+            chunks.map(|chunk| Ok::<_, Infallible>(Bytes::from(chunk))),
+        ));
+        let rows =
+            <JsonStream<Row> as FromRes<NdJson, _, NoCustomError>>::from_res(
+                Response::new(body),
+            )
+            .await
+            .unwrap()
+            .into_inner()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(rows, [row(0), row(1), row(2)]);
+    }
+}
@@ -0,0 +1,222 @@
+use super::{request_headers::RequestHeaders, Encoding, FromRes, IntoRes};
+use crate::{error::ServerFnError, response::ClientRes};
+use http::Method;
+use std::marker::PhantomData;
+
+/// An output encoding that responds with either `A` or `B`, whichever the
+/// `Accept` header of the request prefers.
+///
+/// `A` is used when the client accepts both equally, or sends no `Accept` header,
+/// and is what the built-in clients ask for. The response has the `Content-Type` of
+/// the encoding that was picked, and is decoded on the client accordingly.
+///
+/// ```rust,ignore
+/// // JSON for browsers, CBOR for clients that send `Accept: application/cbor`
+/// #[server(output = Negotiate<Json, Cbor>)]
+/// pub async fn list_posts() -> Result<Vec<Post>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+pub struct Negotiate<A, B>(PhantomData<(A, B)>);
+
+impl<A: Encoding, B: Encoding> Encoding for Negotiate<A, B> {
+    const CONTENT_TYPE: &'static str = A::CONTENT_TYPE;
+    const METHOD: Method = A::METHOD;
+}
+
+impl<A, B, T, Response, Err> IntoRes<Negotiate<A, B>, Response, Err> for T
+where
+    A: Encoding,
+    B: Encoding,
+    T: IntoRes<A, Response, Err> + IntoRes<B, Response, Err> + Send,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<Err>> {
+        let accept =
+            RequestHeaders::with_current(|headers| headers.accept.clone());
+        if prefers(accept.as_deref(), B::CONTENT_TYPE, A::CONTENT_TYPE) {
+            IntoRes::<B, Response, Err>::into_res(self).await
+        } else {
+            IntoRes::<A, Response, Err>::into_res(self).await
+        }
+    }
+}
+
+impl<A, B, T, Response, Err> FromRes<Negotiate<A, B>, Response, Err> for T
+where
+    A: Encoding,
+    B: Encoding,
+    Response: ClientRes<Err> + Send,
+    T: FromRes<A, Response, Err> + FromRes<B, Response, Err>,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<Err>> {
+        let is_b = res.content_type().is_some_and(|content_type| {
+            essence(&content_type).eq_ignore_ascii_case(B::CONTENT_TYPE)
+        });
+        if is_b {
+            <T as FromRes<B, Response, Err>>::from_res(res).await
+        } else {
+            <T as FromRes<A, Response, Err>>::from_res(res).await
+        }
+    }
+}
+
+/// The media type, without any parameters.
+pub(super) fn essence(media_type: &str) -> &str {
+    media_type.split(';').next().unwrap_or_default().trim()
+}
+
+/// How much an `Accept` header wants `content_type`, from 0 to 1.
+pub(super) fn quality(accept: &str, content_type: &str) -> f32 {
+    let (ty, _) = content_type.split_once('/').unwrap_or((content_type, ""));
+    accept
+        .split(',')
+        .filter_map(|range| {
+            let mut params = range.split(';');
+            let media_range = params.next()?.trim();
+            let matches = media_range == "*/*"
+                || media_range.eq_ignore_ascii_case(content_type)
+                || media_range
+                    .strip_suffix("/*")
+                    .is_some_and(|range| range.eq_ignore_ascii_case(ty));
+            if !matches {
+                return None;
+            }
+            let q = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some(q)
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Whether `accept` prefers `other` over `default`.
+fn prefers(accept: Option<&str>, other: &str, default: &str) -> bool {
+    accept
+        .is_some_and(|accept| quality(accept, other) > quality(accept, default))
+}
+
+#[cfg(all(
+    test,
+    feature = "axum-no-default",
+    feature = "json",
+    feature = "cbor"
+))]
+mod tests {
+    use super::{prefers, Negotiate};
+    use crate::{
+        codec::{test_client::ServerOnly, Cbor, FromRes, Json},
+        error::NoCustomError,
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Post {
+        id: u32,
+        title: String,
+    }
+
+    /// What the `#[server]` macro generates for
+    /// `#[server(output = Negotiate<Json, Cbor>)] async fn get_post()`.
+    #[derive(Serialize, Deserialize)]
+    struct GetPost {}
+
+    impl ServerFn for GetPost {
+        const PATH: &'static str = "/api/get_post";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = Post;
+        type InputEncoding = Json;
+        type OutputEncoding = Negotiate<Json, Cbor>;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<Post, ServerFnError> {
+            Ok(post())
+        }
+    }
+
+    fn post() -> Post {
+        Post {
+            id: 1,
+            title: "Negotiation".into(),
+        }
+    }
+
+    async fn call(accept: Option<&str>) -> Response<Body> {
+        let mut req = Request::post(GetPost::PATH)
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(accept) = accept {
+            req = req.header(header::ACCEPT, accept);
+        }
+        GetPost::run_on_server(req.body(Body::from("{}")).unwrap()).await
+    }
+
+    #[tokio::test]
+    async fn responds_with_json_when_asked() {
+        let res = call(Some("application/json")).await;
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "application/json");
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, r#"{"id":1,"title":"Negotiation"}"#);
+    }
+
+    #[tokio::test]
+    async fn responds_with_cbor_when_asked() {
+        let res = call(Some("application/cbor")).await;
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "application/cbor");
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let decoded: Post = ciborium::de::from_reader(body.as_ref()).unwrap();
+        assert_eq!(decoded, post());
+    }
+
+    #[tokio::test]
+    async fn defaults_to_first_encoding() {
+        let res = call(None).await;
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "application/json");
+        let res = call(Some("*/*")).await;
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "application/json");
+    }
+
+    #[tokio::test]
+    async fn client_decodes_either_encoding() {
+        for accept in ["application/json", "application/cbor"] {
+            let res = call(Some(accept)).await;
+            let decoded = <Post as FromRes<
+                Negotiate<Json, Cbor>,
+                _,
+                NoCustomError,
+            >>::from_res(res)
+            .await
+            .unwrap();
+            assert_eq!(decoded, post());
+        }
+    }
+
+    #[test]
+    fn weighs_accept_header() {
+        let (json, cbor) = ("application/json", "application/cbor");
+        assert!(prefers(Some("application/cbor"), cbor, json));
+        assert!(prefers(
+            Some("application/json;q=0.5, application/cbor"),
+            cbor,
+            json
+        ));
+        assert!(prefers(
+            Some("application/*;q=0.2, application/cbor;q=0.9"),
+            cbor,
+            json
+        ));
+        assert!(!prefers(
+            Some("application/cbor;q=0.5, application/json"),
+            cbor,
+            json
+        ));
+        assert!(!prefers(Some("application/*"), cbor, json));
+        assert!(!prefers(None, cbor, json));
+    }
+}
@@ -0,0 +1,184 @@
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    error::ServerFnError,
+    request::{ClientReq, Req},
+    response::{ClientRes, Res},
+};
+use bytes::Bytes;
+use http::Method;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A codec for [Postcard](https://docs.rs/postcard), a compact binary format.
+pub struct Postcard;
+
+impl Encoding for Postcard {
+    const CONTENT_TYPE: &'static str = "application/x-postcard";
+    const METHOD: Method = Method::POST;
+}
+
+impl<T, Request, Err> IntoReq<Postcard, Request, Err> for T
+where
+    Request: ClientReq<Err>,
+    T: Serialize,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<Err>> {
+        let data = postcard::to_allocvec(&self)
+            .map_err(|e| ServerFnError::Serialization(e.to_string()))?;
+        Request::try_new_post_bytes(
+            path,
+            accepts,
+            Postcard::CONTENT_TYPE,
+            Bytes::from(data),
+        )
+    }
+}
+
+impl<T, Request, Err> FromReq<Postcard, Request, Err> for T
+where
+    Request: Req<Err> + Send,
+    T: DeserializeOwned,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<Err>> {
+        let data = req.try_into_bytes().await?;
+        postcard::from_bytes::<T>(&data)
+            .map_err(|e| ServerFnError::Args(e.to_string()))
+    }
+}
+
+impl<T, Response, Err> IntoRes<Postcard, Response, Err> for T
+where
+    Response: Res<Err>,
+    T: Serialize + Send,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<Err>> {
+        let data = postcard::to_allocvec(&self)
+            .map_err(|e| ServerFnError::Serialization(e.to_string()))?;
+        Response::try_from_bytes(Postcard::CONTENT_TYPE, Bytes::from(data))
+    }
+}
+
+impl<T, Response, Err> FromRes<Postcard, Response, Err> for T
+where
+    Response: ClientRes<Err> + Send,
+    T: DeserializeOwned,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<Err>> {
+        let data = res.try_into_bytes().await?;
+        postcard::from_bytes(&data)
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Postcard;
+    use crate::{
+        codec::{Encoding, FromReq, FromRes, IntoReq, IntoRes},
+        error::NoCustomError,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use http::{header, Request, Response};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Circle { radius: f32 },
+        Polygon(Vec<Point>),
+        Group(Vec<Shape>),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Drawing {
+        name: String,
+        layers: Vec<(u8, Option<Shape>)>,
+    }
+
+    fn drawing() -> Drawing {
+        Drawing {
+            name: "logo".into(),
+            layers: vec![
+                (0, Some(Shape::Circle { radius: 1.5 })),
+                (1, None),
+                (
+                    2,
+                    Some(Shape::Group(vec![
+                        Shape::Polygon(vec![
+                            Point { x: 0, y: 0 },
+                            Point { x: 3, y: -4 },
+                        ]),
+                        Shape::Group(vec![]),
+                    ])),
+                ),
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trips_through_a_request() {
+        let req: Request<Bytes> =
+            <Drawing as IntoReq<Postcard, _, NoCustomError>>::into_req(
+                drawing(),
+                "/api/draw",
+                Postcard::CONTENT_TYPE,
+            )
+            .unwrap();
+        assert_eq!(
+            req.headers()[header::CONTENT_TYPE],
+            "application/x-postcard"
+        );
+        assert_eq!(req.headers()[header::ACCEPT], "application/x-postcard");
+
+        let decoded =
+            <Drawing as FromReq<Postcard, _, NoCustomError>>::from_req(
+                req.map(Body::from),
+            )
+            .await
+            .unwrap();
+        assert_eq!(decoded, drawing());
+    }
+
+    #[tokio::test]
+    async fn round_trips_through_a_response() {
+        let res: Response<Body> =
+            <Drawing as IntoRes<Postcard, _, NoCustomError>>::into_res(
+                drawing(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            res.headers()[header::CONTENT_TYPE],
+            "application/x-postcard"
+        );
+
+        let decoded =
+            <Drawing as FromRes<Postcard, _, NoCustomError>>::from_res(res)
+                .await
+                .unwrap();
+        assert_eq!(decoded, drawing());
+    }
+
+    #[tokio::test]
+    async fn rejects_malformed_arguments() {
+        let req = Request::post("/api/draw")
+            .header(header::CONTENT_TYPE, Postcard::CONTENT_TYPE)
+            .body(Body::from(vec![0xff; 4]))
+            .unwrap();
+        let err: ServerFnError =
+            <Drawing as FromReq<Postcard, _, NoCustomError>>::from_req(req)
+                .await
+                .unwrap_err();
+        assert!(matches!(err, ServerFnError::Args(_)));
+    }
+}
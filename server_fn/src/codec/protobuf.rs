@@ -0,0 +1,205 @@
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    error::ServerFnError,
+    request::{ClientReq, Req},
+    response::{ClientRes, Res},
+};
+use bytes::Bytes;
+use http::Method;
+use prost::Message;
+
+/// Pass arguments and receive responses as [Protocol Buffers](https://protobuf.dev)
+/// messages in a `POST` request, using [`prost`].
+///
+/// The server function takes a single argument, the message that is sent, and
+/// returns a message. Both are types generated by `prost-build`, or that derive
+/// [`prost::Message`].
+///
+/// ```rust,ignore
+/// #[server(encoding = "Protobuf")]
+/// pub async fn find_user(query: UserQuery) -> Result<User, ServerFnError> {
+///     // ...
+/// }
+/// ```
+pub struct Protobuf;
+
+impl Encoding for Protobuf {
+    const CONTENT_TYPE: &'static str = "application/x-protobuf";
+    const METHOD: Method = Method::POST;
+}
+
+/// Converts the arguments of a server function to and from the message that is
+/// sent for them with the [`Protobuf`] encoding.
+///
+/// The `#[server]` macro implements this for functions that use the encoding.
+pub trait ProtobufArgs: Sized {
+    /// The message that is sent.
+    type Message: Message + Default;
+
+    /// Turns the arguments into the message.
+    fn into_message(self) -> Self::Message;
+
+    /// Turns the message back into the arguments.
+    fn from_message(message: Self::Message) -> Self;
+}
+
+impl<T, Request, Err> IntoReq<Protobuf, Request, Err> for T
+where
+    Request: ClientReq<Err>,
+    T: ProtobufArgs,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<Err>> {
+        let data = self.into_message().encode_to_vec();
+        Request::try_new_post_bytes(
+            path,
+            accepts,
+            Protobuf::CONTENT_TYPE,
+            Bytes::from(data),
+        )
+    }
+}
+
+impl<T, Request, Err> FromReq<Protobuf, Request, Err> for T
+where
+    Request: Req<Err> + Send,
+    T: ProtobufArgs,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<Err>> {
+        let data = req.try_into_bytes().await?;
+        T::Message::decode(data)
+            .map(T::from_message)
+            .map_err(|e| ServerFnError::Args(e.to_string()))
+    }
+}
+
+impl<T, Response, Err> IntoRes<Protobuf, Response, Err> for T
+where
+    Response: Res<Err>,
+    T: Message + Send,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<Err>> {
+        let data = self.encode_to_vec();
+        Response::try_from_bytes(Protobuf::CONTENT_TYPE, Bytes::from(data))
+    }
+}
+
+impl<T, Response, Err> FromRes<Protobuf, Response, Err> for T
+where
+    Response: ClientRes<Err> + Send,
+    T: Message + Default,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<Err>> {
+        let data = res.try_into_bytes().await?;
+        T::decode(data)
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{Protobuf, ProtobufArgs};
+    use crate::{
+        codec::{FromReq, FromRes, IntoReq, IntoRes},
+        error::NoCustomError,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use http::{header, Request, Response};
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Order {
+        #[prost(string, tag = "1")]
+        id: String,
+        #[prost(uint32, repeated, tag = "2")]
+        quantities: Vec<u32>,
+        #[prost(string, optional, tag = "3")]
+        note: Option<String>,
+    }
+
+    /// What the `#[server]` macro generates for `async fn place(order: Order)`.
+    #[derive(Debug, PartialEq)]
+    struct Place {
+        order: Order,
+    }
+
+    impl ProtobufArgs for Place {
+        type Message = Order;
+
+        fn into_message(self) -> Order {
+            self.order
+        }
+
+        fn from_message(order: Order) -> Self {
+            Place { order }
+        }
+    }
+
+    fn order(note: Option<&str>) -> Order {
+        Order {
+            id: "A-17".into(),
+            quantities: vec![3, 0, 12],
+            note: note.map(Into::into),
+        }
+    }
+
+    #[tokio::test]
+    async fn request_round_trip() {
+        for note in [Some("leave at the door"), None] {
+            let args = Place { order: order(note) };
+            let req: Request<Bytes> =
+                IntoReq::<Protobuf, _, NoCustomError>::into_req(
+                    Place { order: order(note) },
+                    "/api/place",
+                    "application/x-protobuf",
+                )
+                .unwrap();
+            assert_eq!(
+                req.headers()[header::CONTENT_TYPE],
+                "application/x-protobuf"
+            );
+
+            let req = req.map(Body::from);
+            let decoded: Place =
+                <Place as FromReq<Protobuf, _, NoCustomError>>::from_req(req)
+                    .await
+                    .unwrap();
+            assert_eq!(decoded, args);
+        }
+    }
+
+    #[tokio::test]
+    async fn response_round_trip() {
+        for note in [Some("gift wrap"), None] {
+            let res: Response<Body> =
+                IntoRes::<Protobuf, _, NoCustomError>::into_res(order(note))
+                    .await
+                    .unwrap();
+            assert_eq!(
+                res.headers()[header::CONTENT_TYPE],
+                "application/x-protobuf"
+            );
+
+            let decoded: Order =
+                <Order as FromRes<Protobuf, _, NoCustomError>>::from_res(res)
+                    .await
+                    .unwrap();
+            assert_eq!(decoded, order(note));
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_message_is_an_args_error() {
+        let req = Request::post("/api/place")
+            .header(header::CONTENT_TYPE, "application/x-protobuf")
+            .body(Body::from(vec![0xff, 0xff, 0xff]))
+            .unwrap();
+        let err = <Place as FromReq<Protobuf, _, NoCustomError>>::from_req(req)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::ServerFnError::Args(_)));
+    }
+}
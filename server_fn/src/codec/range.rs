@@ -0,0 +1,273 @@
+use super::RequestHeaders;
+use crate::{error::ServerFnError, response::Res};
+use bytes::Bytes;
+use futures::Stream;
+use http::StatusCode;
+use std::task::Poll;
+
+/// The part of a body that a request asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ByteRange {
+    Full,
+    /// The bytes from `start` to `end`, inclusive.
+    Partial {
+        start: u64,
+        end: u64,
+    },
+    Unsatisfiable,
+}
+
+/// Reads the `Range` header of a request for a body of `len` bytes.
+///
+/// Headers that can't be parsed are ignored, as are requests for several ranges,
+/// which would need a multipart response: both are answered with the whole file.
+fn byte_range(header: Option<&str>, len: u64) -> ByteRange {
+    let Some(spec) =
+        header.and_then(|header| header.trim().strip_prefix("bytes="))
+    else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+    if start.is_empty() {
+        // the last `end` bytes of the file
+        let Ok(suffix) = end.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        if suffix == 0 || len == 0 {
+            return ByteRange::Unsatisfiable;
+        }
+        return ByteRange::Partial {
+            start: len.saturating_sub(suffix),
+            end: len - 1,
+        };
+    }
+    let Ok(start) = start.parse::<u64>() else {
+        return ByteRange::Full;
+    };
+    let end = if end.is_empty() {
+        u64::MAX
+    } else {
+        match end.parse::<u64>() {
+            Ok(end) if end >= start => end,
+            _ => return ByteRange::Full,
+        }
+    };
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial {
+        start,
+        end: end.min(len - 1),
+    }
+}
+
+/// Builds a streaming response to a request for a body of `len` bytes, if that is
+/// known.
+///
+/// A request for a single byte range is answered with `206 Partial Content` and a
+/// `Content-Range` header, and one for a range that starts past the end with
+/// `416 Range Not Satisfiable`. Other requests get the whole body. `body` is called
+/// with the offset of the first byte to send and the number of bytes to send, if
+/// known, and returns a stream of exactly those bytes.
+pub(crate) fn ranged_response<Response, CustErr, S>(
+    content_type: &str,
+    len: Option<u64>,
+    body: impl FnOnce(u64, Option<u64>) -> S,
+) -> Result<Response, ServerFnError<CustErr>>
+where
+    Response: Res<CustErr>,
+    S: Stream<Item = Result<Bytes, ServerFnError<CustErr>>> + Send + 'static,
+{
+    let range = match len {
+        Some(len) => RequestHeaders::with_current(|headers| {
+            byte_range(headers.range.as_deref(), len)
+        }),
+        None => ByteRange::Full,
+    };
+    let (start, body_len) = match range {
+        ByteRange::Full => (0, len),
+        ByteRange::Partial { start, end } => (start, Some(end - start + 1)),
+        ByteRange::Unsatisfiable => {
+            let mut res = Response::try_from_bytes(content_type, Bytes::new())?;
+            res.set_status(StatusCode::RANGE_NOT_SATISFIABLE);
+            res.insert_header(
+                "content-range",
+                &format!("bytes */{}", len.unwrap_or_default()),
+            );
+            return Ok(res);
+        }
+    };
+
+    let mut res =
+        Response::try_from_stream(content_type, body(start, body_len))?;
+    if let Some(len) = len {
+        res.insert_header("accept-ranges", "bytes");
+        res.insert_header(
+            "content-length",
+            &body_len.unwrap_or(len).to_string(),
+        );
+        if let ByteRange::Partial { start, end } = range {
+            res.set_status(StatusCode::PARTIAL_CONTENT);
+            res.insert_header(
+                "content-range",
+                &format!("bytes {start}-{end}/{len}"),
+            );
+        }
+    }
+    Ok(res)
+}
+
+/// Skips the first `start` bytes of `stream`, and ends it once `len` more have been
+/// read.
+pub(crate) fn slice_stream<E>(
+    stream: impl Stream<Item = Result<Bytes, E>> + Send + 'static,
+    start: u64,
+    len: Option<u64>,
+) -> impl Stream<Item = Result<Bytes, E>> + Send + 'static {
+    let mut stream = Box::pin(stream);
+    let mut skip = start;
+    let mut remaining = len.unwrap_or(u64::MAX);
+    futures::stream::poll_fn(move |cx| loop {
+        if remaining == 0 {
+            return Poll::Ready(None);
+        }
+        match futures::ready!(stream.as_mut().poll_next(cx)) {
+            Some(Ok(mut chunk)) => {
+                let skipped = skip.min(chunk.len() as u64);
+                skip -= skipped;
+                let mut chunk = chunk.split_off(skipped as usize);
+                chunk.truncate(remaining.min(chunk.len() as u64) as usize);
+                remaining -= chunk.len() as u64;
+                if !chunk.is_empty() {
+                    return Poll::Ready(Some(Ok(chunk)));
+                }
+            }
+            other => return Poll::Ready(other),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{byte_range, slice_stream, ByteRange};
+    use bytes::Bytes;
+    use futures::StreamExt;
+
+    #[test]
+    fn reads_byte_ranges() {
+        let partial = |start, end| ByteRange::Partial { start, end };
+        assert_eq!(byte_range(None, 100), ByteRange::Full);
+        assert_eq!(byte_range(Some("bytes=0-9"), 100), partial(0, 9));
+        assert_eq!(byte_range(Some("bytes=90-"), 100), partial(90, 99));
+        assert_eq!(byte_range(Some("bytes=-10"), 100), partial(90, 99));
+        assert_eq!(byte_range(Some("bytes=50-500"), 100), partial(50, 99));
+        assert_eq!(
+            byte_range(Some("bytes=100-"), 100),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(byte_range(Some("bytes=0-1,5-6"), 100), ByteRange::Full);
+        assert_eq!(byte_range(Some("items=0-9"), 100), ByteRange::Full);
+    }
+
+    #[test]
+    fn slices_across_chunks() {
+        let chunks = ["hello ", "range ", "world"]
+            .map(|chunk| Ok::<_, ()>(Bytes::from(chunk)));
+        let sliced = slice_stream(futures::stream::iter(chunks), 3, Some(10));
+        let sliced = futures::executor::block_on(sliced.collect::<Vec<_>>());
+        assert_eq!(
+            sliced,
+            [
+                Ok(Bytes::from("lo ")),
+                Ok(Bytes::from("range ")),
+                Ok(Bytes::from("w"))
+            ]
+        );
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default", feature = "url"))]
+mod axum_tests {
+    use crate::{
+        codec::{test_client::ServerOnly, ByteStream, GetUrl, Streaming},
+        error::NoCustomError,
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+
+    const CHUNKS: [&str; 3] = ["0123", "4567", "89"];
+
+    /// What the `#[server]` macro generates for
+    /// `#[server(input = GetUrl, output = Streaming)] async fn digits()`.
+    #[derive(Serialize, Deserialize)]
+    struct Digits {}
+
+    impl ServerFn for Digits {
+        const PATH: &'static str = "/api/digits";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = ByteStream;
+        type InputEncoding = GetUrl;
+        type OutputEncoding = Streaming;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<ByteStream, ServerFnError> {
+            Ok(ByteStream::from(futures::stream::iter(CHUNKS))
+                .with_content_length(10))
+        }
+    }
+
+    async fn digits(range: &str) -> (StatusCode, Option<String>, String) {
+        let req = Request::get(Digits::PATH)
+            .header(header::RANGE, range)
+            .body(Body::empty())
+            .unwrap();
+        let res = Digits::run_on_server(req).await;
+        let content_range = res
+            .headers()
+            .get(header::CONTENT_RANGE)
+            .map(|value| value.to_str().unwrap().to_string());
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (
+            status,
+            content_range,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn sends_single_range() {
+        let (status, content_range, body) = digits("bytes=2-5").await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(content_range.as_deref(), Some("bytes 2-5/10"));
+        assert_eq!(body, "2345");
+    }
+
+    #[tokio::test]
+    async fn sends_open_ended_range() {
+        let (status, content_range, body) = digits("bytes=7-").await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(content_range.as_deref(), Some("bytes 7-9/10"));
+        assert_eq!(body, "789");
+    }
+
+    #[tokio::test]
+    async fn rejects_unsatisfiable_range() {
+        let (status, content_range, body) = digits("bytes=10-12").await;
+        assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(content_range.as_deref(), Some("bytes */10"));
+        assert_eq!(body, "");
+    }
+}
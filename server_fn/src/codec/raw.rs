@@ -0,0 +1,219 @@
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    error::ServerFnError,
+    request::{ClientReq, Req},
+    response::{ClientRes, Res},
+};
+use bytes::Bytes;
+use http::Method;
+
+/// Pass arguments and receive responses as raw bytes in a `POST` request, without
+/// any serialization.
+///
+/// The argument and the return value are both a [`RawBody`], which holds the bytes
+/// together with their content type, so that a file such as an image can be sent
+/// and returned as it is.
+///
+/// ```rust,ignore
+/// #[server(input = Raw, output = Raw)]
+/// pub async fn thumbnail(image: RawBody) -> Result<RawBody, ServerFnError> {
+///     let thumbnail = resize(image.bytes())?;
+///     Ok(RawBody::new("image/png", thumbnail))
+/// }
+/// ```
+pub struct Raw;
+
+impl Encoding for Raw {
+    const CONTENT_TYPE: &'static str = "application/octet-stream";
+    const METHOD: Method = Method::POST;
+}
+
+/// A body sent or received with the [`Raw`] encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawBody {
+    content_type: String,
+    data: Bytes,
+}
+
+impl RawBody {
+    /// Creates a body with the given content type.
+    pub fn new(
+        content_type: impl Into<String>,
+        data: impl Into<Bytes>,
+    ) -> Self {
+        Self {
+            content_type: content_type.into(),
+            data: data.into(),
+        }
+    }
+
+    /// The MIME type of the body.
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// The bytes of the body.
+    pub fn bytes(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Consumes the body, returning its bytes.
+    pub fn into_bytes(self) -> Bytes {
+        self.data
+    }
+}
+
+impl From<Bytes> for RawBody {
+    /// Creates a body with the `application/octet-stream` content type.
+    fn from(data: Bytes) -> Self {
+        Self::new(Raw::CONTENT_TYPE, data)
+    }
+}
+
+impl From<Vec<u8>> for RawBody {
+    /// Creates a body with the `application/octet-stream` content type.
+    fn from(data: Vec<u8>) -> Self {
+        Self::new(Raw::CONTENT_TYPE, data)
+    }
+}
+
+impl<T, Request, Err> IntoReq<Raw, Request, Err> for T
+where
+    Request: ClientReq<Err>,
+    T: Into<RawBody>,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<Err>> {
+        let body = self.into();
+        Request::try_new_post_bytes(
+            path,
+            accepts,
+            &body.content_type,
+            body.data,
+        )
+    }
+}
+
+impl<T, Request, Err> FromReq<Raw, Request, Err> for T
+where
+    Request: Req<Err> + Send,
+    T: From<RawBody>,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<Err>> {
+        let content_type = req
+            .to_content_type()
+            .map(|content_type| content_type.into_owned())
+            .unwrap_or_else(|| Raw::CONTENT_TYPE.to_string());
+        let data = req.try_into_bytes().await?;
+        Ok(RawBody::new(content_type, data).into())
+    }
+}
+
+impl<Response, Err> IntoRes<Raw, Response, Err> for RawBody
+where
+    Response: Res<Err>,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<Err>> {
+        Response::try_from_bytes(&self.content_type, self.data)
+    }
+}
+
+impl<Response, Err> FromRes<Raw, Response, Err> for RawBody
+where
+    Response: ClientRes<Err> + Send,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<Err>> {
+        let content_type = res
+            .content_type()
+            .unwrap_or_else(|| Raw::CONTENT_TYPE.to_string());
+        let data = res.try_into_bytes().await?;
+        Ok(RawBody::new(content_type, data))
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{Raw, RawBody};
+    use crate::{
+        codec::{test_client::ServerOnly, FromRes, IntoReq},
+        error::NoCustomError,
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use http::{header, Request, Response};
+
+    /// The signature and the first bytes of the `IHDR` chunk of a PNG image.
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    /// What the `#[server]` macro generates for
+    /// `async fn echo(image: RawBody) -> Result<RawBody, ServerFnError>`.
+    struct Echo {
+        image: RawBody,
+    }
+
+    impl From<RawBody> for Echo {
+        fn from(image: RawBody) -> Self {
+            Echo { image }
+        }
+    }
+
+    impl From<Echo> for RawBody {
+        fn from(value: Echo) -> Self {
+            value.image
+        }
+    }
+
+    impl ServerFn for Echo {
+        const PATH: &'static str = "/api/echo";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = RawBody;
+        type InputEncoding = Raw;
+        type OutputEncoding = Raw;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<RawBody, ServerFnError> {
+            Ok(self.image)
+        }
+    }
+
+    #[tokio::test]
+    async fn echoes_png_with_its_content_type() {
+        let upload = Echo {
+            image: RawBody::new("image/png", PNG),
+        };
+        let req: Request<Bytes> = IntoReq::<Raw, _, NoCustomError>::into_req(
+            upload,
+            Echo::PATH,
+            "image/png",
+        )
+        .unwrap();
+        assert_eq!(req.headers()[header::CONTENT_TYPE], "image/png");
+
+        let res = Echo::run_on_server(req.map(Body::from)).await;
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "image/png");
+
+        let echoed = <RawBody as FromRes<Raw, _, NoCustomError>>::from_res(res)
+            .await
+            .unwrap();
+        assert_eq!(echoed, RawBody::new("image/png", PNG));
+    }
+
+    #[tokio::test]
+    async fn defaults_to_octet_stream() {
+        let req = Request::post(Echo::PATH)
+            .body(Body::from(vec![1, 2, 3]))
+            .unwrap();
+        let res = Echo::run_on_server(req).await;
+        assert_eq!(
+            res.headers()[header::CONTENT_TYPE],
+            "application/octet-stream"
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+thread_local! {
+    static CURRENT: RefCell<Option<RequestHeaders>> = const { RefCell::new(None) };
+}
+
+/// The headers of the request being handled that change how an output encoding
+/// writes its response, like the `Accept` header for [`Negotiate`](super::Negotiate).
+///
+/// Encodings don't see the request, so these are captured before the server
+/// function runs, and read back while its output is encoded.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RequestHeaders {
+    /// The `Accept` header.
+    pub(crate) accept: Option<String>,
+    /// The `Range` header.
+    pub(crate) range: Option<String>,
+}
+
+impl RequestHeaders {
+    /// Calls `f` with the headers of the request currently being handled, which
+    /// are empty outside of a server function.
+    pub(crate) fn with_current<T>(f: impl FnOnce(&RequestHeaders) -> T) -> T {
+        CURRENT.with(|current| match &*current.borrow() {
+            Some(headers) => f(headers),
+            None => f(&RequestHeaders::default()),
+        })
+    }
+
+    /// Makes these headers available from [`RequestHeaders::with_current`] while
+    /// `fut` runs.
+    pub(crate) fn scope<F: Future>(
+        self,
+        fut: F,
+    ) -> impl Future<Output = F::Output> {
+        Scoped {
+            headers: self,
+            fut: Box::pin(fut),
+        }
+    }
+
+    /// Makes these headers available from [`RequestHeaders::with_current`] while
+    /// `f` runs.
+    pub(crate) fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        let prev = CURRENT.with(|current| current.replace(Some(self.clone())));
+        let res = f();
+        CURRENT.with(|current| *current.borrow_mut() = prev);
+        res
+    }
+}
+
+struct Scoped<F> {
+    headers: RequestHeaders,
+    fut: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let prev =
+            CURRENT.with(|current| current.replace(Some(self.headers.clone())));
+        let res = self.fut.as_mut().poll(cx);
+        CURRENT.with(|current| *current.borrow_mut() = prev);
+        res
+    }
+}
@@ -14,6 +14,9 @@ use rkyv::{
 };
 
 /// Pass arguments and receive responses using `rkyv` in a `POST` request.
+///
+/// Archived bytes are always validated with `bytecheck` before they are read, and
+/// malformed bytes are rejected with a [`ServerFnError`].
 pub struct Rkyv;
 
 impl Encoding for Rkyv {
@@ -95,7 +98,56 @@ where
 {
     async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
         let data = res.try_into_bytes().await?;
-        rkyv::from_bytes::<T>(&data)
+        let mut aligned = AlignedVec::with_capacity(data.len());
+        aligned.extend_from_slice(&data);
+        rkyv::from_bytes::<T>(&aligned)
             .map_err(|e| ServerFnError::Deserialization(e.to_string()))
     }
 }
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Rkyv;
+    use crate::{codec::FromReq, error::NoCustomError, ServerFnError};
+    use axum::body::Body;
+    use http::Request;
+    use rkyv::{Archive, Deserialize, Serialize};
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[archive(check_bytes)]
+    struct Post {
+        id: u32,
+        title: String,
+    }
+
+    fn request(body: impl Into<Body>) -> Request<Body> {
+        Request::post("/api/post").body(body.into()).unwrap()
+    }
+
+    fn archived(post: &Post) -> Vec<u8> {
+        rkyv::to_bytes::<_, 256>(post).unwrap().to_vec()
+    }
+
+    #[tokio::test]
+    async fn rejects_malformed_bytes() {
+        let malformed = request(vec![0xff; 16]);
+        let post =
+            <Post as FromReq<Rkyv, _, NoCustomError>>::from_req(malformed)
+                .await;
+        assert!(matches!(post, Err(ServerFnError::Args(_))));
+    }
+
+    #[tokio::test]
+    async fn reads_valid_bytes() {
+        let post = Post {
+            id: 1,
+            title: "Hello".to_string(),
+        };
+        let read = <Post as FromReq<Rkyv, _, NoCustomError>>::from_req(
+            request(archived(&post)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(read, post);
+    }
+}
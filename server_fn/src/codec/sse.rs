@@ -0,0 +1,387 @@
+use super::{Encoding, FromRes};
+use crate::{
+    error::{NoCustomError, ServerFnError},
+    response::{ClientRes, Res},
+    IntoRes,
+};
+use bytes::{Bytes, BytesMut};
+use futures::{stream, Stream, StreamExt};
+use http::Method;
+use std::{fmt::Debug, pin::Pin, time::Duration};
+
+/// An encoding that represents a stream of
+/// [server-sent events](https://html.spec.whatwg.org/multipage/server-sent-events.html),
+/// which browsers can consume with an `EventSource`.
+///
+/// A server function that uses this as its output encoding should return [`SseStream`].
+/// The response is sent with `Cache-Control: no-cache`, so that proxies pass events on
+/// as they are produced.
+///
+/// ```rust,ignore
+/// #[server(output = Sse)]
+/// pub async fn prices() -> Result<SseStream, ServerFnError> {
+///     let events = price_updates()
+///         .map(|update| SseEvent::new(update.to_string()).event("price"));
+///     Ok(SseStream::from(events).keep_alive(Duration::from_secs(15)))
+/// }
+/// ```
+pub struct Sse;
+
+impl Encoding for Sse {
+    const CONTENT_TYPE: &'static str = "text/event-stream";
+    const METHOD: Method = Method::POST;
+}
+
+/// A single server-sent event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The event type. Browsers dispatch events without one as `message`.
+    pub event: Option<String>,
+    /// The event ID, which the browser sends back as `Last-Event-ID` when it reconnects.
+    pub id: Option<String>,
+    /// The event data, which may span multiple lines.
+    pub data: Option<String>,
+    /// How long the browser should wait before reconnecting, if the connection is lost.
+    pub retry: Option<Duration>,
+}
+
+impl SseEvent {
+    /// Creates a new event with the given data.
+    pub fn new(data: impl Into<String>) -> Self {
+        Self {
+            data: Some(data.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the event type.
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Sets the event ID.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the reconnection time.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    fn to_bytes<CustErr>(&self) -> Result<Bytes, ServerFnError<CustErr>> {
+        let mut out = String::new();
+        for (field, value) in
+            [("event", &self.event), ("id", &self.id)].into_iter()
+        {
+            if let Some(value) = value {
+                if value.contains(['\r', '\n']) {
+                    return Err(ServerFnError::Serialization(format!(
+                        "the SSE `{field}` field can't contain line breaks"
+                    )));
+                }
+                out.push_str(field);
+                out.push_str(": ");
+                out.push_str(value);
+                out.push('\n');
+            }
+        }
+        if let Some(retry) = self.retry {
+            out.push_str(&format!("retry: {}\n", retry.as_millis()));
+        }
+        if let Some(data) = &self.data {
+            for line in
+                data.split("\r\n").flat_map(|line| line.split(['\r', '\n']))
+            {
+                out.push_str("data: ");
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+        out.push('\n');
+        Ok(Bytes::from(out))
+    }
+}
+
+/// A stream of server-sent events.
+///
+/// A server function can return this type if its output encoding is [`Sse`].
+pub struct SseStream<CustErr = NoCustomError> {
+    events: Pin<
+        Box<dyn Stream<Item = Result<SseEvent, ServerFnError<CustErr>>> + Send>,
+    >,
+    keep_alive: Option<Duration>,
+}
+
+impl<CustErr> Debug for SseStream<CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SseStream")
+            .field("keep_alive", &self.keep_alive)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SseStream {
+    /// Creates a new `SseStream` from the given stream.
+    pub fn new(
+        value: impl Stream<Item = Result<SseEvent, ServerFnError>> + Send + 'static,
+    ) -> Self {
+        Self {
+            events: Box::pin(value),
+            keep_alive: None,
+        }
+    }
+}
+
+impl<CustErr> SseStream<CustErr> {
+    /// Sends a comment whenever no event has been sent for `interval`, so that proxies
+    /// and load balancers don't close the connection while it is idle.
+    ///
+    /// Keep-alive comments are only sent by the server integrations.
+    pub fn keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = Some(interval);
+        self
+    }
+
+    /// Consumes the wrapper, returning a stream of events.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<SseEvent, ServerFnError<CustErr>>> + Send
+    {
+        self.events
+    }
+}
+
+impl<S> From<S> for SseStream
+where
+    S: Stream<Item = SseEvent> + Send + 'static,
+{
+    fn from(value: S) -> Self {
+        Self::new(value.map(Ok))
+    }
+}
+
+impl<CustErr, Response> IntoRes<Sse, Response, CustErr> for SseStream<CustErr>
+where
+    Response: Res<CustErr>,
+    CustErr: Send + 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        let events = self.events.map(|event| event?.to_bytes());
+        #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+        let events: Pin<Box<dyn Stream<Item = _> + Send>> =
+            match self.keep_alive {
+                Some(interval) => Box::pin(with_keep_alive(events, interval)),
+                None => Box::pin(events),
+            };
+        let mut res = Response::try_from_stream(Sse::CONTENT_TYPE, events)?;
+        res.insert_header("cache-control", "no-cache");
+        Ok(res)
+    }
+}
+
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+fn with_keep_alive<CustErr>(
+    events: impl Stream<Item = Result<Bytes, ServerFnError<CustErr>>>
+        + Send
+        + 'static,
+    interval: Duration,
+) -> impl Stream<Item = Result<Bytes, ServerFnError<CustErr>>> + Send {
+    stream::unfold(Box::pin(events), move |mut events| async move {
+        match tokio::time::timeout(interval, events.next()).await {
+            Ok(event) => event.map(|event| (event, events)),
+            // an empty comment, which `EventSource` ignores
+            Err(_) => Some((Ok(Bytes::from_static(b":\n\n")), events)),
+        }
+    })
+}
+
+impl<CustErr, Response> FromRes<Sse, Response, CustErr> for SseStream
+where
+    Response: ClientRes<CustErr> + Send,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let chunks = res.try_into_stream()?;
+        Ok(SseStream::new(parse_events(chunks)))
+    }
+}
+
+/// Parses a stream of chunks into events, whatever the chunk boundaries are.
+fn parse_events(
+    chunks: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+) -> impl Stream<Item = Result<SseEvent, ServerFnError>> + Send {
+    let chunks = Box::pin(chunks);
+    let state = (chunks, BytesMut::new(), SseEvent::default());
+    stream::unfold(state, |(mut chunks, mut buf, mut event)| async move {
+        loop {
+            let Some(end) = buf.iter().position(|b| *b == b'\n') else {
+                match chunks.next().await? {
+                    Ok(chunk) => buf.extend_from_slice(&chunk),
+                    Err(e) => return Some((Err(e), (chunks, buf, event))),
+                }
+                continue;
+            };
+            let line = buf.split_to(end + 1);
+            let line = match String::from_utf8(line.to_vec()) {
+                Ok(line) => line,
+                Err(e) => {
+                    let err = ServerFnError::Deserialization(e.to_string());
+                    return Some((Err(err), (chunks, buf, event)));
+                }
+            };
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                if event != SseEvent::default() {
+                    let done = std::mem::take(&mut event);
+                    return Some((Ok(done), (chunks, buf, event)));
+                }
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value).to_string();
+            match field {
+                "event" => event.event = Some(value),
+                "id" => event.id = Some(value),
+                "retry" => {
+                    if let Ok(ms) = value.parse() {
+                        event.retry = Some(Duration::from_millis(ms));
+                    }
+                }
+                "data" => match &mut event.data {
+                    Some(data) => {
+                        data.push('\n');
+                        data.push_str(&value);
+                    }
+                    None => event.data = Some(value),
+                },
+                _ => {}
+            }
+        }
+    })
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{Sse, SseEvent, SseStream};
+    use crate::{
+        codec::{FromRes, IntoRes},
+        error::NoCustomError,
+    };
+    use axum::body::Body;
+    use futures::{stream, StreamExt};
+    use http::{header, Response};
+    use http_body_util::BodyExt;
+    use std::time::Duration;
+
+    async fn render(stream: SseStream) -> (Response<()>, String) {
+        let res: Response<Body> =
+            <SseStream as IntoRes<Sse, _, NoCustomError>>::into_res(stream)
+                .await
+                .unwrap();
+        let (parts, body) = res.into_parts();
+        let body = body.collect().await.unwrap().to_bytes();
+        (
+            Response::from_parts(parts, ()),
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    async fn parse(res: Response<Body>) -> Vec<SseEvent> {
+        <SseStream as FromRes<Sse, _, NoCustomError>>::from_res(res)
+            .await
+            .unwrap()
+            .into_inner()
+            .map(Result::unwrap)
+            .collect()
+            .await
+    }
+
+    fn events() -> Vec<SseEvent> {
+        vec![
+            SseEvent::new("first line\nsecond line"),
+            SseEvent::new("{\"price\":42}")
+                .event("price")
+                .id("7")
+                .retry(Duration::from_secs(3)),
+        ]
+    }
+
+    #[tokio::test]
+    async fn formats_events() {
+        let (res, body) = render(SseStream::from(stream::iter(events()))).await;
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "text/event-stream");
+        assert_eq!(res.headers()[header::CACHE_CONTROL], "no-cache");
+        assert_eq!(
+            body,
+            "data: first line\n\
+             data: second line\n\
+             \n\
+             event: price\n\
+             id: 7\n\
+             retry: 3000\n\
+             data: {\"price\":42}\n\
+             \n"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_line_breaks_in_event_names() {
+        let stream = SseStream::from(stream::iter([
+            SseEvent::new("ok").event("price\nid: 1")
+        ]));
+        let res: Response<Body> =
+            <SseStream as IntoRes<Sse, _, NoCustomError>>::into_res(stream)
+                .await
+                .unwrap();
+        assert!(res.into_body().collect().await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sends_keep_alive_comments_while_idle() {
+        let events = stream::once(async {
+            tokio::time::sleep(Duration::from_secs(25)).await;
+            SseEvent::new("late")
+        });
+        let stream =
+            SseStream::from(events).keep_alive(Duration::from_secs(10));
+        let (_, body) = render(stream).await;
+        assert_eq!(body, ":\n\n:\n\ndata: late\n\n");
+    }
+
+    #[tokio::test]
+    async fn round_trips_events() {
+        let res: Response<Body> =
+            <SseStream as IntoRes<Sse, _, NoCustomError>>::into_res(
+                SseStream::from(stream::iter(events()))
+                    .keep_alive(Duration::from_secs(10)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(parse(res).await, events());
+    }
+
+    #[tokio::test]
+    async fn parses_events_split_across_chunks() {
+        let chunks = [
+            ": ignored comment\r\nevent: tick\r\nda",
+            "ta: a\r\ndata:b\r\n\r",
+            "\n\nid: 3\n\ndata: unterminated",
+        ];
+        let body = Body::from_stream(stream::iter(
+            chunks.map(Ok::<_, std::convert::Infallible>),
+        ));
+        assert_eq!(
+            parse(Response::new(body)).await,
+            [
+                SseEvent::new("a\nb").event("tick"),
+                SseEvent::default().id("3")
+            ]
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use super::{Encoding, FromReq, FromRes, IntoReq};
+use super::{
+    range::{ranged_response, slice_stream},
+    Encoding, FromReq, FromRes, IntoReq,
+};
 use crate::{
     error::{NoCustomError, ServerFnError},
     request::{ClientReq, Req},
@@ -14,6 +17,10 @@ use std::{fmt::Debug, pin::Pin};
 ///
 /// A server function that uses this as its output encoding should return [`ByteStream`].
 ///
+/// When used as the input encoding, the server function receives the request body as a
+/// [`ByteStream`] that yields each chunk as it arrives, so large uploads can be processed
+/// incrementally (for example, written to disk) without holding the whole body in memory.
+///
 /// ## Browser Support for Streaming Input
 ///
 /// Browser fetch requests do not currently support full request duplexing, which
@@ -59,6 +66,13 @@ where
 ///
 /// A server function can return this type if its output encoding is [`Streaming`].
 ///
+/// The response body pulls from the stream only when the connection is ready to send
+/// more data, so a client that reads slowly pauses the stream rather than letting
+/// chunks pile up in memory on the server.
+///
+/// If the total length of the stream is set with [`ByteStream::with_content_length`],
+/// the response answers `Range` requests with just the bytes that were asked for.
+///
 /// ## Browser Support for Streaming Input
 ///
 /// Browser fetch requests do not currently support full request duplexing, which
@@ -69,6 +83,7 @@ where
 /// Streaming requests are only allowed over HTTP2 or HTTP3.
 pub struct ByteStream<CustErr = NoCustomError>(
     Pin<Box<dyn Stream<Item = Result<Bytes, ServerFnError<CustErr>>> + Send>>,
+    Option<u64>,
 );
 
 impl<CustErr> ByteStream<CustErr> {
@@ -78,6 +93,20 @@ impl<CustErr> ByteStream<CustErr> {
     ) -> impl Stream<Item = Result<Bytes, ServerFnError<CustErr>>> + Send {
         self.0
     }
+
+    /// Sets the total length of the stream in bytes.
+    ///
+    /// The response then has a `Content-Length`, and a request for a byte range is
+    /// answered with only those bytes. The stream must yield exactly `len` bytes.
+    pub fn with_content_length(mut self, len: u64) -> Self {
+        self.1 = Some(len);
+        self
+    }
+
+    /// The total length of the stream in bytes, if it is known.
+    pub fn content_length(&self) -> Option<u64> {
+        self.1
+    }
 }
 
 impl<CustErr> Debug for ByteStream<CustErr> {
@@ -94,7 +123,7 @@ impl ByteStream {
     where
         T: Into<Bytes>,
     {
-        Self(Box::pin(value.map(|value| value.map(Into::into))))
+        Self(Box::pin(value.map(|value| value.map(Into::into))), None)
     }
 }
 
@@ -104,7 +133,7 @@ where
     T: Into<Bytes>,
 {
     fn from(value: S) -> Self {
-        Self(Box::pin(value.map(|data| Ok(data.into()))))
+        Self(Box::pin(value.map(|data| Ok(data.into()))), None)
     }
 }
 
@@ -115,7 +144,10 @@ where
     CustErr: 'static,
 {
     async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
-        Response::try_from_stream(Streaming::CONTENT_TYPE, self.into_inner())
+        let stream = self.0;
+        ranged_response(Streaming::CONTENT_TYPE, self.1, |start, len| {
+            slice_stream(stream, start, len)
+        })
     }
 }
 
@@ -125,7 +157,7 @@ where
 {
     async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
         let stream = res.try_into_stream()?;
-        Ok(ByteStream(Box::pin(stream)))
+        Ok(ByteStream(Box::pin(stream), None))
     }
 }
 
@@ -175,7 +207,7 @@ impl TextStream {
     pub fn new(
         value: impl Stream<Item = Result<String, ServerFnError>> + Send + 'static,
     ) -> Self {
-        Self(Box::pin(value.map(|value| value.map(Into::into))))
+        Self(Box::pin(value))
     }
 }
 
@@ -263,3 +295,188 @@ where
         }))))
     }
 }
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{ByteStream, Streaming};
+    use crate::{
+        codec::{FromReq, IntoRes},
+        error::NoCustomError,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use futures::StreamExt;
+    use http::{Request, Response};
+    use http_body_util::BodyExt;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    const CHUNK: usize = 64 * 1024;
+    const TOTAL: usize = 10 * 1024 * 1024;
+
+    #[tokio::test]
+    async fn streams_request_body_without_buffering() {
+        let produced = Arc::new(AtomicUsize::new(0));
+        let body = {
+            let produced = Arc::clone(&produced);
+            futures::stream::iter(0..TOTAL / CHUNK).map(move |_| {
+                produced.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, std::io::Error>(Bytes::from(vec![7u8; CHUNK]))
+            })
+        };
+        let req = Request::post("/api/upload")
+            .body(Body::from_stream(body))
+            .unwrap();
+
+        let stream =
+            <ByteStream as FromReq<Streaming, _, NoCustomError>>::from_req(req)
+                .await
+                .unwrap();
+        let mut stream = stream.into_inner();
+        let mut read = 0;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.unwrap();
+            assert!(chunk.iter().all(|byte| *byte == 7));
+            read += chunk.len();
+            // the body is only pulled as the handler reads it
+            let ahead = produced.load(Ordering::SeqCst) * CHUNK - read;
+            assert!(
+                ahead <= CHUNK,
+                "{ahead} bytes buffered ahead of the reader"
+            );
+        }
+        assert_eq!(read, TOTAL);
+    }
+
+    /// Lets spawned tasks run until they are blocked.
+    async fn settle() {
+        for _ in 0..16 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn slow_client_applies_backpressure() {
+        const CHUNKS: usize = 100;
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<Bytes>(1);
+        let sent = Arc::new(AtomicUsize::new(0));
+        let producer = tokio::spawn({
+            let sent = Arc::clone(&sent);
+            async move {
+                for _ in 0..CHUNKS {
+                    tx.send(Bytes::from(vec![7u8; CHUNK])).await.unwrap();
+                    sent.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        });
+        let stream = ByteStream::from(futures::stream::poll_fn(move |cx| {
+            rx.poll_recv(cx)
+        }));
+        let res: Response<Body> =
+            IntoRes::<Streaming, _, NoCustomError>::into_res(stream)
+                .await
+                .unwrap();
+        let mut body = res.into_body();
+
+        // nothing is read, so the producer stops once the channel is full
+        settle().await;
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+
+        // each frame the client reads makes room for one more chunk
+        for read in 1..=3 {
+            body.frame().await.unwrap().unwrap();
+            settle().await;
+            assert!(sent.load(Ordering::SeqCst) <= read + 2);
+        }
+        assert!(!producer.is_finished());
+
+        let mut frames = 3;
+        while let Some(frame) = body.frame().await {
+            frame.unwrap();
+            frames += 1;
+        }
+        assert_eq!(frames, CHUNKS);
+        producer.await.unwrap();
+    }
+}
+
+#[cfg(all(test, feature = "actix"))]
+mod actix_tests {
+    use super::{ByteStream, Streaming};
+    use crate::{
+        codec::IntoRes, error::NoCustomError, response::actix::ActixResponse,
+    };
+    use actix_web::body::{BoxBody, MessageBody};
+    use bytes::Bytes;
+    use std::{
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    const CHUNK: usize = 64 * 1024;
+
+    /// Lets spawned tasks run until they are blocked.
+    async fn settle() {
+        for _ in 0..16 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// Reads the next chunk of the body, like a client would.
+    async fn next_chunk(
+        body: &mut BoxBody,
+    ) -> Option<Result<Bytes, Box<dyn std::error::Error>>> {
+        futures::future::poll_fn(|cx| Pin::new(&mut *body).poll_next(cx)).await
+    }
+
+    #[actix_web::test]
+    async fn slow_client_applies_backpressure() {
+        const CHUNKS: usize = 100;
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<Bytes>(1);
+        let sent = Arc::new(AtomicUsize::new(0));
+        let producer = tokio::spawn({
+            let sent = Arc::clone(&sent);
+            async move {
+                for _ in 0..CHUNKS {
+                    tx.send(Bytes::from(vec![7u8; CHUNK])).await.unwrap();
+                    sent.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        });
+        let stream = ByteStream::from(futures::stream::poll_fn(move |cx| {
+            rx.poll_recv(cx)
+        }));
+        let res: ActixResponse =
+            IntoRes::<Streaming, _, NoCustomError>::into_res(stream)
+                .await
+                .unwrap();
+        let mut body = res.take().into_body();
+
+        // nothing is read, so the producer stops once the channel is full
+        settle().await;
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+
+        // each chunk the client reads makes room for one more
+        for read in 1..=3 {
+            next_chunk(&mut body).await.unwrap().unwrap();
+            settle().await;
+            assert!(sent.load(Ordering::SeqCst) <= read + 2);
+        }
+        assert!(!producer.is_finished());
+
+        let mut chunks = 3;
+        while let Some(chunk) = next_chunk(&mut body).await {
+            chunk.unwrap();
+            chunks += 1;
+        }
+        assert_eq!(chunks, CHUNKS);
+        producer.await.unwrap();
+    }
+}
@@ -0,0 +1,105 @@
+//! A client that builds plain [`http`] requests and reads responses straight from the
+//! server, so codecs can be tested end to end without a network connection.
+//!
+//! Responses are read with the [`ClientRes`](crate::response::ClientRes) impl for
+//! [`Response<Body>`] used by [`call_local`](crate::axum::call_local).
+
+use crate::{client::Client, error::ServerFnError, request::ClientReq};
+use axum::body::Body;
+use bytes::Bytes;
+use futures::Stream;
+use http::{header, Method, Request, Response};
+
+fn request<CustErr>(
+    method: Method,
+    uri: &str,
+    accepts: &str,
+    content_type: &str,
+    body: Bytes,
+) -> Result<Request<Bytes>, ServerFnError<CustErr>> {
+    let mut req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::ACCEPT, accepts);
+    if !content_type.is_empty() {
+        req = req.header(header::CONTENT_TYPE, content_type);
+    }
+    req.body(body)
+        .map_err(|e| ServerFnError::Request(e.to_string()))
+}
+
+impl<CustErr> ClientReq<CustErr> for Request<Bytes> {
+    type FormData = ();
+
+    fn try_new_get(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        query: &str,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        let uri = if query.is_empty() {
+            path.to_string()
+        } else {
+            format!("{path}?{query}")
+        };
+        request(Method::GET, &uri, accepts, content_type, Bytes::new())
+    }
+
+    fn try_new_post(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: String,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        request(Method::POST, path, accepts, content_type, body.into())
+    }
+
+    fn try_new_post_bytes(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        request(Method::POST, path, accepts, content_type, body)
+    }
+
+    fn try_new_post_form_data(
+        _path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        unimplemented!("form data is not supported by the test client")
+    }
+
+    fn try_new_multipart(
+        _path: &str,
+        _accepts: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        unimplemented!("multipart is not supported by the test client")
+    }
+
+    fn try_new_streaming(
+        _path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        _body: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        unimplemented!("streaming is not supported by the test client")
+    }
+}
+
+/// A [`Client`] for server functions that are only called on the server in tests.
+pub(crate) struct ServerOnly;
+
+impl<CustErr> Client<CustErr> for ServerOnly {
+    type Request = Request<Bytes>;
+    type Response = Response<Body>;
+
+    async fn send(
+        _req: Self::Request,
+    ) -> Result<Self::Response, ServerFnError<CustErr>> {
+        unimplemented!("only called on the server")
+    }
+}
@@ -1,5 +1,6 @@
 use super::{Encoding, FromReq, IntoReq};
 use crate::{
+    client::{get_client_config, server_fn_url},
     error::ServerFnError,
     request::{ClientReq, Req},
 };
@@ -12,6 +13,14 @@ pub struct GetUrl;
 /// Pass arguments as the URL-encoded body of a `POST` request.
 pub struct PostUrl;
 
+/// Send a bare `GET` request, without a query string or a `Content-Type`, for
+/// server functions that take no arguments.
+///
+/// The `#[server]` macro uses this in place of [`GetUrl`] for server functions
+/// without arguments. Nothing is encoded on the client, and the request is not read
+/// on the server, where the arguments are created with [`Default`] instead.
+pub struct GetNoArgs;
+
 impl Encoding for GetUrl {
     const CONTENT_TYPE: &'static str = "application/x-www-form-urlencoded";
     const METHOD: Method = Method::GET;
@@ -29,6 +38,18 @@ where
     ) -> Result<Request, ServerFnError<CustErr>> {
         let data = serde_qs::to_string(&self)
             .map_err(|e| ServerFnError::Serialization(e.to_string()))?;
+        let too_long =
+            get_client_config().max_url_length().is_some_and(|max| {
+                server_fn_url(path).len() + "?".len() + data.len() > max
+            });
+        if too_long {
+            return Request::try_new_post(
+                path,
+                accepts,
+                GetUrl::CONTENT_TYPE,
+                data,
+            );
+        }
         Request::try_new_get(path, accepts, GetUrl::CONTENT_TYPE, &data)
     }
 }
@@ -39,13 +60,46 @@ where
     T: DeserializeOwned,
 {
     async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
-        let string_data = req.as_query().unwrap_or_default();
-        let args = serde_qs::from_str::<Self>(string_data)
+        let string_data = match req.as_query() {
+            Some(query) => query.to_owned(),
+            // sent as a `POST` by a client whose URL would have been too long
+            None => req.try_into_string().await?,
+        };
+        let args = serde_qs::from_str::<Self>(&string_data)
             .map_err(|e| ServerFnError::Args(e.to_string()))?;
         Ok(args)
     }
 }
 
+impl Encoding for GetNoArgs {
+    const CONTENT_TYPE: &'static str = GetUrl::CONTENT_TYPE;
+    const METHOD: Method = Method::GET;
+}
+
+impl<CustErr, T, Request> IntoReq<GetNoArgs, Request, CustErr> for T
+where
+    Request: ClientReq<CustErr>,
+    T: Send,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        Request::try_new_get(path, accepts, "", "")
+    }
+}
+
+impl<CustErr, T, Request> FromReq<GetNoArgs, Request, CustErr> for T
+where
+    Request: Req<CustErr> + Send + 'static,
+    T: Default,
+{
+    async fn from_req(_req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(T::default())
+    }
+}
+
 impl Encoding for PostUrl {
     const CONTENT_TYPE: &'static str = "application/x-www-form-urlencoded";
     const METHOD: Method = Method::POST;
@@ -0,0 +1,505 @@
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    error::ServerFnError,
+    request::{BrowserMockReq, ClientReq},
+    response::ClientRes,
+};
+use bytes::Bytes;
+use futures::{
+    channel::oneshot, future, Future, Sink, SinkExt, Stream, StreamExt,
+};
+use http::Method;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{any::Any, fmt::Debug, marker::PhantomData, pin::Pin};
+
+/// An encoding that upgrades the request to a WebSocket connection, for bidirectional
+/// communication with a server function.
+///
+/// The server function takes a [`WebSocketDuplex`] as its only argument and returns a
+/// [`WebSocketResponse`], which completes the handshake. Messages of type `In` are
+/// received from the client and messages of type `Out` are sent to it, each encoded
+/// with the message codec `C` ([`Json`](super::Json) as text frames or
+/// [`Cbor`](super::Cbor) as binary frames).
+///
+/// ```rust,ignore
+/// #[server(
+///     input = WebSocket<String, String, Json>,
+///     output = WebSocket<String, String, Json>
+/// )]
+/// pub async fn echo(
+///     socket: WebSocketDuplex<String, String>,
+/// ) -> Result<WebSocketResponse, ServerFnError> {
+///     Ok(socket.accept(|mut incoming, mut outgoing| async move {
+///         while let Some(message) = incoming.next().await {
+///             outgoing.send(message?).await?;
+///         }
+///         Ok(())
+///     }))
+/// }
+/// ```
+///
+/// The handshake is a `GET` request, so browsers can connect to the server function's
+/// URL with a `WebSocket`. Calling the server function through the HTTP client returns
+/// an error.
+pub struct WebSocket<In, Out, C>(PhantomData<(In, Out, C)>);
+
+impl<In, Out, C> Encoding for WebSocket<In, Out, C>
+where
+    C: Encoding,
+{
+    const CONTENT_TYPE: &'static str = C::CONTENT_TYPE;
+    const METHOD: Method = Method::GET;
+}
+
+/// A message sent over a WebSocket connection.
+///
+/// Ping, pong, and close frames are handled by the server integration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketMessage {
+    /// A text message.
+    Text(String),
+    /// A binary message.
+    Binary(Bytes),
+}
+
+/// Encodes and decodes the messages sent over a [`WebSocket`].
+pub trait WebSocketCodec {
+    /// Encodes a value as a message.
+    fn encode<T: Serialize>(
+        value: &T,
+    ) -> Result<WebSocketMessage, ServerFnError>;
+
+    /// Decodes a value from a message.
+    fn decode<T: DeserializeOwned>(
+        message: WebSocketMessage,
+    ) -> Result<T, ServerFnError>;
+}
+
+#[cfg(feature = "json")]
+impl WebSocketCodec for super::Json {
+    fn encode<T: Serialize>(
+        value: &T,
+    ) -> Result<WebSocketMessage, ServerFnError> {
+        serde_json::to_string(value)
+            .map(WebSocketMessage::Text)
+            .map_err(|e| ServerFnError::Serialization(e.to_string()))
+    }
+
+    fn decode<T: DeserializeOwned>(
+        message: WebSocketMessage,
+    ) -> Result<T, ServerFnError> {
+        let result = match message {
+            WebSocketMessage::Text(text) => serde_json::from_str(&text),
+            WebSocketMessage::Binary(data) => serde_json::from_slice(&data),
+        };
+        result.map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl WebSocketCodec for super::Cbor {
+    fn encode<T: Serialize>(
+        value: &T,
+    ) -> Result<WebSocketMessage, ServerFnError> {
+        let mut buffer = Vec::new();
+        ciborium::ser::into_writer(value, &mut buffer)
+            .map(|_| WebSocketMessage::Binary(Bytes::from(buffer)))
+            .map_err(|e| ServerFnError::Serialization(e.to_string()))
+    }
+
+    fn decode<T: DeserializeOwned>(
+        message: WebSocketMessage,
+    ) -> Result<T, ServerFnError> {
+        let data = match message {
+            WebSocketMessage::Text(text) => Bytes::from(text),
+            WebSocketMessage::Binary(data) => data,
+        };
+        ciborium::de::from_reader(data.as_ref())
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+}
+
+/// The messages received over a WebSocket connection.
+pub type WebSocketReceiver<T = WebSocketMessage> =
+    Pin<Box<dyn Stream<Item = Result<T, ServerFnError>> + Send>>;
+
+/// Sends messages over a WebSocket connection.
+pub type WebSocketSender<T = WebSocketMessage> =
+    Pin<Box<dyn Sink<T, Error = ServerFnError> + Send>>;
+
+/// Runs a WebSocket connection, once the request has been upgraded.
+pub type OnUpgrade = Box<
+    dyn FnOnce(
+            WebSocketReceiver,
+            WebSocketSender,
+        ) -> Pin<Box<dyn Future<Output = ()> + Send>>
+        + Send,
+>;
+
+/// A server request that can be upgraded to a WebSocket connection.
+///
+/// This is the boundary between the [`WebSocket`] encoding and a server integration.
+pub trait WebSocketReq<CustErr>
+where
+    Self: Sized,
+{
+    /// The response that completes the handshake.
+    type Response: Send + 'static;
+
+    /// Checks the handshake request, and returns the response that completes it.
+    ///
+    /// Once the response has been sent and the connection upgraded, the integration
+    /// calls `on_upgrade` with the socket.
+    fn try_into_websocket(
+        self,
+        on_upgrade: OnUpgrade,
+    ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send;
+}
+
+impl<CustErr> WebSocketReq<CustErr> for BrowserMockReq {
+    type Response = ();
+
+    async fn try_into_websocket(
+        self,
+        _on_upgrade: OnUpgrade,
+    ) -> Result<Self::Response, ServerFnError<CustErr>> {
+        unreachable!()
+    }
+}
+
+/// A WebSocket connection that has not been accepted yet.
+///
+/// A server function using the [`WebSocket`] encoding receives this as its argument.
+pub struct WebSocketDuplex<In, Out> {
+    response: Box<dyn Any + Send>,
+    session: oneshot::Sender<OnUpgrade>,
+    encode: fn(&Out) -> Result<WebSocketMessage, ServerFnError>,
+    decode: fn(WebSocketMessage) -> Result<In, ServerFnError>,
+}
+
+impl<In, Out> Debug for WebSocketDuplex<In, Out> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketDuplex").finish_non_exhaustive()
+    }
+}
+
+impl<In, Out> WebSocketDuplex<In, Out>
+where
+    In: 'static,
+    Out: 'static,
+{
+    /// Accepts the connection, returning the response that completes the handshake.
+    ///
+    /// Once the connection is upgraded, `session` is called with a stream of the
+    /// messages from the client and a sink for the messages to the client. The
+    /// connection is closed when it returns. An error also ends the session.
+    pub fn accept<F, Fut>(self, session: F) -> WebSocketResponse
+    where
+        F: FnOnce(WebSocketReceiver<In>, WebSocketSender<Out>) -> Fut
+            + Send
+            + 'static,
+        Fut: Future<Output = Result<(), ServerFnError>> + Send + 'static,
+    {
+        let Self {
+            response,
+            session: start,
+            encode,
+            decode,
+        } = self;
+        let session: OnUpgrade = Box::new(move |receiver, sender| {
+            Box::pin(async move {
+                let incoming =
+                    receiver.map(move |message| message.and_then(decode));
+                let outgoing = sender
+                    .with(move |value: Out| future::ready(encode(&value)));
+                // the error has nowhere to go, but the connection closes either way
+                _ = session(Box::pin(incoming), Box::pin(outgoing)).await;
+            })
+        });
+        // if this fails, the connection was never upgraded
+        _ = start.send(session);
+        WebSocketResponse { response }
+    }
+}
+
+/// The response that completes a WebSocket handshake, created by
+/// [`WebSocketDuplex::accept`].
+pub struct WebSocketResponse {
+    response: Box<dyn Any + Send>,
+}
+
+impl Debug for WebSocketResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketResponse").finish_non_exhaustive()
+    }
+}
+
+fn client_error<CustErr>() -> ServerFnError<CustErr> {
+    ServerFnError::Request(
+        "WebSocket server functions can't be called with an HTTP client; \
+         connect to them with a WebSocket instead"
+            .into(),
+    )
+}
+
+impl<In, Out, C, T, Request, CustErr>
+    IntoReq<WebSocket<In, Out, C>, Request, CustErr> for T
+where
+    Request: ClientReq<CustErr>,
+    T: Into<WebSocketDuplex<In, Out>>,
+{
+    fn into_req(
+        self,
+        _path: &str,
+        _accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        Err(client_error())
+    }
+}
+
+impl<In, Out, C, T, Request, CustErr>
+    FromReq<WebSocket<In, Out, C>, Request, CustErr> for T
+where
+    Request: WebSocketReq<CustErr> + Send,
+    C: WebSocketCodec,
+    T: From<WebSocketDuplex<In, Out>>,
+    In: DeserializeOwned,
+    Out: Serialize,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        let (start, session) = oneshot::channel::<OnUpgrade>();
+        let on_upgrade: OnUpgrade = Box::new(move |receiver, sender| {
+            Box::pin(async move {
+                // the server function may fail instead of accepting the connection
+                if let Ok(session) = session.await {
+                    session(receiver, sender).await;
+                }
+            })
+        });
+        let response = req.try_into_websocket(on_upgrade).await?;
+        Ok(WebSocketDuplex {
+            response: Box::new(response),
+            session: start,
+            encode: C::encode::<Out>,
+            decode: C::decode::<In>,
+        }
+        .into())
+    }
+}
+
+impl<In, Out, C, Response, CustErr>
+    IntoRes<WebSocket<In, Out, C>, Response, CustErr> for WebSocketResponse
+where
+    Response: Send + 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        self.response
+            .downcast::<Response>()
+            .map(|response| *response)
+            .map_err(|_| {
+                ServerFnError::Response(
+                    "the WebSocket handshake was made for a different response \
+                     type"
+                        .into(),
+                )
+            })
+    }
+}
+
+impl<In, Out, C, Response, CustErr>
+    FromRes<WebSocket<In, Out, C>, Response, CustErr> for WebSocketResponse
+where
+    Response: ClientRes<CustErr> + Send,
+{
+    async fn from_res(_res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        Err(client_error())
+    }
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{OnUpgrade, WebSocketMessage, WebSocketReq};
+    use crate::error::ServerFnError;
+    use axum::{
+        body::Body,
+        extract::{
+            ws::{Message, WebSocketUpgrade},
+            FromRequestParts,
+        },
+        response::Response,
+    };
+    use futures::{future, SinkExt, StreamExt};
+    use http::Request;
+
+    impl<CustErr> WebSocketReq<CustErr> for Request<Body> {
+        type Response = Response;
+
+        async fn try_into_websocket(
+            self,
+            on_upgrade: OnUpgrade,
+        ) -> Result<Self::Response, ServerFnError<CustErr>> {
+            let (mut parts, _body) = self.into_parts();
+            let upgrade = WebSocketUpgrade::from_request_parts(&mut parts, &())
+                .await
+                .map_err(|e| ServerFnError::Request(e.to_string()))?;
+            Ok(upgrade.on_upgrade(|socket| {
+                let (sender, receiver) = socket.split();
+                let receiver = receiver.filter_map(|message| {
+                    future::ready(match message {
+                        Ok(Message::Text(text)) => {
+                            Some(Ok(WebSocketMessage::Text(text)))
+                        }
+                        Ok(Message::Binary(data)) => {
+                            Some(Ok(WebSocketMessage::Binary(data.into())))
+                        }
+                        Ok(_) => None,
+                        Err(e) => {
+                            Some(Err(ServerFnError::Request(e.to_string())))
+                        }
+                    })
+                });
+                let sender = sender
+                    .sink_map_err(|e| -> ServerFnError {
+                        ServerFnError::Response(e.to_string())
+                    })
+                    .with(|message| {
+                        future::ready(Ok::<_, ServerFnError>(match message {
+                            WebSocketMessage::Text(text) => Message::Text(text),
+                            WebSocketMessage::Binary(data) => {
+                                Message::Binary(data.into())
+                            }
+                        }))
+                    });
+                on_upgrade(Box::pin(receiver), Box::pin(sender))
+            }))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default", feature = "json"))]
+mod tests {
+    use super::{
+        OnUpgrade, WebSocket, WebSocketDuplex, WebSocketMessage, WebSocketReq,
+        WebSocketResponse,
+    };
+    use crate::{
+        codec::{FromReq, IntoRes, Json},
+        error::{NoCustomError, ServerFnError},
+    };
+    use axum::body::Body;
+    use futures::{channel::mpsc, SinkExt, StreamExt};
+    use http::{Response, StatusCode};
+    use std::sync::{Arc, Mutex};
+
+    type Echo = WebSocket<String, String, Json>;
+
+    /// A handshake request whose connection is upgraded in memory, by calling the
+    /// stored callback.
+    #[derive(Default, Clone)]
+    struct InMemoryUpgrade(Arc<Mutex<Option<OnUpgrade>>>);
+
+    impl WebSocketReq<NoCustomError> for InMemoryUpgrade {
+        type Response = Response<Body>;
+
+        async fn try_into_websocket(
+            self,
+            on_upgrade: OnUpgrade,
+        ) -> Result<Self::Response, ServerFnError> {
+            *self.0.lock().unwrap() = Some(on_upgrade);
+            let mut res = Response::new(Body::empty());
+            *res.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+            Ok(res)
+        }
+    }
+
+    /// The body of an echo server function.
+    async fn echo(
+        socket: WebSocketDuplex<String, String>,
+    ) -> Result<WebSocketResponse, ServerFnError> {
+        Ok(socket.accept(|mut incoming, mut outgoing| async move {
+            while let Some(message) = incoming.next().await {
+                outgoing.send(format!("echo: {}", message?)).await?;
+            }
+            Ok(())
+        }))
+    }
+
+    #[tokio::test]
+    async fn echoes_messages_over_an_in_memory_transport() {
+        let req = InMemoryUpgrade::default();
+        let socket = <WebSocketDuplex<String, String> as FromReq<
+            Echo,
+            _,
+            NoCustomError,
+        >>::from_req(req.clone())
+        .await
+        .unwrap();
+        let res = echo(socket).await.unwrap();
+        let res: Response<Body> =
+            <WebSocketResponse as IntoRes<Echo, _, NoCustomError>>::into_res(
+                res,
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+
+        // the handshake response has been sent, so upgrade the connection
+        let (mut client_tx, server_rx) = mpsc::unbounded::<WebSocketMessage>();
+        let (server_tx, mut client_rx) = mpsc::unbounded::<WebSocketMessage>();
+        let on_upgrade = req.0.lock().unwrap().take().unwrap();
+        let connection = tokio::spawn(on_upgrade(
+            Box::pin(server_rx.map(Ok)),
+            Box::pin(server_tx.sink_map_err(|e| -> ServerFnError {
+                ServerFnError::Response(e.to_string())
+            })),
+        ));
+
+        for message in ["hello", "world"] {
+            let message = WebSocketMessage::Text(format!("\"{message}\""));
+            client_tx.send(message).await.unwrap();
+        }
+        assert_eq!(
+            client_rx.next().await,
+            Some(WebSocketMessage::Text("\"echo: hello\"".into()))
+        );
+        assert_eq!(
+            client_rx.next().await,
+            Some(WebSocketMessage::Text("\"echo: world\"".into()))
+        );
+
+        // closing the connection ends the session
+        drop(client_tx);
+        connection.await.unwrap();
+        assert_eq!(client_rx.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn invalid_messages_end_the_session() {
+        let req = InMemoryUpgrade::default();
+        let socket = <WebSocketDuplex<String, String> as FromReq<
+            Echo,
+            _,
+            NoCustomError,
+        >>::from_req(req.clone())
+        .await
+        .unwrap();
+        echo(socket).await.unwrap();
+
+        let (mut client_tx, server_rx) = mpsc::unbounded::<WebSocketMessage>();
+        let (server_tx, mut client_rx) = mpsc::unbounded::<WebSocketMessage>();
+        let on_upgrade = req.0.lock().unwrap().take().unwrap();
+        let connection = tokio::spawn(on_upgrade(
+            Box::pin(server_rx.map(Ok)),
+            Box::pin(server_tx.sink_map_err(|e| -> ServerFnError {
+                ServerFnError::Response(e.to_string())
+            })),
+        ));
+
+        client_tx
+            .send(WebSocketMessage::Text("not json".into()))
+            .await
+            .unwrap();
+        connection.await.unwrap();
+        assert_eq!(client_rx.next().await, None);
+    }
+}
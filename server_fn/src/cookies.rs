@@ -0,0 +1,202 @@
+use crate::response::Res;
+pub use cookie::Cookie;
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+thread_local! {
+    static CURRENT: RefCell<Option<Cookies>> = const { RefCell::new(None) };
+}
+
+/// The cookies sent with the request that a server function is handling, and those
+/// it sets on its response.
+///
+/// While a server function runs, its cookies are available from [`Cookies::current`],
+/// whichever framework it is served with. Every cookie set with
+/// [`set_cookie`](Cookies::set_cookie) is added to the response in its own
+/// `Set-Cookie` header, even if the server function returns an error.
+///
+/// ```rust,ignore
+/// #[server]
+/// pub async fn login(user: String) -> Result<(), ServerFnError> {
+///     let cookies = Cookies::current().expect("called from a server function");
+///     if cookies.get("session").is_none() {
+///         cookies.set_cookie(
+///             Cookie::build(("session", new_session(&user)))
+///                 .http_only(true)
+///                 .build(),
+///         );
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Cookies(Arc<Mutex<CookiesInner>>);
+
+#[derive(Debug, Default)]
+struct CookiesInner {
+    request: Vec<Cookie<'static>>,
+    response: Vec<Cookie<'static>>,
+}
+
+impl Cookies {
+    /// Parses the value of a `Cookie` request header. Malformed cookies are skipped.
+    pub fn from_header(header: &str) -> Self {
+        let request = Cookie::split_parse(header.to_owned())
+            .filter_map(Result::ok)
+            .collect();
+        Self(Arc::new(Mutex::new(CookiesInner {
+            request,
+            response: Vec::new(),
+        })))
+    }
+
+    /// The cookies of the request currently being handled by a server function, or
+    /// `None` if called outside of a server function.
+    pub fn current() -> Option<Self> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// The value of the cookie called `name`.
+    ///
+    /// Cookies set with [`set_cookie`](Cookies::set_cookie) take precedence over
+    /// those sent with the request.
+    pub fn get(&self, name: &str) -> Option<String> {
+        let inner = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        inner
+            .response
+            .iter()
+            .rev()
+            .chain(inner.request.iter())
+            .find(|cookie| cookie.name() == name)
+            .map(|cookie| cookie.value().to_owned())
+    }
+
+    /// Sets a cookie on the response.
+    pub fn set_cookie(&self, cookie: Cookie<'static>) {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .response
+            .push(cookie);
+    }
+
+    /// Adds a `Set-Cookie` header to `res` for every cookie that has been set.
+    pub fn write_to<CustErr, R: Res<CustErr>>(&self, res: &mut R) {
+        let inner = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        for cookie in &inner.response {
+            res.append_header("set-cookie", &cookie.to_string());
+        }
+    }
+
+    /// Makes these cookies available from [`Cookies::current`] while `fut` runs.
+    pub fn scope<F: Future>(self, fut: F) -> impl Future<Output = F::Output> {
+        Scoped {
+            cookies: self,
+            fut: Box::pin(fut),
+        }
+    }
+}
+
+struct Scoped<F> {
+    cookies: Cookies,
+    fut: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let prev =
+            CURRENT.with(|current| current.replace(Some(self.cookies.clone())));
+        let res = self.fut.as_mut().poll(cx);
+        CURRENT.with(|current| *current.borrow_mut() = prev);
+        res
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{Cookie, Cookies};
+    use crate::{
+        codec::{test_client::ServerOnly, Json},
+        error::NoCustomError,
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+
+    /// Returns the current session, and starts a new one.
+    #[derive(Serialize, Deserialize)]
+    struct Session {}
+
+    impl ServerFn for Session {
+        const PATH: &'static str = "/api/session";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = Option<String>;
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<Option<String>, ServerFnError> {
+            let cookies = Cookies::current().unwrap();
+            let session = cookies.get("session");
+            let next = match &session {
+                Some(session) => format!("{session}+"),
+                None => "1".to_string(),
+            };
+            cookies.set_cookie(Cookie::new("session", next));
+            cookies.set_cookie(Cookie::new("visited", "true"));
+            Ok(session)
+        }
+    }
+
+    /// Calls the server function, returning its output and `Set-Cookie` headers.
+    async fn call(cookie: Option<&str>) -> (String, Vec<String>) {
+        let mut req = Request::post(Session::PATH)
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(cookie) = cookie {
+            req = req.header(header::COOKIE, cookie);
+        }
+        let res =
+            Session::run_on_server(req.body(Body::from("{}")).unwrap()).await;
+        let set_cookies = res
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .map(|value| value.to_str().unwrap().to_string())
+            .collect();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (String::from_utf8(body.to_vec()).unwrap(), set_cookies)
+    }
+
+    #[tokio::test]
+    async fn round_trips_session_cookie() {
+        let (output, set_cookies) = call(None).await;
+        assert_eq!(output, "null");
+        assert_eq!(set_cookies, ["session=1", "visited=true"]);
+
+        let (output, set_cookies) = call(Some("theme=dark; session=1")).await;
+        assert_eq!(output, r#""1""#);
+        assert_eq!(set_cookies, ["session=1+", "visited=true"]);
+    }
+
+    #[test]
+    fn set_cookies_shadow_request_cookies() {
+        let cookies = Cookies::from_header("a=1; b=2; malformed");
+        cookies.set_cookie(Cookie::new("b", "3"));
+        assert_eq!(cookies.get("a").as_deref(), Some("1"));
+        assert_eq!(cookies.get("b").as_deref(), Some("3"));
+        assert_eq!(cookies.get("c"), None);
+        assert!(Cookies::current().is_none());
+    }
+}
@@ -59,6 +59,88 @@ impl From<ServerFnError> for Error {
     }
 }
 
+/// The error that caused a [`ServerFnError::ServerErrorSource`].
+///
+/// It is only available on the server. When the error is sent to the client, only its
+/// message is serialized, and the deserialized source has no further sources.
+#[derive(Clone)]
+pub struct ErrorSource(Arc<dyn error::Error + Send + Sync>);
+
+impl ErrorSource {
+    /// Wraps an error.
+    pub fn new(error: impl error::Error + Send + Sync + 'static) -> Self {
+        Self(Arc::new(error))
+    }
+
+    /// Iterates over this error and each of its sources, in order.
+    pub fn chain(&self) -> impl Iterator<Item = &(dyn error::Error + 'static)> {
+        let mut next = Some(&*self.0 as &(dyn error::Error + 'static));
+        std::iter::from_fn(move || {
+            let current = next?;
+            next = current.source();
+            Some(current)
+        })
+    }
+}
+
+impl ops::Deref for ErrorSource {
+    type Target = dyn error::Error + Send + Sync;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl fmt::Debug for ErrorSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for ErrorSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl error::Error for ErrorSource {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+/// Sources are compared by their messages.
+impl PartialEq for ErrorSource {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.to_string() == other.to_string()
+    }
+}
+
+impl Eq for ErrorSource {}
+
+impl Serialize for ErrorSource {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorSource {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)
+            .map(|message| Self::new(Message(message)))
+    }
+}
+
+/// A deserialized [`ErrorSource`], of which only the message is known.
+#[derive(Debug, Error)]
+#[error("{0}")]
+struct Message(String);
+
 /// An empty value indicating that there is no custom error type associated
 /// with this server function.
 #[derive(
@@ -186,7 +268,22 @@ pub enum ServerFnError<E = NoCustomError> {
     Response(String),
     /// Occurs when there is an error while actually running the function on the server.
     ServerError(String),
+    /// Occurs on the server when the function fails with another error, which is kept
+    /// along with its chain of sources. See [`ServerFnError::from_source`].
+    ///
+    /// Only the message is sent to the client, where it becomes a
+    /// [`ServerFnError::ServerError`].
+    ServerErrorSource {
+        /// The message of the error, which is sent to the client.
+        message: String,
+        /// The error itself.
+        source: ErrorSource,
+    },
     /// Occurs on the client if there is an error deserializing the server's response.
+    ///
+    /// Also occurs on the server if the [`Json`](crate::codec::Json) codec can't
+    /// deserialize the arguments, naming the path of the field at fault. The server
+    /// responds to it with `422 Unprocessable Entity`.
     Deserialization(String),
     /// Occurs on the client if there is an error serializing the server function arguments.
     Serialization(String),
@@ -194,6 +291,200 @@ pub enum ServerFnError<E = NoCustomError> {
     Args(String),
     /// Occurs on the server if there's a missing argument.
     MissingArg(String),
+    /// Occurs on the client if the server answers with an error status, wrapping the
+    /// error it sent. See [`ServerFnError::status`].
+    ///
+    /// This is displayed and sent on as the error it wraps, so a server function that
+    /// returns it does not pass the status on to its own caller.
+    WithStatus {
+        /// The status code of the response.
+        status: u16,
+        /// The error sent by the server.
+        error: Box<ServerFnError<E>>,
+    },
+}
+
+impl<E> ServerFnError<E> {
+    /// The HTTP status code of the response this error was received in, if the
+    /// server answered with one.
+    ///
+    /// ```rust,ignore
+    /// match load_post(id).await {
+    ///     Err(e) if e.status() == Some(401) => redirect_to_login(),
+    ///     // ...
+    /// }
+    /// ```
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ServerFnError::WithStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// This error without the [status](ServerFnError::status) it was received with.
+    pub fn without_status(self) -> Self {
+        match self {
+            ServerFnError::WithStatus { error, .. } => *error,
+            other => other,
+        }
+    }
+
+    /// Wraps this error with the status of the response it was received in.
+    pub(crate) fn with_status(self, status: u16) -> Self {
+        ServerFnError::WithStatus {
+            status,
+            error: Box::new(self.without_status()),
+        }
+    }
+
+    /// The error that caused this one on the server, if it was kept.
+    ///
+    /// Unlike [`Error::source`](std::error::Error::source), this is available for any
+    /// custom error type.
+    pub fn source(&self) -> Option<&ErrorSource> {
+        match self {
+            ServerFnError::ServerErrorSource { source, .. } => Some(source),
+            ServerFnError::WithStatus { error, .. } => error.source(),
+            _ => None,
+        }
+    }
+
+    /// Logs this error with the full chain of its sources, before it is reduced to
+    /// its message for the response.
+    ///
+    /// Errors without sources are only logged if they were sanitized with
+    /// `correlation_id`.
+    #[cfg(all(
+        feature = "tracing",
+        any(feature = "axum-no-default", feature = "actix")
+    ))]
+    pub(crate) fn trace_source(&self, path: &str, correlation_id: Option<&str>)
+    where
+        E: Display,
+    {
+        let chain = match self.source() {
+            Some(source) => source
+                .chain()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(": "),
+            None if correlation_id.is_some() => self.to_string(),
+            None => return,
+        };
+        tracing::error!(
+            path,
+            correlation_id,
+            error = %chain,
+            "server function failed"
+        );
+    }
+
+    /// Copies this error without its custom error type, which is replaced by a
+    /// [`ServerFnError::ServerError`] with its message.
+    #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+    pub(crate) fn to_untyped(&self) -> ServerFnError
+    where
+        E: Display,
+    {
+        match self {
+            ServerFnError::WrappedServerError(e) => {
+                ServerFnError::ServerError(e.to_string())
+            }
+            ServerFnError::Registration(s) => {
+                ServerFnError::Registration(s.clone())
+            }
+            ServerFnError::Request(s) => ServerFnError::Request(s.clone()),
+            ServerFnError::Response(s) => ServerFnError::Response(s.clone()),
+            ServerFnError::ServerError(s) => {
+                ServerFnError::ServerError(s.clone())
+            }
+            ServerFnError::ServerErrorSource { message, source } => {
+                ServerFnError::ServerErrorSource {
+                    message: message.clone(),
+                    source: source.clone(),
+                }
+            }
+            ServerFnError::Deserialization(s) => {
+                ServerFnError::Deserialization(s.clone())
+            }
+            ServerFnError::Serialization(s) => {
+                ServerFnError::Serialization(s.clone())
+            }
+            ServerFnError::Args(s) => ServerFnError::Args(s.clone()),
+            ServerFnError::MissingArg(s) => {
+                ServerFnError::MissingArg(s.clone())
+            }
+            ServerFnError::WithStatus { status, error } => {
+                ServerFnError::WithStatus {
+                    status: *status,
+                    error: Box::new(error.to_untyped()),
+                }
+            }
+        }
+    }
+}
+
+impl<E: FromServerFnError> ServerFnError<E> {
+    /// Converts this error into the application error type `E`.
+    ///
+    /// An error returned by the server function itself is unwrapped, so it can be
+    /// matched on as it was returned. Any other error, like a failure to reach the
+    /// server, is converted with [`FromServerFnError::from_server_fn_error`].
+    pub fn into_app_error(self) -> E {
+        match self {
+            ServerFnError::WrappedServerError(e) => e,
+            ServerFnError::WithStatus { error, .. }
+                if matches!(*error, ServerFnError::WrappedServerError(_)) =>
+            {
+                error.into_app_error()
+            }
+            other => E::from_server_fn_error(other),
+        }
+    }
+}
+
+/// An application error type that a server function can return directly, as
+/// `Result<T, E>` instead of `Result<T, ServerFnError<E>>`.
+///
+/// Errors returned by the server function are sent to the client using their
+/// [`Display`] and [`FromStr`] implementations, and given back to the caller as they
+/// were returned. Failures that happen outside of the server function, like a
+/// request that cannot reach the server or a response that cannot be deserialized,
+/// are converted into `E` with [`from_server_fn_error`](FromServerFnError::from_server_fn_error),
+/// so that the caller can still tell the two apart.
+///
+/// ```rust,ignore
+/// // with `Display` and `FromStr` implemented, for example through `serde_json`
+/// #[derive(Debug, Clone)]
+/// pub enum AppError {
+///     Validation { field: String, reason: String },
+///     Transport(String),
+/// }
+///
+/// impl FromServerFnError for AppError {
+///     fn from_server_fn_error(err: ServerFnError<Self>) -> Self {
+///         AppError::Transport(err.to_string())
+///     }
+/// }
+///
+/// #[server]
+/// pub async fn rename(name: String) -> Result<(), AppError> {
+///     if name.is_empty() {
+///         return Err(AppError::Validation { field: "name".into(), reason: "empty".into() });
+///     }
+///     // ...
+/// }
+///
+/// match rename(String::new()).await {
+///     Err(AppError::Validation { field, .. }) => show_error(&field),
+///     // ...
+/// }
+/// ```
+pub trait FromServerFnError: FromStr + Display + Sized {
+    /// Converts an error that did not come from the server function itself.
+    ///
+    /// `err` is never a [`ServerFnError::WrappedServerError`].
+    fn from_server_fn_error(err: ServerFnError<Self>) -> Self;
 }
 
 impl ServerFnError<NoCustomError> {
@@ -201,6 +492,21 @@ impl ServerFnError<NoCustomError> {
     pub fn new(msg: impl ToString) -> Self {
         Self::ServerError(msg.to_string())
     }
+
+    /// Constructs a new [`ServerFnError::ServerErrorSource`] from an error, which
+    /// keeps its chain of sources on the server.
+    ///
+    /// ```rust,ignore
+    /// let post = load_post(id).await.map_err(ServerFnError::from_source)?;
+    /// ```
+    pub fn from_source(
+        error: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Self::ServerErrorSource {
+            message: error.to_string(),
+            source: ErrorSource::new(error),
+        }
+    }
 }
 
 impl<CustErr> From<CustErr> for ServerFnError<CustErr> {
@@ -230,7 +536,8 @@ where
                 ServerFnError::Request(s) => format!(
                     "error reaching server to call server function: {s}"
                 ),
-                ServerFnError::ServerError(s) =>
+                ServerFnError::ServerError(s)
+                | ServerFnError::ServerErrorSource { message: s, .. } =>
                     format!("error running server function: {s}"),
                 ServerFnError::Deserialization(s) =>
                     format!("error deserializing server function results: {s}"),
@@ -243,6 +550,7 @@ where
                 ServerFnError::Response(s) =>
                     format!("error generating HTTP response: {s}"),
                 ServerFnError::WrappedServerError(e) => format!("{}", e),
+                ServerFnError::WithStatus { error, .. } => error.to_string(),
             }
         )
     }
@@ -280,7 +588,8 @@ where
             }
             ServerFnError::Request(e) => write!(&mut buf, "Request|{}", e),
             ServerFnError::Response(e) => write!(&mut buf, "Response|{}", e),
-            ServerFnError::ServerError(e) => {
+            ServerFnError::ServerError(e)
+            | ServerFnError::ServerErrorSource { message: e, .. } => {
                 write!(&mut buf, "ServerError|{}", e)
             }
             ServerFnError::Deserialization(e) => {
@@ -293,6 +602,7 @@ where
             ServerFnError::MissingArg(e) => {
                 write!(&mut buf, "MissingArg|{}", e)
             }
+            ServerFnError::WithStatus { error, .. } => return error.ser(),
         }?;
         Ok(buf)
     }
@@ -340,6 +650,10 @@ where
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             ServerFnError::WrappedServerError(e) => Some(e),
+            ServerFnError::ServerErrorSource { source, .. } => Some(source),
+            ServerFnError::WithStatus { error, .. } => {
+                std::error::Error::source(&**error)
+            }
             _ => None,
         }
     }
@@ -368,6 +682,16 @@ pub enum ServerFnErrorErr<E = NoCustomError> {
     /// Occurs when there is an error while actually running the function on the server.
     #[error("error running server function: {0}")]
     ServerError(String),
+    /// Occurs on the server when the function fails with another error, which is kept
+    /// along with its chain of sources.
+    #[error("error running server function: {message}")]
+    ServerErrorSource {
+        /// The message of the error.
+        message: String,
+        /// The error itself.
+        #[source]
+        source: ErrorSource,
+    },
     /// Occurs on the client if there is an error deserializing the server's response.
     #[error("error deserializing server function results: {0}")]
     Deserialization(String),
@@ -395,6 +719,9 @@ impl<CustErr> From<ServerFnError<CustErr>> for ServerFnErrorErr<CustErr> {
             ServerFnError::ServerError(value) => {
                 ServerFnErrorErr::ServerError(value)
             }
+            ServerFnError::ServerErrorSource { message, source } => {
+                ServerFnErrorErr::ServerErrorSource { message, source }
+            }
             ServerFnError::Deserialization(value) => {
                 ServerFnErrorErr::Deserialization(value)
             }
@@ -409,6 +736,7 @@ impl<CustErr> From<ServerFnError<CustErr>> for ServerFnErrorErr<CustErr> {
                 ServerFnErrorErr::WrappedServerError(value)
             }
             ServerFnError::Response(value) => ServerFnErrorErr::Response(value),
+            ServerFnError::WithStatus { error, .. } => (*error).into(),
         }
     }
 }
@@ -495,3 +823,202 @@ impl<CustErr> From<ServerFnUrlError<CustErr>> for ServerFnErrorErr<CustErr> {
         error.error.into()
     }
 }
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{
+        FromServerFnError, ServerFnError, ServerFnErrorErr, ServerFnErrorSerde,
+    };
+    use crate::{
+        client::Client,
+        codec::{test_client::ServerOnly, Encoding, IntoReq, Json},
+        ServerFn,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use http::{header, Request, Response};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+    use std::{error::Error, fmt, io, str::FromStr};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum AppError {
+        Validation { field: String, reason: String },
+        Transport(String),
+    }
+
+    impl fmt::Display for AppError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&serde_json::to_string(self).map_err(|_| fmt::Error)?)
+        }
+    }
+
+    impl FromStr for AppError {
+        type Err = serde_json::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            serde_json::from_str(s)
+        }
+    }
+
+    impl FromServerFnError for AppError {
+        fn from_server_fn_error(err: ServerFnError<Self>) -> Self {
+            AppError::Transport(err.to_string())
+        }
+    }
+
+    /// Sends requests straight to [`Rename`] on the server.
+    struct Loopback;
+
+    impl Client<AppError> for Loopback {
+        type Request = Request<Bytes>;
+        type Response = Response<Body>;
+
+        async fn send(
+            req: Self::Request,
+        ) -> Result<Self::Response, ServerFnError<AppError>> {
+            Ok(Rename::run_on_server(req.map(Body::from)).await)
+        }
+    }
+
+    /// What the `#[server]` macro generates for
+    /// `async fn rename(name: String) -> Result<(), AppError>`.
+    #[derive(Serialize, Deserialize)]
+    struct Rename {
+        name: String,
+    }
+
+    impl ServerFn for Rename {
+        const PATH: &'static str = "/api/rename";
+
+        type Client = Loopback;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = ();
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = AppError;
+
+        async fn run_body(self) -> Result<(), ServerFnError<AppError>> {
+            if self.name.is_empty() {
+                return Err(ServerFnError::WrappedServerError(
+                    AppError::Validation {
+                        field: "name".into(),
+                        reason: "must not be empty".into(),
+                    },
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    async fn rename(name: &str) -> Result<(), AppError> {
+        let req = <Rename as IntoReq<Json, _, AppError>>::into_req(
+            Rename { name: name.into() },
+            Rename::PATH,
+            Json::CONTENT_TYPE,
+        )
+        .unwrap();
+        Rename::run_on_client_with_req(req, None)
+            .await
+            .map_err(ServerFnError::into_app_error)
+    }
+
+    #[tokio::test]
+    async fn application_error_round_trips_typed() {
+        assert_eq!(rename("alice").await, Ok(()));
+        assert_eq!(
+            rename("").await,
+            Err(AppError::Validation {
+                field: "name".into(),
+                reason: "must not be empty".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn transport_errors_are_converted() {
+        let err =
+            ServerFnError::<AppError>::Request("connection refused".into());
+        assert!(matches!(
+            err.into_app_error(),
+            AppError::Transport(message) if message.contains("connection refused")
+        ));
+    }
+
+    #[test]
+    fn status_is_not_sent_on() {
+        let err = ServerFnError::<AppError>::ServerError("forbidden".into())
+            .with_status(403);
+        assert_eq!(err.status(), Some(403));
+        assert_eq!(err.to_string(), "error running server function: forbidden");
+        // a server function that returns the error answers with its own status
+        assert_eq!(err.ser().unwrap(), "ServerError|forbidden");
+    }
+
+    /// An error with a source that should stay on the server.
+    #[derive(Debug, thiserror::Error)]
+    #[error("could not load post")]
+    struct LoadError(#[source] io::Error);
+
+    fn load_error() -> LoadError {
+        LoadError(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            "connection to db.internal:5432 refused",
+        ))
+    }
+
+    /// What the `#[server]` macro generates for
+    /// `async fn load_post() -> Result<String, ServerFnError>`.
+    #[derive(Serialize, Deserialize)]
+    struct LoadPost {}
+
+    impl ServerFn for LoadPost {
+        const PATH: &'static str = "/api/load_post";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = String;
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = super::NoCustomError;
+
+        async fn run_body(self) -> Result<String, ServerFnError> {
+            Err(ServerFnError::from_source(load_error()))
+        }
+    }
+
+    #[test]
+    fn source_chain_is_kept_on_the_server() {
+        let err = ServerFnError::from_source(load_error());
+        let chain = err
+            .source()
+            .unwrap()
+            .chain()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            chain,
+            [
+                "could not load post",
+                "connection to db.internal:5432 refused"
+            ]
+        );
+
+        let err = ServerFnErrorErr::from(err);
+        let cause = err.source().and_then(Error::source).unwrap();
+        assert_eq!(cause.to_string(), "connection to db.internal:5432 refused");
+    }
+
+    #[tokio::test]
+    async fn source_chain_is_not_sent_to_the_client() {
+        let req = Request::post(LoadPost::PATH)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        let res = LoadPost::run_on_server(req).await;
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "ServerError|could not load post");
+    }
+}
@@ -0,0 +1,194 @@
+//! Logs the bodies of server function calls as they are sent and received.
+//!
+//! With the `debug-wire` feature enabled, the start of every request and response
+//! body is logged through [`tracing`] as a `DEBUG` event with the target
+//! `server_fn::wire`, along with its content type and the path of the server
+//! function. Bodies are logged as text if they are valid UTF-8, and as hex
+//! otherwise. Streaming bodies are passed through as they are read, rather than
+//! collected first.
+//!
+//! This happens on the server, for every registered server function, and in the
+//! [`reqwest`](crate::client::reqwest) client. Bodies often contain credentials or
+//! personal data, so the feature is meant for development only.
+//!
+//! ```rust,ignore
+//! server_fn::inspect::set_preview_len(1024);
+//! tracing_subscriber::fmt()
+//!     .with_env_filter("server_fn::wire=debug")
+//!     .init();
+//! ```
+
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+use std::{
+    fmt::{self, Write},
+    sync::atomic::{AtomicUsize, Ordering},
+    task::Poll,
+};
+
+static PREVIEW_LEN: AtomicUsize = AtomicUsize::new(256);
+
+/// Sets how many bytes of each body are logged. Defaults to 256.
+pub fn set_preview_len(len: usize) {
+    PREVIEW_LEN.store(len, Ordering::Relaxed);
+}
+
+/// How many bytes of each body are logged.
+pub fn preview_len() -> usize {
+    PREVIEW_LEN.load(Ordering::Relaxed)
+}
+
+/// The start of a body, shown as text if it is valid UTF-8, or as hex otherwise.
+struct Preview<'a>(&'a [u8]);
+
+impl fmt::Display for Preview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match std::str::from_utf8(self.0) {
+            Ok(text) => Some(text),
+            // the preview may end in the middle of a character
+            Err(e) if e.error_len().is_none() => {
+                Some(std::str::from_utf8(&self.0[..e.valid_up_to()]).unwrap())
+            }
+            Err(_) => None,
+        };
+        match text {
+            Some(text) => f.write_str(text),
+            None => {
+                for (i, byte) in self.0.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(' ')?;
+                    }
+                    write!(f, "{byte:02x}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// One body sent or received by a server function call, to be logged.
+pub(crate) struct Wire {
+    side: &'static str,
+    direction: &'static str,
+    path: String,
+    content_type: Option<String>,
+}
+
+impl Wire {
+    /// A request body seen by the client.
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn client_request(
+        path: impl Into<String>,
+        content_type: Option<String>,
+    ) -> Self {
+        Self::new("client", "request", path.into(), content_type)
+    }
+
+    /// A response body seen by the client.
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn client_response(
+        path: impl Into<String>,
+        content_type: Option<String>,
+    ) -> Self {
+        Self::new("client", "response", path.into(), content_type)
+    }
+
+    /// A request body seen by the server.
+    #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+    pub(crate) fn server_request(
+        path: impl Into<String>,
+        content_type: Option<String>,
+    ) -> Self {
+        Self::new("server", "request", path.into(), content_type)
+    }
+
+    /// A response body seen by the server.
+    #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+    pub(crate) fn server_response(
+        path: impl Into<String>,
+        content_type: Option<String>,
+    ) -> Self {
+        Self::new("server", "response", path.into(), content_type)
+    }
+
+    fn new(
+        side: &'static str,
+        direction: &'static str,
+        path: String,
+        content_type: Option<String>,
+    ) -> Self {
+        Self {
+            side,
+            direction,
+            path,
+            content_type,
+        }
+    }
+
+    /// Logs the start of a body that has been read in full.
+    pub(crate) fn log(&self, body: &[u8]) {
+        let len = body.len().min(preview_len());
+        tracing::debug!(
+            target: "server_fn::wire",
+            side = self.side,
+            direction = self.direction,
+            path = self.path.as_str(),
+            content_type = self.content_type.as_deref(),
+            preview = %Preview(&body[..len]),
+            truncated = len < body.len(),
+            "server function body"
+        );
+    }
+
+    /// Passes `stream` through unchanged, logging its start once enough of it has
+    /// been read, or once it ends.
+    pub(crate) fn tee<S, E>(
+        self,
+        stream: S,
+    ) -> impl Stream<Item = Result<Bytes, E>>
+    where
+        S: Stream<Item = Result<Bytes, E>>,
+    {
+        let limit = preview_len();
+        let mut stream = Box::pin(stream);
+        let mut seen = Some(BytesMut::new());
+        futures::stream::poll_fn(move |cx| {
+            let next = stream.poll_next_unpin(cx);
+            if let (Some(buf), Poll::Ready(next)) = (seen.as_mut(), &next) {
+                if let Some(Ok(chunk)) = next {
+                    // one byte past the limit shows that the body was truncated
+                    let wanted = (limit + 1).saturating_sub(buf.len());
+                    buf.extend_from_slice(&chunk[..wanted.min(chunk.len())]);
+                }
+                if buf.len() > limit || !matches!(next, Some(Ok(_))) {
+                    self.log(buf);
+                    seen = None;
+                }
+            }
+            next
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Preview;
+
+    #[test]
+    fn previews_text_as_text() {
+        assert_eq!(Preview(br#"{"id":1}"#).to_string(), r#"{"id":1}"#);
+    }
+
+    #[test]
+    fn previews_binary_as_hex() {
+        assert_eq!(
+            Preview(&[0xa1, 0x62, 0x69, 0x64]).to_string(),
+            "a1 62 69 64"
+        );
+    }
+
+    #[test]
+    fn drops_character_cut_off_by_preview() {
+        assert_eq!(Preview(&"héllo".as_bytes()[..2]).to_string(), "h");
+    }
+}
@@ -102,17 +102,31 @@ pub mod client;
 /// Encodings for arguments and results.
 pub mod codec;
 
+/// Reading and setting cookies from inside server functions.
+#[cfg(feature = "cookies")]
+pub mod cookies;
+
+/// Logs the bodies of server function calls, to debug their encodings.
+#[cfg(feature = "debug-wire")]
+pub mod inspect;
+
 #[macro_use]
 /// Error types and utilities.
 pub mod error;
 /// Types to add server middleware to a server function.
 pub mod middleware;
+/// Describes registered server functions as an OpenAPI document.
+#[cfg(feature = "openapi")]
+pub mod openapi;
 /// Utilities to allow client-side redirects.
 pub mod redirect;
 /// Types and traits for  for HTTP requests.
 pub mod request;
 /// Types and traits for HTTP responses.
 pub mod response;
+/// Helpers for testing middleware.
+#[cfg(any(feature = "testing", all(test, feature = "axum-no-default")))]
+pub mod testing;
 
 #[cfg(feature = "actix")]
 #[doc(hidden)]
@@ -120,28 +134,43 @@ pub use ::actix_web as actix_export;
 #[cfg(feature = "axum-no-default")]
 #[doc(hidden)]
 pub use ::axum as axum_export;
+use bytes::Bytes;
 use client::Client;
 use codec::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
 #[doc(hidden)]
 pub use const_format;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
 use dashmap::DashMap;
 pub use error::ServerFnError;
-use error::ServerFnErrorSerde;
 #[cfg(feature = "form-redirects")]
 use error::ServerFnUrlError;
-use http::Method;
-use middleware::{Layer, Service};
+#[cfg(feature = "flatbuffers")]
+pub use flatbuffers;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+use futures::future::Shared;
+use http::{Method, StatusCode};
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+use middleware::SharedService;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use middleware::{on_request, on_response};
+use middleware::{AsyncLayer, BoxedService, Service};
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
 use once_cell::sync::Lazy;
 use redirect::RedirectHook;
 use request::Req;
-use response::{ClientRes, Res};
+use response::{ClientRes, Res, ResponseOptions};
 #[cfg(feature = "rkyv")]
 pub use rkyv;
 #[doc(hidden)]
+#[cfg(feature = "openapi")]
+pub use schemars;
+#[doc(hidden)]
 pub use serde;
 #[doc(hidden)]
 #[cfg(feature = "serde-lite")]
 pub use serde_lite;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+use std::task::{Context, Poll};
 use std::{fmt::Display, future::Future, pin::Pin, str::FromStr, sync::Arc};
 #[doc(hidden)]
 pub use xxhash_rust;
@@ -188,6 +217,14 @@ where
     /// A unique path for the server function’s API endpoint, relative to the host, including its prefix.
     const PATH: &'static str;
 
+    /// The version of the server function, set with `#[server(version = "v2")]`.
+    ///
+    /// A versioned server function has the version as a segment of its path, after
+    /// the prefix, like `/api/v2/get_user`. Several versions of a function can be
+    /// registered at once, and a request for the path without the version is routed
+    /// to the one named by its `Accept-Version` header.
+    const VERSION: Option<&'static str> = None;
+
     /// The type of the HTTP client that will send the request from the client side.
     ///
     /// For example, this might be `gloo-net` in the browser, or `reqwest` for a desktop app.
@@ -226,8 +263,17 @@ where
     }
 
     /// Middleware that should be applied to this server function.
+    ///
+    /// This can include both synchronous [`Layer`](middleware::Layer)s and
+    /// [`AsyncLayer`]s.
+    ///
+    /// Registered server functions only call this once, and share the same layers
+    /// between all requests, so layers can hold state such as a semaphore. With
+    /// Actix, whose middleware is not `Send`, it is called once for each worker
+    /// thread instead.
     fn middlewares(
-    ) -> Vec<Arc<dyn Layer<Self::ServerRequest, Self::ServerResponse>>> {
+    ) -> Vec<Arc<dyn AsyncLayer<Self::ServerRequest, Self::ServerResponse>>>
+    {
         Vec::new()
     }
 
@@ -243,30 +289,82 @@ where
         // Server functions can either be called by a real Client,
         // or directly by an HTML <form>. If they're accessed by a <form>, default to
         // redirecting back to the Referer.
-        #[cfg(feature = "form-redirects")]
         let accepts_html = req
             .accepts()
             .map(|n| n.contains("text/html"))
             .unwrap_or(false);
         #[cfg(feature = "form-redirects")]
         let mut referer = req.referer().as_deref().map(ToOwned::to_owned);
+        let extensions = req.to_extensions();
+        let headers = codec::RequestHeaders {
+            accept: req.accepts().map(|accept| accept.into_owned()),
+            range: req.range_header().map(|range| range.into_owned()),
+        };
+        let if_modified_since = (Self::InputEncoding::METHOD == Method::GET)
+            .then(|| req.if_modified_since().map(|since| since.into_owned()))
+            .flatten();
+        #[cfg(feature = "cookies")]
+        let cookies = cookies::Cookies::from_header(
+            req.cookie_header().as_deref().unwrap_or_default(),
+        );
+        #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+        let raw_errors = middleware::RawErrorSlot::claim();
 
         async move {
-            #[allow(unused_variables, unused_mut)]
+            let options = ResponseOptions::default();
+            let fut = options.clone().scope(Self::execute_on_server(req));
+            let fut = extensions.clone().scope(fut);
+            let fut = headers.clone().scope(fut);
+            #[cfg(feature = "cookies")]
+            let fut = cookies.clone().scope(fut);
+
+            #[allow(unused_variables)]
             // used in form redirects feature
-            let (mut res, err) = Self::execute_on_server(req)
-                .await
-                .map(|res| (res, None))
-                .unwrap_or_else(|e| {
-                    (
-                        Self::ServerResponse::error_response(Self::PATH, &e),
-                        Some(e),
-                    )
-                });
+            let (mut res, err) = match fut.await {
+                Ok(mut res) => {
+                    // the client already has the current version, so its body is
+                    // left out
+                    if if_modified_since
+                        .is_some_and(|since| options.is_not_modified(&since))
+                    {
+                        if let Ok(not_modified) =
+                            Self::ServerResponse::try_from_bytes(
+                                Self::OutputEncoding::CONTENT_TYPE,
+                                Bytes::new(),
+                            )
+                        {
+                            res = not_modified;
+                            options.set_status(StatusCode::NOT_MODIFIED);
+                        }
+                    }
+                    options.apply::<Self::Error, _>(&mut res);
+                    (res, None)
+                }
+                Err(e) => {
+                    #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+                    if let Some(raw_errors) = &raw_errors {
+                        raw_errors.set(&e, options.error_status());
+                    }
+                    // keeps the request ID available to the error sanitizer, and
+                    // the `Accept` header to the error encoding
+                    let res = headers.in_scope(|| {
+                        extensions.in_scope(|| {
+                            response::error_response_with_status(
+                                Self::PATH,
+                                &e,
+                                options.error_status(),
+                            )
+                        })
+                    });
+                    (res, Some(e))
+                }
+            };
+            let redirect_to = options.redirect();
 
-            // if it accepts HTML, we'll redirect to the Referer
+            // if it accepts HTML, we'll redirect to the Referer, unless the
+            // server function redirected somewhere else
             #[cfg(feature = "form-redirects")]
-            if accepts_html {
+            if accepts_html && redirect_to.is_none() {
                 // if it had an error, encode that error in the URL
                 if let Some(err) = err {
                     if let Ok(url) = ServerFnUrlError::new(Self::PATH, err)
@@ -285,6 +383,20 @@ where
                 res.redirect(referer.as_deref().unwrap_or("/"));
             }
 
+            if let Some(path) = redirect_to {
+                if accepts_html {
+                    res.redirect(&path);
+                } else {
+                    // a real redirect would be followed by `fetch`, and its
+                    // result lost, so the client is told to redirect instead
+                    res.insert_header("location", &path);
+                    res.insert_header(redirect::REDIRECT_HEADER, "");
+                }
+            }
+
+            #[cfg(feature = "cookies")]
+            cookies.write_to::<Self::Error, _>(&mut res);
+
             res
         }
     }
@@ -318,8 +430,14 @@ where
 
             // if it returns an error status, deserialize the error using FromStr
             let res = if (400..=599).contains(&status) {
-                let text = res.try_into_string().await?;
-                Err(ServerFnError::<Self::Error>::de(&text))
+                let content_type = res.content_type();
+                let body = res.try_into_bytes().await?;
+                Err(codec::decode_error::<Self::Error>(
+                    Self::PATH,
+                    content_type.as_deref(),
+                    &body,
+                )
+                .with_status(status))
             } else {
                 // otherwise, deserialize the body as is
                 Ok(Self::Output::from_res(res).await)
@@ -343,7 +461,15 @@ where
         Output = Result<Self::ServerResponse, ServerFnError<Self::Error>>,
     > + Send {
         async {
-            let this = Self::from_req(req).await?;
+            let this = Self::from_req(req).await.map_err(|e| {
+                // arguments that were sent but don't fit the server function
+                if let ServerFnError::Deserialization(_) = e {
+                    ResponseOptions::set_current_error_status(
+                        StatusCode::UNPROCESSABLE_ENTITY.as_u16(),
+                    );
+                }
+                e
+            })?;
             let output = this.run_body().await?;
             let res = output.into_res().await?;
             Ok(res)
@@ -362,14 +488,14 @@ macro_rules! initialize_server_fn_map {
         once_cell::sync::Lazy::new(|| {
             $crate::inventory::iter::<ServerFnTraitObj<$req, $res>>
                 .into_iter()
-                .map(|obj| (obj.path(), obj.clone()))
+                .map(|obj| (obj.path(), obj.clone().with_shared_middleware()))
                 .collect()
         })
     };
 }
 
 /// A list of middlewares that can be applied to a server function.
-pub type MiddlewareSet<Req, Res> = Vec<Arc<dyn Layer<Req, Res>>>;
+pub type MiddlewareSet<Req, Res> = Vec<Arc<dyn AsyncLayer<Req, Res>>>;
 
 /// A trait object that allows multiple server functions that take the same
 /// request type and return the same response type to be gathered into a single
@@ -377,8 +503,10 @@ pub type MiddlewareSet<Req, Res> = Vec<Arc<dyn Layer<Req, Res>>>;
 pub struct ServerFnTraitObj<Req, Res> {
     path: &'static str,
     method: Method,
+    version: Option<&'static str>,
     handler: fn(Req) -> Pin<Box<dyn Future<Output = Res> + Send>>,
     middleware: fn() -> MiddlewareSet<Req, Res>,
+    shared_middleware: Option<Arc<MiddlewareSet<Req, Res>>>,
 }
 
 impl<Req, Res> ServerFnTraitObj<Req, Res> {
@@ -388,15 +516,47 @@ impl<Req, Res> ServerFnTraitObj<Req, Res> {
         method: Method,
         handler: fn(Req) -> Pin<Box<dyn Future<Output = Res> + Send>>,
         middleware: fn() -> MiddlewareSet<Req, Res>,
+    ) -> Self {
+        Self::new_versioned(path, method, None, handler, middleware)
+    }
+
+    /// Converts the relevant parts of a server function with a
+    /// [version](ServerFn::VERSION) into a trait object.
+    pub const fn new_versioned(
+        path: &'static str,
+        method: Method,
+        version: Option<&'static str>,
+        handler: fn(Req) -> Pin<Box<dyn Future<Output = Res> + Send>>,
+        middleware: fn() -> MiddlewareSet<Req, Res>,
     ) -> Self {
         Self {
             path,
             method,
+            version,
             handler,
             middleware,
+            shared_middleware: None,
         }
     }
 
+    /// Sets the [version](ServerFn::VERSION) of the server function.
+    pub fn with_version(mut self, version: Option<&'static str>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Builds the middleware for this server function once, so that every request
+    /// is handled by the same set of layers, rather than a new one.
+    ///
+    /// The layers are built on the calling thread, and then used from any thread
+    /// the server function is cloned to. Layers that can only be used on the thread
+    /// they were built on, like an `ActixLayer`, should only be shared by the server
+    /// functions of that thread.
+    pub fn with_shared_middleware(mut self) -> Self {
+        self.shared_middleware = Some(Arc::new((self.middleware)()));
+        self
+    }
+
     /// The path of the server function.
     pub fn path(&self) -> &'static str {
         self.path
@@ -407,6 +567,11 @@ impl<Req, Res> ServerFnTraitObj<Req, Res> {
         self.method.clone()
     }
 
+    /// The version of the server function, if it has one.
+    pub fn version(&self) -> Option<&'static str> {
+        self.version
+    }
+
     /// The handler for this server function.
     pub fn handler(&self, req: Req) -> impl Future<Output = Res> + Send {
         (self.handler)(req)
@@ -414,7 +579,36 @@ impl<Req, Res> ServerFnTraitObj<Req, Res> {
 
     /// The set of middleware that should be applied to this function.
     pub fn middleware(&self) -> MiddlewareSet<Req, Res> {
-        (self.middleware)()
+        match &self.shared_middleware {
+            Some(middleware) => middleware.as_ref().clone(),
+            None => (self.middleware)(),
+        }
+    }
+}
+
+impl<Req, Res> ServerFnTraitObj<Req, Res>
+where
+    Req: Send + 'static,
+    Res: 'static,
+{
+    /// Converts the server function into a service, with all of its middleware applied.
+    pub async fn into_service(self) -> BoxedService<Req, Res> {
+        self.into_service_with(|service| service).await
+    }
+
+    /// Converts the server function into a service like
+    /// [`into_service`](Self::into_service), wrapping the handler with `base` before
+    /// any of the middleware is applied.
+    pub(crate) async fn into_service_with(
+        self,
+        base: impl FnOnce(BoxedService<Req, Res>) -> BoxedService<Req, Res>,
+    ) -> BoxedService<Req, Res> {
+        let middleware = self.middleware();
+        let mut service = base(BoxedService::new(self));
+        for middleware in middleware {
+            service = middleware.layer(service).await;
+        }
+        service
     }
 }
 
@@ -434,16 +628,149 @@ impl<Req, Res> Clone for ServerFnTraitObj<Req, Res> {
         Self {
             path: self.path,
             method: self.method.clone(),
+            version: self.version,
             handler: self.handler,
             middleware: self.middleware,
+            shared_middleware: self.shared_middleware.clone(),
         }
     }
 }
 
-#[allow(unused)] // used by server integrations
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
 type LazyServerFnMap<Req, Res> =
     Lazy<DashMap<&'static str, ServerFnTraitObj<Req, Res>>>;
 
+/// A server function with all of its middleware applied, which is built the first
+/// time it is awaited, and then shared by every request.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+type BuildOnce<Req, Res> =
+    Shared<Pin<Box<dyn Future<Output = SharedService<Req, Res>> + Send>>>;
+
+/// The built server functions, keyed by path.
+#[cfg(feature = "axum-no-default")]
+type LazyServices<Req, Res> = Lazy<DashMap<&'static str, BuildOnce<Req, Res>>>;
+
+/// Starts building a server function with all of its middleware applied, wrapping
+/// the handler with `base` before any of the middleware, and `outer` around all of
+/// it.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+fn build_once<Req, Res>(
+    server_fn: ServerFnTraitObj<Req, Res>,
+    base: impl FnOnce(BoxedService<Req, Res>) -> BoxedService<Req, Res>
+        + Send
+        + 'static,
+    outer: impl FnOnce(BoxedService<Req, Res>) -> BoxedService<Req, Res>
+        + Send
+        + 'static,
+) -> BuildOnce<Req, Res>
+where
+    Req: Send + 'static,
+    Res: 'static,
+{
+    let build: Pin<Box<dyn Future<Output = SharedService<Req, Res>> + Send>> =
+        Box::pin(async move {
+            outer(server_fn.into_service_with(base).await).into_shared()
+        });
+    futures::FutureExt::shared(build)
+}
+
+/// A service that waits for a server function to be [built](BuildOnce), and then
+/// runs each request through it.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+struct BuiltService<Req, Res>(BuildOnce<Req, Res>);
+
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+impl<Req, Res> Service<Req, Res> for BuiltService<Req, Res>
+where
+    Req: Send + 'static,
+    Res: 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let service = self.0.clone();
+        Box::pin(async move { service.await.run(req).await })
+    }
+
+    fn try_run(
+        &mut self,
+        req: Req,
+    ) -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send>> {
+        let service = self.0.clone();
+        Box::pin(async move { service.await.try_run(req).await })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        match self.0.peek() {
+            Some(service) => service.clone().poll_ready(cx),
+            // the middleware is still being built, and requests wait for it
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+/// The paths of [versioned](ServerFn::VERSION) server functions, keyed by
+/// [`version_key`].
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+type LazyVersionedPaths = Lazy<DashMap<String, &'static str>>;
+
+/// The key of a versioned server function in a [`LazyVersionedPaths`] map: its
+/// version, and its path without the version segment.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+fn version_key(version: &str, path: &str) -> String {
+    format!("{version}\n{path}")
+}
+
+/// Adds a server function to a map of versioned paths, if it has a version.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+fn insert_versioned_path(
+    versioned_paths: &DashMap<String, &'static str>,
+    path: &'static str,
+    version: Option<&'static str>,
+) {
+    if let Some(version) = version {
+        let unversioned = path.replacen(&format!("/{version}/"), "/", 1);
+        versioned_paths.insert(version_key(version, &unversioned), path);
+    }
+}
+
+/// Builds the map of versioned paths for the registered server functions.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+fn versioned_paths<Req, Res>(
+    server_fns: &LazyServerFnMap<Req, Res>,
+) -> DashMap<String, &'static str> {
+    let versioned_paths = DashMap::new();
+    for server_fn in server_fns.iter() {
+        insert_versioned_path(
+            &versioned_paths,
+            server_fn.path(),
+            server_fn.version(),
+        );
+    }
+    versioned_paths
+}
+
+/// Finds the registered path of the server function a request should go to.
+///
+/// A registered path is used as it is. Otherwise, with the `version` from an
+/// `Accept-Version` header, this is the path of the function with that
+/// [version](ServerFn::VERSION) whose path, without its version segment, is `path`.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+fn resolve_path<Req, Res>(
+    server_fns: &LazyServerFnMap<Req, Res>,
+    versioned_paths: &LazyVersionedPaths,
+    path: &str,
+    version: Option<&str>,
+) -> Option<&'static str> {
+    if let Some(server_fn) = server_fns.get(path) {
+        return Some(server_fn.path());
+    }
+    versioned_paths
+        .get(&version_key(version?, path))
+        .map(|path| *path.value())
+}
+
 #[cfg(feature = "ssr")]
 impl<Req: 'static, Res: 'static> inventory::Collect
     for ServerFnTraitObj<Req, Res>
@@ -455,14 +782,70 @@ impl<Req: 'static, Res: 'static> inventory::Collect
     }
 }
 
+/// Describes a registered server function, independent of the server it runs on.
+///
+/// This is what [`registry`] lists, for things like generating API documentation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerFnMetadata {
+    /// The path of the server function.
+    pub path: &'static str,
+    /// The HTTP method the server function expects.
+    pub method: Method,
+    /// The MIME type of the arguments, from the [`ServerFn::InputEncoding`].
+    pub input_encoding: &'static str,
+    /// The MIME type of the response, from the [`ServerFn::OutputEncoding`].
+    pub output_encoding: &'static str,
+}
+
+impl ServerFnMetadata {
+    /// Collects the metadata of a server function.
+    pub const fn new(
+        path: &'static str,
+        method: Method,
+        input_encoding: &'static str,
+        output_encoding: &'static str,
+    ) -> Self {
+        Self {
+            path,
+            method,
+            input_encoding,
+            output_encoding,
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+inventory::collect!(ServerFnMetadata);
+
+/// Lists every server function registered by the `#[server]` macro.
+///
+/// Functions are listed in no particular order. Those only registered with
+/// `register_explicit` are not included.
+#[cfg(feature = "ssr")]
+pub fn registry() -> impl Iterator<Item = &'static ServerFnMetadata> {
+    inventory::iter::<ServerFnMetadata>.into_iter()
+}
+
 /// Axum integration.
 #[cfg(feature = "axum-no-default")]
 pub mod axum {
+    #[cfg(feature = "debug-wire")]
+    use crate::middleware::InspectWire;
     use crate::{
-        middleware::{BoxedService, Service},
-        Encoding, LazyServerFnMap, ServerFn, ServerFnTraitObj,
+        build_once,
+        codec::{self, FromRes, IntoReq},
+        insert_versioned_path,
+        middleware::{
+            AnswerHead, AnswerOptions, BoxedService, Layer, RunHooks,
+        },
+        resolve_path,
+        response::{error_response_with_status, ClientRes, ResponseOptions},
+        versioned_paths, BuildOnce, BuiltService, Encoding, LazyServerFnMap,
+        LazyServices, LazyVersionedPaths, ServerFn, ServerFnError,
+        ServerFnTraitObj,
     };
     use axum::body::Body;
+    use dashmap::DashMap;
     use http::{Method, Request, Response, StatusCode};
 
     static REGISTERED_SERVER_FUNCTIONS: LazyServerFnMap<
@@ -470,6 +853,14 @@ pub mod axum {
         Response<Body>,
     > = initialize_server_fn_map!(Request<Body>, Response<Body>);
 
+    static SERVICES: LazyServices<Request<Body>, Response<Body>> =
+        once_cell::sync::Lazy::new(DashMap::new);
+
+    static VERSIONED_PATHS: LazyVersionedPaths =
+        once_cell::sync::Lazy::new(|| {
+            versioned_paths(&REGISTERED_SERVER_FUNCTIONS)
+        });
+
     /// Explicitly register a server function. This is only necessary if you are
     /// running the server in a WASM environment (or a rare environment that the
     /// `inventory` crate won't work in.).
@@ -487,8 +878,12 @@ pub mod axum {
                 T::InputEncoding::METHOD,
                 |req| Box::pin(T::run_on_server(req)),
                 T::middlewares,
-            ),
+            )
+            .with_version(T::VERSION)
+            .with_shared_middleware(),
         );
+        SERVICES.remove(T::PATH);
+        insert_versioned_path(&VERSIONED_PATHS, T::PATH, T::VERSION);
     }
 
     /// The set of all registered server function paths.
@@ -499,11 +894,39 @@ pub mod axum {
     }
 
     /// An Axum handler that responds to a server function request.
+    ///
+    /// A request for the path of a [versioned](ServerFn::VERSION) server function
+    /// without its version is sent to the version named by its `Accept-Version`
+    /// header.
     pub async fn handle_server_fn(req: Request<Body>) -> Response<Body> {
         let path = req.uri().path();
+        let version = req
+            .headers()
+            .get("accept-version")
+            .and_then(|version| version.to_str().ok());
+        let path = resolve_path(
+            &REGISTERED_SERVER_FUNCTIONS,
+            &VERSIONED_PATHS,
+            path,
+            version,
+        )
+        .unwrap_or(path);
 
         if let Some(mut service) = get_server_fn_service(path) {
-            service.run(req).await
+            let path = path.to_string();
+            // the status of an error returned by `RawErrors` is left here
+            let options = ResponseOptions::default();
+            options
+                .clone()
+                .scope(service.0.try_run(req))
+                .await
+                .unwrap_or_else(|e| {
+                    error_response_with_status(
+                        &path,
+                        &e,
+                        options.error_status(),
+                    )
+                })
         } else {
             Response::builder()
                 .status(StatusCode::BAD_REQUEST)
@@ -521,42 +944,187 @@ pub mod axum {
         }
     }
 
+    /// Calls the server function `T` on this server, without an HTTP round trip.
+    ///
+    /// This is meant for server functions that call other server functions. Calling
+    /// the other function directly only runs its body, while this sends the call
+    /// through its [registered service](get_server_fn_service), so its middleware
+    /// runs as it would for a request from a client. The arguments and the output are
+    /// still encoded with its codecs, but never leave the process.
+    ///
+    /// If `T` answers with an error status, the error carries it as its
+    /// [`status`](ServerFnError::status). The response of the calling server function
+    /// is left as it is.
+    ///
+    /// ```rust,ignore
+    /// #[server]
+    /// pub async fn checkout(cart: Cart) -> Result<Receipt, ServerFnError> {
+    ///     let total = call_local(PriceCart { cart: cart.clone() }).await?;
+    ///     // ...
+    /// }
+    /// ```
+    pub async fn call_local<T>(
+        input: T,
+    ) -> Result<T::Output, ServerFnError<T::Error>>
+    where
+        T: ServerFn<
+                ServerRequest = Request<Body>,
+                ServerResponse = Response<Body>,
+            > + IntoReq<T::InputEncoding, Request<Body>, T::Error>,
+        T::Output: FromRes<T::OutputEncoding, Response<Body>, T::Error>,
+    {
+        let req: Request<Body> =
+            input.into_req(T::PATH, T::OutputEncoding::CONTENT_TYPE)?;
+        let Some(mut service) = get_server_fn_service(T::PATH) else {
+            return Err(ServerFnError::Registration(format!(
+                "no server function is registered at {}",
+                T::PATH
+            )));
+        };
+        let res = service.0.run(req).await;
+        let status = res.status().as_u16();
+        if (400..=599).contains(&status) {
+            let content_type = ClientRes::<T::Error>::content_type(&res);
+            let body = ClientRes::<T::Error>::try_into_bytes(res).await?;
+            return Err(codec::decode_error::<T::Error>(
+                T::PATH,
+                content_type.as_deref(),
+                &body,
+            )
+            .with_status(status));
+        }
+        <T::Output as FromRes<_, Response<Body>, _>>::from_res(res).await
+    }
+
     /// Returns the server function at the given path as a service that can be modified.
+    ///
+    /// A `GET` server function also answers `HEAD` requests, with the headers of its
+    /// response and no body. `OPTIONS` requests are answered with the methods the
+    /// server function supports. With the `debug-wire` feature, its request and
+    /// response bodies are [logged](crate::inspect). The hooks registered with
+    /// [`on_request`](crate::on_request) and [`on_response`](crate::on_response) run
+    /// around it.
+    ///
+    /// Its middleware, including any [`AsyncLayer`](crate::middleware::AsyncLayer)s,
+    /// is only built once, by the first request that runs through it, and shared by
+    /// every request after that. Use
+    /// [`get_server_fn_service_async`] to wait for it to be built.
     pub fn get_server_fn_service(
         path: &str,
     ) -> Option<BoxedService<Request<Body>, Response<Body>>> {
-        REGISTERED_SERVER_FUNCTIONS.get(path).map(|server_fn| {
-            let middleware = (server_fn.middleware)();
-            let mut service = BoxedService::new(server_fn.clone());
-            for middleware in middleware {
-                service = middleware.layer(service);
-            }
-            service
-        })
+        built_service(path)
+            .map(|service| BoxedService::new(BuiltService(service)))
+    }
+
+    /// Returns the server function at the given path as a service like
+    /// [`get_server_fn_service`], once all of its middleware has been built.
+    pub async fn get_server_fn_service_async(
+        path: &str,
+    ) -> Option<BoxedService<Request<Body>, Response<Body>>> {
+        let service = built_service(path)?;
+        Some(BoxedService::new(service.await))
+    }
+
+    fn built_service(
+        path: &str,
+    ) -> Option<BuildOnce<Request<Body>, Response<Body>>> {
+        let server_fn = REGISTERED_SERVER_FUNCTIONS
+            .get(path)
+            .map(|server_fn| server_fn.clone())?;
+        let service = SERVICES
+            .entry(server_fn.path())
+            .or_insert_with(|| {
+                let method = server_fn.method();
+                build_once(
+                    server_fn,
+                    {
+                        let method = method.clone();
+                        move |service| {
+                            #[cfg(feature = "debug-wire")]
+                            let service = InspectWire.layer(service);
+                            AnswerOptions::new(&method).layer(service)
+                        }
+                    },
+                    move |service| {
+                        let service = if method == Method::GET {
+                            AnswerHead.layer(service)
+                        } else {
+                            service
+                        };
+                        RunHooks.layer(service)
+                    },
+                )
+            })
+            .clone();
+        Some(service)
     }
 }
 
 /// Actix integration.
 #[cfg(feature = "actix")]
 pub mod actix {
+    #[cfg(feature = "debug-wire")]
+    use crate::middleware::InspectWire;
     use crate::{
-        middleware::BoxedService, request::actix::ActixRequest,
-        response::actix::ActixResponse, Encoding, LazyServerFnMap, ServerFn,
-        ServerFnTraitObj,
+        build_once, insert_versioned_path,
+        middleware::{AnswerOptions, BoxedService, Layer, RunHooks},
+        request::actix::ActixRequest,
+        resolve_path,
+        response::{
+            actix::ActixResponse, error_response_with_status, ResponseOptions,
+        },
+        versioned_paths, BuildOnce, BuiltService, Encoding, LazyServerFnMap,
+        LazyVersionedPaths, ServerFn, ServerFnTraitObj,
     };
     use actix_web::{web::Payload, HttpRequest, HttpResponse};
     use http::Method;
     #[doc(hidden)]
     pub use send_wrapper::SendWrapper;
+    use std::{
+        cell::RefCell,
+        collections::HashMap,
+        sync::atomic::{AtomicU64, Ordering},
+    };
 
+    // the middleware of these isn't built here, since Actix middleware can only be
+    // used on the worker thread it was built on
     static REGISTERED_SERVER_FUNCTIONS: LazyServerFnMap<
         ActixRequest,
         ActixResponse,
-    > = initialize_server_fn_map!(ActixRequest, ActixResponse);
+    > = once_cell::sync::Lazy::new(|| {
+        crate::inventory::iter::<ServerFnTraitObj<ActixRequest, ActixResponse>>
+            .into_iter()
+            .map(|obj| (obj.path(), obj.clone()))
+            .collect()
+    });
+
+    /// Incremented by [`register_explicit`], so that each worker drops the services
+    /// it built before a server function was registered again.
+    static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+    thread_local! {
+        /// The server functions of this worker thread, each with a set of middleware
+        /// that is shared between the requests the worker handles, and the
+        /// [`GENERATION`] they were built in.
+        static WORKER_SERVICES: RefCell<(u64, WorkerServices)> =
+            RefCell::default();
+    }
+
+    type WorkerServices =
+        HashMap<&'static str, BuildOnce<ActixRequest, ActixResponse>>;
+
+    static VERSIONED_PATHS: LazyVersionedPaths =
+        once_cell::sync::Lazy::new(|| {
+            versioned_paths(&REGISTERED_SERVER_FUNCTIONS)
+        });
 
     /// Explicitly register a server function. This is only necessary if you are
     /// running the server in a WASM environment (or a rare environment that the
     /// `inventory` crate won't work in.).
+    ///
+    /// Each worker builds the middleware of its server functions again after this
+    /// has been called, so a server function registered again while the server
+    /// is running uses its new middleware on every worker.
     pub fn register_explicit<T>()
     where
         T: ServerFn<
@@ -571,8 +1139,11 @@ pub mod actix {
                 T::InputEncoding::METHOD,
                 |req| Box::pin(T::run_on_server(req)),
                 T::middlewares,
-            ),
+            )
+            .with_version(T::VERSION),
         );
+        GENERATION.fetch_add(1, Ordering::AcqRel);
+        insert_versioned_path(&VERSIONED_PATHS, T::PATH, T::VERSION);
     }
 
     /// The set of all registered server function paths.
@@ -583,23 +1154,40 @@ pub mod actix {
     }
 
     /// An Actix handler that responds to a server function request.
+    ///
+    /// Like the Axum handler, this sends a request for a versioned path without its
+    /// version to the version named by its `Accept-Version` header.
     pub async fn handle_server_fn(
         req: HttpRequest,
         payload: Payload,
     ) -> HttpResponse {
         let path = req.uri().path();
-        if let Some(server_fn) = REGISTERED_SERVER_FUNCTIONS.get(path) {
-            let middleware = (server_fn.middleware)();
-            // http::Method is the only non-Copy type here
-            let mut service = BoxedService::new(server_fn.clone());
-            for middleware in middleware {
-                service = middleware.layer(service);
-            }
-            service
-                .0
-                .run(ActixRequest::from((req, payload)))
+        let version = req
+            .headers()
+            .get("accept-version")
+            .and_then(|version| version.to_str().ok());
+        let path = resolve_path(
+            &REGISTERED_SERVER_FUNCTIONS,
+            &VERSIONED_PATHS,
+            path,
+            version,
+        )
+        .unwrap_or(path);
+        if let Some(mut service) = get_server_fn_service(path) {
+            let path = path.to_string();
+            // the status of an error returned by `RawErrors` is left here
+            let options = ResponseOptions::default();
+            options
+                .clone()
+                .scope(service.0.try_run(ActixRequest::from((req, payload))))
                 .await
-                .0
+                .unwrap_or_else(|e| {
+                    error_response_with_status(
+                        &path,
+                        &e,
+                        options.error_status(),
+                    )
+                })
                 .take()
         } else {
             HttpResponse::BadRequest().body(format!(
@@ -616,16 +1204,62 @@ pub mod actix {
     }
 
     /// Returns the server function at the given path as a service that can be modified.
+    ///
+    /// `OPTIONS` requests are answered with the methods the server function
+    /// supports. With the `debug-wire` feature, its request and response bodies are
+    /// [logged](crate::inspect). The hooks registered with
+    /// [`on_request`](crate::on_request) and [`on_response`](crate::on_response) run
+    /// around it.
+    ///
+    /// Its middleware, including any [`AsyncLayer`](crate::middleware::AsyncLayer)s,
+    /// is only built once for each worker thread, by the first request that runs
+    /// through it, and shared by the requests that worker handles after that. Use
+    /// [`get_server_fn_service_async`] to wait for it to be built.
     pub fn get_server_fn_service(
         path: &str,
     ) -> Option<BoxedService<ActixRequest, ActixResponse>> {
-        REGISTERED_SERVER_FUNCTIONS.get(path).map(|server_fn| {
-            let middleware = (server_fn.middleware)();
-            let mut service = BoxedService::new(server_fn.clone());
-            for middleware in middleware {
-                service = middleware.layer(service);
+        built_service(path)
+            .map(|service| BoxedService::new(BuiltService(service)))
+    }
+
+    /// Returns the server function at the given path as a service like
+    /// [`get_server_fn_service`], once all of its middleware has been built.
+    pub async fn get_server_fn_service_async(
+        path: &str,
+    ) -> Option<BoxedService<ActixRequest, ActixResponse>> {
+        let service = built_service(path)?;
+        Some(BoxedService::new(service.await))
+    }
+
+    fn built_service(
+        path: &str,
+    ) -> Option<BuildOnce<ActixRequest, ActixResponse>> {
+        let server_fn = REGISTERED_SERVER_FUNCTIONS
+            .get(path)
+            .map(|server_fn| server_fn.clone())?;
+        let service = WORKER_SERVICES.with(|services| {
+            let (built_in, services) = &mut *services.borrow_mut();
+            let generation = GENERATION.load(Ordering::Acquire);
+            if *built_in != generation {
+                services.clear();
+                *built_in = generation;
             }
-            service
-        })
+            services
+                .entry(server_fn.path())
+                .or_insert_with(|| {
+                    let method = server_fn.method();
+                    build_once(
+                        server_fn,
+                        move |service| {
+                            #[cfg(feature = "debug-wire")]
+                            let service = InspectWire.layer(service);
+                            AnswerOptions::new(&method).layer(service)
+                        },
+                        |service| RunHooks.layer(service),
+                    )
+                })
+                .clone()
+        });
+        Some(service)
     }
 }
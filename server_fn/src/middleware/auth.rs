@@ -0,0 +1,228 @@
+use super::{
+    BoxedService, Layer, RequestExtensionsMut, RequestHeaders, RequestPath,
+    Service, SharedService,
+};
+use crate::{error::NoCustomError, ServerFnError};
+use http::StatusCode;
+use std::{
+    fmt::Display,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// A middleware [`Layer`] that authenticates requests with a bearer token.
+///
+/// The token is read from the `Authorization: Bearer <token>` header and passed to an
+/// async validator. If the validator returns claims, they are inserted into the request
+/// extensions, where the server function can read them. Requests without a token, or
+/// whose token is rejected, get a `401 Unauthorized` error response, and the server
+/// function is not called.
+///
+/// ```rust,ignore
+/// #[derive(Clone)]
+/// struct Claims {
+///     user_id: u32,
+/// }
+///
+/// async fn validate(token: String) -> Result<Claims, AuthError> {
+///     // check the signature, expiry, ...
+/// }
+///
+/// #[server]
+/// #[middleware(Auth::bearer(validate))]
+/// pub async fn delete_post(
+///     id: u32,
+///     Extension(claims): Extension<Claims>,
+/// ) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+/// ```
+pub struct Auth<V> {
+    validate: Arc<V>,
+}
+
+impl<V> Auth<V> {
+    /// Creates a new layer that validates bearer tokens with `validate`.
+    pub fn bearer(validate: V) -> Self {
+        Self {
+            validate: Arc::new(validate),
+        }
+    }
+
+    fn missing_token() -> ServerFnError {
+        ServerFnError::ServerError("missing bearer token".into())
+    }
+
+    fn invalid_token(err: impl Display) -> ServerFnError {
+        ServerFnError::ServerError(format!("invalid bearer token: {err}"))
+    }
+
+    /// The token from an `Authorization: Bearer <token>` header.
+    fn bearer_token(authorization: Option<&str>) -> Option<String> {
+        let (scheme, token) = authorization?.trim().split_once(' ')?;
+        let token = token.trim();
+        (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty())
+            .then(|| token.to_string())
+    }
+}
+
+impl<V> Clone for Auth<V> {
+    fn clone(&self) -> Self {
+        Self {
+            validate: Arc::clone(&self.validate),
+        }
+    }
+}
+
+struct AuthService<V, Req, Res> {
+    validate: Arc<V>,
+    inner: SharedService<Req, Res>,
+}
+
+impl<V, Fut, C, E, Req, Res> Layer<Req, Res> for Auth<V>
+where
+    V: Fn(String) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<C, E>> + Send + 'static,
+    C: Clone + Send + Sync + 'static,
+    E: Display + Send,
+    Req: RequestHeaders + RequestExtensionsMut + RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(AuthService {
+            validate: Arc::clone(&self.validate),
+            inner: inner.into_shared(),
+        })
+    }
+}
+
+impl<V, Fut, C, E, Req, Res> Service<Req, Res> for AuthService<V, Req, Res>
+where
+    V: Fn(String) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<C, E>> + Send + 'static,
+    C: Clone + Send + Sync + 'static,
+    E: Display + Send,
+    Req: RequestHeaders + RequestExtensionsMut + RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        mut req: Req,
+    ) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let unauthorized = |path: &str, err: ServerFnError| {
+            let mut res = Res::error_response(path, &err);
+            res.set_status(StatusCode::UNAUTHORIZED);
+            res
+        };
+        let Some(token) = Auth::<V>::bearer_token(req.header("authorization"))
+        else {
+            let res = unauthorized(req.path(), Auth::<V>::missing_token());
+            return Box::pin(async move { res });
+        };
+        let validation = (self.validate)(token);
+        let mut inner = self.inner.clone();
+        Box::pin(async move {
+            match validation.await {
+                Ok(claims) => {
+                    req.insert_extension(claims);
+                    inner.run(req).await
+                }
+                Err(e) => unauthorized(req.path(), Auth::<V>::invalid_token(e)),
+            }
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Auth;
+    use crate::middleware::{BoxedService, Layer, Service};
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{future::Future, pin::Pin};
+
+    #[derive(Clone)]
+    struct Claims {
+        user: String,
+    }
+
+    async fn validate(token: String) -> Result<Claims, &'static str> {
+        match token.as_str() {
+            "secret" => Ok(Claims {
+                user: "alice".into(),
+            }),
+            _ => Err("unknown token"),
+        }
+    }
+
+    /// Responds with the user from the claims, like a server function would.
+    struct WhoAmI;
+
+    impl Service<Request<Body>, Response<Body>> for WhoAmI {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let user = req
+                .extensions()
+                .get::<Claims>()
+                .map(|claims| claims.user.clone())
+                .unwrap_or_default();
+            Box::pin(async move { Response::new(Body::from(user)) })
+        }
+    }
+
+    async fn run(authorization: Option<&str>) -> (StatusCode, String) {
+        let mut service =
+            Auth::bearer(validate).layer(BoxedService::new(WhoAmI));
+        let mut req = Request::post("/api/whoami");
+        if let Some(authorization) = authorization {
+            req = req.header(header::AUTHORIZATION, authorization);
+        }
+        let res = service.0.run(req.body(Body::empty()).unwrap()).await;
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_token() {
+        assert_eq!(
+            run(None).await,
+            (
+                StatusCode::UNAUTHORIZED,
+                "ServerError|missing bearer token".into()
+            )
+        );
+        assert_eq!(run(Some("Basic secret")).await.0, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_token() {
+        assert_eq!(
+            run(Some("Bearer guess")).await,
+            (
+                StatusCode::UNAUTHORIZED,
+                "ServerError|invalid bearer token: unknown token".into()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn valid_token_reaches_handler_with_claims() {
+        assert_eq!(
+            run(Some("Bearer secret")).await,
+            (StatusCode::OK, "alice".into())
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use super::BoxedService;
+use crate::ServerFnError;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A middleware [`Layer`](super::Layer) that rejects request bodies larger than a
+/// given number of bytes, with a `413 Payload Too Large` error response.
+///
+/// Requests with a `Content-Length` header over the limit are rejected without calling
+/// the inner service. The body is also wrapped so that the limit is enforced while it
+/// is being read, in case the `Content-Length` header is missing or wrong.
+///
+/// ```rust,ignore
+/// #[server(input = MultipartFormData)]
+/// #[middleware(RequestBodyLimit::new(10 * 1024 * 1024))]
+/// pub async fn upload(data: MultipartData) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RequestBodyLimit {
+    limit: usize,
+}
+
+impl RequestBodyLimit {
+    /// Creates a new body limit of `limit` bytes.
+    pub fn new(limit: usize) -> Self {
+        Self { limit }
+    }
+
+    pub(super) fn error() -> ServerFnError {
+        ServerFnError::ServerError("request body too large".into())
+    }
+
+    fn content_length_exceeded(&self, content_length: Option<&str>) -> bool {
+        content_length
+            .and_then(|len| len.parse::<usize>().ok())
+            .is_some_and(|len| len > self.limit)
+    }
+
+    /// Wraps a body stream so that it errors once more than `limit` bytes have been
+    /// read, setting `exceeded` when it does.
+    pub(super) fn limit_stream<E>(
+        &self,
+        stream: impl Stream<Item = Result<Bytes, E>>,
+        exceeded: Arc<AtomicBool>,
+        overflow: impl Fn() -> E,
+    ) -> impl Stream<Item = Result<Bytes, E>> {
+        let limit = self.limit;
+        let mut read = 0;
+        stream.map(move |chunk| {
+            let chunk = chunk?;
+            read += chunk.len();
+            if read > limit {
+                exceeded.store(true, Ordering::Relaxed);
+                Err(overflow())
+            } else {
+                Ok(chunk)
+            }
+        })
+    }
+}
+
+struct RequestBodyLimitService<Req, Res> {
+    config: RequestBodyLimit,
+    inner: BoxedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{RequestBodyLimit, RequestBodyLimitService};
+    use crate::{
+        middleware::{BoxedService, Layer, RequestPath, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use std::{
+        future::Future,
+        io,
+        pin::Pin,
+        sync::{atomic::Ordering, Arc},
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for RequestBodyLimit {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(RequestBodyLimitService {
+                config: *self,
+                inner,
+            })
+        }
+    }
+
+    fn too_large(path: &str) -> Response<Body> {
+        let mut res =
+            Response::<Body>::error_response(path, &RequestBodyLimit::error());
+        *res.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+        res
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for RequestBodyLimitService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let path = req.path().to_string();
+            let content_length = req
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|len| len.to_str().ok());
+            if self.config.content_length_exceeded(content_length) {
+                return Box::pin(async move { too_large(&path) });
+            }
+
+            let exceeded = Arc::default();
+            let req = req.map(|body| {
+                Body::from_stream(self.config.limit_stream(
+                    body.into_data_stream(),
+                    Arc::clone(&exceeded),
+                    || {
+                        axum::Error::new(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "request body too large",
+                        ))
+                    },
+                ))
+            });
+            let inner = self.inner.0.run(req);
+            Box::pin(async move {
+                let res = inner.await;
+                if exceeded.load(Ordering::Relaxed) {
+                    too_large(&path)
+                } else {
+                    res
+                }
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{RequestBodyLimit, RequestBodyLimitService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{
+        dev,
+        error::PayloadError,
+        http::{header, StatusCode},
+    };
+    use futures::Stream;
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{atomic::Ordering, Arc},
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for RequestBodyLimit {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(RequestBodyLimitService {
+                config: *self,
+                inner,
+            })
+        }
+    }
+
+    fn too_large(path: &str) -> ActixResponse {
+        let mut res =
+            ActixResponse::error_response(path, &RequestBodyLimit::error());
+        *res.0.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+        res
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for RequestBodyLimitService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let path = req.path().to_string();
+            let content_length = req
+                .0
+                 .0
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|len| len.to_str().ok());
+            if self.config.content_length_exceeded(content_length) {
+                return Box::pin(async move { too_large(&path) });
+            }
+
+            let exceeded = Arc::default();
+            let rewritten = req.1.clone();
+            let (http_req, payload) = req.0.take();
+            let payload = dev::Payload::from(
+                Box::pin(self.config.limit_stream(
+                    payload,
+                    Arc::clone(&exceeded),
+                    || PayloadError::Overflow,
+                )) as Pin<Box<dyn Stream<Item = _>>>,
+            );
+            let inner = self.inner.0.run(
+                ActixRequest::from((http_req, payload)).with_path(rewritten),
+            );
+            Box::pin(async move {
+                let res = inner.await;
+                if exceeded.load(Ordering::Relaxed) {
+                    too_large(&path)
+                } else {
+                    res
+                }
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::RequestBodyLimit;
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        convert::Infallible,
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    };
+
+    /// Reads the whole body, like a server function decoding its arguments would.
+    struct ReadBody(Arc<AtomicBool>);
+
+    impl Service<Request<Body>, Response<Body>> for ReadBody {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            self.0.store(true, Ordering::SeqCst);
+            Box::pin(async move {
+                match req.into_body().collect().await {
+                    Ok(body) => Response::new(Body::from(body.to_bytes())),
+                    Err(e) => {
+                        let err = ServerFnError::new(e);
+                        Response::<Body>::error_response("/api/upload", &err)
+                    }
+                }
+            })
+        }
+    }
+
+    async fn run(req: Request<Body>) -> (StatusCode, bool) {
+        let called = Arc::new(AtomicBool::new(false));
+        let mut service = RequestBodyLimit::new(8)
+            .layer(BoxedService::new(ReadBody(called.clone())));
+        let status = service.0.run(req).await.status();
+        (status, called.load(Ordering::SeqCst))
+    }
+
+    fn chunked(chunks: &'static [&'static str]) -> Body {
+        Body::from_stream(futures::stream::iter(chunks.iter().map(|chunk| {
+            Ok::<_, Infallible>(Bytes::from_static(chunk.as_bytes()))
+        })))
+    }
+
+    #[tokio::test]
+    async fn rejects_large_content_length_up_front() {
+        let req = Request::post("/api/upload")
+            .header(header::CONTENT_LENGTH, "100")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(run(req).await, (StatusCode::PAYLOAD_TOO_LARGE, false));
+    }
+
+    #[tokio::test]
+    async fn rejects_streamed_body_over_the_limit() {
+        // no Content-Length, so the limit can only be enforced while reading
+        let req = Request::post("/api/upload")
+            .body(chunked(&["12345", "67890"]))
+            .unwrap();
+        assert_eq!(run(req).await, (StatusCode::PAYLOAD_TOO_LARGE, true));
+    }
+
+    #[tokio::test]
+    async fn rejects_body_longer_than_its_content_length() {
+        let req = Request::post("/api/upload")
+            .header(header::CONTENT_LENGTH, "4")
+            .body(chunked(&["12345", "67890"]))
+            .unwrap();
+        assert_eq!(run(req).await, (StatusCode::PAYLOAD_TOO_LARGE, true));
+    }
+
+    #[tokio::test]
+    async fn accepts_small_bodies() {
+        let req = Request::post("/api/upload")
+            .body(chunked(&["1234", "5678"]))
+            .unwrap();
+        assert_eq!(run(req).await, (StatusCode::OK, true));
+    }
+}
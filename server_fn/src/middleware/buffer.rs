@@ -0,0 +1,191 @@
+use super::{BoxedService, Layer, RequestPath, Service};
+use crate::{error::NoCustomError, ServerFnError};
+use http::StatusCode;
+use once_cell::sync::OnceCell;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+/// A request waiting in the queue, which sends its response back once it has run.
+type Job = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A middleware [`Layer`] that accepts requests into a bounded queue, from which a
+/// fixed number of workers take them and run the inner service.
+///
+/// At most `workers` requests run at once, and up to `capacity` more wait in the
+/// queue. A request that arrives while the queue is full is shed right away with a
+/// `503 Service Unavailable` error response, rather than waiting for room.
+/// [`Service::poll_ready`] is forwarded to the inner service.
+///
+/// All services created by the same layer share its queue and workers. The workers
+/// are spawned onto the Tokio runtime when the layer is first applied.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(Buffer::new(64, 4))]
+/// pub async fn render_thumbnail(id: u32) -> Result<Vec<u8>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Buffer {
+    capacity: usize,
+    workers: usize,
+    queue: Arc<OnceCell<mpsc::Sender<Job>>>,
+}
+
+impl Buffer {
+    /// Creates a new buffer that queues up to `capacity` requests for `workers`
+    /// workers.
+    ///
+    /// # Panics
+    /// Panics if `capacity` or `workers` is zero.
+    pub fn new(capacity: usize, workers: usize) -> Self {
+        assert!(capacity > 0, "a buffer needs room for at least one request");
+        assert!(workers > 0, "a buffer needs at least one worker");
+        Self {
+            capacity,
+            workers,
+            queue: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// The sending half of the queue, spawning the workers the first time.
+    fn queue(&self) -> mpsc::Sender<Job> {
+        self.queue
+            .get_or_init(|| {
+                let (tx, rx) = mpsc::channel::<Job>(self.capacity);
+                let rx = Arc::new(Mutex::new(rx));
+                for _ in 0..self.workers {
+                    let rx = Arc::clone(&rx);
+                    tokio::spawn(async move {
+                        loop {
+                            let job = rx.lock().await.recv().await;
+                            let Some(job) = job else { break };
+                            job.await;
+                        }
+                    });
+                }
+                tx
+            })
+            .clone()
+    }
+
+    fn error() -> ServerFnError {
+        ServerFnError::ServerError("request queue is full".into())
+    }
+}
+
+struct BufferService<Req, Res> {
+    queue: mpsc::Sender<Job>,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for Buffer
+where
+    Req: RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(BufferService {
+            queue: self.queue(),
+            inner,
+        })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for BufferService<Req, Res>
+where
+    Req: RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let path = req.path().to_string();
+        let (tx, rx) = oneshot::channel();
+        let inner = self.inner.0.run(req);
+        let job: Job = Box::pin(async move {
+            // the caller may have gone away in the meantime
+            let _ = tx.send(inner.await);
+        });
+        let queued = self.queue.try_send(job).is_ok();
+        Box::pin(async move {
+            if queued {
+                // the job is only dropped without running if the workers stopped
+                if let Ok(res) = rx.await {
+                    return res;
+                }
+            }
+            let mut res = Res::error_response(&path, &Buffer::error());
+            res.set_status(StatusCode::SERVICE_UNAVAILABLE);
+            res
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Buffer;
+    use crate::middleware::{BoxedService, Layer, Service};
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use std::{future::Future, pin::Pin, time::Duration};
+    use tokio::time::Instant;
+
+    struct Sleep;
+
+    impl Service<Request<Body>, Response<Body>> for Sleep {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Response::new(Body::empty())
+            })
+        }
+    }
+
+    fn call(
+        layer: &Buffer,
+    ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+        let req = Request::post("/api/thumbnail").body(Body::empty()).unwrap();
+        layer.layer(BoxedService::new(Sleep)).0.run(req)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn workers_run_queued_requests() {
+        let layer = Buffer::new(8, 2);
+        let start = Instant::now();
+        let (a, b, c) =
+            futures::join!(call(&layer), call(&layer), call(&layer));
+        assert_eq!([a.status(), b.status(), c.status()], [StatusCode::OK; 3]);
+        // two run at once, and the third waits for a worker
+        assert_eq!(start.elapsed(), Duration::from_secs(20));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sheds_requests_when_queue_is_full() {
+        let layer = Buffer::new(1, 1);
+        let running = call(&layer);
+        // lets the worker take the first request off the queue
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        let queued = call(&layer);
+        let shed = call(&layer);
+
+        assert_eq!(shed.await.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let (running, queued) = futures::join!(running, queued);
+        assert_eq!(running.status(), StatusCode::OK);
+        assert_eq!(queued.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,157 @@
+use super::{BoxedService, Layer, Service, SharedService};
+use crate::ServerFnError;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// A [`Layer`] that skips another layer for requests that match a predicate, and
+/// passes them straight to the inner service instead.
+///
+/// This is created with [`Layer::bypass_if`]. It is useful for endpoints like health
+/// checks, which should not be blocked by authentication or rate limiting that is
+/// applied to every other request.
+///
+/// ```rust,ignore
+/// let auth = Auth::bearer(verify_token)
+///     .bypass_if(|req: &Request<Body>| req.path() == "/health");
+/// ```
+pub struct Bypass<L, F> {
+    layer: L,
+    predicate: Arc<F>,
+}
+
+impl<L, F> Bypass<L, F> {
+    /// Wraps `layer` so that it is skipped for requests for which `predicate`
+    /// returns `true`.
+    pub fn new(layer: L, predicate: F) -> Self {
+        Self {
+            layer,
+            predicate: Arc::new(predicate),
+        }
+    }
+}
+
+impl<L: Clone, F> Clone for Bypass<L, F> {
+    fn clone(&self) -> Self {
+        Self {
+            layer: self.layer.clone(),
+            predicate: Arc::clone(&self.predicate),
+        }
+    }
+}
+
+struct BypassService<F, Req, Res> {
+    predicate: Arc<F>,
+    /// The inner service, wrapped in the layer.
+    layered: BoxedService<Req, Res>,
+    /// The same inner service, without the layer.
+    inner: SharedService<Req, Res>,
+}
+
+impl<L, F, Req, Res> Layer<Req, Res> for Bypass<L, F>
+where
+    L: Layer<Req, Res>,
+    F: Fn(&Req) -> bool + Send + Sync + 'static,
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        bypass(&self.layer, Arc::clone(&self.predicate), inner)
+    }
+}
+
+/// Wraps `inner` in `layer`, except for requests for which `predicate` returns
+/// `true`.
+pub(super) fn bypass<L, F, Req, Res>(
+    layer: &L,
+    predicate: Arc<F>,
+    inner: BoxedService<Req, Res>,
+) -> BoxedService<Req, Res>
+where
+    L: Layer<Req, Res>,
+    F: Fn(&Req) -> bool + Send + Sync + 'static,
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    let inner = inner.into_shared();
+    BoxedService::new(BypassService {
+        predicate,
+        layered: layer.layer(BoxedService::new(inner.clone())),
+        inner,
+    })
+}
+
+impl<F, Req, Res> Service<Req, Res> for BypassService<F, Req, Res>
+where
+    F: Fn(&Req) -> bool,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        if (self.predicate)(&req) {
+            self.inner.run(req)
+        } else {
+            self.layered.0.run(req)
+        }
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.layered.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use crate::middleware::{service_fn, Auth, Layer, RequestPath};
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+
+    async fn validate(token: String) -> Result<String, &'static str> {
+        match token.as_str() {
+            "secret" => Ok("alice".into()),
+            _ => Err("unknown token"),
+        }
+    }
+
+    async fn status(path: &str, token: Option<&str>) -> StatusCode {
+        let layer = Layer::<Request<Body>, Response<Body>>::bypass_if(
+            Auth::bearer(validate),
+            |req: &Request<Body>| req.path() == "/health",
+        );
+        let mut service =
+            layer.layer(service_fn(|_req: Request<Body>| async move {
+                Response::new(Body::from("ok"))
+            }));
+        let mut req = Request::get(path);
+        if let Some(token) = token {
+            req = req.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        service
+            .0
+            .run(req.body(Body::empty()).unwrap())
+            .await
+            .status()
+    }
+
+    #[tokio::test]
+    async fn bypassed_path_skips_layer() {
+        assert_eq!(status("/health", None).await, StatusCode::OK);
+        assert_eq!(status("/health", Some("wrong")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn other_paths_still_run_layer() {
+        assert_eq!(
+            status("/api/delete_post", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status("/api/delete_post", Some("secret")).await,
+            StatusCode::OK
+        );
+    }
+}
@@ -0,0 +1,550 @@
+use super::BoxedService;
+use bytes::Bytes;
+use http::HeaderName;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// The default number of responses that a [`Cache`] keeps.
+pub const DEFAULT_CACHE_MAX_ENTRIES: usize = 1024;
+
+/// A middleware [`Layer`](super::Layer) that caches the responses of `GET`
+/// requests in memory.
+///
+/// Responses are keyed by the path and query of the request, and optionally by the
+/// value of a request header (see [`Cache::vary`]). While a cached response is
+/// fresh, it is returned right away, without calling the inner service. Only
+/// `200 OK` responses without a `Set-Cookie` header are cached; their bodies are
+/// buffered, so a streaming response is only sent once it has finished.
+///
+/// This is only useful for server functions whose result depends on nothing but
+/// their arguments, and which use a `GET` input encoding like
+/// [`GetUrl`](crate::codec::GetUrl).
+///
+/// ```rust,ignore
+/// #[server(input = GetUrl)]
+/// #[middleware(Cache::new(Duration::from_secs(60)).max_entries(100))]
+/// pub async fn exchange_rate(from: String, to: String) -> Result<f64, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cache {
+    ttl: Duration,
+    max_entries: usize,
+    vary: Option<HeaderName>,
+    entries: Arc<Mutex<HashMap<String, CachedResponse>>>,
+}
+
+/// A buffered response, stored independently of the framework.
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    status: u16,
+    headers: Vec<(String, Bytes)>,
+    body: Bytes,
+    expires: Instant,
+}
+
+impl Cache {
+    /// Creates a new cache that keeps responses for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: DEFAULT_CACHE_MAX_ENTRIES,
+            vary: None,
+            entries: Arc::default(),
+        }
+    }
+
+    /// Keeps at most `n` responses. When the cache is full, the response that
+    /// expires first is dropped.
+    ///
+    /// Defaults to [`DEFAULT_CACHE_MAX_ENTRIES`].
+    pub fn max_entries(mut self, n: usize) -> Self {
+        self.max_entries = n;
+        self
+    }
+
+    /// Caches responses separately for each value of the request header called
+    /// `name`, like `Accept-Language`.
+    ///
+    /// # Panics
+    /// Panics if `name` is not a valid header name.
+    pub fn vary(mut self, name: &str) -> Self {
+        self.vary = Some(
+            HeaderName::from_bytes(name.as_bytes())
+                .expect("invalid header name"),
+        );
+        self
+    }
+
+    /// The cache key for a request, or `None` if it should not be cached.
+    fn key(
+        &self,
+        method: &str,
+        path: &str,
+        query: Option<&str>,
+        vary: Option<&str>,
+    ) -> Option<String> {
+        if method != "GET" {
+            return None;
+        }
+        let mut key = path.to_string();
+        if let Some(query) = query {
+            key.push('?');
+            key.push_str(query);
+        }
+        if self.vary.is_some() {
+            key.push('\n');
+            key.push_str(vary.unwrap_or_default());
+        }
+        Some(key)
+    }
+
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        let mut entries =
+            self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        match entries.get(key) {
+            Some(entry) if entry.expires > Instant::now() => {
+                Some(entry.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Whether a response with these properties can be cached.
+    fn is_cacheable(status: u16, headers: &[(String, Bytes)]) -> bool {
+        status == 200
+            && !headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("set-cookie"))
+    }
+
+    fn insert(
+        &self,
+        key: String,
+        status: u16,
+        headers: Vec<(String, Bytes)>,
+        body: Bytes,
+    ) {
+        if self.max_entries == 0 || !Self::is_cacheable(status, &headers) {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries =
+            self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.retain(|_, entry| entry.expires > now);
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            CachedResponse {
+                status,
+                headers,
+                body,
+                expires: now + self.ttl,
+            },
+        );
+    }
+}
+
+struct CacheService<Req, Res> {
+    cache: Cache,
+    inner: BoxedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{Cache, CacheService, CachedResponse};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use http::{HeaderName, HeaderValue, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for Cache {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(CacheService {
+                cache: self.clone(),
+                inner,
+            })
+        }
+    }
+
+    fn to_response(cached: CachedResponse) -> Response<Body> {
+        let mut res = Response::new(Body::from(cached.body));
+        *res.status_mut() =
+            StatusCode::from_u16(cached.status).unwrap_or(StatusCode::OK);
+        for (name, value) in cached.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_maybe_shared(value),
+            ) {
+                res.headers_mut().append(name, value);
+            }
+        }
+        res
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for CacheService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let key = self.cache.key(
+                req.method().as_str(),
+                req.uri().path(),
+                req.uri().query(),
+                self.cache
+                    .vary
+                    .as_ref()
+                    .and_then(|name| req.headers().get(name))
+                    .and_then(|value| value.to_str().ok()),
+            );
+            let Some(key) = key else {
+                return self.inner.0.run(req);
+            };
+            if let Some(cached) = self.cache.get(&key) {
+                return Box::pin(async move { to_response(cached) });
+            }
+
+            let path = req.uri().path().to_string();
+            let cache = self.cache.clone();
+            let inner = self.inner.0.run(req);
+            Box::pin(async move {
+                let res = inner.await;
+                if res.status() != StatusCode::OK {
+                    return res;
+                }
+                let (parts, body) = res.into_parts();
+                let body = match body.collect().await {
+                    Ok(body) => body.to_bytes(),
+                    Err(e) => {
+                        return Response::error_response(
+                            &path,
+                            &ServerFnError::new(e),
+                        )
+                    }
+                };
+                let headers = parts
+                    .headers
+                    .iter()
+                    .map(|(name, value)| {
+                        (
+                            name.to_string(),
+                            Bytes::copy_from_slice(value.as_bytes()),
+                        )
+                    })
+                    .collect();
+                cache.insert(key, parts.status.as_u16(), headers, body.clone());
+                Response::from_parts(parts, Body::from(body))
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{Cache, CacheService, CachedResponse};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{
+        http::{
+            header::{HeaderName, HeaderValue},
+            StatusCode,
+        },
+        HttpResponse,
+    };
+    use bytes::Bytes;
+    use send_wrapper::SendWrapper;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for Cache {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(CacheService {
+                cache: self.clone(),
+                inner,
+            })
+        }
+    }
+
+    fn to_response(cached: CachedResponse) -> ActixResponse {
+        build_response(cached.status, cached.headers, cached.body)
+    }
+
+    fn build_response(
+        status: u16,
+        headers: Vec<(String, Bytes)>,
+        body: Bytes,
+    ) -> ActixResponse {
+        let mut res = HttpResponse::build(
+            StatusCode::from_u16(status).unwrap_or(StatusCode::OK),
+        );
+        for (name, value) in headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_maybe_shared(value),
+            ) {
+                res.append_header((name, value));
+            }
+        }
+        ActixResponse::from(res.body(body))
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for CacheService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let http_req = req.request();
+            let key = self.cache.key(
+                http_req.method().as_str(),
+                req.path(),
+                Some(http_req.query_string()).filter(|query| !query.is_empty()),
+                self.cache
+                    .vary
+                    .as_ref()
+                    .and_then(|name| http_req.headers().get(name.as_str()))
+                    .and_then(|value| value.to_str().ok()),
+            );
+            let Some(key) = key else {
+                return self.inner.0.run(req);
+            };
+            if let Some(cached) = self.cache.get(&key) {
+                return Box::pin(async move { to_response(cached) });
+            }
+
+            let path = req.path().to_string();
+            let cache = self.cache.clone();
+            let inner = self.inner.0.run(req);
+            Box::pin(async move {
+                let res = inner.await;
+                if res.0.status() != StatusCode::OK {
+                    return res;
+                }
+                // the response head isn't `Send`, so only its parts are kept
+                // while the body is read
+                let (status, headers, body) = {
+                    let (res, body) = res.take().into_parts();
+                    let headers: Vec<_> = res
+                        .headers()
+                        .iter()
+                        .map(|(name, value)| {
+                            (
+                                name.to_string(),
+                                Bytes::copy_from_slice(value.as_bytes()),
+                            )
+                        })
+                        .collect();
+                    (res.status().as_u16(), headers, body)
+                };
+                // Actix keeps the response on a single thread, so reading the body
+                // only needs to look `Send`
+                let body =
+                    match SendWrapper::new(actix_web::body::to_bytes(body))
+                        .await
+                    {
+                        Ok(body) => body,
+                        Err(e) => {
+                            return ActixResponse::error_response(
+                                &path,
+                                &ServerFnError::new(e),
+                            )
+                        }
+                    };
+                cache.insert(key, status, headers.clone(), body.clone());
+                build_response(status, headers, body)
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Cache;
+    use crate::middleware::{service_fn, BoxedService, Layer};
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    /// Counts its calls, and responds with the count, streaming the body.
+    fn counter(
+        calls: Arc<AtomicUsize>,
+        status: StatusCode,
+    ) -> BoxedService<Request<Body>, Response<Body>> {
+        service_fn(move |_req: Request<Body>| {
+            let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                let chunks = futures::stream::iter([
+                    Ok::<_, std::io::Error>("call "),
+                    Ok(if n == 1 { "1" } else { "n" }),
+                ]);
+                let mut res = Response::new(Body::from_stream(chunks));
+                *res.status_mut() = status;
+                res.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    "text/plain".parse().unwrap(),
+                );
+                res
+            }
+        })
+    }
+
+    async fn call(
+        service: &mut BoxedService<Request<Body>, Response<Body>>,
+        method: &str,
+        uri: &str,
+    ) -> (StatusCode, String) {
+        let req = Request::builder().method(method).uri(uri);
+        call_with(service, req).await
+    }
+
+    async fn call_with(
+        service: &mut BoxedService<Request<Body>, Response<Body>>,
+        req: http::request::Builder,
+    ) -> (StatusCode, String) {
+        let req = req.body(Body::empty()).unwrap();
+        let res = service.0.run(req).await;
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn cached(
+        cache: Cache,
+        status: StatusCode,
+    ) -> (
+        BoxedService<Request<Body>, Response<Body>>,
+        Arc<AtomicUsize>,
+    ) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        (cache.layer(counter(Arc::clone(&calls), status)), calls)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hits_and_misses() {
+        let (mut service, calls) =
+            cached(Cache::new(Duration::from_secs(60)), StatusCode::OK);
+        let ok = (StatusCode::OK, "call 1".to_string());
+        assert_eq!(call(&mut service, "GET", "/api/rate?from=eur").await, ok);
+        assert_eq!(call(&mut service, "GET", "/api/rate?from=eur").await, ok);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // other queries and other methods miss
+        call(&mut service, "GET", "/api/rate?from=usd").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        call(&mut service, "POST", "/api/rate?from=eur").await;
+        call(&mut service, "POST", "/api/rate?from=eur").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn entries_expire() {
+        let (mut service, calls) =
+            cached(Cache::new(Duration::from_secs(60)), StatusCode::OK);
+        call(&mut service, "GET", "/api/rate").await;
+        tokio::time::advance(Duration::from_secs(59)).await;
+        call(&mut service, "GET", "/api/rate").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(call(&mut service, "GET", "/api/rate").await.1, "call n");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn skips_errors_and_evicts_when_full() {
+        let (mut service, calls) = cached(
+            Cache::new(Duration::from_secs(60)),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        );
+        call(&mut service, "GET", "/api/rate").await;
+        call(&mut service, "GET", "/api/rate").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let (mut service, calls) = cached(
+            Cache::new(Duration::from_secs(60)).max_entries(1),
+            StatusCode::OK,
+        );
+        call(&mut service, "GET", "/api/rate?a").await;
+        call(&mut service, "GET", "/api/rate?b").await;
+        call(&mut service, "GET", "/api/rate?a").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn varies_by_header() {
+        let (mut service, calls) = cached(
+            Cache::new(Duration::from_secs(60)).vary("accept-language"),
+            StatusCode::OK,
+        );
+        for lang in ["en", "fr", "en"] {
+            let req = Request::get("/api/greeting")
+                .header(header::ACCEPT_LANGUAGE, lang);
+            call_with(&mut service, req).await;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,240 @@
+use super::{BoxedService, Layer, RequestPath, Service};
+use crate::{error::NoCustomError, ServerFnError};
+use futures::FutureExt;
+use http::StatusCode;
+use std::{
+    any::Any,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+type PanicHook = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+/// A middleware [`Layer`] that turns a panic in a server function into an error response.
+///
+/// Without this layer, what happens after a panic depends on the framework, and can
+/// take down the worker that was handling the request. With it, the panic is caught and
+/// rendered as a `500 Internal Server Error` response for
+/// [`ServerFnError::ServerError`], including the panic message if there is one.
+///
+/// Panics are still printed by the panic hook as usual. With the `tracing` feature, an
+/// error event is also emitted for each one, unless a custom
+/// [`on_panic`](CatchPanic::on_panic) callback is set.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(CatchPanic::new())]
+/// pub async fn parse_report(data: String) -> Result<Report, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct CatchPanic {
+    on_panic: Option<PanicHook>,
+}
+
+impl CatchPanic {
+    /// Creates a new layer that catches panics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `on_panic` with the request path and the panic message whenever a panic
+    /// is caught.
+    pub fn on_panic(
+        mut self,
+        on_panic: impl Fn(&str, &str) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_panic = Some(Arc::new(on_panic));
+        self
+    }
+
+    fn error(message: Option<&str>) -> ServerFnError {
+        ServerFnError::ServerError(match message {
+            Some(message) => format!("server function panicked: {message}"),
+            None => "server function panicked".into(),
+        })
+    }
+}
+
+impl std::fmt::Debug for CatchPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CatchPanic")
+            .field("on_panic", &self.on_panic.is_some())
+            .finish()
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+}
+
+struct CatchPanicService<Req, Res> {
+    on_panic: Option<PanicHook>,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for CatchPanic
+where
+    Req: RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(CatchPanicService {
+            on_panic: self.on_panic.clone(),
+            inner,
+        })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for CatchPanicService<Req, Res>
+where
+    Req: RequestPath,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let path = req.path().to_string();
+        let on_panic = self.on_panic.clone();
+        // the service can panic while creating its future, as well as while polling it
+        let inner =
+            panic::catch_unwind(AssertUnwindSafe(|| self.inner.0.run(req)));
+        Box::pin(async move {
+            let res = match inner {
+                Ok(inner) => AssertUnwindSafe(inner).catch_unwind().await,
+                Err(payload) => Err(payload),
+            };
+            res.unwrap_or_else(|payload| {
+                let message = panic_message(&*payload);
+                match on_panic {
+                    Some(on_panic) => {
+                        on_panic(&path, message.unwrap_or_default())
+                    }
+                    #[cfg(feature = "tracing")]
+                    None => tracing::error!(
+                        path = path.as_str(),
+                        panic = message.unwrap_or_default(),
+                        "server function panicked"
+                    ),
+                    #[cfg(not(feature = "tracing"))]
+                    None => {}
+                }
+                let mut res =
+                    Res::error_response(&path, &CatchPanic::error(message));
+                res.set_status(StatusCode::INTERNAL_SERVER_ERROR);
+                res
+            })
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::CatchPanic;
+    use crate::{
+        error::ServerFnErrorSerde,
+        middleware::{BoxedService, Layer, Service},
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        collections::HashMap,
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+    };
+
+    /// Looks up a user that doesn't exist, and panics.
+    struct Panics(u32);
+
+    impl Service<Request<Body>, Response<Body>> for Panics {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let id = self.0;
+            Box::pin(async move {
+                let users = HashMap::<u32, String>::new();
+                let name = match users.get(&id) {
+                    Some(name) => name.clone(),
+                    None => panic!("no user with id {id}"),
+                };
+                Response::new(Body::from(name))
+            })
+        }
+    }
+
+    struct PanicsEagerly;
+
+    impl Service<Request<Body>, Response<Body>> for PanicsEagerly {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            panic!("not ready")
+        }
+    }
+
+    type Logged = Arc<Mutex<Vec<(String, String)>>>;
+
+    async fn run(
+        service: impl Service<Request<Body>, Response<Body>> + Send + 'static,
+    ) -> (Response<Body>, Logged) {
+        let logged = Logged::default();
+        let layer = CatchPanic::new().on_panic({
+            let logged = Arc::clone(&logged);
+            move |path, message| {
+                logged
+                    .lock()
+                    .unwrap()
+                    .push((path.to_string(), message.to_string()));
+            }
+        });
+        let mut service = layer.layer(BoxedService::new(service));
+        let req = Request::post("/api/user").body(Body::empty()).unwrap();
+        (service.0.run(req).await, logged)
+    }
+
+    #[tokio::test]
+    async fn panics_become_error_responses() {
+        let (res, logged) = run(Panics(7)).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let err: ServerFnError =
+            ServerFnError::de(std::str::from_utf8(&body).unwrap());
+        assert_eq!(
+            err,
+            ServerFnError::ServerError(
+                "server function panicked: no user with id 7".into()
+            )
+        );
+        assert_eq!(
+            *logged.lock().unwrap(),
+            [("/api/user".to_string(), "no user with id 7".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn catches_panics_while_creating_the_future() {
+        let (res, logged) = run(PanicsEagerly).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            *logged.lock().unwrap(),
+            [("/api/user".to_string(), "not ready".to_string())]
+        );
+    }
+}
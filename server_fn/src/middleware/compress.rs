@@ -0,0 +1,531 @@
+use super::BoxedService;
+use bytes::Bytes;
+use flate2::{write::GzEncoder, Compression};
+use futures::{Stream, StreamExt};
+use std::io::{self, Write};
+
+/// The smallest response, in bytes, that [`Compress`] compresses by default.
+pub const DEFAULT_COMPRESS_MIN_SIZE: u64 = 32;
+
+/// A content coding that [`Compress`] can apply to responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    /// `gzip`
+    Gzip,
+    /// `br`
+    Brotli,
+}
+
+impl CompressionAlgorithm {
+    /// The name of this coding in `Accept-Encoding` and `Content-Encoding` headers.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompressionAlgorithm::Gzip => "gzip",
+            CompressionAlgorithm::Brotli => "br",
+        }
+    }
+}
+
+/// A middleware [`Layer`](super::Layer) that compresses response bodies.
+///
+/// The coding is negotiated from the request's `Accept-Encoding` header. When a response
+/// is compressed, its `Content-Encoding` header is set, and its `Content-Length` header is
+/// removed, since the compressed length isn't known until the body has been written.
+/// Streaming bodies are compressed chunk by chunk, so every chunk reaches the client as
+/// soon as it is ready.
+///
+/// Responses are passed through as they are if they
+/// - already have a `Content-Encoding`,
+/// - have a content type that is usually compressed already, like images or archives,
+/// - are known to be smaller than the [minimum size](Compress::min_size).
+///
+/// ```rust,ignore
+/// use server_fn::middleware::CompressionAlgorithm::{Brotli, Gzip};
+///
+/// #[server]
+/// #[middleware(Compress::new().algorithms(&[Gzip, Brotli]).min_size(1024))]
+/// pub async fn list_users() -> Result<Vec<User>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Compress {
+    algorithms: Vec<CompressionAlgorithm>,
+    min_size: u64,
+}
+
+impl Compress {
+    /// Creates a new compression layer that supports brotli and gzip, preferring brotli.
+    pub fn new() -> Self {
+        Self {
+            algorithms: vec![
+                CompressionAlgorithm::Brotli,
+                CompressionAlgorithm::Gzip,
+            ],
+            min_size: DEFAULT_COMPRESS_MIN_SIZE,
+        }
+    }
+
+    /// Sets the supported codings. When the client accepts several of them equally,
+    /// the one that comes first is used.
+    pub fn algorithms(mut self, algorithms: &[CompressionAlgorithm]) -> Self {
+        self.algorithms = algorithms.to_vec();
+        self
+    }
+
+    /// Only compresses responses of at least `bytes` bytes.
+    ///
+    /// Responses with a streaming body of unknown length are always compressed.
+    /// Defaults to [`DEFAULT_COMPRESS_MIN_SIZE`].
+    pub fn min_size(mut self, bytes: u64) -> Self {
+        self.min_size = bytes;
+        self
+    }
+
+    /// Picks the coding to use for a request with the given `Accept-Encoding` header.
+    fn negotiate(
+        &self,
+        accept_encoding: Option<&str>,
+    ) -> Option<CompressionAlgorithm> {
+        let mut best: Option<(CompressionAlgorithm, f32)> = None;
+        for entry in accept_encoding?.split(',') {
+            let mut params = entry.split(';');
+            let coding = params.next().unwrap_or_default().trim();
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality <= 0.0 {
+                continue;
+            }
+            for &algorithm in &self.algorithms {
+                let matches = coding == "*"
+                    || coding.eq_ignore_ascii_case(algorithm.as_str());
+                let prefer = match best {
+                    None => true,
+                    Some((current, current_quality)) => {
+                        quality > current_quality
+                            || (quality == current_quality
+                                && self.rank(algorithm) < self.rank(current))
+                    }
+                };
+                if matches && prefer {
+                    best = Some((algorithm, quality));
+                }
+            }
+        }
+        best.map(|(algorithm, _)| algorithm)
+    }
+
+    fn rank(&self, algorithm: CompressionAlgorithm) -> usize {
+        self.algorithms
+            .iter()
+            .position(|a| *a == algorithm)
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Whether a response with these properties should be compressed.
+    fn should_compress(
+        &self,
+        content_type: Option<&str>,
+        already_encoded: bool,
+        size: Option<u64>,
+    ) -> bool {
+        !already_encoded
+            && !content_type.is_some_and(is_precompressed)
+            && size.map_or(true, |size| size > 0 && size >= self.min_size)
+    }
+}
+
+impl Default for Compress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_precompressed(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    (essence.starts_with("image/") && essence != "image/svg+xml")
+        || essence.starts_with("audio/")
+        || essence.starts_with("video/")
+        || matches!(
+            essence.as_str(),
+            "application/gzip"
+                | "application/x-gzip"
+                | "application/zip"
+                | "application/zstd"
+                | "application/x-7z-compressed"
+                | "application/x-rar-compressed"
+                | "font/woff"
+                | "font/woff2"
+        )
+}
+
+enum Encoder {
+    Gzip(GzEncoder<Vec<u8>>),
+    Brotli(Box<brotli::CompressorWriter<Vec<u8>>>),
+}
+
+impl Encoder {
+    fn new(algorithm: CompressionAlgorithm) -> Self {
+        match algorithm {
+            CompressionAlgorithm::Gzip => Encoder::Gzip(GzEncoder::new(
+                Vec::new(),
+                Compression::default(),
+            )),
+            // quality 4 is a lot faster than the maximum of 11, which matters when
+            // compressing on every request
+            CompressionAlgorithm::Brotli => Encoder::Brotli(Box::new(
+                brotli::CompressorWriter::new(Vec::new(), 4096, 4, 22),
+            )),
+        }
+    }
+
+    /// Compresses a chunk, and flushes it so that it can be sent right away.
+    fn encode(&mut self, chunk: &[u8]) -> io::Result<Bytes> {
+        let buf = match self {
+            Encoder::Gzip(encoder) => {
+                encoder.write_all(chunk)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+            Encoder::Brotli(encoder) => {
+                encoder.write_all(chunk)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+        };
+        Ok(std::mem::take(buf).into())
+    }
+
+    fn finish(self) -> io::Result<Bytes> {
+        match self {
+            Encoder::Gzip(encoder) => encoder.finish().map(Bytes::from),
+            Encoder::Brotli(encoder) => Ok(encoder.into_inner().into()),
+        }
+    }
+}
+
+/// Compresses a body stream with the given coding.
+fn compress_stream<E>(
+    stream: impl Stream<Item = Result<Bytes, E>> + Send + 'static,
+    algorithm: CompressionAlgorithm,
+    io_error: fn(io::Error) -> E,
+) -> impl Stream<Item = Result<Bytes, E>> + Send + 'static
+where
+    E: Send + 'static,
+{
+    let state = (Box::pin(stream), Some(Encoder::new(algorithm)));
+    futures::stream::unfold(
+        state,
+        move |(mut stream, mut encoder)| async move {
+            let chunk = match stream.next().await {
+                Some(Ok(chunk)) => {
+                    encoder.as_mut()?.encode(&chunk).map_err(io_error)
+                }
+                Some(Err(e)) => {
+                    encoder.take()?;
+                    Err(e)
+                }
+                None => encoder.take()?.finish().map_err(io_error),
+            };
+            Some((chunk, (stream, encoder)))
+        },
+    )
+}
+
+struct CompressService<Req, Res> {
+    config: Compress,
+    inner: BoxedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{compress_stream, Compress, CompressService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        ServerFnError,
+    };
+    use axum::body::{Body, HttpBody};
+    use http::{header, HeaderValue, Request, Response};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for Compress {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(CompressService {
+                config: self.clone(),
+                inner,
+            })
+        }
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for CompressService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let algorithm = self.config.negotiate(
+                req.headers()
+                    .get(header::ACCEPT_ENCODING)
+                    .and_then(|value| value.to_str().ok()),
+            );
+            let config = self.config.clone();
+            let inner = self.inner.0.run(req);
+            Box::pin(async move {
+                let res = inner.await;
+                let Some(algorithm) = algorithm else {
+                    return res;
+                };
+                let headers = res.headers();
+                let size = headers
+                    .get(header::CONTENT_LENGTH)
+                    .and_then(|len| len.to_str().ok()?.parse().ok())
+                    .or_else(|| res.body().size_hint().exact());
+                let compress = config.should_compress(
+                    headers
+                        .get(header::CONTENT_TYPE)
+                        .and_then(|value| value.to_str().ok()),
+                    headers.contains_key(header::CONTENT_ENCODING),
+                    size,
+                );
+                if !compress {
+                    return res;
+                }
+
+                let (mut parts, body) = res.into_parts();
+                parts.headers.remove(header::CONTENT_LENGTH);
+                parts.headers.insert(
+                    header::CONTENT_ENCODING,
+                    HeaderValue::from_static(algorithm.as_str()),
+                );
+                parts.headers.append(
+                    header::VARY,
+                    HeaderValue::from_static("accept-encoding"),
+                );
+                let body = Body::from_stream(compress_stream(
+                    body.into_data_stream(),
+                    algorithm,
+                    axum::Error::new,
+                ));
+                Response::from_parts(parts, body)
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{compress_stream, Compress, CompressService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::actix::ActixResponse,
+        ServerFnError,
+    };
+    use actix_web::{
+        body::{BodySize, BodyStream, MessageBody},
+        http::header::{self, HeaderValue},
+    };
+    use send_wrapper::SendWrapper;
+    use std::{
+        future::Future,
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for Compress {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(CompressService {
+                config: self.clone(),
+                inner,
+            })
+        }
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for CompressService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let algorithm = self.config.negotiate(
+                req.request()
+                    .headers()
+                    .get(header::ACCEPT_ENCODING)
+                    .and_then(|value| value.to_str().ok()),
+            );
+            let config = self.config.clone();
+            let inner = self.inner.0.run(req);
+            Box::pin(async move {
+                let res = inner.await;
+                let Some(algorithm) = algorithm else {
+                    return res;
+                };
+                let headers = res.0.headers();
+                let size = match res.0.body().size() {
+                    BodySize::None => Some(0),
+                    BodySize::Sized(size) => Some(size),
+                    BodySize::Stream => None,
+                };
+                let compress = config.should_compress(
+                    headers
+                        .get(header::CONTENT_TYPE)
+                        .and_then(|value| value.to_str().ok()),
+                    headers.contains_key(header::CONTENT_ENCODING),
+                    size,
+                );
+                if !compress {
+                    return res;
+                }
+
+                let (mut res, body) = res.take().into_parts();
+                let headers = res.headers_mut();
+                headers.remove(header::CONTENT_LENGTH);
+                headers.insert(
+                    header::CONTENT_ENCODING,
+                    HeaderValue::from_static(algorithm.as_str()),
+                );
+                headers.append(
+                    header::VARY,
+                    HeaderValue::from_static("accept-encoding"),
+                );
+                // Actix keeps the response on a single thread, so the body only
+                // needs to look `Send`
+                let mut body = SendWrapper::new(Box::pin(body));
+                let chunks = futures::stream::poll_fn(move |cx| {
+                    body.as_mut()
+                        .poll_next(cx)
+                        .map_err(|e| io::Error::other(e.to_string()))
+                });
+                let body = BodyStream::new(compress_stream(
+                    Box::pin(chunks),
+                    algorithm,
+                    std::convert::identity,
+                ));
+                ActixResponse::from(res.set_body(body).map_into_boxed_body())
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{Compress, CompressionAlgorithm::*};
+    use crate::middleware::{BoxedService, Layer, Service};
+    use axum::body::Body;
+    use http::{header, Request, Response};
+    use http_body_util::BodyExt;
+    use std::{future::Future, io::Read, pin::Pin};
+
+    struct Json(String);
+
+    impl Service<Request<Body>, Response<Body>> for Json {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let body = self.0.clone();
+            Box::pin(async move {
+                Response::builder()
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header(header::CONTENT_LENGTH, body.len())
+                    .body(Body::from(body))
+                    .unwrap()
+            })
+        }
+    }
+
+    fn large_json() -> String {
+        let users = (0..200)
+            .map(|id| format!(r#"{{"id":{id},"name":"user {id}"}}"#))
+            .collect::<Vec<_>>();
+        format!("[{}]", users.join(","))
+    }
+
+    async fn run(body: String, accept_encoding: &str) -> Response<Body> {
+        let mut service = Compress::new()
+            .algorithms(&[Gzip, Brotli])
+            .min_size(1024)
+            .layer(BoxedService::new(Json(body)));
+        let req = Request::post("/api/users")
+            .header(header::ACCEPT_ENCODING, accept_encoding)
+            .body(Body::empty())
+            .unwrap();
+        service.0.run(req).await
+    }
+
+    async fn bytes(res: Response<Body>) -> Vec<u8> {
+        res.into_body().collect().await.unwrap().to_bytes().to_vec()
+    }
+
+    #[tokio::test]
+    async fn compresses_large_responses() {
+        let json = large_json();
+        let res = run(json.clone(), "gzip, deflate, br").await;
+        assert_eq!(res.headers()[header::CONTENT_ENCODING], "gzip");
+        assert!(res.headers().get(header::CONTENT_LENGTH).is_none());
+
+        let body = bytes(res).await;
+        assert!(body.len() < json.len());
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(body.as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, json);
+    }
+
+    #[tokio::test]
+    async fn respects_quality_values() {
+        let json = large_json();
+        let res = run(json.clone(), "gzip;q=0.5, br").await;
+        assert_eq!(res.headers()[header::CONTENT_ENCODING], "br");
+
+        let body = bytes(res).await;
+        let mut decoded = String::new();
+        brotli::Decompressor::new(body.as_slice(), 4096)
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, json);
+    }
+
+    #[tokio::test]
+    async fn passes_small_responses_through() {
+        let res = run(r#"{"id":1}"#.to_string(), "gzip, br").await;
+        assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(res.headers()[header::CONTENT_LENGTH], "8");
+        assert_eq!(bytes(res).await, br#"{"id":1}"#);
+    }
+}
@@ -0,0 +1,165 @@
+use super::{BoxedService, Layer, RequestPath, Service, SharedService};
+use crate::{error::NoCustomError, ServerFnError};
+use http::StatusCode;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+
+/// A middleware [`Layer`] that limits how many requests can be handled at once.
+///
+/// Each request acquires a permit before the inner service is called, and releases it
+/// once the response is ready. By default, requests wait until a permit is available.
+/// If an [`acquire_timeout`](ConcurrencyLimit::acquire_timeout) is set, requests that
+/// cannot get a permit in time are shed with a `503 Service Unavailable` error response.
+///
+/// All services created by the same layer share its permits.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(ConcurrencyLimit::new(16).acquire_timeout(Duration::from_secs(1)))]
+/// pub async fn render_report(id: u32) -> Result<Report, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit {
+    semaphore: Arc<Semaphore>,
+    acquire_timeout: Option<Duration>,
+}
+
+impl ConcurrencyLimit {
+    /// Creates a new concurrency limit that allows at most `max` requests at once.
+    pub fn new(max: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max)),
+            acquire_timeout: None,
+        }
+    }
+
+    /// Sheds requests that have waited longer than `timeout` for a permit.
+    pub fn acquire_timeout(mut self, timeout: Duration) -> Self {
+        self.acquire_timeout = Some(timeout);
+        self
+    }
+
+    fn error() -> ServerFnError {
+        ServerFnError::ServerError("too many concurrent requests".into())
+    }
+}
+
+struct ConcurrencyLimitService<Req, Res> {
+    semaphore: Arc<Semaphore>,
+    acquire_timeout: Option<Duration>,
+    inner: SharedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for ConcurrencyLimit
+where
+    Req: RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(ConcurrencyLimitService {
+            semaphore: Arc::clone(&self.semaphore),
+            acquire_timeout: self.acquire_timeout,
+            inner: inner.into_shared(),
+        })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for ConcurrencyLimitService<Req, Res>
+where
+    Req: RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let semaphore = Arc::clone(&self.semaphore);
+        let acquire_timeout = self.acquire_timeout;
+        let mut inner = self.inner.clone();
+        Box::pin(async move {
+            let permit = match acquire_timeout {
+                None => semaphore.acquire_owned().await.ok(),
+                Some(timeout) => {
+                    tokio::time::timeout(timeout, semaphore.acquire_owned())
+                        .await
+                        .ok()
+                        .and_then(Result::ok)
+                }
+            };
+            let Some(_permit) = permit else {
+                let mut res =
+                    Res::error_response(req.path(), &ConcurrencyLimit::error());
+                res.set_status(StatusCode::SERVICE_UNAVAILABLE);
+                return res;
+            };
+            inner.run(req).await
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::ConcurrencyLimit;
+    use crate::middleware::{BoxedService, Layer, Service};
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use std::{future::Future, pin::Pin, time::Duration};
+
+    struct Sleep;
+
+    impl Service<Request<Body>, Response<Body>> for Sleep {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Response::new(Body::empty())
+            })
+        }
+    }
+
+    async fn run_three(layer: ConcurrencyLimit) -> [StatusCode; 3] {
+        let run = || {
+            let req = Request::post("/api/report").body(Body::empty()).unwrap();
+            let mut service = layer.layer(BoxedService::new(Sleep));
+            async move { service.0.run(req).await.status() }
+        };
+        let (a, b, c) = futures::join!(run(), run(), run());
+        [a, b, c]
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sheds_requests_over_the_limit() {
+        let layer =
+            ConcurrencyLimit::new(2).acquire_timeout(Duration::from_secs(1));
+        assert_eq!(
+            run_three(layer).await,
+            [
+                StatusCode::OK,
+                StatusCode::OK,
+                StatusCode::SERVICE_UNAVAILABLE
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_a_permit_without_timeout() {
+        let start = tokio::time::Instant::now();
+        let statuses = run_three(ConcurrencyLimit::new(2)).await;
+        assert_eq!(statuses, [StatusCode::OK; 3]);
+        assert_eq!(start.elapsed(), Duration::from_secs(20));
+    }
+}
@@ -0,0 +1,372 @@
+use super::BoxedService;
+
+/// A middleware [`Layer`](super::Layer) that answers conditional requests with
+/// `304 Not Modified`.
+///
+/// The body of every `200 OK` response to a `GET` or `HEAD` request is buffered and
+/// hashed into a weak `ETag`, unless the response already has an `ETag` of its own.
+/// If the `If-None-Match` header of the request matches that tag, the client
+/// already has the current version, so the response is replaced by a `304` with an
+/// empty body.
+///
+/// Add this layer *inside* [`Compress`](super::Compress) (that is, after it in the
+/// list of `#[middleware]` attributes, which are applied from the outside in), so
+/// that the tag is computed over the uncompressed body and stays the same whichever
+/// coding the client accepts.
+///
+/// ```rust,ignore
+/// #[server(input = GetUrl)]
+/// #[middleware(Compress::new())]
+/// #[middleware(Conditional::new())]
+/// pub async fn list_posts() -> Result<Vec<Post>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Conditional;
+
+impl Conditional {
+    /// Creates a new conditional response layer.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Whether responses to a request with this method get an `ETag`.
+    fn applies_to(method: &str) -> bool {
+        matches!(method, "GET" | "HEAD")
+    }
+
+    /// A weak `ETag` for a response body.
+    fn etag(body: &[u8]) -> String {
+        format!("W/\"{:016x}\"", xxhash_rust::const_xxh64::xxh64(body, 0))
+    }
+
+    /// Whether the tags listed in an `If-None-Match` header match `etag`, using
+    /// the weak comparison.
+    fn matches(if_none_match: &str, etag: &str) -> bool {
+        let opaque = |tag: &str| {
+            let tag = tag.trim();
+            tag.strip_prefix("W/").unwrap_or(tag).to_string()
+        };
+        let etag = opaque(etag);
+        if_none_match
+            .split(',')
+            .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+    }
+}
+
+struct ConditionalService<Req, Res> {
+    inner: BoxedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{Conditional, ConditionalService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, HeaderValue, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for Conditional {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(ConditionalService { inner })
+        }
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for ConditionalService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            if !Conditional::applies_to(req.method().as_str()) {
+                return self.inner.0.run(req);
+            }
+            let if_none_match = req
+                .headers()
+                .get(header::IF_NONE_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned);
+            let path = req.uri().path().to_string();
+            let inner = self.inner.0.run(req);
+            Box::pin(async move {
+                let res = inner.await;
+                if res.status() != StatusCode::OK {
+                    return res;
+                }
+                let (mut parts, body) = res.into_parts();
+                let existing = parts
+                    .headers
+                    .get(header::ETAG)
+                    .and_then(|value| value.to_str().ok())
+                    .map(ToOwned::to_owned);
+                let (etag, body) = match existing {
+                    Some(etag) => (etag, body),
+                    None => {
+                        let body = match body.collect().await {
+                            Ok(body) => body.to_bytes(),
+                            Err(e) => {
+                                return Response::error_response(
+                                    &path,
+                                    &ServerFnError::new(e),
+                                )
+                            }
+                        };
+                        let etag = Conditional::etag(&body);
+                        if let Ok(value) = HeaderValue::from_str(&etag) {
+                            parts.headers.insert(header::ETAG, value);
+                        }
+                        (etag, Body::from(body))
+                    }
+                };
+
+                if if_none_match
+                    .is_some_and(|tags| Conditional::matches(&tags, &etag))
+                {
+                    parts.status = StatusCode::NOT_MODIFIED;
+                    parts.headers.remove(header::CONTENT_LENGTH);
+                    return Response::from_parts(parts, Body::empty());
+                }
+                Response::from_parts(parts, body)
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{Conditional, ConditionalService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{
+        body::MessageBody,
+        http::{
+            header::{self, HeaderValue},
+            StatusCode,
+        },
+    };
+    use send_wrapper::SendWrapper;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for Conditional {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(ConditionalService { inner })
+        }
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for ConditionalService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            if !Conditional::applies_to(req.request().method().as_str()) {
+                return self.inner.0.run(req);
+            }
+            let if_none_match = req
+                .request()
+                .headers()
+                .get(header::IF_NONE_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned);
+            let path = req.path().to_string();
+            let inner = self.inner.0.run(req);
+            // the response head is kept across the body read, and isn't `Send`;
+            // Actix runs each worker on a single thread, so, like `ActixLayer`,
+            // the whole future is wrapped instead
+            Box::pin(SendWrapper::new(async move {
+                let res = inner.await;
+                if res.0.status() != StatusCode::OK {
+                    return res;
+                }
+                let (mut res, body) = res.take().into_parts();
+                let existing = res
+                    .headers()
+                    .get(header::ETAG)
+                    .and_then(|value| value.to_str().ok())
+                    .map(ToOwned::to_owned);
+                let (etag, body) = match existing {
+                    Some(etag) => (etag, body),
+                    None => {
+                        let body = match actix_web::body::to_bytes(body).await {
+                            Ok(body) => body,
+                            Err(e) => {
+                                return ActixResponse::error_response(
+                                    &path,
+                                    &ServerFnError::new(e),
+                                )
+                            }
+                        };
+                        let etag = Conditional::etag(&body);
+                        if let Ok(value) = HeaderValue::from_str(&etag) {
+                            res.headers_mut().insert(header::ETAG, value);
+                        }
+                        (etag, body.boxed())
+                    }
+                };
+
+                if if_none_match
+                    .is_some_and(|tags| Conditional::matches(&tags, &etag))
+                {
+                    *res.status_mut() = StatusCode::NOT_MODIFIED;
+                    res.headers_mut().remove(header::CONTENT_LENGTH);
+                    return ActixResponse::from(
+                        res.set_body(()).map_into_boxed_body(),
+                    );
+                }
+                ActixResponse::from(res.set_body(body))
+            }))
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Conditional;
+    use crate::middleware::{service_fn, BoxedService, Layer};
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+
+    fn posts() -> BoxedService<Request<Body>, Response<Body>> {
+        service_fn(|_req: Request<Body>| async move {
+            let chunks = futures::stream::iter([
+                Ok::<_, std::io::Error>(r#"["first","#),
+                Ok(r#""second"]"#),
+            ]);
+            let mut res = Response::new(Body::from_stream(chunks));
+            res.headers_mut().insert(
+                header::CONTENT_TYPE,
+                "application/json".parse().unwrap(),
+            );
+            res
+        })
+    }
+
+    async fn call(
+        service: &mut BoxedService<Request<Body>, Response<Body>>,
+        if_none_match: Option<&str>,
+    ) -> (StatusCode, Option<String>, String) {
+        let mut req = Request::get("/api/list_posts");
+        if let Some(tags) = if_none_match {
+            req = req.header(header::IF_NONE_MATCH, tags);
+        }
+        let res = service.0.run(req.body(Body::empty()).unwrap()).await;
+        let status = res.status();
+        let etag = res
+            .headers()
+            .get(header::ETAG)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (status, etag, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn mismatch_returns_body_with_etag() {
+        let mut service = Conditional::new().layer(posts());
+        let (status, etag, body) = call(&mut service, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(etag.as_deref().unwrap().starts_with("W/\""), "{etag:?}");
+        assert_eq!(body, r#"["first","second"]"#);
+
+        let (status, same, body) =
+            call(&mut service, Some(r#""stale", W/"0123""#)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(same, etag);
+        assert_eq!(body, r#"["first","second"]"#);
+    }
+
+    #[tokio::test]
+    async fn match_returns_not_modified() {
+        let mut service = Conditional::new().layer(posts());
+        let (_, etag, _) = call(&mut service, None).await;
+        let etag = etag.unwrap();
+
+        let tags = format!(r#""stale", {etag}"#);
+        let (status, same, body) = call(&mut service, Some(&tags)).await;
+        assert_eq!(status, StatusCode::NOT_MODIFIED);
+        assert_eq!(same.as_deref(), Some(etag.as_str()));
+        assert_eq!(body, "");
+
+        // the weak comparison ignores the `W/` prefix
+        let strong = etag.trim_start_matches("W/");
+        assert_eq!(
+            call(&mut service, Some(strong)).await.0,
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            call(&mut service, Some("*")).await.0,
+            StatusCode::NOT_MODIFIED
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn works_inside_compression() {
+        use crate::middleware::Compress;
+
+        // the fixture body is too short to be compressed by default
+        let mut service = Compress::new()
+            .min_size(0)
+            .layer(Conditional::new().layer(posts()));
+        let (_, plain, _) =
+            call(&mut Conditional::new().layer(posts()), None).await;
+
+        let req = Request::get("/api/list_posts")
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .unwrap();
+        let res = service.0.run(req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(res.headers()[header::ETAG].to_str().ok(), plain.as_deref());
+
+        let req = Request::get("/api/list_posts")
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .header(header::IF_NONE_MATCH, plain.unwrap())
+            .body(Body::empty())
+            .unwrap();
+        let res = service.0.run(req).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert!(!res.headers().contains_key(header::CONTENT_ENCODING));
+    }
+}
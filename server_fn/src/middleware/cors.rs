@@ -0,0 +1,477 @@
+use super::BoxedService;
+use http::{header, HeaderName, Method};
+use std::{sync::Arc, time::Duration};
+
+/// A middleware [`Layer`](super::Layer) that adds [CORS](https://developer.mozilla.org/en-US/docs/Web/HTTP/CORS)
+/// headers to server function responses.
+///
+/// Requests from an allowed `Origin` get `Access-Control-Allow-Origin` (and, if enabled,
+/// `Access-Control-Allow-Credentials`) added to the response of the inner service.
+/// Preflight requests (`OPTIONS` requests with an `Access-Control-Request-Method`
+/// header) are answered directly with `204 No Content`, along with the allowed methods
+/// and headers. Requests from other origins are passed through without any CORS
+/// headers, so the browser will block them.
+///
+/// Note that the server function route must accept `OPTIONS` requests for preflight
+/// requests to reach this layer.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(Cors::new().allow_origin("https://example.com").allow_credentials(true))]
+/// pub async fn update_profile(name: String) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cors(Arc<CorsConfig>);
+
+#[derive(Debug, Clone)]
+struct CorsConfig {
+    origins: AllowedOrigins,
+    methods: Vec<Method>,
+    headers: Vec<String>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+enum AllowedOrigins {
+    Any,
+    List(Vec<String>),
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self(Arc::new(CorsConfig {
+            origins: AllowedOrigins::List(Vec::new()),
+            methods: vec![Method::GET, Method::POST],
+            headers: vec![header::CONTENT_TYPE.to_string()],
+            credentials: false,
+            max_age: None,
+        }))
+    }
+}
+
+impl Cors {
+    /// Creates a new CORS layer, which does not allow any origins yet.
+    ///
+    /// By default, `GET` and `POST` requests with a `Content-Type` header are allowed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn config(&mut self) -> &mut CorsConfig {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// Allows requests from the given origin, like `https://example.com`.
+    pub fn allow_origin(mut self, origin: impl Into<String>) -> Self {
+        let config = self.config();
+        match &mut config.origins {
+            AllowedOrigins::Any => {}
+            AllowedOrigins::List(origins) => origins.push(origin.into()),
+        }
+        self
+    }
+
+    /// Allows requests from any origin.
+    pub fn allow_any_origin(mut self) -> Self {
+        self.config().origins = AllowedOrigins::Any;
+        self
+    }
+
+    /// Sets the methods that are allowed in cross-origin requests.
+    pub fn allow_methods(
+        mut self,
+        methods: impl IntoIterator<Item = Method>,
+    ) -> Self {
+        self.config().methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Sets the request headers that are allowed in cross-origin requests.
+    pub fn allow_headers(
+        mut self,
+        headers: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.config().headers = headers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets whether cross-origin requests may include credentials, like cookies.
+    pub fn allow_credentials(mut self, allow: bool) -> Self {
+        self.config().credentials = allow;
+        self
+    }
+
+    /// Sets how long browsers may cache the result of a preflight request.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.config().max_age = Some(max_age);
+        self
+    }
+}
+
+impl CorsConfig {
+    fn is_preflight(method: &str, has_request_method: bool) -> bool {
+        method == Method::OPTIONS.as_str() && has_request_method
+    }
+
+    /// The CORS headers for a request from `origin`, or `None` if the origin is
+    /// not allowed.
+    fn headers(
+        &self,
+        origin: &str,
+        preflight: bool,
+    ) -> Option<Vec<(HeaderName, String)>> {
+        let allow_origin = match &self.origins {
+            // browsers reject `*` for requests that include credentials
+            AllowedOrigins::Any if !self.credentials => "*",
+            AllowedOrigins::Any => origin,
+            AllowedOrigins::List(origins) => origins
+                .iter()
+                .find(|allowed| allowed.as_str() == origin)?
+                .as_str(),
+        };
+
+        let mut headers = vec![
+            (
+                header::ACCESS_CONTROL_ALLOW_ORIGIN,
+                allow_origin.to_string(),
+            ),
+            (header::VARY, header::ORIGIN.to_string()),
+        ];
+        if self.credentials {
+            headers.push((
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                "true".to_string(),
+            ));
+        }
+        if preflight {
+            let methods = self
+                .methods
+                .iter()
+                .map(Method::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            headers.push((header::ACCESS_CONTROL_ALLOW_METHODS, methods));
+            headers.push((
+                header::ACCESS_CONTROL_ALLOW_HEADERS,
+                self.headers.join(", "),
+            ));
+            if let Some(max_age) = self.max_age {
+                headers.push((
+                    header::ACCESS_CONTROL_MAX_AGE,
+                    max_age.as_secs().to_string(),
+                ));
+            }
+        }
+        Some(headers)
+    }
+}
+
+struct CorsService<Req, Res> {
+    config: Arc<CorsConfig>,
+    inner: BoxedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{Cors, CorsConfig, CorsService};
+    use crate::{
+        error::NoCustomError,
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for Cors {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(CorsService {
+                config: self.0.clone(),
+                inner,
+            })
+        }
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for CorsService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let Some(origin) = req
+                .headers()
+                .get(header::ORIGIN)
+                .and_then(|origin| origin.to_str().ok())
+            else {
+                return self.inner.0.run(req);
+            };
+            let preflight = CorsConfig::is_preflight(
+                req.method().as_str(),
+                req.headers()
+                    .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD),
+            );
+            let headers = self.config.headers(origin, preflight);
+
+            let inner = (!preflight).then(|| self.inner.0.run(req));
+            Box::pin(async move {
+                let mut res = match inner {
+                    Some(inner) => inner.await,
+                    None => {
+                        let mut res = Response::new(Body::empty());
+                        *res.status_mut() = StatusCode::NO_CONTENT;
+                        res
+                    }
+                };
+                for (name, value) in headers.into_iter().flatten() {
+                    // keep the `Vary` values set by inner layers
+                    if name == header::VARY {
+                        Res::<NoCustomError>::append_header(
+                            &mut res,
+                            name.as_str(),
+                            &value,
+                        );
+                    } else {
+                        Res::<NoCustomError>::insert_header(
+                            &mut res,
+                            name.as_str(),
+                            &value,
+                        );
+                    }
+                }
+                res
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{Cors, CorsConfig, CorsService};
+    use crate::{
+        error::NoCustomError,
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{http::header, HttpResponse};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for Cors {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(CorsService {
+                config: self.0.clone(),
+                inner,
+            })
+        }
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for CorsService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let http_req = &req.0 .0;
+            let Some(origin) = http_req
+                .headers()
+                .get(header::ORIGIN)
+                .and_then(|origin| origin.to_str().ok())
+            else {
+                return self.inner.0.run(req);
+            };
+            let preflight = CorsConfig::is_preflight(
+                http_req.method().as_str(),
+                http_req
+                    .headers()
+                    .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD),
+            );
+            let headers = self.config.headers(origin, preflight);
+
+            let inner = (!preflight).then(|| self.inner.0.run(req));
+            Box::pin(async move {
+                let mut res = match inner {
+                    Some(inner) => inner.await,
+                    None => {
+                        ActixResponse::from(HttpResponse::NoContent().finish())
+                    }
+                };
+                for (name, value) in headers.into_iter().flatten() {
+                    // keep the `Vary` values set by inner layers
+                    if name == http::header::VARY {
+                        Res::<NoCustomError>::append_header(
+                            &mut res,
+                            name.as_str(),
+                            &value,
+                        );
+                    } else {
+                        Res::<NoCustomError>::insert_header(
+                            &mut res,
+                            name.as_str(),
+                            &value,
+                        );
+                    }
+                }
+                res
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Cors;
+    use crate::middleware::{BoxedService, Layer, Service};
+    use axum::body::Body;
+    use http::{header, Method, Request, Response, StatusCode};
+    use std::{future::Future, pin::Pin, time::Duration};
+
+    struct Hello;
+
+    impl Service<Request<Body>, Response<Body>> for Hello {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            Box::pin(async move { Response::new(Body::from("hello")) })
+        }
+    }
+
+    fn cors() -> Cors {
+        Cors::new()
+            .allow_origin("https://example.com")
+            .allow_credentials(true)
+            .max_age(Duration::from_secs(600))
+    }
+
+    async fn run(req: Request<Body>) -> Response<Body> {
+        cors().layer(BoxedService::new(Hello)).0.run(req).await
+    }
+
+    fn header(res: &Response<Body>, name: header::HeaderName) -> &str {
+        res.headers()
+            .get(name)
+            .map(|value| value.to_str().unwrap())
+            .unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn answers_preflight_requests() {
+        let req = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/api/update")
+            .header(header::ORIGIN, "https://example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap();
+        let res = run(req).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            header(&res, header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            "https://example.com"
+        );
+        assert_eq!(
+            header(&res, header::ACCESS_CONTROL_ALLOW_METHODS),
+            "GET, POST"
+        );
+        assert_eq!(
+            header(&res, header::ACCESS_CONTROL_ALLOW_HEADERS),
+            "content-type"
+        );
+        assert_eq!(header(&res, header::ACCESS_CONTROL_MAX_AGE), "600");
+    }
+
+    #[tokio::test]
+    async fn adds_headers_to_allowed_requests() {
+        let req = Request::post("/api/update")
+            .header(header::ORIGIN, "https://example.com")
+            .body(Body::empty())
+            .unwrap();
+        let res = run(req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            header(&res, header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            "https://example.com"
+        );
+        assert_eq!(
+            header(&res, header::ACCESS_CONTROL_ALLOW_CREDENTIALS),
+            "true"
+        );
+        assert!(!res
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_METHODS));
+    }
+
+    #[tokio::test]
+    async fn ignores_other_origins() {
+        let req = Request::post("/api/update")
+            .header(header::ORIGIN, "https://evil.example")
+            .body(Body::empty())
+            .unwrap();
+        let res = run(req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(!res
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn keeps_vary_of_compression() {
+        use crate::middleware::Compress;
+
+        let req = Request::post("/api/update")
+            .header(header::ORIGIN, "https://example.com")
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .unwrap();
+        let res = cors()
+            .layer(Compress::new().min_size(0).layer(BoxedService::new(Hello)))
+            .0
+            .run(req)
+            .await;
+        assert_eq!(res.headers()[header::CONTENT_ENCODING], "gzip");
+        let vary = res
+            .headers()
+            .get_all(header::VARY)
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert!(vary.contains(&"accept-encoding"));
+        assert!(vary.contains(&"origin"));
+    }
+}
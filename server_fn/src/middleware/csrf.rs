@@ -0,0 +1,656 @@
+use super::{constant_time_eq, SharedService};
+use crate::{cookies::Cookies, request::RequestExtensions, ServerFnError};
+use cookie::{Cookie, SameSite};
+use std::fmt;
+
+/// A middleware [`Layer`](super::Layer) that protects server functions called from
+/// plain HTML forms against cross-site request forgery.
+///
+/// Every response carries a random token in a cookie, issued the first time a client
+/// is seen. The same token is available to the server function as a [`CsrfToken`],
+/// so that pages can echo it in a hidden form field with [`CsrfToken::hidden_input`].
+///
+/// `POST`, `PUT`, `PATCH` and `DELETE` requests must then submit the token in that
+/// field of a URL-encoded form body, and are rejected with `403 Forbidden` if it is
+/// missing or does not match the cookie. Requests that carry the
+/// `X-Requested-With` header skip the check: that header can only be set by
+/// same-origin scripts (or origins allowed by CORS), so it is a good fit for the
+/// `fetch` calls made by the client once the app is hydrated. Add it to those
+/// calls with [`set_headers_hook`](crate::client::set_headers_hook).
+///
+/// ```rust,ignore
+/// #[server(input = PostUrl)]
+/// #[middleware(Csrf::new())]
+/// pub async fn delete_post(id: u32) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+///
+/// // when rendering the form
+/// let token = CsrfToken::current().expect("rendered behind the Csrf layer");
+/// format!(r#"<form method="post" action="/api/delete_post">{}</form>"#, token.hidden_input())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Csrf {
+    cookie: &'static str,
+    field: &'static str,
+    skip_header: &'static str,
+}
+
+impl Default for Csrf {
+    fn default() -> Self {
+        Self {
+            cookie: "csrf_token",
+            field: "csrf_token",
+            skip_header: "x-requested-with",
+        }
+    }
+}
+
+impl Csrf {
+    /// Creates a new CSRF protection layer, with the token in the `csrf_token` cookie
+    /// and form field.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores the token in the cookie called `name` instead.
+    pub fn cookie_name(mut self, name: &'static str) -> Self {
+        self.cookie = name;
+        self
+    }
+
+    /// Reads the submitted token from the form field called `name` instead.
+    pub fn field_name(mut self, name: &'static str) -> Self {
+        self.field = name;
+        self
+    }
+
+    /// Skips the check for requests with the header called `name`, instead of
+    /// `X-Requested-With`.
+    pub fn skip_header(mut self, name: &'static str) -> Self {
+        self.skip_header = name;
+        self
+    }
+
+    fn missing() -> ServerFnError {
+        ServerFnError::ServerError("missing CSRF token".into())
+    }
+
+    fn mismatched() -> ServerFnError {
+        ServerFnError::ServerError("invalid CSRF token".into())
+    }
+
+    /// Whether requests with this method must submit a token.
+    fn is_mutating(method: &str) -> bool {
+        matches!(method, "POST" | "PUT" | "PATCH" | "DELETE")
+    }
+
+    /// Whether a request body of this type can hold the token field.
+    fn is_form(content_type: Option<&str>) -> bool {
+        content_type.is_some_and(|content_type| {
+            content_type.starts_with("application/x-www-form-urlencoded")
+        })
+    }
+
+    fn new_token() -> String {
+        uuid::Uuid::new_v4().simple().to_string()
+    }
+
+    /// The token in the `Cookie` headers of a request, if any.
+    fn cookie_token(&self, cookie_header: &str) -> Option<String> {
+        Cookies::from_header(cookie_header)
+            .get(self.cookie)
+            .filter(|token| !token.is_empty())
+    }
+
+    /// The token submitted in a URL-encoded form body, if any.
+    fn field_token(&self, body: &[u8]) -> Option<String> {
+        url::form_urlencoded::parse(body)
+            .find(|(name, _)| name == self.field)
+            .map(|(_, value)| value.into_owned())
+    }
+
+    /// Checks the submitted token against the one in the cookie.
+    fn verify(
+        cookie: Option<&str>,
+        submitted: Option<&str>,
+    ) -> Result<(), ServerFnError> {
+        match (cookie, submitted) {
+            (Some(cookie), Some(submitted))
+                if constant_time_eq(
+                    cookie.as_bytes(),
+                    submitted.as_bytes(),
+                ) =>
+            {
+                Ok(())
+            }
+            (Some(_), Some(_)) => Err(Self::mismatched()),
+            _ => Err(Self::missing()),
+        }
+    }
+
+    /// The `Set-Cookie` header that issues `token`.
+    fn set_cookie(&self, token: &str) -> String {
+        Cookie::build((self.cookie, token))
+            .path("/")
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .build()
+            .to_string()
+    }
+
+    fn token(&self, token: String) -> CsrfToken {
+        CsrfToken {
+            token,
+            field: self.field,
+        }
+    }
+}
+
+/// The CSRF token of a request, as issued or accepted by a [`Csrf`] layer.
+///
+/// Server functions can take it as an
+/// [`Extension<CsrfToken>`](crate::request::Extension) argument, or read it with
+/// [`CsrfToken::current`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfToken {
+    token: String,
+    field: &'static str,
+}
+
+impl CsrfToken {
+    /// The token of the request currently being handled by a server function, or
+    /// `None` if it is not behind a [`Csrf`] layer or if called outside of a server
+    /// function.
+    pub fn current() -> Option<Self> {
+        RequestExtensions::current()?.get()
+    }
+
+    /// The token itself.
+    pub fn as_str(&self) -> &str {
+        &self.token
+    }
+
+    /// The name of the form field the token is expected in.
+    pub fn field_name(&self) -> &'static str {
+        self.field
+    }
+
+    /// A hidden `<input>` that submits the token with a form.
+    pub fn hidden_input(&self) -> String {
+        // the token is hex and the field name is chosen by the app, so neither
+        // needs escaping
+        format!(
+            r#"<input type="hidden" name="{}" value="{}">"#,
+            self.field, self.token
+        )
+    }
+}
+
+impl fmt::Display for CsrfToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.token)
+    }
+}
+
+struct CsrfService<Req, Res> {
+    config: Csrf,
+    inner: SharedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{Csrf, CsrfService};
+    use crate::{
+        error::NoCustomError,
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for Csrf {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(CsrfService {
+                config: self.clone(),
+                inner: inner.into_shared(),
+            })
+        }
+    }
+
+    fn forbidden(path: &str, err: &ServerFnError) -> Response<Body> {
+        let mut res = Response::<Body>::error_response(path, err);
+        *res.status_mut() = StatusCode::FORBIDDEN;
+        res
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for CsrfService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let config = self.config.clone();
+            let mut inner = self.inner.clone();
+            Box::pin(async move {
+                let path = req.uri().path().to_string();
+                let cookie_header = req
+                    .headers()
+                    .get_all(header::COOKIE)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                let cookie = config.cookie_token(&cookie_header);
+
+                let mut req = if Csrf::is_mutating(req.method().as_str())
+                    && !req.headers().contains_key(config.skip_header)
+                {
+                    let is_form = Csrf::is_form(
+                        req.headers()
+                            .get(header::CONTENT_TYPE)
+                            .and_then(|value| value.to_str().ok()),
+                    );
+                    let (parts, body) = req.into_parts();
+                    let (submitted, body) = if is_form {
+                        let body = match body.collect().await {
+                            Ok(body) => body.to_bytes(),
+                            Err(e) => {
+                                return Response::error_response(
+                                    &path,
+                                    &ServerFnError::new(e),
+                                )
+                            }
+                        };
+                        (config.field_token(&body), Body::from(body))
+                    } else {
+                        (None, body)
+                    };
+                    if let Err(e) =
+                        Csrf::verify(cookie.as_deref(), submitted.as_deref())
+                    {
+                        return forbidden(&path, &e);
+                    }
+                    Request::from_parts(parts, body)
+                } else {
+                    req
+                };
+
+                let issued = cookie.is_none();
+                let token = cookie.unwrap_or_else(Csrf::new_token);
+                req.extensions_mut().insert(config.token(token.clone()));
+                let mut res = inner.run(req).await;
+                if issued {
+                    Res::<NoCustomError>::append_header(
+                        &mut res,
+                        "set-cookie",
+                        &config.set_cookie(&token),
+                    );
+                }
+                res
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{Csrf, CsrfService};
+    use crate::{
+        error::NoCustomError,
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{
+        dev,
+        error::PayloadError,
+        http::{header, StatusCode},
+        HttpMessage,
+    };
+    use bytes::{Bytes, BytesMut};
+    use futures::{Stream, StreamExt};
+    use send_wrapper::SendWrapper;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for Csrf {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(CsrfService {
+                config: self.clone(),
+                inner: inner.into_shared(),
+            })
+        }
+    }
+
+    fn forbidden(path: &str, err: &ServerFnError) -> ActixResponse {
+        let mut res = ActixResponse::error_response(path, err);
+        *res.0.status_mut() = StatusCode::FORBIDDEN;
+        res
+    }
+
+    /// A payload that yields `body` in a single chunk.
+    fn payload(body: Bytes) -> dev::Payload {
+        dev::Payload::from(Box::pin(futures::stream::once(async move {
+            Ok::<_, PayloadError>(body)
+        }))
+            as Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>>)
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for CsrfService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let config = self.config.clone();
+            let mut inner = self.inner.clone();
+            let path = req.path().to_string();
+            let rewritten = req.1.clone();
+            let http_req = req.request();
+            let cookie_header = http_req
+                .headers()
+                .get_all(header::COOKIE)
+                .filter_map(|value| value.to_str().ok())
+                .collect::<Vec<_>>()
+                .join("; ");
+            let cookie = config.cookie_token(&cookie_header);
+            let check = Csrf::is_mutating(http_req.method().as_str())
+                && !http_req.headers().contains_key(config.skip_header);
+            let is_form = Csrf::is_form(
+                http_req
+                    .headers()
+                    .get(header::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok()),
+            );
+
+            // Actix keeps the request on a single thread, so reading the payload
+            // only needs to look `Send`
+            let read = SendWrapper::new(async move {
+                if !(check && is_form) {
+                    return Ok((req, None));
+                }
+                let (http_req, mut body) = req.0.take();
+                let mut buf = BytesMut::new();
+                while let Some(chunk) = body.next().await {
+                    buf.extend_from_slice(&chunk?);
+                }
+                let body = buf.freeze();
+                let submitted = config.field_token(&body);
+                let req = ActixRequest::from((http_req, payload(body)))
+                    .with_path(rewritten);
+                Ok::<_, PayloadError>((req, submitted))
+            });
+            let config = self.config.clone();
+            Box::pin(async move {
+                let (req, submitted) = match read.await {
+                    Ok(read) => read,
+                    Err(e) => {
+                        return ActixResponse::error_response(
+                            &path,
+                            &ServerFnError::new(e),
+                        )
+                    }
+                };
+                if check {
+                    if let Err(e) =
+                        Csrf::verify(cookie.as_deref(), submitted.as_deref())
+                    {
+                        return forbidden(&path, &e);
+                    }
+                }
+
+                let issued = cookie.is_none();
+                let token = cookie.unwrap_or_else(Csrf::new_token);
+                req.request()
+                    .extensions_mut()
+                    .insert(config.token(token.clone()));
+                let mut res = inner.run(req).await;
+                if issued {
+                    Res::<NoCustomError>::append_header(
+                        &mut res,
+                        "set-cookie",
+                        &config.set_cookie(&token),
+                    );
+                }
+                res
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{Csrf, CsrfToken};
+    use crate::middleware::{service_fn, BoxedService, Layer};
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+
+    /// Answers with the token it sees, and the form body it receives.
+    fn handler() -> BoxedService<Request<Body>, Response<Body>> {
+        service_fn(|req: Request<Body>| async move {
+            let token = req
+                .extensions()
+                .get::<CsrfToken>()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            let body = req.into_body().collect().await.unwrap().to_bytes();
+            Response::new(Body::from(format!(
+                "{token} {}",
+                String::from_utf8_lossy(&body)
+            )))
+        })
+    }
+
+    async fn post(
+        cookie: Option<&str>,
+        field: Option<&str>,
+    ) -> (StatusCode, String) {
+        let mut service = Csrf::new().layer(handler());
+        let mut req = Request::post("/api/delete_post")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+        if let Some(cookie) = cookie {
+            req = req.header(header::COOKIE, format!("csrf_token={cookie}"));
+        }
+        let body = match field {
+            Some(field) => format!("id=1&csrf_token={field}"),
+            None => "id=1".to_string(),
+        };
+        let res = service.0.run(req.body(Body::from(body)).unwrap()).await;
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn issues_token_on_first_visit() {
+        let mut service = Csrf::new().layer(handler());
+        let req = Request::get("/api/form").body(Body::empty()).unwrap();
+        let res = service.0.run(req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let set_cookie = res.headers()[header::SET_COOKIE].to_str().unwrap();
+        let cookie = cookie::Cookie::parse(set_cookie.to_string()).unwrap();
+        assert_eq!(cookie.name(), "csrf_token");
+        assert_eq!(cookie.http_only(), Some(true));
+
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, format!("{} ", cookie.value()));
+        let token = CsrfToken {
+            token: cookie.value().to_string(),
+            field: "csrf_token",
+        };
+        assert_eq!(
+            token.hidden_input(),
+            format!(
+                r#"<input type="hidden" name="csrf_token" value="{}">"#,
+                cookie.value()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn valid_token_is_accepted() {
+        let (status, body) = post(Some("abc123"), Some("abc123")).await;
+        assert_eq!(status, StatusCode::OK);
+        // the form body still reaches the server function
+        assert_eq!(body, "abc123 id=1&csrf_token=abc123");
+    }
+
+    #[tokio::test]
+    async fn missing_token_is_rejected() {
+        let (status, body) = post(Some("abc123"), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body, "ServerError|missing CSRF token");
+
+        let (status, _) = post(None, Some("abc123")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn mismatched_token_is_rejected() {
+        let (status, body) = post(Some("abc123"), Some("evil")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body, "ServerError|invalid CSRF token");
+    }
+
+    #[tokio::test]
+    async fn same_origin_fetch_skips_check() {
+        let mut service = Csrf::new().layer(handler());
+        let req = Request::post("/api/delete_post")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("x-requested-with", "fetch")
+            .body(Body::from(r#"{"id":1}"#))
+            .unwrap();
+        assert_eq!(service.0.run(req).await.status(), StatusCode::OK);
+    }
+}
+
+#[cfg(all(test, feature = "actix"))]
+mod actix_tests {
+    use super::{Csrf, CsrfToken};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::actix::ActixResponse,
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test::TestRequest,
+        HttpMessage, HttpResponse,
+    };
+    use bytes::BytesMut;
+    use futures::StreamExt;
+    use send_wrapper::SendWrapper;
+    use std::{future::Future, pin::Pin};
+
+    /// Answers with the token it sees, and the form body it receives.
+    struct Handler;
+
+    impl Service<ActixRequest, ActixResponse> for Handler {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let (http_req, mut body) = req.take();
+            let token = http_req
+                .extensions()
+                .get::<CsrfToken>()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            Box::pin(SendWrapper::new(async move {
+                let mut buf = BytesMut::new();
+                while let Some(chunk) = body.next().await {
+                    buf.extend_from_slice(&chunk.unwrap());
+                }
+                let body = format!("{token} {}", String::from_utf8_lossy(&buf));
+                ActixResponse::from(HttpResponse::Ok().body(body))
+            }))
+        }
+    }
+
+    async fn run(req: TestRequest) -> HttpResponse {
+        let mut service = Csrf::new().layer(BoxedService::new(Handler));
+        let req = req.to_srv_request().into_parts();
+        service.0.run(ActixRequest::from(req)).await.take()
+    }
+
+    async fn post(cookie: &str, field: &str) -> (StatusCode, String) {
+        let req = TestRequest::post()
+            .uri("/api/delete_post")
+            .insert_header((
+                header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded",
+            ))
+            .insert_header((header::COOKIE, format!("csrf_token={cookie}")))
+            .set_payload(format!("id=1&csrf_token={field}"));
+        let res = run(req).await;
+        let status = res.status();
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[actix_web::test]
+    async fn issues_token_on_first_visit() {
+        let res = run(TestRequest::get().uri("/api/form")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let set_cookie = res
+            .headers()
+            .get(header::SET_COOKIE)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        let cookie = cookie::Cookie::parse(set_cookie.to_string()).unwrap();
+        assert_eq!(cookie.name(), "csrf_token");
+
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, format!("{} ", cookie.value()));
+    }
+
+    #[actix_web::test]
+    async fn valid_token_is_accepted() {
+        let (status, body) = post("abc123", "abc123").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "abc123 id=1&csrf_token=abc123");
+    }
+
+    #[actix_web::test]
+    async fn mismatched_token_is_rejected() {
+        let (status, body) = post("abc123", "evil").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body, "ServerError|invalid CSRF token");
+    }
+}
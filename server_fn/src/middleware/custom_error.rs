@@ -0,0 +1,255 @@
+use crate::{error::ServerFnError, response::Res};
+
+/// An error that knows how to turn itself into a response.
+///
+/// By default, when a framework service wrapped as a [`Service`](super::Service) fails,
+/// its error is collapsed into [`ServerFnError::ServerError`] and rendered by
+/// [`Res::error_response`]: a `500` response containing only the error message. Wrapping
+/// the service in [`CustomErrors`] uses this trait instead, so that the error can pick
+/// its own status and body.
+///
+/// This is implemented for
+/// - [`ServerFnError<CustErr>`], which still renders through [`Res::error_response`],
+///   but keeps [`ServerFnError::WrappedServerError`] intact so the client can decode
+///   the custom error type,
+/// - any `axum` error that implements `IntoResponse`,
+/// - any Actix error that implements `ResponseError`.
+///
+/// Note that the client only knows how to decode bodies produced by
+/// [`Res::error_response`]. Any other error status is reported on the client as a
+/// [`ServerFnError::Deserialization`] containing the raw body.
+pub trait CustomErrorResponse<Res> {
+    /// Converts this error into the response for a request to `path`.
+    fn into_error_response(self, path: &str) -> Res;
+}
+
+impl<CustErr, R> CustomErrorResponse<R> for ServerFnError<CustErr>
+where
+    R: Res<CustErr>,
+{
+    fn into_error_response(self, path: &str) -> R {
+        R::error_response(path, &self)
+    }
+}
+
+/// Wraps a framework service so that its errors are rendered with
+/// [`CustomErrorResponse`], rather than being collapsed into a generic
+/// [`ServerFnError`].
+///
+/// ```rust,ignore
+/// let service = BoxedService::new(CustomErrors::new(my_tower_service));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CustomErrors<S>(S);
+
+impl<S> CustomErrors<S> {
+    /// Wraps the given service.
+    pub fn new(service: S) -> Self {
+        Self(service)
+    }
+
+    /// Returns the inner service.
+    pub fn into_inner(self) -> S {
+        self.0
+    }
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{CustomErrorResponse, CustomErrors};
+    use crate::{
+        middleware::{RequestPath, Service},
+        ServerFnError,
+    };
+    use axum::{body::Body, response::IntoResponse};
+    use http::{Request, Response};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl<E> CustomErrorResponse<Response<Body>> for E
+    where
+        E: IntoResponse,
+    {
+        fn into_error_response(self, _path: &str) -> Response<Body> {
+            self.into_response()
+        }
+    }
+
+    impl<S> Service<Request<Body>, Response<Body>> for CustomErrors<S>
+    where
+        S: tower::Service<Request<Body>, Response = Response<Body>>,
+        S::Future: Send + 'static,
+        S::Error: CustomErrorResponse<Response<Body>> + Send + 'static,
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let path = req.path().to_string();
+            let inner = self.0.call(req);
+            Box::pin(async move {
+                inner.await.unwrap_or_else(|e| e.into_error_response(&path))
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            // readiness errors can't be rendered as a response here, so they
+            // are left to surface from the call itself
+            tower::Service::poll_ready(&mut self.0, cx).map(|_| Ok(()))
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{CustomErrorResponse, CustomErrors};
+    use crate::{
+        middleware::Service, request::actix::ActixRequest,
+        response::actix::ActixResponse, ServerFnError,
+    };
+    use actix_web::{HttpRequest, HttpResponse, ResponseError};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl<E> CustomErrorResponse<HttpResponse> for E
+    where
+        E: ResponseError,
+    {
+        fn into_error_response(self, _path: &str) -> HttpResponse {
+            self.error_response()
+        }
+    }
+
+    impl<E> CustomErrorResponse<ActixResponse> for E
+    where
+        E: ResponseError,
+    {
+        fn into_error_response(self, _path: &str) -> ActixResponse {
+            ActixResponse::from(self.error_response())
+        }
+    }
+
+    impl<S> Service<HttpRequest, HttpResponse> for CustomErrors<S>
+    where
+        S: actix_web::dev::Service<HttpRequest, Response = HttpResponse>,
+        S::Future: Send + 'static,
+        S::Error: CustomErrorResponse<HttpResponse> + 'static,
+    {
+        fn run(
+            &mut self,
+            req: HttpRequest,
+        ) -> Pin<Box<dyn Future<Output = HttpResponse> + Send>> {
+            let path = req.path().to_string();
+            let inner = self.0.call(req);
+            Box::pin(async move {
+                inner.await.unwrap_or_else(|e| e.into_error_response(&path))
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            actix_web::dev::Service::poll_ready(&self.0, cx).map(|_| Ok(()))
+        }
+    }
+
+    impl<S> Service<ActixRequest, ActixResponse> for CustomErrors<S>
+    where
+        S: actix_web::dev::Service<HttpRequest, Response = HttpResponse>,
+        S::Future: Send + 'static,
+        S::Error: CustomErrorResponse<ActixResponse> + 'static,
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let path = req.path().to_string();
+            let inner = self.0.call(req.0.take().0);
+            Box::pin(async move {
+                inner
+                    .await
+                    .map(ActixResponse::from)
+                    .unwrap_or_else(|e| e.into_error_response(&path))
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            actix_web::dev::Service::poll_ready(&self.0, cx).map(|_| Ok(()))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::CustomErrors;
+    use crate::{
+        error::{ServerFnErrorSerde, SERVER_FN_ERROR_HEADER},
+        middleware::Service,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        future::{ready, Ready},
+        task::{Context, Poll},
+    };
+
+    struct Failing<E>(E);
+
+    impl<E: Clone> tower::Service<Request<Body>> for Failing<E> {
+        type Response = Response<Body>;
+        type Error = E;
+        type Future = Ready<Result<Response<Body>, E>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), E>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _req: Request<Body>) -> Self::Future {
+            ready(Err(self.0.clone()))
+        }
+    }
+
+    async fn run<E>(err: E) -> Response<Body>
+    where
+        CustomErrors<Failing<E>>: Service<Request<Body>, Response<Body>>,
+    {
+        let req = Request::post("/api/register").body(Body::empty()).unwrap();
+        CustomErrors::new(Failing(err)).run(req).await
+    }
+
+    #[tokio::test]
+    async fn into_response_errors_keep_their_status_and_body() {
+        let res = run((StatusCode::CONFLICT, "username taken")).await;
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        assert!(res.headers().get(SERVER_FN_ERROR_HEADER).is_none());
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "username taken");
+    }
+
+    #[tokio::test]
+    async fn custom_server_fn_errors_can_be_decoded() {
+        let err: ServerFnError<String> =
+            ServerFnError::WrappedServerError("username taken".into());
+        let res = run(err.clone()).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let decoded =
+            ServerFnError::<String>::de(std::str::from_utf8(&body).unwrap());
+        assert_eq!(decoded, err);
+    }
+}
@@ -0,0 +1,249 @@
+use super::{
+    BoxedService, Layer, RequestExtensionsMut, RequestHeaders, RequestPath,
+    Service, Timeout,
+};
+use crate::{error::NoCustomError, request::RequestExtensions, ServerFnError};
+use http::StatusCode;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// The header in which a client sends how many milliseconds it will wait for a
+/// response.
+const BUDGET_HEADER: &str = "x-request-deadline";
+
+/// The point in time by which a request should be answered.
+///
+/// It is set by a [`RequestDeadline`] or [`Timeout`] layer, and inserted into the
+/// request extensions, so that the layers and the server function after it can
+/// budget their own work, like a database query, to fit in the time that is left.
+/// Server functions can take it as an
+/// [`Extension<Deadline>`](crate::request::Extension) argument, or read it with
+/// [`Deadline::current`].
+///
+/// When several layers set a deadline, the earliest one is kept.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(RequestDeadline::new(Duration::from_secs(5)))]
+/// pub async fn search(query: String) -> Result<Vec<Hit>, ServerFnError> {
+///     let budget = Deadline::current().map(|deadline| deadline.remaining());
+///     db::search(&query).timeout(budget.unwrap_or(DEFAULT_BUDGET)).await
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// A deadline at `instant`.
+    pub fn at(instant: Instant) -> Self {
+        Self(instant)
+    }
+
+    /// A deadline `budget` from now.
+    pub fn after(budget: Duration) -> Self {
+        Self(Instant::now() + budget)
+    }
+
+    /// The deadline of the request currently being handled by a server function, or
+    /// `None` if it has none or if called outside of a server function.
+    pub fn current() -> Option<Self> {
+        RequestExtensions::current()?.get()
+    }
+
+    /// The point in time of the deadline.
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// How much time is left until the deadline, which is zero once it has passed.
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    /// Whether the deadline has passed.
+    pub fn is_expired(&self) -> bool {
+        self.0 <= Instant::now()
+    }
+}
+
+/// Sets `deadline` on `req`, unless it already has an earlier one, and runs it on
+/// `inner`, answering with `504 Gateway Timeout` if the deadline passes first.
+pub(super) fn run_until<Req, Res>(
+    inner: &mut BoxedService<Req, Res>,
+    mut req: Req,
+    deadline: Deadline,
+) -> Pin<Box<dyn Future<Output = Res> + Send>>
+where
+    Req: RequestExtensionsMut + RequestPath,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    let deadline = req
+        .extension::<Deadline>()
+        .map_or(deadline, |set| set.min(deadline));
+    req.insert_extension(deadline);
+    let path = req.path().to_string();
+    let inner = tokio::time::timeout_at(deadline.instant(), inner.0.run(req));
+    Box::pin(async move {
+        inner.await.unwrap_or_else(|_| {
+            let mut res = Res::error_response(&path, &Timeout::error());
+            res.set_status(StatusCode::GATEWAY_TIMEOUT);
+            res
+        })
+    })
+}
+
+/// A middleware [`Layer`] that gives every request a [`Deadline`].
+///
+/// A client can ask for a shorter deadline by sending the number of milliseconds it
+/// will wait in an `X-Request-Deadline` header. Otherwise, and for longer or invalid
+/// values, the request gets the maximum budget of the layer. Like [`Timeout`], the
+/// request is cancelled with `504 Gateway Timeout` once the deadline passes.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(RequestDeadline::new(Duration::from_secs(10)))]
+/// pub async fn generate_report(id: u32) -> Result<Report, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RequestDeadline {
+    max: Duration,
+}
+
+impl RequestDeadline {
+    /// Creates a new layer that gives each request at most `max` to respond.
+    pub fn new(max: Duration) -> Self {
+        Self { max }
+    }
+
+    fn budget(&self, header: Option<&str>) -> Duration {
+        header
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_millis)
+            .map_or(self.max, |budget| budget.min(self.max))
+    }
+}
+
+struct RequestDeadlineService<Req, Res> {
+    layer: RequestDeadline,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for RequestDeadline
+where
+    Req: RequestHeaders + RequestExtensionsMut + RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(RequestDeadlineService {
+            layer: *self,
+            inner,
+        })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for RequestDeadlineService<Req, Res>
+where
+    Req: RequestHeaders + RequestExtensionsMut + RequestPath,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let budget = self.layer.budget(req.header(BUDGET_HEADER));
+        run_until(&mut self.inner, req, Deadline::after(budget))
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{Deadline, RequestDeadline};
+    use crate::middleware::{service_fn, BoxedService, Layer, Timeout};
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::time::Duration;
+
+    /// Answers with the seconds left before and after working for `work`.
+    fn budgeted(work: Duration) -> BoxedService<Request<Body>, Response<Body>> {
+        service_fn(move |req: Request<Body>| async move {
+            let deadline = *req.extensions().get::<Deadline>().unwrap();
+            let before = deadline.remaining().as_secs();
+            tokio::time::sleep(work).await;
+            let after = deadline.remaining().as_secs();
+            Response::new(Body::from(format!("{before} {after}")))
+        })
+    }
+
+    async fn call(
+        mut service: BoxedService<Request<Body>, Response<Body>>,
+        budget: Option<&str>,
+    ) -> (StatusCode, String) {
+        let mut req = Request::post("/api/search");
+        if let Some(budget) = budget {
+            req = req.header("x-request-deadline", budget);
+        }
+        let res = service.0.run(req.body(Body::empty()).unwrap()).await;
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn handler_sees_shrinking_budget() {
+        let service = RequestDeadline::new(Duration::from_secs(10))
+            .layer(budgeted(Duration::from_secs(3)));
+        let res = call(service, None).await;
+        assert_eq!(res, (StatusCode::OK, "10 7".to_string()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn header_shortens_deadline() {
+        let layer = RequestDeadline::new(Duration::from_secs(10));
+        let res =
+            call(layer.layer(budgeted(Duration::ZERO)), Some("4000")).await;
+        assert_eq!(res, (StatusCode::OK, "4 4".to_string()));
+
+        // the budget can't be raised past the maximum
+        let res =
+            call(layer.layer(budgeted(Duration::ZERO)), Some("60000")).await;
+        assert_eq!(res, (StatusCode::OK, "10 10".to_string()));
+
+        let res =
+            call(layer.layer(budgeted(Duration::from_secs(5))), Some("2000"))
+                .await;
+        assert_eq!(
+            res,
+            (
+                StatusCode::GATEWAY_TIMEOUT,
+                "ServerError|timeout".to_string()
+            )
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn earliest_deadline_wins() {
+        let service = RequestDeadline::new(Duration::from_secs(10)).layer(
+            Timeout::new(Duration::from_secs(2))
+                .layer(budgeted(Duration::ZERO)),
+        );
+        assert_eq!(call(service, None).await.1, "2 2");
+
+        let service = Timeout::new(Duration::from_secs(2)).layer(
+            RequestDeadline::new(Duration::from_secs(10))
+                .layer(budgeted(Duration::ZERO)),
+        );
+        assert_eq!(call(service, None).await.1, "2 2");
+    }
+}
@@ -0,0 +1,484 @@
+use super::{BoxedService, RequestBodyLimit};
+use crate::ServerFnError;
+use bytes::Bytes;
+use flate2::write::{GzDecoder, ZlibDecoder};
+use futures::{Stream, StreamExt};
+use std::{
+    io::{self, Write},
+    sync::{atomic::AtomicBool, Arc},
+};
+
+/// The default limit on the decompressed size of a request body: 16 MiB.
+pub const DEFAULT_DECOMPRESS_LIMIT: usize = 16 * 1024 * 1024;
+
+/// A middleware [`Layer`](super::Layer) that decompresses request bodies.
+///
+/// Bodies with a `Content-Encoding` of `gzip`, `deflate` or `br` are inflated before
+/// the inner service reads them. With `axum`, the `Content-Encoding` and
+/// `Content-Length` headers are also removed. (Actix requests can't be changed once
+/// they have been extracted, so they keep their original headers.) Requests with any
+/// other encoding are rejected with a `415 Unsupported Media Type` error response.
+///
+/// To guard against decompression bombs, the decompressed body is limited to
+/// [`max_size`](Decompress::max_size) bytes. Larger bodies are rejected with a
+/// `413 Payload Too Large` error response.
+///
+/// ```rust,ignore
+/// #[server(input = Json)]
+/// #[middleware(Decompress::new().max_size(64 * 1024 * 1024))]
+/// pub async fn import(rows: Vec<Row>) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Decompress {
+    max_size: usize,
+}
+
+impl Decompress {
+    /// Creates a new decompression layer, with a limit of [`DEFAULT_DECOMPRESS_LIMIT`].
+    pub fn new() -> Self {
+        Self {
+            max_size: DEFAULT_DECOMPRESS_LIMIT,
+        }
+    }
+
+    /// Sets the maximum size of a decompressed body, in bytes.
+    pub fn max_size(mut self, bytes: usize) -> Self {
+        self.max_size = bytes;
+        self
+    }
+
+    fn unsupported() -> ServerFnError {
+        ServerFnError::ServerError("unsupported content encoding".into())
+    }
+
+    /// Decompresses a body stream, stopping once more than `max_size` bytes have been
+    /// produced, and setting `exceeded` when it does.
+    fn decompress_stream<E>(
+        &self,
+        stream: impl Stream<Item = Result<Bytes, E>> + Send + 'static,
+        decoder: Box<Decoder>,
+        exceeded: Arc<AtomicBool>,
+        io_error: fn(io::Error) -> E,
+        overflow: impl Fn() -> E,
+    ) -> impl Stream<Item = Result<Bytes, E>>
+    where
+        E: Send + 'static,
+    {
+        let state = (Box::pin(stream), Some(decoder));
+        let decoded = futures::stream::unfold(
+            state,
+            move |(mut stream, mut decoder)| async move {
+                let chunk = match stream.next().await {
+                    Some(Ok(chunk)) => {
+                        decoder.as_mut()?.decode(&chunk).map_err(io_error)
+                    }
+                    Some(Err(e)) => {
+                        decoder.take()?;
+                        Err(e)
+                    }
+                    None => decoder.take()?.finish().map_err(io_error),
+                };
+                Some((chunk, (stream, decoder)))
+            },
+        );
+        RequestBodyLimit::new(self.max_size)
+            .limit_stream(decoded, exceeded, overflow)
+    }
+}
+
+impl Default for Decompress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+enum Decoder {
+    Gzip(GzDecoder<Vec<u8>>),
+    Deflate(ZlibDecoder<Vec<u8>>),
+    Brotli(Box<brotli::DecompressorWriter<Vec<u8>>>),
+}
+
+/// How a request body has to be decoded, based on its `Content-Encoding`.
+enum Decoding {
+    Identity,
+    Decode(Box<Decoder>),
+    Unsupported,
+}
+
+impl Decoding {
+    fn from_header(content_encoding: Option<&str>) -> Self {
+        let Some(content_encoding) = content_encoding else {
+            return Decoding::Identity;
+        };
+        match content_encoding.trim().to_ascii_lowercase().as_str() {
+            "" | "identity" => Decoding::Identity,
+            "gzip" | "x-gzip" => {
+                Decoder::Gzip(GzDecoder::new(Vec::new())).into()
+            }
+            "deflate" => Decoder::Deflate(ZlibDecoder::new(Vec::new())).into(),
+            "br" => Decoder::Brotli(Box::new(brotli::DecompressorWriter::new(
+                Vec::new(),
+                4096,
+            )))
+            .into(),
+            _ => Decoding::Unsupported,
+        }
+    }
+}
+
+impl From<Decoder> for Decoding {
+    fn from(decoder: Decoder) -> Self {
+        Decoding::Decode(Box::new(decoder))
+    }
+}
+
+impl Decoder {
+    fn decode(&mut self, chunk: &[u8]) -> io::Result<Bytes> {
+        let buf = match self {
+            Decoder::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                decoder.get_mut()
+            }
+            Decoder::Deflate(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                decoder.get_mut()
+            }
+            Decoder::Brotli(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                decoder.get_mut()
+            }
+        };
+        Ok(std::mem::take(buf).into())
+    }
+
+    fn finish(self) -> io::Result<Bytes> {
+        match self {
+            Decoder::Gzip(decoder) => decoder.finish().map(Bytes::from),
+            Decoder::Deflate(decoder) => decoder.finish().map(Bytes::from),
+            Decoder::Brotli(mut decoder) => {
+                decoder.close()?;
+                Ok(std::mem::take(decoder.get_mut()).into())
+            }
+        }
+    }
+}
+
+struct DecompressService<Req, Res> {
+    config: Decompress,
+    inner: BoxedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{Decoding, Decompress, DecompressService};
+    use crate::{
+        middleware::{BoxedService, Layer, RequestBodyLimit, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use std::{
+        future::Future,
+        io,
+        pin::Pin,
+        sync::{atomic::Ordering, Arc},
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for Decompress {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(DecompressService {
+                config: *self,
+                inner,
+            })
+        }
+    }
+
+    fn error_response(
+        path: &str,
+        err: ServerFnError,
+        status: StatusCode,
+    ) -> Response<Body> {
+        let mut res = Response::<Body>::error_response(path, &err);
+        *res.status_mut() = status;
+        res
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for DecompressService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            mut req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let path = req.uri().path().to_string();
+            let decoding = Decoding::from_header(
+                req.headers()
+                    .get(header::CONTENT_ENCODING)
+                    .and_then(|value| value.to_str().ok()),
+            );
+            let decoder = match decoding {
+                Decoding::Identity => return self.inner.0.run(req),
+                Decoding::Decode(decoder) => decoder,
+                Decoding::Unsupported => {
+                    return Box::pin(async move {
+                        error_response(
+                            &path,
+                            Decompress::unsupported(),
+                            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                        )
+                    })
+                }
+            };
+
+            req.headers_mut().remove(header::CONTENT_ENCODING);
+            req.headers_mut().remove(header::CONTENT_LENGTH);
+            let exceeded = Arc::default();
+            let req = req.map(|body| {
+                Body::from_stream(self.config.decompress_stream(
+                    body.into_data_stream(),
+                    decoder,
+                    Arc::clone(&exceeded),
+                    axum::Error::new,
+                    || {
+                        axum::Error::new(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "request body too large",
+                        ))
+                    },
+                ))
+            });
+            let inner = self.inner.0.run(req);
+            Box::pin(async move {
+                let res = inner.await;
+                if exceeded.load(Ordering::Relaxed) {
+                    error_response(
+                        &path,
+                        RequestBodyLimit::error(),
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                    )
+                } else {
+                    res
+                }
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{Decoding, Decompress, DecompressService};
+    use crate::{
+        middleware::{BoxedService, Layer, RequestBodyLimit, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{
+        dev,
+        error::PayloadError,
+        http::{header, StatusCode},
+    };
+    use futures::Stream;
+    use send_wrapper::SendWrapper;
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{atomic::Ordering, Arc},
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for Decompress {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(DecompressService {
+                config: *self,
+                inner,
+            })
+        }
+    }
+
+    fn error_response(
+        path: &str,
+        err: ServerFnError,
+        status: StatusCode,
+    ) -> ActixResponse {
+        let mut res = ActixResponse::error_response(path, &err);
+        *res.0.status_mut() = status;
+        res
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for DecompressService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let path = req.path().to_string();
+            let decoding = Decoding::from_header(
+                req.request()
+                    .headers()
+                    .get(header::CONTENT_ENCODING)
+                    .and_then(|value| value.to_str().ok()),
+            );
+            let decoder = match decoding {
+                Decoding::Identity => return self.inner.0.run(req),
+                Decoding::Decode(decoder) => decoder,
+                Decoding::Unsupported => {
+                    return Box::pin(async move {
+                        error_response(
+                            &path,
+                            Decompress::unsupported(),
+                            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                        )
+                    })
+                }
+            };
+
+            // the headers of an `HttpRequest` can't be changed, but server functions
+            // only read the body through the payload, which is replaced here
+            let exceeded = Arc::default();
+            let rewritten = req.1.clone();
+            let (http_req, payload) = req.0.take();
+            // Actix keeps the request on a single thread, so the payload only
+            // needs to look `Send`
+            let payload = SendWrapper::new(payload);
+            let payload =
+                dev::Payload::from(Box::pin(self.config.decompress_stream(
+                    payload,
+                    decoder,
+                    Arc::clone(&exceeded),
+                    PayloadError::Io,
+                    || PayloadError::Overflow,
+                ))
+                    as Pin<Box<dyn Stream<Item = _>>>);
+            let inner = self.inner.0.run(
+                ActixRequest::from((http_req, payload)).with_path(rewritten),
+            );
+            Box::pin(async move {
+                let res = inner.await;
+                if exceeded.load(Ordering::Relaxed) {
+                    error_response(
+                        &path,
+                        RequestBodyLimit::error(),
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                    )
+                } else {
+                    res
+                }
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Decompress;
+    use crate::{
+        codec::{FromReq, IntoRes, Json},
+        error::NoCustomError,
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use flate2::{write::GzEncoder, Compression};
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+    use std::{future::Future, io::Write, pin::Pin};
+
+    #[derive(Serialize, Deserialize)]
+    struct Sum {
+        values: Vec<u32>,
+    }
+
+    /// Decodes its arguments with the `Json` codec, like a server function would.
+    struct Handler;
+
+    impl Service<Request<Body>, Response<Body>> for Handler {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            Box::pin(async move {
+                let res: Result<Response<Body>, ServerFnError> = async {
+                    let input =
+                        <Sum as FromReq<Json, _, NoCustomError>>::from_req(req)
+                            .await?;
+                    let sum: u32 = input.values.iter().sum();
+                    <u32 as IntoRes<Json, _, NoCustomError>>::into_res(sum)
+                        .await
+                }
+                .await;
+                res.unwrap_or_else(|e| {
+                    Response::<Body>::error_response("/api/sum", &e)
+                })
+            })
+        }
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    async fn run(layer: Decompress, body: Vec<u8>) -> Response<Body> {
+        let mut service = layer.layer(BoxedService::new(Handler));
+        let req = Request::post("/api/sum")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(body))
+            .unwrap();
+        service.0.run(req).await
+    }
+
+    #[tokio::test]
+    async fn inflates_gzipped_json() {
+        let input = serde_json::to_vec(&Sum {
+            values: (1..=100).collect(),
+        })
+        .unwrap();
+        let res = run(Decompress::new(), gzip(&input)).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "5050");
+    }
+
+    #[tokio::test]
+    async fn rejects_bodies_that_inflate_past_the_limit() {
+        let input = serde_json::to_vec(&Sum {
+            values: vec![0; 10_000],
+        })
+        .unwrap();
+        let res = run(Decompress::new().max_size(1024), gzip(&input)).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
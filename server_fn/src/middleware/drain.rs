@@ -0,0 +1,208 @@
+use super::{BoxedService, Layer, RequestPath, Service};
+use crate::{error::NoCustomError, ServerFnError};
+use http::StatusCode;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::watch;
+
+/// A middleware [`Layer`] that lets in-flight requests finish during a graceful
+/// shutdown, while turning new ones away.
+///
+/// The layer is tied to a shutdown signal: a [`watch`] channel that is set to `true`
+/// when the server starts shutting down. From then on, new requests are answered
+/// with `503 Service Unavailable` without reaching the server function, but requests
+/// that were already running are left to complete. [`Drain::drained`] waits until
+/// they all have.
+///
+/// ```rust,ignore
+/// static SHUTDOWN: Lazy<watch::Sender<bool>> = Lazy::new(|| watch::channel(false).0);
+/// static DRAIN: Lazy<Drain> = Lazy::new(|| Drain::new(SHUTDOWN.subscribe()));
+///
+/// #[server]
+/// #[middleware(DRAIN.clone())]
+/// pub async fn save_post(post: Post) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+///
+/// // on SIGTERM
+/// SHUTDOWN.send_replace(true);
+/// DRAIN.drained().await;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Drain {
+    shutdown: watch::Receiver<bool>,
+    in_flight: Arc<watch::Sender<usize>>,
+}
+
+impl Drain {
+    /// Creates a new layer that starts draining once `shutdown` is `true`.
+    pub fn new(shutdown: watch::Receiver<bool>) -> Self {
+        Self {
+            shutdown,
+            in_flight: Arc::new(watch::channel(0).0),
+        }
+    }
+
+    /// Whether the shutdown signal has been triggered.
+    pub fn is_draining(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+
+    /// The number of requests currently running through this layer.
+    pub fn in_flight(&self) -> usize {
+        *self.in_flight.borrow()
+    }
+
+    /// Waits until no requests are running through this layer.
+    ///
+    /// Once the shutdown signal has been triggered, no new requests are let in, so
+    /// this resolves as soon as the last in-flight request has completed.
+    pub async fn drained(&self) {
+        let mut in_flight = self.in_flight.subscribe();
+        // the sender is kept alive by `self`, so this can't fail
+        let _ = in_flight.wait_for(|count| *count == 0).await;
+    }
+
+    fn error() -> ServerFnError {
+        ServerFnError::ServerError("server is shutting down".into())
+    }
+}
+
+/// Counts a request as in flight until it is dropped.
+struct InFlight(Arc<watch::Sender<usize>>);
+
+impl InFlight {
+    fn new(in_flight: &Arc<watch::Sender<usize>>) -> Self {
+        in_flight.send_modify(|count| *count += 1);
+        Self(Arc::clone(in_flight))
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.send_modify(|count| *count -= 1);
+    }
+}
+
+struct DrainService<Req, Res> {
+    drain: Drain,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for Drain
+where
+    Req: RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(DrainService {
+            drain: self.clone(),
+            inner,
+        })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for DrainService<Req, Res>
+where
+    Req: RequestPath,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        // the request is counted before the signal is checked, so that `drained`
+        // can't miss a request that is let in just as the shutdown starts
+        let guard = InFlight::new(&self.drain.in_flight);
+        if self.drain.is_draining() {
+            drop(guard);
+            let path = req.path().to_string();
+            return Box::pin(async move {
+                let mut res = Res::error_response(&path, &Drain::error());
+                res.set_status(StatusCode::SERVICE_UNAVAILABLE);
+                res
+            });
+        }
+        let inner = self.inner.0.run(req);
+        Box::pin(async move {
+            let res = inner.await;
+            drop(guard);
+            res
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Drain;
+    use crate::middleware::{service_fn, Layer};
+    use axum::body::Body;
+    use futures::FutureExt;
+    use http::{Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::sync::Arc;
+    use tokio::sync::{watch, Notify};
+
+    fn request() -> Request<Body> {
+        Request::post("/api/save_post").body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn in_flight_request_completes_after_shutdown() {
+        let (shutdown, signal) = watch::channel(false);
+        let drain = Drain::new(signal);
+        let release = Arc::new(Notify::new());
+        let mut service = drain.clone().layer(service_fn({
+            let release = Arc::clone(&release);
+            move |_req: Request<Body>| {
+                let release = Arc::clone(&release);
+                async move {
+                    release.notified().await;
+                    Response::new(Body::from("saved"))
+                }
+            }
+        }));
+
+        let in_flight = tokio::spawn(service.0.run(request()));
+        tokio::task::yield_now().await;
+        assert_eq!(drain.in_flight(), 1);
+
+        shutdown.send_replace(true);
+        let rejected = service.0.run(request()).await;
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = rejected.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "ServerError|server is shutting down");
+
+        // still waiting on the in-flight request
+        assert!(drain.drained().now_or_never().is_none());
+
+        release.notify_one();
+        let res = in_flight.await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        drain.drained().await;
+        assert_eq!(drain.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn passes_requests_through_before_shutdown() {
+        let (_shutdown, signal) = watch::channel(false);
+        let drain = Drain::new(signal);
+        let mut service =
+            drain
+                .clone()
+                .layer(service_fn(|_req: Request<Body>| async move {
+                    Response::new(Body::empty())
+                }));
+        assert_eq!(service.0.run(request()).await.status(), StatusCode::OK);
+        assert!(!drain.is_draining());
+        drain.drained().await;
+    }
+}
@@ -0,0 +1,190 @@
+use super::{BoxedService, Layer, RequestPath, Service};
+use crate::{
+    error::NoCustomError,
+    request::{Req, RequestExtensions},
+    ServerFnError,
+};
+use std::{
+    any::type_name,
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// Formats the extension of one type, if the request has one.
+type Dump = fn(&RequestExtensions) -> Option<String>;
+
+/// A middleware [`Layer`] that logs the request extensions of chosen types, for
+/// debugging the layers that insert them.
+///
+/// Each request is logged through [`tracing`] as a `DEBUG` event with the target
+/// `server_fn::extensions`. Its `extensions` field holds the [`Debug`] output of
+/// every registered type that the request has an extension of, and its `missing`
+/// field names the registered types that it doesn't.
+///
+/// The extensions are read when the request reaches this layer, so it should be
+/// the first in the list of middleware, which puts it closest to the server
+/// function, after all the other layers have run.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(
+///     DumpExtensions::new()
+///         .with::<RequestIdValue>()
+///         .with::<Claims>()
+/// )]
+/// #[middleware(RequestId::new())]
+/// #[middleware(Auth::bearer(verify_token))]
+/// pub async fn delete_post(id: u32) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DumpExtensions {
+    types: Arc<Vec<(&'static str, Dump)>>,
+}
+
+impl DumpExtensions {
+    /// Creates a new layer that logs no extensions until types are added with
+    /// [`DumpExtensions::with`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Logs the extension of type `T`.
+    pub fn with<T>(mut self) -> Self
+    where
+        T: Clone + Debug + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.types).push((type_name::<T>(), |extensions| {
+            extensions.get::<T>().map(|value| format!("{value:?}"))
+        }));
+        self
+    }
+
+    fn log(&self, path: &str, extensions: &RequestExtensions) {
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+        for (name, dump) in self.types.iter() {
+            match dump(extensions) {
+                Some(value) => found.push(value),
+                None => missing.push(*name),
+            }
+        }
+        tracing::debug!(
+            target: "server_fn::extensions",
+            path,
+            extensions = %found.join(", "),
+            missing = %missing.join(", "),
+            "server function request extensions"
+        );
+    }
+}
+
+struct DumpExtensionsService<Req, Res> {
+    layer: DumpExtensions,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Rq, Rs> Layer<Rq, Rs> for DumpExtensions
+where
+    Rq: Req<NoCustomError> + RequestPath + Send + 'static,
+    Rs: Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Rq, Rs>) -> BoxedService<Rq, Rs> {
+        BoxedService::new(DumpExtensionsService {
+            layer: self.clone(),
+            inner,
+        })
+    }
+}
+
+impl<Rq, Rs> Service<Rq, Rs> for DumpExtensionsService<Rq, Rs>
+where
+    Rq: Req<NoCustomError> + RequestPath,
+{
+    fn run(&mut self, req: Rq) -> Pin<Box<dyn Future<Output = Rs> + Send>> {
+        self.layer.log(req.path(), &req.to_extensions());
+        self.inner.0.run(req)
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::DumpExtensions;
+    use crate::middleware::{service_fn, Layer, RequestId, RequestIdValue};
+    use axum::body::Body;
+    use http::{Request, Response};
+    use std::{
+        collections::HashMap,
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    };
+    use tracing::{
+        field::{Field, Visit},
+        Event, Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, Registry};
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<HashMap<&'static str, String>>>>);
+
+    struct Fields(HashMap<&'static str, String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.insert(field.name(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name(), value.to_string());
+        }
+    }
+
+    impl<S: Subscriber> tracing_subscriber::Layer<S> for Capture {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            let mut fields = Fields(HashMap::new());
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct UserId;
+
+    #[test]
+    fn logs_extensions_inserted_by_other_layers() {
+        let dump = DumpExtensions::new()
+            .with::<RequestIdValue>()
+            .with::<UserId>();
+        let handler = service_fn(|_req: Request<Body>| async move {
+            Response::new(Body::empty())
+        });
+        let mut service = RequestId::new().layer(dump.layer(handler));
+
+        let capture = Capture::default();
+        let subscriber = Registry::default().with(capture.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let req = Request::post("/api/delete_post")
+                .header("x-request-id", "req-42")
+                .body(Body::empty())
+                .unwrap();
+            futures::executor::block_on(service.0.run(req));
+        });
+
+        let events = capture.0.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["path"], "/api/delete_post");
+        assert_eq!(events[0]["extensions"], r#"RequestIdValue("req-42")"#);
+        assert_eq!(events[0]["missing"], std::any::type_name::<UserId>());
+    }
+}
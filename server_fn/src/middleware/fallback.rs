@@ -0,0 +1,351 @@
+use super::{BoxedService, Layer, SharedService};
+use crate::ServerFnError;
+
+/// The default maximum request body size buffered by [`Fallback`] (2 MiB).
+pub const DEFAULT_FALLBACK_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// A middleware [`Layer`] that sends a request to a secondary service when the
+/// inner, primary service fails.
+///
+/// A response counts as a failure if it has a `5xx` status, which includes every
+/// [`ServerFnError`] response. The request body is buffered up front so that the
+/// same request can be sent to the secondary service; bodies larger than the
+/// configured limit are rejected with `413 Payload Too Large`. The secondary service
+/// is only called if the primary one fails, and its response is returned as it is.
+///
+/// A request that fails part of the way through the primary service still runs in
+/// full on the secondary one, so both should be safe to run for the same request.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(Fallback::new(service_fn(|req| read_from_replica(req))))]
+/// pub async fn load_profile(id: u32) -> Result<Profile, ServerFnError> {
+///     // ...
+/// }
+/// ```
+///
+/// The same can be done to a service directly with [`BoxedService::fallback`].
+pub struct Fallback<Req, Res> {
+    secondary: SharedService<Req, Res>,
+    max_body_size: usize,
+}
+
+impl<Req, Res> Fallback<Req, Res> {
+    /// Creates a new layer that falls back to `secondary`.
+    pub fn new(secondary: BoxedService<Req, Res>) -> Self {
+        Self {
+            secondary: secondary.into_shared(),
+            max_body_size: DEFAULT_FALLBACK_BODY_LIMIT,
+        }
+    }
+
+    /// Sets the maximum size of the request body that will be buffered.
+    pub fn with_max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+}
+
+fn too_large() -> ServerFnError {
+    ServerFnError::ServerError("request body too large".into())
+}
+
+impl<Req, Res> Clone for Fallback<Req, Res> {
+    fn clone(&self) -> Self {
+        Self {
+            secondary: self.secondary.clone(),
+            max_body_size: self.max_body_size,
+        }
+    }
+}
+
+impl<Req, Res> BoxedService<Req, Res> {
+    /// Sends requests to `secondary` when this service fails, as described in
+    /// [`Fallback`].
+    pub fn fallback(self, secondary: BoxedService<Req, Res>) -> Self
+    where
+        Fallback<Req, Res>: Layer<Req, Res>,
+    {
+        Fallback::new(secondary).layer(self)
+    }
+}
+
+struct FallbackService<Req, Res> {
+    layer: Fallback<Req, Res>,
+    primary: SharedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{too_large, Fallback, FallbackService};
+    use crate::{
+        middleware::{BoxedService, Layer, RequestPath, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>>
+        for Fallback<Request<Body>, Response<Body>>
+    {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(FallbackService {
+                layer: self.clone(),
+                primary: inner.into_shared(),
+            })
+        }
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for FallbackService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let max_body_size = self.layer.max_body_size;
+            let mut primary = self.primary.clone();
+            let mut secondary = self.layer.secondary.clone();
+            Box::pin(async move {
+                let path = req.path().to_string();
+                let (parts, body) = req.into_parts();
+                let body = match axum::body::to_bytes(body, max_body_size).await
+                {
+                    Ok(body) => body,
+                    Err(_) => {
+                        let mut res = Response::<Body>::error_response(
+                            &path,
+                            &too_large(),
+                        );
+                        *res.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+                        return res;
+                    }
+                };
+
+                let req =
+                    Request::from_parts(parts.clone(), body.clone().into());
+                let res = primary.run(req).await;
+                if !res.status().is_server_error() {
+                    return res;
+                }
+                secondary.run(Request::from_parts(parts, body.into())).await
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.primary.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{too_large, Fallback, FallbackService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{dev, http::StatusCode};
+    use send_wrapper::SendWrapper;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse>
+        for Fallback<ActixRequest, ActixResponse>
+    {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(FallbackService {
+                layer: self.clone(),
+                primary: inner.into_shared(),
+            })
+        }
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for FallbackService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let max_body_size = self.layer.max_body_size;
+            let mut primary = self.primary.clone();
+            let mut secondary = self.layer.secondary.clone();
+            let path = req.path().to_string();
+            let rewritten = req.1.clone();
+            Box::pin(SendWrapper::new(async move {
+                let (req, payload) = req.0.take();
+                let body = match payload.to_bytes_limited(max_body_size).await {
+                    Ok(Ok(body)) => body,
+                    Ok(Err(e)) => {
+                        let err: ServerFnError =
+                            ServerFnError::Deserialization(e.to_string());
+                        return ActixResponse::error_response(&path, &err);
+                    }
+                    Err(_) => {
+                        let mut res =
+                            ActixResponse::error_response(&path, &too_large());
+                        *res.0.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+                        return res;
+                    }
+                };
+
+                let res = primary
+                    .run(
+                        ActixRequest::from((
+                            req.clone(),
+                            dev::Payload::from(body.clone()),
+                        ))
+                        .with_path(rewritten.clone()),
+                    )
+                    .await;
+                if !res.0.status().is_server_error() {
+                    return res;
+                }
+                secondary
+                    .run(
+                        ActixRequest::from((req, dev::Payload::from(body)))
+                            .with_path(rewritten),
+                    )
+                    .await
+            }))
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.primary.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Fallback;
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    /// Counts its calls, and either fails or echoes the request body after `name`.
+    struct Backend {
+        name: &'static str,
+        healthy: bool,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Service<Request<Body>, Response<Body>> for Backend {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let (name, healthy) = (self.name, self.healthy);
+            Box::pin(async move {
+                if !healthy {
+                    let err: ServerFnError =
+                        ServerFnError::ServerError("unavailable".into());
+                    return Response::<Body>::error_response("/api/load", &err);
+                }
+                let body = req.into_body().collect().await.unwrap().to_bytes();
+                Response::new(Body::from(format!(
+                    "{name}: {}",
+                    String::from_utf8_lossy(&body)
+                )))
+            })
+        }
+    }
+
+    fn backend(
+        name: &'static str,
+        healthy: bool,
+    ) -> (
+        BoxedService<Request<Body>, Response<Body>>,
+        Arc<AtomicUsize>,
+    ) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = BoxedService::new(Backend {
+            name,
+            healthy,
+            calls: Arc::clone(&calls),
+        });
+        (service, calls)
+    }
+
+    async fn call(
+        service: &mut BoxedService<Request<Body>, Response<Body>>,
+    ) -> (StatusCode, String) {
+        let req = Request::post("/api/load").body(Body::from("42")).unwrap();
+        let res = service.0.run(req).await;
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn failing_primary_falls_back_to_secondary() {
+        let (primary, primary_calls) = backend("primary", false);
+        let (secondary, secondary_calls) = backend("secondary", true);
+        let mut service = primary.fallback(secondary);
+        let res = call(&mut service).await;
+        assert_eq!(res, (StatusCode::OK, "secondary: 42".to_string()));
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(secondary_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn healthy_primary_never_calls_secondary() {
+        let (primary, _) = backend("primary", true);
+        let (secondary, secondary_calls) = backend("secondary", true);
+        let mut service = primary.fallback(secondary);
+        for _ in 0..2 {
+            let res = call(&mut service).await;
+            assert_eq!(res, (StatusCode::OK, "primary: 42".to_string()));
+        }
+        assert_eq!(secondary_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_body() {
+        let (primary, primary_calls) = backend("primary", true);
+        let (secondary, _) = backend("secondary", true);
+        let mut service = Fallback::new(secondary)
+            .with_max_body_size(1)
+            .layer(primary);
+        assert_eq!(call(&mut service).await.0, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 0);
+    }
+}
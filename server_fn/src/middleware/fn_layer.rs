@@ -0,0 +1,272 @@
+use super::{BoxedService, Layer, Service, SharedService};
+use crate::ServerFnError;
+pub use futures::future::Either;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// A middleware [`Layer`] built from an async function, which can either pass the
+/// request on to the inner service or respond to it directly.
+///
+/// The function is called with each request. If it returns [`Either::Left`] with a
+/// request, that request is passed to the inner service. If it returns
+/// [`Either::Right`] with a response, that response is returned right away, and the
+/// inner service is never called. This makes it easy to write layers for things like
+/// authentication or caching.
+///
+/// ```rust,ignore
+/// fn require_auth() -> impl Layer<Request<Body>, Response<Body>> {
+///     FnLayer::new(|req: Request<Body>| async move {
+///         if req.headers().contains_key(header::AUTHORIZATION) {
+///             Either::Left(req)
+///         } else {
+///             let mut res = Response::new(Body::from("unauthorized"));
+///             *res.status_mut() = StatusCode::UNAUTHORIZED;
+///             Either::Right(res)
+///         }
+///     })
+/// }
+///
+/// #[server]
+/// #[middleware(require_auth())]
+/// pub async fn delete_post(id: u32) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+/// ```
+pub struct FnLayer<F> {
+    f: Arc<F>,
+}
+
+impl<F> FnLayer<F> {
+    /// Creates a new layer from the given function.
+    pub fn new(f: F) -> Self {
+        Self { f: Arc::new(f) }
+    }
+}
+
+impl<F> Clone for FnLayer<F> {
+    fn clone(&self) -> Self {
+        Self {
+            f: Arc::clone(&self.f),
+        }
+    }
+}
+
+struct FnLayerService<F, Req, Res> {
+    f: Arc<F>,
+    inner: SharedService<Req, Res>,
+}
+
+impl<F, Fut, Req, Res> Layer<Req, Res> for FnLayer<F>
+where
+    F: Fn(Req) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Either<Req, Res>> + Send + 'static,
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(FnLayerService {
+            f: Arc::clone(&self.f),
+            inner: inner.into_shared(),
+        })
+    }
+}
+
+impl<F, Fut, Req, Res> Service<Req, Res> for FnLayerService<F, Req, Res>
+where
+    F: Fn(Req) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Either<Req, Res>> + Send + 'static,
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let next = (self.f)(req);
+        let mut inner = self.inner.clone();
+        Box::pin(async move {
+            match next.await {
+                Either::Left(req) => inner.run(req).await,
+                Either::Right(res) => res,
+            }
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.poll_ready(cx)
+    }
+}
+
+/// Creates a [`Service`] from a function that turns a request into a response.
+///
+/// ```rust,ignore
+/// let hello = service_fn(|req: Request<Body>| async move {
+///     Response::new(Body::from(format!("hello from {}", req.uri().path())))
+/// });
+/// ```
+pub fn service_fn<F, Fut, Req, Res>(f: F) -> BoxedService<Req, Res>
+where
+    F: FnMut(Req) -> Fut + Send + 'static,
+    Fut: Future<Output = Res> + Send + 'static,
+{
+    BoxedService::new(ServiceFn(f))
+}
+
+struct ServiceFn<F>(F);
+
+impl<F, Fut, Req, Res> Service<Req, Res> for ServiceFn<F>
+where
+    F: FnMut(Req) -> Fut,
+    Fut: Future<Output = Res> + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        Box::pin((self.0)(req))
+    }
+}
+
+/// Creates a [`Layer`] from a function that wraps the inner service.
+///
+/// Together with [`service_fn`], this is a quick way to write middleware without
+/// defining any new types.
+///
+/// ```rust,ignore
+/// let add_header = layer_fn(|inner: BoxedService<Request<Body>, Response<Body>>| {
+///     let inner = inner.into_shared();
+///     service_fn(move |req| {
+///         let mut inner = inner.clone();
+///         async move {
+///             let mut res = inner.run(req).await;
+///             res.headers_mut().insert("x-powered-by", "server_fn".parse().unwrap());
+///             res
+///         }
+///     })
+/// });
+/// ```
+pub fn layer_fn<F>(f: F) -> LayerFn<F> {
+    LayerFn(f)
+}
+
+/// A [`Layer`] created with [`layer_fn`].
+#[derive(Debug, Clone, Copy)]
+pub struct LayerFn<F>(F);
+
+impl<F, Req, Res> Layer<Req, Res> for LayerFn<F>
+where
+    F: Fn(BoxedService<Req, Res>) -> BoxedService<Req, Res>
+        + Send
+        + Sync
+        + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        (self.0)(inner)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{layer_fn, service_fn, Either, FnLayer};
+    use crate::middleware::{BoxedService, Layer, Service};
+    use axum::body::Body;
+    use http::{header, HeaderValue, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    };
+
+    struct Handler(Arc<AtomicBool>);
+
+    impl Service<Request<Body>, Response<Body>> for Handler {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            self.0.store(true, Ordering::SeqCst);
+            Box::pin(async move { Response::new(Body::empty()) })
+        }
+    }
+
+    fn require_auth() -> impl Layer<Request<Body>, Response<Body>> {
+        FnLayer::new(|req: Request<Body>| async move {
+            if req.headers().contains_key(header::AUTHORIZATION) {
+                Either::Left(req)
+            } else {
+                let mut res = Response::new(Body::from("unauthorized"));
+                *res.status_mut() = StatusCode::UNAUTHORIZED;
+                Either::Right(res)
+            }
+        })
+    }
+
+    async fn run(req: Request<Body>) -> (StatusCode, bool) {
+        let called = Arc::new(AtomicBool::new(false));
+        let mut service =
+            require_auth().layer(BoxedService::new(Handler(called.clone())));
+        let status = service.0.run(req).await.status();
+        (status, called.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn short_circuits_without_calling_the_handler() {
+        let req = Request::post("/api/delete").body(Body::empty()).unwrap();
+        assert_eq!(run(req).await, (StatusCode::UNAUTHORIZED, false));
+    }
+
+    #[tokio::test]
+    async fn passes_requests_on_to_the_handler() {
+        let req = Request::post("/api/delete")
+            .header(header::AUTHORIZATION, "Bearer token")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(run(req).await, (StatusCode::OK, true));
+    }
+
+    /// Appends `value` to the `x-trail` response header.
+    fn trail(value: &'static str) -> impl Layer<Request<Body>, Response<Body>> {
+        layer_fn(move |inner: BoxedService<Request<Body>, Response<Body>>| {
+            let inner = inner.into_shared();
+            service_fn(move |req| {
+                let mut inner = inner.clone();
+                async move {
+                    let mut res = inner.run(req).await;
+                    res.headers_mut()
+                        .append("x-trail", HeaderValue::from_static(value));
+                    res
+                }
+            })
+        })
+    }
+
+    #[tokio::test]
+    async fn composes_layer_fns_around_a_service_fn() {
+        let mut calls = 0;
+        let handler = service_fn(move |req: Request<Body>| {
+            calls += 1;
+            let body = format!("{} #{calls}", req.uri().path());
+            async move { Response::new(Body::from(body)) }
+        });
+        let mut service = trail("outer").layer(trail("inner").layer(handler));
+
+        for call in 1..=2 {
+            let req = Request::get("/api/hello").body(Body::empty()).unwrap();
+            let res = service.0.run(req).await;
+            let trail = res
+                .headers()
+                .get_all("x-trail")
+                .iter()
+                .map(|value| value.to_str().unwrap().to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(trail, ["inner", "outer"]);
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(body, format!("/api/hello #{call}"));
+        }
+    }
+}
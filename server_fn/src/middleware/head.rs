@@ -0,0 +1,118 @@
+use super::{BoxedService, Layer, Service};
+use crate::ServerFnError;
+use axum::body::{Body, HttpBody};
+use http::{header, HeaderValue, Method, Request, Response};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Answers `HEAD` requests to a `GET` server function by running it as usual, and
+/// sending its response without the body.
+///
+/// The response keeps all of its headers, and gets a `Content-Length` if the length
+/// of the body is known. This is added to every `GET` server function by
+/// [`get_server_fn_service`](crate::axum::get_server_fn_service). Actix already
+/// leaves out the body of responses to `HEAD` requests on its own.
+pub(crate) struct AnswerHead;
+
+/// Drops the body of `res`, keeping its length in `Content-Length` if it is known.
+fn without_body(res: Response<Body>) -> Response<Body> {
+    let (mut parts, body) = res.into_parts();
+    if let Some(len) = body.size_hint().exact() {
+        parts
+            .headers
+            .entry(header::CONTENT_LENGTH)
+            .or_insert_with(|| HeaderValue::from(len));
+    }
+    Response::from_parts(parts, Body::empty())
+}
+
+struct AnswerHeadService {
+    inner: BoxedService<Request<Body>, Response<Body>>,
+}
+
+impl Layer<Request<Body>, Response<Body>> for AnswerHead {
+    fn layer(
+        &self,
+        inner: BoxedService<Request<Body>, Response<Body>>,
+    ) -> BoxedService<Request<Body>, Response<Body>> {
+        BoxedService::new(AnswerHeadService { inner })
+    }
+}
+
+impl Service<Request<Body>, Response<Body>> for AnswerHeadService {
+    fn run(
+        &mut self,
+        req: Request<Body>,
+    ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+        if req.method() != Method::HEAD {
+            return self.inner.0.run(req);
+        }
+        let inner = self.inner.0.run(req);
+        Box::pin(async move { without_body(inner.await) })
+    }
+
+    fn try_run(
+        &mut self,
+        req: Request<Body>,
+    ) -> Pin<
+        Box<dyn Future<Output = Result<Response<Body>, ServerFnError>> + Send>,
+    > {
+        if req.method() != Method::HEAD {
+            return self.inner.0.try_run(req);
+        }
+        let inner = self.inner.0.try_run(req);
+        Box::pin(async move { inner.await.map(without_body) })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "url"))]
+mod tests {
+    use crate::{
+        axum::{handle_server_fn, register_explicit},
+        codec::{GetUrl, Json},
+        testing::{request, server_fn_fixture, TestResponse},
+        ServerFn, ServerFnError,
+    };
+    use http::{header, Method, StatusCode};
+
+    server_fn_fixture! {
+        /// `#[server(input = GetUrl)] async fn greet(name: String)`
+        struct Greet { name: String }
+        path = "/api/greet_head", input = GetUrl, output = Json;
+
+        async fn run_body(self) -> Result<String, ServerFnError> {
+            Ok(format!("Hello, {}!", self.name))
+        }
+    }
+
+    async fn call(method: Method) -> TestResponse {
+        register_explicit::<Greet>();
+        let req = request(method, &format!("{}?name=Ada", Greet::PATH));
+        TestResponse::read(handle_server_fn(req).await).await
+    }
+
+    #[tokio::test]
+    async fn head_keeps_headers_without_body() {
+        let get = call(Method::GET).await;
+        let head = call(Method::HEAD).await;
+
+        head.assert_status(StatusCode::OK)
+            .assert_header(header::CONTENT_TYPE.as_str(), "application/json")
+            .assert_header(
+                header::CONTENT_LENGTH.as_str(),
+                &r#""Hello, Ada!""#.len().to_string(),
+            )
+            .assert_body("");
+        get.assert_body(r#""Hello, Ada!""#);
+    }
+}
@@ -0,0 +1,128 @@
+use super::{BoxedService, Layer, Service};
+use crate::ServerFnError;
+use std::{
+    any::Any,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, PoisonError, RwLock},
+    task::{Context, Poll},
+};
+
+/// Hooks of any request or response type, in the order they were registered.
+type Hooks = RwLock<Vec<Arc<dyn Any + Send + Sync>>>;
+
+/// A hook for values of type `T`, as stored in [`Hooks`].
+type Hook<T> = Box<dyn Fn(&T) + Send + Sync>;
+
+static REQUEST_HOOKS: Hooks = RwLock::new(Vec::new());
+static RESPONSE_HOOKS: Hooks = RwLock::new(Vec::new());
+
+/// Registers a function that is called with every request to a server function,
+/// before any of its middleware runs.
+///
+/// This suits cross-cutting concerns that only need to look at the request, like
+/// counting calls, without writing a [`Layer`]. Hooks are added to those already
+/// registered, and run in the order they were registered. A hook only runs for
+/// requests of the type it takes, like `Request<Body>` with Axum or `ActixRequest`
+/// with Actix.
+///
+/// ```rust,ignore
+/// server_fn::on_request(|req: &Request<Body>| {
+///     tracing::debug!(path = req.uri().path(), "server function called");
+/// });
+/// ```
+pub fn on_request<Req: 'static>(hook: impl Fn(&Req) + Send + Sync + 'static) {
+    register(&REQUEST_HOOKS, hook);
+}
+
+/// Registers a function that is called with every response of a server function,
+/// after all of its middleware has run.
+///
+/// Like [`on_request`], hooks are additive and run in the order they were
+/// registered, only for responses of the type they take. They are not called for an
+/// error that is returned from [`Service::try_run`] rather than turned into a
+/// response.
+pub fn on_response<Res: 'static>(hook: impl Fn(&Res) + Send + Sync + 'static) {
+    register(&RESPONSE_HOOKS, hook);
+}
+
+fn register<T: 'static>(
+    hooks: &Hooks,
+    hook: impl Fn(&T) + Send + Sync + 'static,
+) {
+    let hook: Hook<T> = Box::new(hook);
+    hooks
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(Arc::new(hook));
+}
+
+fn run_hooks<T: 'static>(hooks: &Hooks, value: &T) {
+    // copied, so that a hook can register another one without a deadlock
+    let hooks = hooks.read().unwrap_or_else(PoisonError::into_inner).clone();
+    for hook in hooks {
+        if let Some(hook) = hook.downcast_ref::<Hook<T>>() {
+            hook(value);
+        }
+    }
+}
+
+/// Runs the hooks registered with [`on_request`] and [`on_response`] around every
+/// server function.
+///
+/// This is added to every server function by `get_server_fn_service`, outside of
+/// any of its middleware.
+pub(crate) struct RunHooks;
+
+struct RunHooksService<Req, Res> {
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for RunHooks
+where
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(RunHooksService { inner })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for RunHooksService<Req, Res>
+where
+    Req: 'static,
+    Res: Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        run_hooks(&REQUEST_HOOKS, &req);
+        let inner = self.inner.0.run(req);
+        Box::pin(async move {
+            let res = inner.await;
+            run_hooks(&RESPONSE_HOOKS, &res);
+            res
+        })
+    }
+
+    fn try_run(
+        &mut self,
+        req: Req,
+    ) -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send>>
+    where
+        Res: 'static,
+    {
+        run_hooks(&REQUEST_HOOKS, &req);
+        let inner = self.inner.0.try_run(req);
+        Box::pin(async move {
+            let res = inner.await?;
+            run_hooks(&RESPONSE_HOOKS, &res);
+            Ok(res)
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
@@ -0,0 +1,162 @@
+use super::{BoxedService, Layer, RequestPath, Service};
+use crate::{error::NoCustomError, request::Req, ServerFnError};
+use http::StatusCode;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// A middleware [`Layer`] that only lets through requests sent to one of a list of
+/// hosts, and rejects all others with `421 Misdirected Request`.
+///
+/// Each allowed host is either an exact name, like `example.com`, or a wildcard for
+/// all of its subdomains, like `*.example.com`, which matches `shop.example.com`
+/// but not `example.com` itself. Names are compared without their case and without
+/// the port of the request. The host is read from the `Host` header, or from the
+/// URI of an HTTP/2 request, and requests without one are rejected as well.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(HostFilter::new(["example.com", "*.example.com"]))]
+/// pub async fn list_tenants() -> Result<Vec<Tenant>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HostFilter {
+    allowed: Arc<[String]>,
+}
+
+impl HostFilter {
+    /// Creates a new layer that allows the given hosts.
+    pub fn new<H>(hosts: impl IntoIterator<Item = H>) -> Self
+    where
+        H: Into<String>,
+    {
+        Self {
+            allowed: hosts
+                .into_iter()
+                .map(|host| host.into().to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Whether a request sent to `host`, which may include a port, is allowed.
+    fn allows(&self, host: &str) -> bool {
+        let host = Self::without_port(host).to_ascii_lowercase();
+        self.allowed
+            .iter()
+            .any(|allowed| match allowed.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+                None => *allowed == host,
+            })
+    }
+
+    /// Strips the port from a host, keeping the brackets of an IPv6 address.
+    fn without_port(host: &str) -> &str {
+        match host.rfind(':') {
+            Some(colon) if !host[colon..].contains(']') => &host[..colon],
+            _ => host,
+        }
+    }
+
+    fn error() -> ServerFnError {
+        ServerFnError::ServerError("misdirected request".into())
+    }
+}
+
+struct HostFilterService<Req, Res> {
+    filter: HostFilter,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Rq, Rs> Layer<Rq, Rs> for HostFilter
+where
+    Rq: Req<NoCustomError> + RequestPath + Send + 'static,
+    Rs: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Rq, Rs>) -> BoxedService<Rq, Rs> {
+        BoxedService::new(HostFilterService {
+            filter: self.clone(),
+            inner,
+        })
+    }
+}
+
+impl<Rq, Rs> Service<Rq, Rs> for HostFilterService<Rq, Rs>
+where
+    Rq: Req<NoCustomError> + RequestPath,
+    Rs: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(&mut self, req: Rq) -> Pin<Box<dyn Future<Output = Rs> + Send>> {
+        if req.host().is_some_and(|host| self.filter.allows(&host)) {
+            return self.inner.0.run(req);
+        }
+        let mut res = Rs::error_response(req.path(), &HostFilter::error());
+        res.set_status(StatusCode::MISDIRECTED_REQUEST);
+        Box::pin(async move { res })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::HostFilter;
+    use crate::middleware::{service_fn, Layer};
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+
+    async fn call(host: &str) -> StatusCode {
+        let mut service =
+            HostFilter::new(["example.com", "*.tenants.example.com"]).layer(
+                service_fn(|_req: Request<Body>| async move {
+                    Response::new(Body::empty())
+                }),
+            );
+        let req = Request::post("/api/list_tenants")
+            .header(header::HOST, host)
+            .body(Body::empty())
+            .unwrap();
+        service.0.run(req).await.status()
+    }
+
+    #[tokio::test]
+    async fn allows_exact_hosts() {
+        assert_eq!(call("example.com").await, StatusCode::OK);
+        assert_eq!(call("Example.COM:8080").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn allows_subdomains_of_wildcards() {
+        assert_eq!(call("acme.tenants.example.com").await, StatusCode::OK);
+        assert_eq!(call("eu.acme.tenants.example.com").await, StatusCode::OK);
+        // the wildcard needs a subdomain
+        assert_eq!(
+            call("tenants.example.com").await,
+            StatusCode::MISDIRECTED_REQUEST
+        );
+        assert_eq!(
+            call("eviltenants.example.com").await,
+            StatusCode::MISDIRECTED_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_hosts() {
+        assert_eq!(call("evil.com").await, StatusCode::MISDIRECTED_REQUEST);
+        assert_eq!(
+            call("shop.example.com").await,
+            StatusCode::MISDIRECTED_REQUEST
+        );
+    }
+}
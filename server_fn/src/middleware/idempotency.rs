@@ -0,0 +1,526 @@
+use super::SharedService;
+use bytes::Bytes;
+use dashmap::DashMap;
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
+use tokio::time::Instant;
+
+/// How long an [`Idempotency`] layer keeps responses by default: one day.
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The header that a retried request repeats, so that it isn't run twice.
+const KEY_HEADER: &str = "idempotency-key";
+
+/// The header added to a response that is replayed from the store.
+const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// The longest idempotency key that is accepted from a client.
+const MAX_KEY_LEN: usize = 255;
+
+/// A response recorded by an [`Idempotency`] layer, stored independently of the
+/// framework.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredResponse {
+    /// The status code of the response.
+    pub status: u16,
+    /// The headers of the response, in order.
+    pub headers: Vec<(String, Bytes)>,
+    /// The body of the response.
+    pub body: Bytes,
+}
+
+/// Storage for the responses replayed by [`Idempotency`].
+///
+/// The default [`InMemoryIdempotencyStore`] keeps responses in memory. Implement this
+/// trait to share them between several servers, for example by keeping them in Redis.
+pub trait IdempotencyStore: Send + Sync + 'static {
+    /// Returns the response stored for `key`, if it has not expired.
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Option<StoredResponse>> + Send + 'a>>;
+
+    /// Stores `response` for `key`, for at least `ttl`.
+    fn insert<'a>(
+        &'a self,
+        key: &'a str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+}
+
+/// An [`IdempotencyStore`] that keeps responses in memory.
+#[derive(Debug, Default)]
+pub struct InMemoryIdempotencyStore {
+    entries: DashMap<String, (StoredResponse, Instant)>,
+}
+
+impl IdempotencyStore for InMemoryIdempotencyStore {
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Option<StoredResponse>> + Send + 'a>> {
+        let now = Instant::now();
+        self.entries
+            .remove_if(key, |_, (_, expires)| *expires <= now);
+        let res = self.entries.get(key).map(|entry| entry.0.clone());
+        Box::pin(async move { res })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        key: &'a str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        let now = Instant::now();
+        self.entries.retain(|_, (_, expires)| *expires > now);
+        self.entries.insert(key.to_string(), (response, now + ttl));
+        Box::pin(async move {})
+    }
+}
+
+/// A middleware [`Layer`](super::Layer) that makes it safe to retry a request, by
+/// running it only once per `Idempotency-Key`.
+///
+/// A client that may need to retry a request, like a `POST` that times out, sends it
+/// with an `Idempotency-Key` header holding a unique value, and sends that same value
+/// again with every retry. The first request runs as usual, and its response is
+/// buffered and recorded in the [`IdempotencyStore`]. Later requests to the same
+/// server function with the same key get the recorded response, with an
+/// `Idempotent-Replayed: true` header, without calling the inner service.
+///
+/// Responses with a `5xx` status are not recorded, so that a request that failed
+/// on the server can be retried. Requests without the header are passed through
+/// unchanged. Requests that arrive while the first one with the same key is still
+/// running are not held back, and run as well.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(
+///     Idempotency::new(InMemoryIdempotencyStore::default())
+///         .ttl(Duration::from_secs(60 * 60))
+/// )]
+/// pub async fn place_order(cart: Cart) -> Result<OrderId, ServerFnError> {
+///     // ...
+/// }
+/// ```
+pub struct Idempotency<S> {
+    store: Arc<S>,
+    ttl: Duration,
+}
+
+impl<S> Idempotency<S> {
+    /// Creates a new layer that records responses in `store`.
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            ttl: DEFAULT_IDEMPOTENCY_TTL,
+        }
+    }
+
+    /// Keeps recorded responses for `ttl`.
+    ///
+    /// Defaults to [`DEFAULT_IDEMPOTENCY_TTL`].
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The key a response is stored under, or `None` if the request should not be
+    /// recorded.
+    fn key(path: &str, header: Option<&str>) -> Option<String> {
+        let key = header?.trim();
+        if key.is_empty() || key.len() > MAX_KEY_LEN {
+            return None;
+        }
+        Some(format!("{path}\n{key}"))
+    }
+
+    /// Whether a response with this status can be replayed.
+    fn is_recorded(status: u16) -> bool {
+        status < 500
+    }
+}
+
+impl<S> Clone for Idempotency<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            ttl: self.ttl,
+        }
+    }
+}
+
+struct IdempotencyService<S, Req, Res> {
+    layer: Idempotency<S>,
+    inner: SharedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{
+        Idempotency, IdempotencyService, IdempotencyStore, StoredResponse,
+        KEY_HEADER, REPLAYED_HEADER,
+    };
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use http::{HeaderName, HeaderValue, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl<S> Layer<Request<Body>, Response<Body>> for Idempotency<S>
+    where
+        S: IdempotencyStore,
+    {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(IdempotencyService {
+                layer: self.clone(),
+                inner: inner.into_shared(),
+            })
+        }
+    }
+
+    fn to_response(stored: StoredResponse) -> Response<Body> {
+        let mut res = Response::new(Body::from(stored.body));
+        *res.status_mut() =
+            StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+        for (name, value) in stored.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_maybe_shared(value),
+            ) {
+                res.headers_mut().append(name, value);
+            }
+        }
+        res.headers_mut().insert(
+            HeaderName::from_static(REPLAYED_HEADER),
+            HeaderValue::from_static("true"),
+        );
+        res
+    }
+
+    impl<S> Service<Request<Body>, Response<Body>>
+        for IdempotencyService<S, Request<Body>, Response<Body>>
+    where
+        S: IdempotencyStore,
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let path = req.uri().path().to_string();
+            let key = Idempotency::<S>::key(
+                &path,
+                req.headers()
+                    .get(KEY_HEADER)
+                    .and_then(|value| value.to_str().ok()),
+            );
+            let Some(key) = key else {
+                return self.inner.run(req);
+            };
+
+            let layer = self.layer.clone();
+            let mut inner = self.inner.clone();
+            Box::pin(async move {
+                if let Some(stored) = layer.store.get(&key).await {
+                    return to_response(stored);
+                }
+                let res = inner.run(req).await;
+                if !Idempotency::<S>::is_recorded(res.status().as_u16()) {
+                    return res;
+                }
+                let (parts, body) = res.into_parts();
+                let body = match body.collect().await {
+                    Ok(body) => body.to_bytes(),
+                    Err(e) => {
+                        return Response::error_response(
+                            &path,
+                            &ServerFnError::new(e),
+                        )
+                    }
+                };
+                let headers = parts
+                    .headers
+                    .iter()
+                    .map(|(name, value)| {
+                        (
+                            name.to_string(),
+                            Bytes::copy_from_slice(value.as_bytes()),
+                        )
+                    })
+                    .collect();
+                let stored = StoredResponse {
+                    status: parts.status.as_u16(),
+                    headers,
+                    body: body.clone(),
+                };
+                layer.store.insert(&key, stored, layer.ttl).await;
+                Response::from_parts(parts, Body::from(body))
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{
+        Idempotency, IdempotencyService, IdempotencyStore, StoredResponse,
+        KEY_HEADER, REPLAYED_HEADER,
+    };
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{
+        http::{
+            header::{HeaderName, HeaderValue},
+            StatusCode,
+        },
+        HttpResponse,
+    };
+    use bytes::Bytes;
+    use send_wrapper::SendWrapper;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl<S> Layer<ActixRequest, ActixResponse> for Idempotency<S>
+    where
+        S: IdempotencyStore,
+    {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(IdempotencyService {
+                layer: self.clone(),
+                inner: inner.into_shared(),
+            })
+        }
+    }
+
+    fn to_response(stored: StoredResponse, replayed: bool) -> ActixResponse {
+        let mut res = HttpResponse::build(
+            StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK),
+        );
+        for (name, value) in stored.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_maybe_shared(value),
+            ) {
+                res.append_header((name, value));
+            }
+        }
+        if replayed {
+            res.insert_header((REPLAYED_HEADER, "true"));
+        }
+        ActixResponse::from(res.body(stored.body))
+    }
+
+    impl<S> Service<ActixRequest, ActixResponse>
+        for IdempotencyService<S, ActixRequest, ActixResponse>
+    where
+        S: IdempotencyStore,
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let path = req.path().to_string();
+            let key = Idempotency::<S>::key(
+                &path,
+                req.request()
+                    .headers()
+                    .get(KEY_HEADER)
+                    .and_then(|value| value.to_str().ok()),
+            );
+            let Some(key) = key else {
+                return self.inner.run(req);
+            };
+
+            let layer = self.layer.clone();
+            let mut inner = self.inner.clone();
+            Box::pin(async move {
+                if let Some(stored) = layer.store.get(&key).await {
+                    return to_response(stored, true);
+                }
+                let res = inner.run(req).await;
+                if !Idempotency::<S>::is_recorded(res.0.status().as_u16()) {
+                    return res;
+                }
+                // the response head isn't `Send`, so only its parts are kept
+                // while the body is read
+                let (status, headers, body) = {
+                    let (res, body) = res.take().into_parts();
+                    let headers: Vec<_> = res
+                        .headers()
+                        .iter()
+                        .map(|(name, value)| {
+                            (
+                                name.to_string(),
+                                Bytes::copy_from_slice(value.as_bytes()),
+                            )
+                        })
+                        .collect();
+                    (res.status().as_u16(), headers, body)
+                };
+                // Actix keeps the response on a single thread, so reading the body
+                // only needs to look `Send`
+                let body =
+                    match SendWrapper::new(actix_web::body::to_bytes(body))
+                        .await
+                    {
+                        Ok(body) => body,
+                        Err(e) => {
+                            return ActixResponse::error_response(
+                                &path,
+                                &ServerFnError::new(e),
+                            )
+                        }
+                    };
+                let stored = StoredResponse {
+                    status,
+                    headers,
+                    body,
+                };
+                layer.store.insert(&key, stored.clone(), layer.ttl).await;
+                to_response(stored, false)
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{Idempotency, InMemoryIdempotencyStore};
+    use crate::middleware::{service_fn, BoxedService, Layer};
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    /// Counts its calls, and responds with the count, streaming the body.
+    fn orders(
+        calls: Arc<AtomicUsize>,
+        status: StatusCode,
+    ) -> BoxedService<Request<Body>, Response<Body>> {
+        service_fn(move |_req: Request<Body>| {
+            let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                let chunks = futures::stream::iter([
+                    Ok::<_, std::io::Error>("order ".to_string()),
+                    Ok(n.to_string()),
+                ]);
+                let mut res = Response::new(Body::from_stream(chunks));
+                *res.status_mut() = status;
+                res
+            }
+        })
+    }
+
+    fn idempotent(
+        layer: Idempotency<InMemoryIdempotencyStore>,
+        status: StatusCode,
+    ) -> (
+        BoxedService<Request<Body>, Response<Body>>,
+        Arc<AtomicUsize>,
+    ) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        (layer.layer(orders(Arc::clone(&calls), status)), calls)
+    }
+
+    async fn post(
+        service: &mut BoxedService<Request<Body>, Response<Body>>,
+        key: Option<&str>,
+    ) -> (String, bool) {
+        let mut req = Request::post("/api/place_order");
+        if let Some(key) = key {
+            req = req.header("idempotency-key", key);
+        }
+        let res = service.0.run(req.body(Body::empty()).unwrap()).await;
+        let replayed = res.headers().contains_key("idempotent-replayed");
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (String::from_utf8(body.to_vec()).unwrap(), replayed)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn runs_once_per_key() {
+        let (mut service, calls) = idempotent(
+            Idempotency::new(InMemoryIdempotencyStore::default()),
+            StatusCode::OK,
+        );
+        let first = post(&mut service, Some("a")).await;
+        let retry = post(&mut service, Some("a")).await;
+        assert_eq!(first, ("order 1".to_string(), false));
+        assert_eq!(retry, ("order 1".to_string(), true));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // other keys, and requests without a key, run again
+        assert_eq!(post(&mut service, Some("b")).await.0, "order 2");
+        assert_eq!(post(&mut service, None).await.0, "order 3");
+        assert_eq!(post(&mut service, None).await.0, "order 4");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn recorded_responses_expire() {
+        let (mut service, calls) = idempotent(
+            Idempotency::new(InMemoryIdempotencyStore::default())
+                .ttl(Duration::from_secs(60)),
+            StatusCode::OK,
+        );
+        post(&mut service, Some("a")).await;
+        tokio::time::advance(Duration::from_secs(59)).await;
+        post(&mut service, Some("a")).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(post(&mut service, Some("a")).await.0, "order 2");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn server_errors_can_be_retried() {
+        let (mut service, calls) = idempotent(
+            Idempotency::new(InMemoryIdempotencyStore::default()),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        );
+        post(&mut service, Some("a")).await;
+        post(&mut service, Some("a")).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
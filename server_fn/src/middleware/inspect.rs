@@ -0,0 +1,259 @@
+use super::BoxedService;
+
+/// Logs the start of the request and response bodies of a server function, as
+/// described in [`inspect`](crate::inspect), without changing them.
+///
+/// This wraps the server function itself, beneath its middleware, so it sees the
+/// bodies as the codecs read and write them. It is added to every registered server
+/// function by `get_server_fn_service` when the `debug-wire` feature is enabled.
+pub(crate) struct InspectWire;
+
+struct InspectWireService<Req, Res> {
+    inner: BoxedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{InspectWire, InspectWireService};
+    use crate::{
+        inspect::Wire,
+        middleware::{BoxedService, Layer, Service},
+        ServerFnError,
+    };
+    use axum::body::{Body, HttpBody};
+    use http::{header, HeaderMap, HeaderValue, Request, Response};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    fn content_type(headers: &HeaderMap) -> Option<String> {
+        headers
+            .get(header::CONTENT_TYPE)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+    }
+
+    impl Layer<Request<Body>, Response<Body>> for InspectWire {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(InspectWireService { inner })
+        }
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for InspectWireService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let path = req.uri().path().to_string();
+            let (parts, body) = req.into_parts();
+            let wire = Wire::server_request(
+                path.as_str(),
+                content_type(&parts.headers),
+            );
+            let body = Body::from_stream(wire.tee(body.into_data_stream()));
+            let inner = self.inner.0.run(Request::from_parts(parts, body));
+            Box::pin(async move {
+                let (mut parts, body) = inner.await.into_parts();
+                // the stream below hides the length of the body, so keep it in
+                // `Content-Length`
+                if let Some(len) = body.size_hint().exact() {
+                    parts
+                        .headers
+                        .entry(header::CONTENT_LENGTH)
+                        .or_insert_with(|| HeaderValue::from(len));
+                }
+                let wire =
+                    Wire::server_response(path, content_type(&parts.headers));
+                let body = Body::from_stream(wire.tee(body.into_data_stream()));
+                Response::from_parts(parts, body)
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{InspectWire, InspectWireService};
+    use crate::{
+        inspect::Wire,
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::actix::ActixResponse,
+        ServerFnError,
+    };
+    use actix_web::{
+        body::{BodySize, BodyStream, MessageBody, SizedStream},
+        dev,
+        http::header::{self, HeaderMap},
+    };
+    use futures::Stream;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    fn content_type(headers: &HeaderMap) -> Option<String> {
+        headers
+            .get(header::CONTENT_TYPE)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+    }
+
+    impl Layer<ActixRequest, ActixResponse> for InspectWire {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(InspectWireService { inner })
+        }
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for InspectWireService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let path = req.path().to_string();
+            let rewritten = req.1.clone();
+            let (http_req, payload) = req.0.take();
+            let wire = Wire::server_request(
+                path.as_str(),
+                content_type(http_req.headers()),
+            );
+            let payload =
+                dev::Payload::from(Box::pin(wire.tee(payload))
+                    as Pin<Box<dyn Stream<Item = _>>>);
+            let inner = self.inner.0.run(
+                ActixRequest::from((http_req, payload)).with_path(rewritten),
+            );
+            Box::pin(async move {
+                let (res, body) = inner.await.take().into_parts();
+                let wire =
+                    Wire::server_response(path, content_type(res.headers()));
+                let size = body.size();
+                let mut body = Box::pin(body);
+                let chunks = wire.tee(futures::stream::poll_fn(move |cx| {
+                    body.as_mut().poll_next(cx)
+                }));
+                let res = match size {
+                    BodySize::Sized(len) => res
+                        .set_body(SizedStream::new(len, chunks))
+                        .map_into_boxed_body(),
+                    _ => res
+                        .set_body(BodyStream::new(chunks))
+                        .map_into_boxed_body(),
+                };
+                ActixResponse::from(res)
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use crate::{
+        axum::{handle_server_fn, register_explicit},
+        codec::Json,
+        testing::{json_request, server_fn_fixture, TestResponse},
+        ServerFn, ServerFnError,
+    };
+    use http::header;
+    use std::{
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    };
+    use tracing::{
+        field::{Field, Visit},
+        Event, Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, Registry};
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<Vec<String>>>>);
+
+    struct Fields(Vec<String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.push(format!("{}={value:?}", field.name()));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push(format!("{}={value}", field.name()));
+        }
+    }
+
+    impl<S: Subscriber> tracing_subscriber::Layer<S> for Capture {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            let mut fields = Fields(Vec::new());
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+    }
+
+    server_fn_fixture! {
+        /// `async fn rename(id: u32, name: String)`
+        struct Rename { id: u32, name: String }
+        path = "/api/rename_inspect", input = Json, output = Json;
+
+        async fn run_body(self) -> Result<String, ServerFnError> {
+            Ok(format!("{} #{}", self.name, self.id))
+        }
+    }
+
+    #[test]
+    fn logs_json_request_body_preview() {
+        register_explicit::<Rename>();
+        let capture = Capture::default();
+        let subscriber = Registry::default().with(capture.clone());
+        let res = tracing::subscriber::with_default(subscriber, || {
+            let req = json_request(Rename::PATH, r#"{"id":7,"name":"Ada"}"#);
+            futures::executor::block_on(async {
+                TestResponse::read(handle_server_fn(req).await).await
+            })
+        });
+        res.assert_header(header::CONTENT_LENGTH.as_str(), "8")
+            .assert_body(r#""Ada #7""#);
+
+        let events = capture.0.lock().unwrap();
+        let request = events
+            .iter()
+            .find(|fields| fields.contains(&"direction=request".to_string()))
+            .unwrap();
+        assert!(request.contains(&"side=server".to_string()));
+        assert!(request.contains(&format!("path={}", Rename::PATH)));
+        assert!(request.contains(&"content_type=application/json".to_string()));
+        assert!(
+            request.contains(&r#"preview={"id":7,"name":"Ada"}"#.to_string())
+        );
+        assert!(request.contains(&"truncated=false".to_string()));
+
+        let response = events
+            .iter()
+            .find(|fields| fields.contains(&"direction=response".to_string()))
+            .unwrap();
+        assert!(response.contains(&r#"preview="Ada #7""#.to_string()));
+    }
+}
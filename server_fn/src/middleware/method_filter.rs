@@ -0,0 +1,90 @@
+use super::{bypass::bypass, BoxedService, Layer, RequestMethod};
+use http::Method;
+use std::sync::Arc;
+
+/// A [`Layer`] that only applies another layer to requests with certain HTTP
+/// methods, and passes all other requests straight to the inner service.
+///
+/// This is created with [`Layer::only_methods`]. It is useful for checks that only
+/// matter for requests that change something, like verifying a CSRF token.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(Auth::bearer(verify_token).only_methods(&[Method::POST, Method::DELETE]))]
+/// pub async fn delete_post(id: u32) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MethodFilter<L> {
+    layer: L,
+    methods: Arc<[Method]>,
+}
+
+impl<L> MethodFilter<L> {
+    /// Wraps `layer` so that it only applies to requests with one of `methods`.
+    pub fn new(layer: L, methods: &[Method]) -> Self {
+        Self {
+            layer,
+            methods: methods.into(),
+        }
+    }
+}
+
+impl<L, Req, Res> Layer<Req, Res> for MethodFilter<L>
+where
+    L: Layer<Req, Res>,
+    Req: RequestMethod + Send + 'static,
+    Res: Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        let methods = Arc::clone(&self.methods);
+        bypass(
+            &self.layer,
+            Arc::new(move |req: &Req| !methods.contains(&req.method())),
+            inner,
+        )
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use crate::middleware::{service_fn, FnLayer, Layer};
+    use axum::body::Body;
+    use futures::future::Either;
+    use http::{Method, Request, Response, StatusCode};
+
+    /// Rejects every request it sees.
+    fn reject() -> impl Layer<Request<Body>, Response<Body>> {
+        FnLayer::new(|_req: Request<Body>| async move {
+            let mut res = Response::new(Body::empty());
+            *res.status_mut() = StatusCode::FORBIDDEN;
+            Either::Right(res)
+        })
+    }
+
+    async fn status(method: Method) -> StatusCode {
+        let mut service = reject()
+            .only_methods(&[Method::POST, Method::DELETE])
+            .layer(service_fn(|_req: Request<Body>| async move {
+                Response::new(Body::empty())
+            }));
+        let req = Request::builder()
+            .method(method)
+            .uri("/api/delete_post")
+            .body(Body::empty())
+            .unwrap();
+        service.0.run(req).await.status()
+    }
+
+    #[tokio::test]
+    async fn listed_methods_run_layer() {
+        assert_eq!(status(Method::POST).await, StatusCode::FORBIDDEN);
+        assert_eq!(status(Method::DELETE).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn other_methods_bypass_layer() {
+        assert_eq!(status(Method::GET).await, StatusCode::OK);
+    }
+}
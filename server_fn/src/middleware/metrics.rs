@@ -0,0 +1,150 @@
+use super::{BoxedService, Layer, RequestPath, ResponseStatus, Service};
+use crate::ServerFnError;
+use http::StatusCode;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+/// A middleware [`Layer`] that reports the outcome of every server function call to a
+/// callback.
+///
+/// The callback receives the request path, the response status and the time it took
+/// to respond. Errors returned by the server function are reported with the status of
+/// their error response (usually `500`).
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(Metrics::new(|path, status, elapsed| {
+///     HISTOGRAM
+///         .with_label_values(&[path, status.as_str()])
+///         .observe(elapsed.as_secs_f64())
+/// }))]
+/// pub async fn load_user(id: u32) -> Result<User, ServerFnError> {
+///     // ...
+/// }
+/// ```
+pub struct Metrics<F> {
+    callback: Arc<F>,
+}
+
+impl<F> Metrics<F>
+where
+    F: Fn(&str, StatusCode, Duration) + Send + Sync + 'static,
+{
+    /// Creates a new metrics layer that calls `callback` after each request.
+    pub fn new(callback: F) -> Self {
+        Self {
+            callback: Arc::new(callback),
+        }
+    }
+}
+
+impl<F> Clone for Metrics<F> {
+    fn clone(&self) -> Self {
+        Self {
+            callback: Arc::clone(&self.callback),
+        }
+    }
+}
+
+struct MetricsService<F, Req, Res> {
+    callback: Arc<F>,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<F, Req, Res> Layer<Req, Res> for Metrics<F>
+where
+    F: Fn(&str, StatusCode, Duration) + Send + Sync + 'static,
+    Req: RequestPath + Send + 'static,
+    Res: ResponseStatus + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(MetricsService {
+            callback: Arc::clone(&self.callback),
+            inner,
+        })
+    }
+}
+
+impl<F, Req, Res> Service<Req, Res> for MetricsService<F, Req, Res>
+where
+    F: Fn(&str, StatusCode, Duration) + Send + Sync + 'static,
+    Req: RequestPath,
+    Res: ResponseStatus + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let path = req.path().to_string();
+        let callback = Arc::clone(&self.callback);
+        let start = Instant::now();
+        let inner = self.inner.0.run(req);
+        Box::pin(async move {
+            let res = inner.await;
+            callback(&path, res.status(), start.elapsed());
+            res
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Metrics;
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+    };
+
+    struct Failing;
+
+    impl Service<Request<Body>, Response<Body>> for Failing {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let path = req.uri().path().to_string();
+            Box::pin(async move {
+                let err: ServerFnError =
+                    ServerFnError::ServerError("database is down".into());
+                Response::<Body>::error_response(&path, &err)
+            })
+        }
+    }
+
+    #[test]
+    fn reports_status_of_error_responses() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let metrics = Metrics::new({
+            let calls = Arc::clone(&calls);
+            move |path: &str, status, _elapsed| {
+                calls.lock().unwrap().push((path.to_string(), status));
+            }
+        });
+        let mut service = metrics.layer(BoxedService::new(Failing));
+        let req = Request::post("/api/users").body(Body::empty()).unwrap();
+        let res = futures::executor::block_on(service.0.run(req));
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [("/api/users".to_string(), StatusCode::INTERNAL_SERVER_ERROR)]
+        );
+    }
+}
@@ -1,10 +1,300 @@
-use std::{future::Future, pin::Pin};
+use crate::ServerFnError;
+use http::{Method, StatusCode};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod auth;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod body_limit;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod buffer;
+mod bypass;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod cache;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod catch_panic;
+#[cfg(all(
+    feature = "compression",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+mod compress;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod concurrency_limit;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod conditional;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod cors;
+#[cfg(all(
+    feature = "cookies",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+mod csrf;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod custom_error;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod deadline;
+#[cfg(all(
+    feature = "compression",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+mod decompress;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod drain;
+#[cfg(all(
+    feature = "tracing",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+mod dump_extensions;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod fallback;
+mod fn_layer;
+#[cfg(feature = "axum-no-default")]
+mod head;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod hooks;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod host_filter;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod idempotency;
+#[cfg(all(
+    feature = "debug-wire",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+mod inspect;
+mod method_filter;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod metrics;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod on_error;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod options;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod path_rewrite;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod provide;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod rate_limit;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod raw_errors;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod recorder;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod request_id;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod require_content_type;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod require_header;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod retry;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod security_headers;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod server_timing;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod single_flight;
+#[cfg(all(
+    feature = "tracing",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+mod slow_log;
+mod stack;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod timeout;
+#[cfg(all(
+    feature = "tracing",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+mod trace;
+#[cfg(all(
+    feature = "tracing",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+mod trace_context;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+mod url_length_limit;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use auth::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use body_limit::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use buffer::*;
+pub use bypass::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use cache::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use catch_panic::*;
+#[cfg(all(
+    feature = "compression",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+pub use compress::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use concurrency_limit::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use conditional::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use cors::*;
+#[cfg(all(
+    feature = "cookies",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+pub use csrf::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use custom_error::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use deadline::{Deadline, RequestDeadline};
+#[cfg(all(
+    feature = "compression",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+pub use decompress::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use drain::*;
+#[cfg(all(
+    feature = "tracing",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+pub use dump_extensions::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use fallback::*;
+pub use fn_layer::*;
+#[cfg(feature = "axum-no-default")]
+pub(crate) use head::AnswerHead;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub(crate) use hooks::RunHooks;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use hooks::{on_request, on_response};
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use host_filter::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use idempotency::*;
+#[cfg(all(
+    feature = "debug-wire",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+pub(crate) use inspect::InspectWire;
+pub use method_filter::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use metrics::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use on_error::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub(crate) use options::AnswerOptions;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use path_rewrite::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use provide::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use rate_limit::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub(crate) use raw_errors::RawErrorSlot;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use raw_errors::RawErrors;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use recorder::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use request_id::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use require_content_type::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use require_header::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use retry::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use security_headers::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use server_timing::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use single_flight::*;
+#[cfg(all(
+    feature = "tracing",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+pub use slow_log::*;
+pub use stack::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use timeout::*;
+#[cfg(all(
+    feature = "tracing",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+pub use trace::*;
+#[cfg(all(
+    feature = "tracing",
+    any(feature = "axum-no-default", feature = "actix")
+))]
+pub use trace_context::*;
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+pub use url_length_limit::*;
 
 /// An abstraction over a middleware layer, which can be used to add additional
 /// middleware layer to a [`Service`].
 pub trait Layer<Req, Res>: Send + Sync + 'static {
     /// Adds this layer to the inner service.
     fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res>;
+
+    /// Skips this layer for requests for which `predicate` returns `true`, passing
+    /// them straight to the inner service.
+    ///
+    /// ```rust,ignore
+    /// #[middleware(
+    ///     RateLimit::new(Quota::new(10, Duration::from_secs(1)), client_ip)
+    ///         .bypass_if(|req: &Request<Body>| req.path() == "/health")
+    /// )]
+    /// ```
+    fn bypass_if<F>(self, predicate: F) -> Bypass<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&Req) -> bool + Send + Sync + 'static,
+    {
+        Bypass::new(self, predicate)
+    }
+
+    /// Only applies this layer to requests with one of `methods`, passing all other
+    /// requests straight to the inner service.
+    ///
+    /// ```rust,ignore
+    /// #[middleware(Auth::bearer(verify_token).only_methods(&[Method::POST]))]
+    /// ```
+    fn only_methods(self, methods: &[Method]) -> MethodFilter<Self>
+    where
+        Self: Sized,
+    {
+        MethodFilter::new(self, methods)
+    }
+}
+
+/// An abstraction over a middleware layer that needs to do some asynchronous work
+/// (like opening a connection pool or loading a key) before it can wrap a [`Service`].
+///
+/// Every [`Layer`] is also an `AsyncLayer`, so synchronous and asynchronous layers
+/// can be mixed in the same set of middleware.
+pub trait AsyncLayer<Req, Res>: Send + Sync + 'static {
+    /// Adds this layer to the inner service.
+    fn layer(
+        &self,
+        inner: BoxedService<Req, Res>,
+    ) -> Pin<Box<dyn Future<Output = BoxedService<Req, Res>> + Send + '_>>;
+}
+
+impl<L, Req, Res> AsyncLayer<Req, Res> for L
+where
+    L: Layer<Req, Res>,
+    Req: 'static,
+    Res: 'static,
+{
+    fn layer(
+        &self,
+        inner: BoxedService<Req, Res>,
+    ) -> Pin<Box<dyn Future<Output = BoxedService<Req, Res>> + Send + '_>> {
+        let service = Layer::layer(self, inner);
+        Box::pin(async move { service })
+    }
 }
 
 /// A type-erased service, which takes an HTTP request and returns a response.
@@ -15,6 +305,83 @@ impl<Req, Res> BoxedService<Req, Res> {
     pub fn new(service: impl Service<Req, Res> + Send + 'static) -> Self {
         Self(Box::new(service))
     }
+
+    /// Constructs a type-erased service from an async function that is given some
+    /// shared state along with each request.
+    ///
+    /// Each call gets its own clone of `state`, which the returned future can hold
+    /// on to for as long as it needs.
+    ///
+    /// ```rust,ignore
+    /// let visits = Arc::new(AtomicUsize::new(0));
+    /// let service = BoxedService::from_fn_with_state(visits, |visits, _req| async move {
+    ///     let count = visits.fetch_add(1, Ordering::SeqCst) + 1;
+    ///     Response::new(Body::from(format!("visit #{count}")))
+    /// });
+    /// ```
+    pub fn from_fn_with_state<S, F, Fut>(state: Arc<S>, mut f: F) -> Self
+    where
+        S: Send + Sync + 'static,
+        F: FnMut(Arc<S>, Req) -> Fut + Send + 'static,
+        Fut: Future<Output = Res> + Send + 'static,
+    {
+        service_fn(move |req| f(Arc::clone(&state), req))
+    }
+
+    /// Converts this service into a [`SharedService`], which can be cloned.
+    pub fn into_shared(self) -> SharedService<Req, Res> {
+        SharedService(Arc::new(Mutex::new(self)))
+    }
+}
+
+/// A type-erased service that can be cloned and handed to several tasks.
+///
+/// Every clone refers to the same inner service, which is kept behind a mutex. Calls to
+/// [`Service::run`] are serialized: the lock is held while the inner service creates
+/// its response future, but not while that future is awaited. This means the responses
+/// themselves are driven in parallel, so the inner service should not rely on one
+/// request finishing before the next one starts.
+pub struct SharedService<Req, Res>(Arc<Mutex<BoxedService<Req, Res>>>);
+
+impl<Req, Res> Clone for SharedService<Req, Res> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for SharedService<Req, Res> {
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .0
+            .run(req)
+    }
+
+    fn try_run(
+        &mut self,
+        req: Req,
+    ) -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send>>
+    where
+        Res: 'static,
+    {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .0
+            .try_run(req)
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .0
+            .poll_ready(cx)
+    }
 }
 
 /// A service converts an HTTP request into a response.
@@ -24,20 +391,174 @@ pub trait Service<Request, Response> {
         &mut self,
         req: Request,
     ) -> Pin<Box<dyn Future<Output = Response> + Send>>;
+
+    /// Converts a request into a response, or fails with an error.
+    ///
+    /// A service that can fail should override this to return its errors, and
+    /// implement [`Service::run`] by turning them into an error response. The server
+    /// integrations call `try_run`, and answer an error with a
+    /// `500 Internal Server Error`. By default, this calls [`Service::run`] and never
+    /// fails.
+    fn try_run(
+        &mut self,
+        req: Request,
+    ) -> Pin<Box<dyn Future<Output = Result<Response, ServerFnError>> + Send>>
+    where
+        Response: 'static,
+    {
+        let inner = self.run(req);
+        Box::pin(async move { Ok(inner.await) })
+    }
+
+    /// Checks whether the service is ready to accept another request.
+    ///
+    /// Services that wrap another service should forward this to it, so that
+    /// backpressure from the innermost service reaches the server. By default,
+    /// the service is always ready.
+    fn poll_ready(
+        &mut self,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Gives access to the path of a server function request, regardless of the
+/// framework-specific request type.
+///
+/// This allows generic [`Layer`]s to log or route on the path without reaching into
+/// the underlying request.
+pub trait RequestPath {
+    /// The path of the request, without the query string.
+    fn path(&self) -> &str;
+}
+
+/// Gives access to the HTTP method of a server function request, regardless of the
+/// framework-specific request type.
+pub trait RequestMethod {
+    /// The HTTP method of the request.
+    fn method(&self) -> Method;
+}
+
+/// Gives access to the headers of a server function request, regardless of the
+/// framework-specific request type.
+pub trait RequestHeaders {
+    /// The value of the header called `name`, if any and if it is valid UTF-8.
+    fn header(&self, name: &str) -> Option<&str>;
+}
+
+/// Gives access to the extensions of a server function request, regardless of the
+/// framework-specific request type.
+///
+/// The server function reads the values inserted here through
+/// [`RequestExtensions`](crate::request::RequestExtensions), for example as an
+/// [`Extension`](crate::request::Extension) argument.
+pub trait RequestExtensionsMut {
+    /// A clone of the extension of type `T`, if there is one.
+    fn extension<T>(&self) -> Option<T>
+    where
+        T: Clone + Send + Sync + 'static;
+
+    /// Inserts `value`, replacing any extension of the same type.
+    fn insert_extension<T>(&mut self, value: T)
+    where
+        T: Clone + Send + Sync + 'static;
+}
+
+/// Gives access to the status code of a server function response, regardless of the
+/// framework-specific response type.
+pub trait ResponseStatus {
+    /// The status code of the response.
+    ///
+    /// The status code is changed with
+    /// [`Res::set_status`](crate::response::Res::set_status).
+    fn status(&self) -> StatusCode;
+}
+
+/// Gives access to the headers of a server function response, regardless of the
+/// framework-specific response type.
+///
+/// Headers are changed with [`Res::insert_header`](crate::response::Res::insert_header),
+/// [`Res::append_header`](crate::response::Res::append_header) and
+/// [`Res::remove_header`](crate::response::Res::remove_header).
+pub trait ResponseHeaders {
+    /// The value of the header called `name`, if any and if it is valid UTF-8.
+    fn header(&self, name: &str) -> Option<&str>;
+}
+
+/// Compares two byte strings in time that only depends on their lengths.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
 }
 
 #[cfg(feature = "axum-no-default")]
 mod axum {
-    use super::{BoxedService, Service};
+    use super::{
+        BoxedService, RequestExtensionsMut, RequestHeaders, RequestMethod,
+        RequestPath, ResponseHeaders, ResponseStatus, Service,
+    };
     use crate::{response::Res, ServerFnError};
     use axum::body::Body;
-    use http::{Request, Response};
+    use http::{Method, Request, Response, StatusCode};
     use std::{
         fmt::{Debug, Display},
         future::Future,
         pin::Pin,
+        task::{Context, Poll},
     };
 
+    impl<B> RequestPath for Request<B> {
+        fn path(&self) -> &str {
+            self.uri().path()
+        }
+    }
+
+    impl<B> RequestMethod for Request<B> {
+        fn method(&self) -> Method {
+            Request::method(self).clone()
+        }
+    }
+
+    impl<B> RequestHeaders for Request<B> {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        }
+    }
+
+    impl<B> RequestExtensionsMut for Request<B> {
+        fn extension<T>(&self) -> Option<T>
+        where
+            T: Clone + Send + Sync + 'static,
+        {
+            self.extensions().get::<T>().cloned()
+        }
+
+        fn insert_extension<T>(&mut self, value: T)
+        where
+            T: Clone + Send + Sync + 'static,
+        {
+            self.extensions_mut().insert(value);
+        }
+    }
+
+    impl<B> ResponseStatus for Response<B> {
+        fn status(&self) -> StatusCode {
+            Response::status(self)
+        }
+    }
+
+    impl<B> ResponseHeaders for Response<B> {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        }
+    }
+
     impl<S> super::Service<Request<Body>, Response<Body>> for S
     where
         S: tower::Service<Request<Body>, Response = Response<Body>>,
@@ -48,7 +569,7 @@ mod axum {
             &mut self,
             req: Request<Body>,
         ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
-            let path = req.uri().path().to_string();
+            let path = req.path().to_string();
             let inner = self.call(req);
             Box::pin(async move {
                 inner.await.unwrap_or_else(|e| {
@@ -57,6 +578,26 @@ mod axum {
                 })
             })
         }
+
+        fn try_run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<
+            Box<
+                dyn Future<Output = Result<Response<Body>, ServerFnError>>
+                    + Send,
+            >,
+        > {
+            let inner = self.call(req);
+            Box::pin(async move { inner.await.map_err(ServerFnError::new) })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            tower::Service::poll_ready(self, cx).map_err(ServerFnError::new)
+        }
     }
 
     impl tower::Service<Request<Body>>
@@ -74,14 +615,13 @@ mod axum {
 
         fn poll_ready(
             &mut self,
-            _cx: &mut std::task::Context<'_>,
-        ) -> std::task::Poll<Result<(), Self::Error>> {
-            Ok(()).into()
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            self.0.poll_ready(cx)
         }
 
         fn call(&mut self, req: Request<Body>) -> Self::Future {
-            let inner = self.0.run(req);
-            Box::pin(async move { Ok(inner.await) })
+            self.0.try_run(req)
         }
     }
 
@@ -104,18 +644,115 @@ mod axum {
 
 #[cfg(feature = "actix")]
 mod actix {
+    use super::{
+        BoxedService, RequestExtensionsMut, RequestHeaders, RequestMethod,
+        RequestPath, ResponseHeaders, ResponseStatus,
+    };
     use crate::{
         request::actix::ActixRequest,
         response::{actix::ActixResponse, Res},
         ServerFnError,
     };
-    use actix_web::{HttpRequest, HttpResponse};
+    use actix_web::{
+        body::MessageBody,
+        dev::{ServiceRequest, ServiceResponse, Transform},
+        HttpMessage, HttpRequest, HttpResponse,
+    };
+    use send_wrapper::SendWrapper;
     use std::{
+        cell::RefCell,
         fmt::{Debug, Display},
         future::Future,
         pin::Pin,
+        task::{Context, Poll},
     };
 
+    impl RequestPath for HttpRequest {
+        fn path(&self) -> &str {
+            HttpRequest::path(self)
+        }
+    }
+
+    impl RequestPath for ActixRequest {
+        fn path(&self) -> &str {
+            ActixRequest::path(self)
+        }
+    }
+
+    // Actix is still on `http` 0.2, so methods and status codes are converted
+    // through their string and integer forms.
+    impl RequestMethod for HttpRequest {
+        fn method(&self) -> http::Method {
+            http::Method::from_bytes(
+                HttpRequest::method(self).as_str().as_bytes(),
+            )
+            .expect("http 0.2 and 1.0 accept the same methods")
+        }
+    }
+
+    impl RequestMethod for ActixRequest {
+        fn method(&self) -> http::Method {
+            RequestMethod::method(&self.0 .0)
+        }
+    }
+
+    impl RequestHeaders for HttpRequest {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        }
+    }
+
+    impl RequestHeaders for ActixRequest {
+        fn header(&self, name: &str) -> Option<&str> {
+            RequestHeaders::header(&self.0 .0, name)
+        }
+    }
+
+    impl RequestExtensionsMut for ActixRequest {
+        fn extension<T>(&self) -> Option<T>
+        where
+            T: Clone + Send + Sync + 'static,
+        {
+            self.request().extensions().get::<T>().cloned()
+        }
+
+        fn insert_extension<T>(&mut self, value: T)
+        where
+            T: Clone + Send + Sync + 'static,
+        {
+            self.request().extensions_mut().insert(value);
+        }
+    }
+
+    impl ResponseStatus for HttpResponse {
+        fn status(&self) -> http::StatusCode {
+            http::StatusCode::from_u16(HttpResponse::status(self).as_u16())
+                .expect("http 0.2 and 1.0 accept the same status codes")
+        }
+    }
+
+    impl ResponseStatus for ActixResponse {
+        fn status(&self) -> http::StatusCode {
+            ResponseStatus::status(&*self.0)
+        }
+    }
+
+    impl ResponseHeaders for HttpResponse {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        }
+    }
+
+    impl ResponseHeaders for ActixResponse {
+        fn header(&self, name: &str) -> Option<&str> {
+            ResponseHeaders::header(&*self.0, name)
+        }
+    }
+
     impl<S> super::Service<HttpRequest, HttpResponse> for S
     where
         S: actix_web::dev::Service<HttpRequest, Response = HttpResponse>,
@@ -126,7 +763,7 @@ mod actix {
             &mut self,
             req: HttpRequest,
         ) -> Pin<Box<dyn Future<Output = HttpResponse> + Send>> {
-            let path = req.uri().path().to_string();
+            let path = req.path().to_string();
             let inner = self.call(req);
             Box::pin(async move {
                 inner.await.unwrap_or_else(|e| {
@@ -135,6 +772,14 @@ mod actix {
                 })
             })
         }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            actix_web::dev::Service::poll_ready(self, cx)
+                .map_err(ServerFnError::new)
+        }
     }
 
     impl<S> super::Service<ActixRequest, ActixResponse> for S
@@ -147,7 +792,7 @@ mod actix {
             &mut self,
             req: ActixRequest,
         ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
-            let path = req.0 .0.uri().path().to_string();
+            let path = req.path().to_string();
             let inner = self.call(req.0.take().0);
             Box::pin(async move {
                 ActixResponse::from(inner.await.unwrap_or_else(|e| {
@@ -156,5 +801,693 @@ mod actix {
                 }))
             })
         }
+
+        fn try_run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<
+            Box<
+                dyn Future<Output = Result<ActixResponse, ServerFnError>>
+                    + Send,
+            >,
+        > {
+            let inner = self.call(req.0.take().0);
+            Box::pin(async move {
+                inner
+                    .await
+                    .map(ActixResponse::from)
+                    .map_err(ServerFnError::new)
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            actix_web::dev::Service::poll_ready(self, cx)
+                .map_err(ServerFnError::new)
+        }
+    }
+
+    /// Adapts an Actix middleware (anything that implements [`Transform`]) so that it can be
+    /// used as a [`Layer`](super::Layer) for server functions.
+    ///
+    /// ```rust,ignore
+    /// #[server]
+    /// #[middleware(ActixLayer::new(actix_web::middleware::Compress::default()))]
+    /// pub async fn my_server_fn() -> Result<String, ServerFnError> {
+    ///     Ok("this will be compressed".to_string())
+    /// }
+    /// ```
+    ///
+    /// ## Initialization
+    /// Actix builds middleware asynchronously with [`Transform::new_transform`], so this is
+    /// an [`AsyncLayer`](super::AsyncLayer) rather than a [`Layer`](super::Layer): the
+    /// `new_transform` future is awaited when the server function's service is built. If it
+    /// fails, the resulting service will respond to every request with an error response.
+    ///
+    /// ## Threads
+    /// Actix middleware is usually not `Send` (like `Logger`), so, like [`ActixRequest`], this
+    /// uses a [`SendWrapper`] internally. Each Actix worker builds its own copy of a server
+    /// function's middleware and only ever uses it on that worker's thread; moving the layer
+    /// or the services it builds to another thread will panic.
+    pub struct ActixLayer<T>(SendWrapper<T>);
+
+    impl<T> ActixLayer<T> {
+        /// Wraps the given Actix middleware.
+        pub fn new(transform: T) -> Self {
+            Self(SendWrapper::new(transform))
+        }
+    }
+
+    impl<T, B> super::AsyncLayer<ActixRequest, ActixResponse> for ActixLayer<T>
+    where
+        T: Transform<
+                ActixInnerService,
+                ServiceRequest,
+                Response = ServiceResponse<B>,
+            > + 'static,
+        T::Error: Display,
+        T::InitError: Debug,
+        B: MessageBody + 'static,
+    {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> Pin<
+            Box<
+                dyn Future<Output = BoxedService<ActixRequest, ActixResponse>>
+                    + Send
+                    + '_,
+            >,
+        > {
+            let transform =
+                self.0.new_transform(ActixInnerService(RefCell::new(inner)));
+            // see the note on threads above
+            Box::pin(SendWrapper::new(async move {
+                match transform.await {
+                    Ok(transform) => BoxedService::new(ActixTransformService(
+                        SendWrapper::new(transform),
+                    )),
+                    Err(e) => BoxedService::new(ActixInitError(format!(
+                        "could not initialize Actix middleware: {e:?}"
+                    ))),
+                }
+            }))
+        }
+    }
+
+    /// The inner server function service, as seen by an Actix middleware wrapped in [`ActixLayer`].
+    pub struct ActixInnerService(
+        RefCell<BoxedService<ActixRequest, ActixResponse>>,
+    );
+
+    impl actix_web::dev::Service<ServiceRequest> for ActixInnerService {
+        type Response = ServiceResponse;
+        type Error = actix_web::Error;
+        type Future =
+            Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+        fn poll_ready(
+            &self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            self.0
+                .borrow_mut()
+                .0
+                .poll_ready(cx)
+                .map_err(actix_web::error::ErrorInternalServerError)
+        }
+
+        fn call(&self, req: ServiceRequest) -> Self::Future {
+            let (req, payload) = req.into_parts();
+            let inner = self
+                .0
+                .borrow_mut()
+                .0
+                .try_run(ActixRequest::from((req.clone(), payload)));
+            Box::pin(async move {
+                let res = inner
+                    .await
+                    .map_err(actix_web::error::ErrorInternalServerError)?;
+                Ok(ServiceResponse::new(req, res.take()))
+            })
+        }
+    }
+
+    struct ActixTransformService<S>(SendWrapper<S>);
+
+    impl<S, B> super::Service<ActixRequest, ActixResponse>
+        for ActixTransformService<S>
+    where
+        S: actix_web::dev::Service<
+                ServiceRequest,
+                Response = ServiceResponse<B>,
+            > + 'static,
+        S::Error: Display,
+        B: MessageBody + 'static,
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let path = req.path().to_string();
+            let (req, payload) = req.take();
+            let inner = self
+                .0
+                .call(ServiceRequest::from_parts(req, payload.into_inner()));
+            // Actix is going to keep this on a single thread anyway so it's fine to wrap it
+            // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
+            Box::pin(SendWrapper::new(async move {
+                match inner.await {
+                    Ok(res) => ActixResponse::from(
+                        res.map_into_boxed_body().into_parts().1,
+                    ),
+                    Err(e) => {
+                        let err = ServerFnError::new(e);
+                        ActixResponse::error_response(&path, &err)
+                    }
+                }
+            }))
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.0.poll_ready(cx).map_err(ServerFnError::new)
+        }
+    }
+
+    struct ActixInitError(String);
+
+    impl super::Service<ActixRequest, ActixResponse> for ActixInitError {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let path = req.path().to_string();
+            let err = ServerFnError::new(&self.0);
+            Box::pin(async move { ActixResponse::error_response(&path, &err) })
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+pub use actix::{ActixInnerService, ActixLayer};
+
+#[cfg(test)]
+mod tests {
+    use super::{AsyncLayer, BoxedService, Layer, Service};
+    use crate::ServerFnTraitObj;
+    use http::Method;
+    use std::{future::Future, pin::Pin, sync::Arc};
+
+    struct Append(&'static str, BoxedService<String, String>);
+
+    impl Service<String, String> for Append {
+        fn run(
+            &mut self,
+            req: String,
+        ) -> Pin<Box<dyn Future<Output = String> + Send>> {
+            let suffix = self.0;
+            let inner = self.1 .0.run(req);
+            Box::pin(async move { format!("{}{suffix}", inner.await) })
+        }
+    }
+
+    struct SyncLayer;
+
+    impl Layer<String, String> for SyncLayer {
+        fn layer(
+            &self,
+            inner: BoxedService<String, String>,
+        ) -> BoxedService<String, String> {
+            BoxedService::new(Append(" sync", inner))
+        }
+    }
+
+    struct AsyncSetupLayer;
+
+    impl AsyncLayer<String, String> for AsyncSetupLayer {
+        fn layer(
+            &self,
+            inner: BoxedService<String, String>,
+        ) -> Pin<
+            Box<dyn Future<Output = BoxedService<String, String>> + Send + '_>,
+        > {
+            Box::pin(async move {
+                // stands in for some async setup, like connecting to a database
+                let suffix = async { " async" }.await;
+                BoxedService::new(Append(suffix, inner))
+            })
+        }
+    }
+
+    #[test]
+    fn mixes_sync_and_async_layers() {
+        let server_fn = ServerFnTraitObj::new(
+            "/api/echo",
+            Method::POST,
+            |req: String| Box::pin(async move { req }),
+            || {
+                vec![
+                    Arc::new(SyncLayer),
+                    Arc::new(AsyncSetupLayer),
+                    Arc::new(SyncLayer),
+                ]
+            },
+        );
+        let res = futures::executor::block_on(async move {
+            let mut service = server_fn.into_service().await;
+            service.0.run("hello".to_string()).await
+        });
+        assert_eq!(res, "hello sync async sync");
+    }
+
+    #[test]
+    fn shared_middleware_is_built_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static BUILT: AtomicUsize = AtomicUsize::new(0);
+
+        let server_fn = ServerFnTraitObj::new(
+            "/api/echo",
+            Method::POST,
+            |req: String| Box::pin(async move { req }),
+            || {
+                BUILT.fetch_add(1, Ordering::SeqCst);
+                vec![Arc::new(SyncLayer)]
+            },
+        )
+        .with_shared_middleware();
+        for _ in 0..3 {
+            let res = futures::executor::block_on(async {
+                let mut service = server_fn.clone().into_service().await;
+                service.0.run("hello".to_string()).await
+            });
+            assert_eq!(res, "hello sync");
+        }
+        assert_eq!(BUILT.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+    #[test]
+    fn built_service_layers_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static LAYERED: AtomicUsize = AtomicUsize::new(0);
+
+        struct CountingLayer;
+
+        impl AsyncLayer<String, String> for CountingLayer {
+            fn layer(
+                &self,
+                inner: BoxedService<String, String>,
+            ) -> Pin<
+                Box<
+                    dyn Future<Output = BoxedService<String, String>>
+                        + Send
+                        + '_,
+                >,
+            > {
+                LAYERED.fetch_add(1, Ordering::SeqCst);
+                Box::pin(
+                    async move { BoxedService::new(Append(" async", inner)) },
+                )
+            }
+        }
+
+        let server_fn = ServerFnTraitObj::new(
+            "/api/echo",
+            Method::POST,
+            |req: String| Box::pin(async move { req }),
+            || vec![Arc::new(CountingLayer)],
+        );
+        let built =
+            crate::build_once(server_fn, |service| service, |service| service);
+        for _ in 0..3 {
+            let mut service =
+                BoxedService::new(crate::BuiltService(built.clone()));
+            let res =
+                futures::executor::block_on(service.0.run("hello".to_string()));
+            assert_eq!(res, "hello async");
+        }
+        assert_eq!(LAYERED.load(Ordering::SeqCst), 1);
+    }
+
+    struct Count(usize);
+
+    impl Service<String, String> for Count {
+        fn run(
+            &mut self,
+            req: String,
+        ) -> Pin<Box<dyn Future<Output = String> + Send>> {
+            self.0 += 1;
+            let count = self.0;
+            Box::pin(async move { format!("{req} {count}") })
+        }
+    }
+
+    #[test]
+    fn shared_service_clones_drive_the_same_service() {
+        let mut first = BoxedService::new(Count(0)).into_shared();
+        let mut second = first.clone();
+        let (a, b) = futures::executor::block_on(async move {
+            futures::join!(
+                first.run("first".to_string()),
+                second.run("second".to_string())
+            )
+        });
+        assert_eq!(a, "first 1");
+        assert_eq!(b, "second 2");
+    }
+
+    #[test]
+    fn fn_with_state_shares_the_state_between_calls() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut service = BoxedService::from_fn_with_state(
+            Arc::clone(&calls),
+            |calls, req: String| async move {
+                let count = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                format!("{req} {count}")
+            },
+        );
+        let (a, b) = futures::executor::block_on(async move {
+            let a = service.0.run("first".to_string()).await;
+            let b = service.0.run("second".to_string()).await;
+            (a, b)
+        });
+        assert_eq!(a, "first 1");
+        assert_eq!(b, "second 2");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+    /// Forbids framing on any kind of response, and hides what powers it.
+    pub(super) struct DenyFraming;
+
+    #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+    struct DenyFramingService<Req, Res>(BoxedService<Req, Res>);
+
+    #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+    impl<Req, Res> Layer<Req, Res> for DenyFraming
+    where
+        Req: Send + 'static,
+        Res: crate::response::Res<crate::error::NoCustomError> + Send + 'static,
+    {
+        fn layer(
+            &self,
+            inner: BoxedService<Req, Res>,
+        ) -> BoxedService<Req, Res> {
+            BoxedService::new(DenyFramingService(inner))
+        }
+    }
+
+    #[cfg(any(feature = "axum-no-default", feature = "actix"))]
+    impl<Req, Res> Service<Req, Res> for DenyFramingService<Req, Res>
+    where
+        Res: crate::response::Res<crate::error::NoCustomError> + Send + 'static,
+    {
+        fn run(
+            &mut self,
+            req: Req,
+        ) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+            let inner = self.0 .0.run(req);
+            Box::pin(async move {
+                let mut res = inner.await;
+                res.insert_header("x-frame-options", "DENY");
+                res.remove_header("x-powered-by");
+                res
+            })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod axum_tests {
+    use super::{
+        service_fn, tests::DenyFraming, BoxedService, Layer, Service, Timeout,
+    };
+    use crate::{
+        axum::{handle_server_fn, register_explicit},
+        codec::Json,
+        response::Res,
+        testing::{json_request, server_fn_fixture},
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use futures::task::noop_waker_ref;
+    use http::{Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        task::{Context, Poll},
+        time::Duration,
+    };
+
+    struct NotReadyUntil(Arc<AtomicBool>);
+
+    impl Service<Request<Body>, Response<Body>> for NotReadyUntil {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            Box::pin(async move { Response::new(Body::empty()) })
+        }
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            if self.0.load(Ordering::SeqCst) {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn poll_ready_is_forwarded_through_layers() {
+        let ready = Arc::new(AtomicBool::new(false));
+        let mut service = Timeout::new(Duration::from_secs(1))
+            .layer(BoxedService::new(NotReadyUntil(ready.clone())));
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        assert!(tower::Service::poll_ready(&mut service, &mut cx).is_pending());
+        ready.store(true, Ordering::SeqCst);
+        assert!(matches!(
+            tower::Service::poll_ready(&mut service, &mut cx),
+            Poll::Ready(Ok(()))
+        ));
+    }
+
+    /// Rejects every request with an error from [`Service::try_run`].
+    struct Reject;
+
+    impl Service<Request<Body>, Response<Body>> for Reject {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let path = req.uri().path().to_string();
+            let inner = self.try_run(req);
+            Box::pin(async move {
+                inner
+                    .await
+                    .unwrap_or_else(|e| Response::error_response(&path, &e))
+            })
+        }
+
+        fn try_run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<
+            Box<
+                dyn Future<Output = Result<Response<Body>, ServerFnError>>
+                    + Send,
+            >,
+        > {
+            Box::pin(async move {
+                Err(ServerFnError::ServerError("rejected".to_string()))
+            })
+        }
+    }
+
+    struct RejectLayer;
+
+    impl Layer<Request<Body>, Response<Body>> for RejectLayer {
+        fn layer(
+            &self,
+            _inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(Reject)
+        }
+    }
+
+    #[test]
+    fn try_run_errors_reach_the_caller() {
+        let mut service = BoxedService::new(Reject);
+        let res = futures::executor::block_on(tower::Service::call(
+            &mut service,
+            Request::new(Body::empty()),
+        ));
+        assert_eq!(
+            res.unwrap_err(),
+            ServerFnError::ServerError("rejected".to_string())
+        );
+    }
+
+    server_fn_fixture! {
+        /// `#[middleware(RejectLayer)] async fn guarded()`
+        struct Guarded {}
+        path = "/api/guarded", input = Json, output = Json;
+
+        async fn run_body(self) -> Result<String, ServerFnError> {
+            Ok("unreachable".to_string())
+        }
+
+        fn middlewares(
+        ) -> Vec<Arc<dyn super::AsyncLayer<Request<Body>, Response<Body>>>>
+        {
+            vec![Arc::new(RejectLayer)]
+        }
+    }
+
+    #[tokio::test]
+    async fn handler_answers_try_run_errors_with_500() {
+        register_explicit::<Guarded>();
+        let res = handle_server_fn(json_request(Guarded::PATH, "{}")).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert!(String::from_utf8_lossy(&body).contains("rejected"));
+    }
+
+    #[tokio::test]
+    async fn generic_layer_sets_response_headers() {
+        let mut service =
+            DenyFraming.layer(service_fn(|_req: Request<Body>| async move {
+                Response::builder()
+                    .header("x-powered-by", "server_fn")
+                    .body(Body::empty())
+                    .unwrap()
+            }));
+        let req = Request::get("/api/page").body(Body::empty()).unwrap();
+        let res = service.0.run(req).await;
+        assert_eq!(res.headers()["x-frame-options"], "DENY");
+        assert!(!res.headers().contains_key("x-powered-by"));
+    }
+}
+
+#[cfg(all(test, feature = "actix"))]
+mod actix_tests {
+    use super::{
+        service_fn, tests::DenyFraming, ActixInnerService, ActixLayer,
+        AsyncLayer, BoxedService, Layer, Service,
+    };
+    use crate::{request::actix::ActixRequest, response::actix::ActixResponse};
+    use actix_web::{
+        body::MessageBody,
+        dev::{ServiceRequest, Transform},
+        http::header::{ACCEPT_ENCODING, CONTENT_ENCODING},
+        middleware::Compress,
+        test::TestRequest,
+        HttpResponse,
+    };
+    use std::{future::Future, pin::Pin, sync::Arc};
+
+    struct Hello;
+
+    impl Service<ActixRequest, ActixResponse> for Hello {
+        fn run(
+            &mut self,
+            _req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let body = "hello, world! ".repeat(100);
+            Box::pin(async move {
+                ActixResponse::from(HttpResponse::Ok().body(body))
+            })
+        }
+    }
+
+    /// [`Compress`], but its `new_transform` future is not ready on the first poll.
+    struct SlowCompress;
+
+    impl Transform<ActixInnerService, ServiceRequest> for SlowCompress {
+        type Response = <Compress as Transform<
+            ActixInnerService,
+            ServiceRequest,
+        >>::Response;
+        type Error = actix_web::Error;
+        type Transform = <Compress as Transform<
+            ActixInnerService,
+            ServiceRequest,
+        >>::Transform;
+        type InitError = ();
+        type Future =
+            Pin<Box<dyn Future<Output = Result<Self::Transform, ()>>>>;
+
+        fn new_transform(&self, service: ActixInnerService) -> Self::Future {
+            let transform = Compress::default().new_transform(service);
+            Box::pin(async move {
+                actix_web::rt::task::yield_now().await;
+                transform.await
+            })
+        }
+    }
+
+    #[test]
+    fn compress_can_be_used_as_layer() {
+        let _layer: Arc<dyn AsyncLayer<ActixRequest, ActixResponse>> =
+            Arc::new(ActixLayer::new(Compress::default()));
+    }
+
+    #[actix_web::test]
+    async fn compress_layer_compresses_response() {
+        let layer = ActixLayer::new(Compress::default());
+        let mut service = layer.layer(BoxedService::new(Hello)).await;
+        let req = TestRequest::default()
+            .insert_header((ACCEPT_ENCODING, "gzip"))
+            .to_http_parts();
+        let res = service.0.run(ActixRequest::from(req)).await.take();
+        assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+        let body = res.into_body().try_into_bytes().unwrap_or_default();
+        assert!(body.len() < 1400);
+    }
+
+    #[actix_web::test]
+    async fn layer_waits_for_slow_transform() {
+        let layer = ActixLayer::new(SlowCompress);
+        let mut service = layer.layer(BoxedService::new(Hello)).await;
+        let req = TestRequest::default()
+            .insert_header((ACCEPT_ENCODING, "gzip"))
+            .to_http_parts();
+        let res = service.0.run(ActixRequest::from(req)).await.take();
+        assert!(res.status().is_success());
+        assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+    }
+
+    #[actix_web::test]
+    async fn generic_layer_sets_response_headers() {
+        let inner = service_fn(|_req: ActixRequest| async move {
+            ActixResponse::from(
+                HttpResponse::Ok()
+                    .insert_header(("x-powered-by", "server_fn"))
+                    .finish(),
+            )
+        });
+        let mut service = Layer::layer(&DenyFraming, inner);
+        let req = TestRequest::default().to_http_parts();
+        let res = service.0.run(ActixRequest::from(req)).await.take();
+        assert_eq!(res.headers().get("x-frame-options").unwrap(), "DENY");
+        assert!(!res.headers().contains_key("x-powered-by"));
     }
 }
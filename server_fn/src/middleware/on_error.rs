@@ -0,0 +1,177 @@
+use super::{
+    BoxedService, Layer, RequestPath, ResponseHeaders, ResponseStatus, Service,
+};
+use crate::{error::SERVER_FN_ERROR_HEADER, ServerFnError};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// A middleware [`Layer`] that calls a function with the path and response of every
+/// request that fails, and leaves successful responses untouched.
+///
+/// A response counts as a failure if it has a `5xx` status, or if it carries a
+/// [`ServerFnError`], which is told by its `serverfnerror` header. This covers the
+/// errors returned by the server function, as well as those that other layers answer
+/// with a different status, like a `401 Unauthorized` from [`Auth`](super::Auth).
+///
+/// The function can change the response, like adding a diagnostic header, or just
+/// look at it to report the failure.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(OnError::new(|path: &str, res: &mut Response<Body>| {
+///     alerts::report(path, res.status());
+///     res.headers_mut().insert("x-support-contact", SUPPORT_EMAIL.parse().unwrap());
+/// }))]
+/// pub async fn place_order(order: Order) -> Result<OrderId, ServerFnError> {
+///     // ...
+/// }
+/// ```
+pub struct OnError<F> {
+    f: Arc<F>,
+}
+
+impl<F> OnError<F> {
+    /// Creates a new layer that calls `f` for failed responses.
+    pub fn new(f: F) -> Self {
+        Self { f: Arc::new(f) }
+    }
+}
+
+impl<F> Clone for OnError<F> {
+    fn clone(&self) -> Self {
+        Self {
+            f: Arc::clone(&self.f),
+        }
+    }
+}
+
+/// Whether `res` is a failed response, as described in [`OnError`].
+fn is_error<Res>(res: &Res) -> bool
+where
+    Res: ResponseHeaders + ResponseStatus,
+{
+    res.status().is_server_error()
+        || res.header(SERVER_FN_ERROR_HEADER).is_some()
+}
+
+struct OnErrorService<F, Req, Res> {
+    f: Arc<F>,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<F, Req, Res> Layer<Req, Res> for OnError<F>
+where
+    F: Fn(&str, &mut Res) + Send + Sync + 'static,
+    Req: RequestPath + Send + 'static,
+    Res: ResponseHeaders + ResponseStatus + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(OnErrorService {
+            f: Arc::clone(&self.f),
+            inner,
+        })
+    }
+}
+
+impl<F, Req, Res> Service<Req, Res> for OnErrorService<F, Req, Res>
+where
+    F: Fn(&str, &mut Res) + Send + Sync + 'static,
+    Req: RequestPath,
+    Res: ResponseHeaders + ResponseStatus + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let path = req.path().to_string();
+        let f = Arc::clone(&self.f);
+        let inner = self.inner.0.run(req);
+        Box::pin(async move {
+            let mut res = inner.await;
+            if is_error(&res) {
+                f(&path, &mut res);
+            }
+            res
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::OnError;
+    use crate::{
+        middleware::{service_fn, BoxedService, Layer},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{HeaderValue, Request, Response, StatusCode};
+    use std::sync::{Arc, Mutex};
+
+    /// Answers with `status`, either as a plain response or as a server function
+    /// error.
+    fn respond(
+        status: StatusCode,
+        error: bool,
+    ) -> BoxedService<Request<Body>, Response<Body>> {
+        service_fn(move |_req: Request<Body>| async move {
+            let mut res = if error {
+                let err: ServerFnError =
+                    ServerFnError::ServerError("failed".into());
+                Response::<Body>::error_response("/api/place_order", &err)
+            } else {
+                Response::new(Body::empty())
+            };
+            *res.status_mut() = status;
+            res
+        })
+    }
+
+    #[tokio::test]
+    async fn runs_only_for_failed_responses() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let layer = OnError::new({
+            let seen = Arc::clone(&seen);
+            move |path: &str, res: &mut Response<Body>| {
+                seen.lock().unwrap().push((path.to_string(), res.status()));
+                res.headers_mut()
+                    .insert("x-diagnostic", HeaderValue::from_static("yes"));
+            }
+        });
+
+        let cases = [
+            (StatusCode::OK, false, false),
+            (StatusCode::INTERNAL_SERVER_ERROR, true, true),
+            (StatusCode::BAD_GATEWAY, false, true),
+            (StatusCode::UNAUTHORIZED, true, true),
+            (StatusCode::NOT_FOUND, false, false),
+        ];
+        for (status, error, fires) in cases {
+            let mut service = layer.layer(respond(status, error));
+            let req = Request::post("/api/place_order")
+                .body(Body::empty())
+                .unwrap();
+            let res = service.0.run(req).await;
+            assert_eq!(res.status(), status);
+            assert_eq!(res.headers().contains_key("x-diagnostic"), fires);
+        }
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::UNAUTHORIZED
+            ]
+            .map(|status| ("/api/place_order".to_string(), status))
+        );
+    }
+}
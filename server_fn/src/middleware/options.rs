@@ -0,0 +1,289 @@
+use super::BoxedService;
+use crate::ServerFnError;
+use http::Method;
+
+/// Answers `OPTIONS` requests to a server function with `204 No Content` and an
+/// `Allow` header listing the methods it supports, instead of running it.
+///
+/// Requests with any other method the server function does not support, like a
+/// `GET` request to a server function that expects `POST`, are answered with
+/// `405 Method Not Allowed` and the same `Allow` header, rather than failing to
+/// read their arguments. The exception is a `POST` request with URL-encoded
+/// arguments to a server function that expects `GET`, which is how a client sends
+/// arguments that would make the URL too long, as set with
+/// [`ClientConfig::with_max_url_length`](crate::client::ClientConfig::with_max_url_length).
+///
+/// This wraps the server function itself, beneath its middleware, so that layers like
+/// [`Cors`](super::Cors) still see the request and can answer preflight requests on
+/// their own. It is added to every registered server function by
+/// `get_server_fn_service`, with the method it was registered with.
+pub(crate) struct AnswerOptions {
+    method: Method,
+    allow: String,
+}
+
+impl AnswerOptions {
+    /// Creates a new layer for a server function that expects `method`.
+    pub(crate) fn new(method: &Method) -> Self {
+        let allow = if method == Method::GET {
+            // `GET` server functions also answer `HEAD` requests
+            "GET, HEAD, OPTIONS".to_string()
+        } else {
+            format!("{method}, OPTIONS")
+        };
+        Self {
+            method: method.clone(),
+            allow,
+        }
+    }
+
+    /// Whether a request with the `requested` method and `content_type` is passed on
+    /// to a server function that expects `method`.
+    fn allows(
+        method: &Method,
+        requested: &str,
+        content_type: Option<&str>,
+    ) -> bool {
+        let url_encoded = content_type.is_some_and(|content_type| {
+            content_type.starts_with("application/x-www-form-urlencoded")
+        });
+        requested == method.as_str()
+            || (method == Method::GET
+                && (requested == Method::HEAD.as_str()
+                    || (requested == Method::POST.as_str() && url_encoded)))
+    }
+
+    fn error() -> ServerFnError {
+        ServerFnError::ServerError("method not allowed".into())
+    }
+}
+
+struct AnswerOptionsService<Req, Res> {
+    method: Method,
+    allow: String,
+    inner: BoxedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{AnswerOptions, AnswerOptionsService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, HeaderValue, Method, Request, Response, StatusCode};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for AnswerOptions {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(AnswerOptionsService {
+                method: self.method.clone(),
+                allow: self.allow.clone(),
+                inner,
+            })
+        }
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for AnswerOptionsService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let mut res = if req.method() == Method::OPTIONS {
+                let mut res = Response::new(Body::empty());
+                *res.status_mut() = StatusCode::NO_CONTENT;
+                res
+            } else if AnswerOptions::allows(
+                &self.method,
+                req.method().as_str(),
+                req.headers()
+                    .get(header::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok()),
+            ) {
+                return self.inner.0.run(req);
+            } else {
+                let mut res = Response::error_response(
+                    req.uri().path(),
+                    &AnswerOptions::error(),
+                );
+                *res.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
+                res
+            };
+            if let Ok(allow) = HeaderValue::from_str(&self.allow) {
+                res.headers_mut().insert(header::ALLOW, allow);
+            }
+            Box::pin(async move { res })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{AnswerOptions, AnswerOptionsService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{
+        http::{header, Method, StatusCode},
+        HttpResponse,
+    };
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for AnswerOptions {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(AnswerOptionsService {
+                method: self.method.clone(),
+                allow: self.allow.clone(),
+                inner,
+            })
+        }
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for AnswerOptionsService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let method = req.request().method();
+            let mut res = if method == Method::OPTIONS {
+                HttpResponse::NoContent().finish()
+            } else if AnswerOptions::allows(
+                &self.method,
+                method.as_str(),
+                req.request()
+                    .headers()
+                    .get(header::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok()),
+            ) {
+                return self.inner.0.run(req);
+            } else {
+                let mut res = ActixResponse::error_response(
+                    req.request().path(),
+                    &AnswerOptions::error(),
+                )
+                .take();
+                *res.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
+                res
+            };
+            if let Ok(allow) = header::HeaderValue::from_str(&self.allow) {
+                res.headers_mut().insert(header::ALLOW, allow);
+            }
+            let res = ActixResponse::from(res);
+            Box::pin(async move { res })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default", feature = "url"))]
+mod tests {
+    use crate::{
+        axum::{handle_server_fn, register_explicit},
+        codec::{GetUrl, Json},
+        testing::{request, server_fn_fixture, TestResponse},
+        ServerFn, ServerFnError,
+    };
+    use http::{Method, StatusCode};
+
+    server_fn_fixture! {
+        /// `async fn create_post(title: String)`
+        struct CreatePost { title: String }
+        path = "/api/create_post_options", input = Json, output = Json;
+
+        async fn run_body(self) -> Result<(), ServerFnError> {
+            panic!("only POST requests should run the server function");
+        }
+    }
+
+    server_fn_fixture! {
+        /// `#[server(input = GetUrl)] async fn list_posts()`
+        struct ListPosts {}
+        path = "/api/list_posts_options", input = GetUrl, output = Json;
+
+        async fn run_body(self) -> Result<Vec<String>, ServerFnError> {
+            Ok(Vec::new())
+        }
+    }
+
+    async fn call(method: Method, path: &str) -> TestResponse {
+        TestResponse::read(handle_server_fn(request(method, path)).await).await
+    }
+
+    #[tokio::test]
+    async fn lists_methods_of_post_function() {
+        register_explicit::<CreatePost>();
+        call(Method::OPTIONS, CreatePost::PATH)
+            .await
+            .assert_status(StatusCode::NO_CONTENT)
+            .assert_header("allow", "POST, OPTIONS");
+    }
+
+    #[tokio::test]
+    async fn lists_methods_of_get_function() {
+        register_explicit::<ListPosts>();
+        call(Method::OPTIONS, ListPosts::PATH)
+            .await
+            .assert_status(StatusCode::NO_CONTENT)
+            .assert_header("allow", "GET, HEAD, OPTIONS");
+    }
+
+    #[tokio::test]
+    async fn rejects_get_on_post_function() {
+        register_explicit::<CreatePost>();
+        call(Method::GET, CreatePost::PATH)
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED)
+            .assert_header("allow", "POST, OPTIONS")
+            .assert_body("ServerError|method not allowed");
+    }
+
+    #[tokio::test]
+    async fn rejects_post_on_get_function() {
+        register_explicit::<ListPosts>();
+        call(Method::POST, ListPosts::PATH)
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED)
+            .assert_header("allow", "GET, HEAD, OPTIONS");
+
+        // while `HEAD` is answered like `GET`
+        call(Method::HEAD, ListPosts::PATH)
+            .await
+            .assert_status(StatusCode::OK);
+    }
+}
@@ -0,0 +1,291 @@
+use super::BoxedService;
+use std::sync::Arc;
+
+/// A middleware [`Layer`](super::Layer) that rewrites the path of each request before
+/// it reaches the inner service.
+///
+/// The query string is kept as it is. This is useful when server functions are mounted
+/// under a prefix that should not be visible to the rest of the middleware stack, or to
+/// error responses.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(PathRewrite::strip_prefix("/app"))]
+/// pub async fn load_user(id: u32) -> Result<User, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Clone)]
+pub struct PathRewrite {
+    rewrite: Arc<dyn Fn(&str) -> String + Send + Sync>,
+}
+
+impl PathRewrite {
+    /// Creates a new layer that replaces each request path with `rewrite(path)`.
+    pub fn new(
+        rewrite: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            rewrite: Arc::new(rewrite),
+        }
+    }
+
+    /// Creates a new layer that removes `prefix` from the start of each request path.
+    ///
+    /// Paths that don't start with `prefix` are left unchanged.
+    pub fn strip_prefix(prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        Self::new(move |path| match path.strip_prefix(prefix.as_str()) {
+            Some("") => "/".to_string(),
+            Some(rest) if rest.starts_with('/') => rest.to_string(),
+            _ => path.to_string(),
+        })
+    }
+
+    fn rewrite(&self, path: &str) -> String {
+        (self.rewrite)(path)
+    }
+}
+
+impl std::fmt::Debug for PathRewrite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PathRewrite").finish_non_exhaustive()
+    }
+}
+
+struct PathRewriteService<Req, Res> {
+    config: PathRewrite,
+    inner: BoxedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{PathRewrite, PathRewriteService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{uri::PathAndQuery, Request, Response};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for PathRewrite {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(PathRewriteService {
+                config: self.clone(),
+                inner,
+            })
+        }
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for PathRewriteService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            mut req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let path = self.config.rewrite(req.uri().path());
+            let path_and_query = match req.uri().query() {
+                Some(query) => format!("{path}?{query}"),
+                None => path,
+            };
+            let mut parts = req.uri().clone().into_parts();
+            if let Ok(path_and_query) = PathAndQuery::try_from(path_and_query) {
+                parts.path_and_query = Some(path_and_query);
+                if let Ok(uri) = parts.try_into() {
+                    *req.uri_mut() = uri;
+                }
+            }
+            self.inner.0.run(req)
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{PathRewrite, PathRewriteService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::actix::ActixResponse,
+        ServerFnError,
+    };
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for PathRewrite {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(PathRewriteService {
+                config: self.clone(),
+                inner,
+            })
+        }
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for PathRewriteService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            // the query string is read from the original URI, so it is kept
+            let path = self.config.rewrite(req.path());
+            self.inner.0.run(req.with_path(Some(path)))
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.0.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::PathRewrite;
+    use crate::{
+        error::{ServerFnErrorSerde, SERVER_FN_ERROR_HEADER},
+        middleware::{BoxedService, Layer, RequestPath, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Request, Response};
+    use http_body_util::BodyExt;
+    use std::{future::Future, pin::Pin};
+
+    /// Responds with the URI it was called with.
+    struct EchoUri;
+
+    impl Service<Request<Body>, Response<Body>> for EchoUri {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let uri = req.uri().to_string();
+            Box::pin(async move { Response::new(Body::from(uri)) })
+        }
+    }
+
+    struct Failing;
+
+    impl Service<Request<Body>, Response<Body>> for Failing {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let path = req.path().to_string();
+            Box::pin(async move {
+                let err: ServerFnError =
+                    ServerFnError::ServerError("not found".into());
+                Response::<Body>::error_response(&path, &err)
+            })
+        }
+    }
+
+    async fn body(res: Response<Body>) -> String {
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn strips_prefix_and_keeps_query() {
+        let mut service =
+            PathRewrite::strip_prefix("/app").layer(BoxedService::new(EchoUri));
+        let req = Request::get("/app/api/user?id=1")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(body(service.0.run(req).await).await, "/api/user?id=1");
+
+        // paths that only share a prefix in name are left alone
+        let req = Request::get("/application/api/user")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(
+            body(service.0.run(req).await).await,
+            "/application/api/user"
+        );
+    }
+
+    #[tokio::test]
+    async fn error_responses_use_the_rewritten_path() {
+        let mut service =
+            PathRewrite::strip_prefix("/app").layer(BoxedService::new(Failing));
+        let req = Request::post("/app/api/user").body(Body::empty()).unwrap();
+        let res = service.0.run(req).await;
+        assert_eq!(res.headers()[SERVER_FN_ERROR_HEADER], "/api/user");
+        let err: ServerFnError = ServerFnError::de(&body(res).await);
+        assert_eq!(err, ServerFnError::ServerError("not found".into()));
+    }
+}
+
+#[cfg(all(test, feature = "actix"))]
+mod actix_tests {
+    use super::PathRewrite;
+    use crate::{
+        error::SERVER_FN_ERROR_HEADER,
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::test::TestRequest;
+    use std::{future::Future, pin::Pin};
+
+    struct Failing;
+
+    impl Service<ActixRequest, ActixResponse> for Failing {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let path = req.path().to_string();
+            let query = req.request().query_string().to_string();
+            Box::pin(async move {
+                let err: ServerFnError = ServerFnError::ServerError(query);
+                ActixResponse::error_response(&path, &err)
+            })
+        }
+    }
+
+    #[actix_web::test]
+    async fn error_responses_use_the_rewritten_path() {
+        let mut service =
+            PathRewrite::strip_prefix("/app").layer(BoxedService::new(Failing));
+        let req = TestRequest::get()
+            .uri("/app/api/user?id=1")
+            .to_srv_request()
+            .into_parts();
+        let res = service.0.run(ActixRequest::from(req)).await.take();
+        assert_eq!(
+            res.headers().get(SERVER_FN_ERROR_HEADER).unwrap(),
+            "/api/user"
+        );
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "ServerError|id=1");
+    }
+}
@@ -0,0 +1,146 @@
+use super::{
+    BoxedService, Layer, RequestExtensionsMut, Service, SharedService,
+};
+use crate::ServerFnError;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A middleware [`Layer`] that makes a shared value, like a database pool, available
+/// to server functions.
+///
+/// The value is cloned into the extensions of every request, where the server
+/// function can take it as an [`Extension<T>`](crate::request::Extension) argument.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(Provide::new(POOL.clone()))]
+/// pub async fn list_posts(
+///     Extension(pool): Extension<PgPool>,
+/// ) -> Result<Vec<Post>, ServerFnError> {
+///     let posts = sqlx::query_as("SELECT * FROM posts")
+///         .fetch_all(&pool)
+///         .await?;
+///     Ok(posts)
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Provide<T> {
+    value: T,
+}
+
+impl<T> Provide<T> {
+    /// Creates a new layer that provides `value` to every request.
+    pub fn new(value: T) -> Self {
+        Self { value }
+    }
+}
+
+struct ProvideService<T, Req, Res> {
+    value: T,
+    inner: SharedService<Req, Res>,
+}
+
+impl<T, Req, Res> Layer<Req, Res> for Provide<T>
+where
+    T: Clone + Send + Sync + 'static,
+    Req: RequestExtensionsMut + Send + 'static,
+    Res: Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(ProvideService {
+            value: self.value.clone(),
+            inner: inner.into_shared(),
+        })
+    }
+}
+
+impl<T, Req, Res> Service<Req, Res> for ProvideService<T, Req, Res>
+where
+    T: Clone + Send + Sync + 'static,
+    Req: RequestExtensionsMut + Send + 'static,
+    Res: Send + 'static,
+{
+    fn run(
+        &mut self,
+        mut req: Req,
+    ) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        req.insert_extension(self.value.clone());
+        self.inner.run(req)
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Provide;
+    use crate::{
+        codec::Json,
+        middleware::{service_fn, Layer},
+        request::Extension,
+        testing::{call, json_request, server_fn_fixture},
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Request, StatusCode};
+    use std::sync::{Arc, Mutex};
+
+    /// A stand-in for a connection pool, shared between its clones.
+    #[derive(Clone, Default)]
+    struct Pool(Arc<Mutex<Vec<String>>>);
+
+    impl Pool {
+        fn insert(&self, title: String) -> usize {
+            let mut rows = self.0.lock().unwrap();
+            rows.push(title);
+            rows.len()
+        }
+    }
+
+    server_fn_fixture! {
+        /// `async fn add_post(title: String, Extension(pool): Extension<Pool>)`
+        struct AddPost { title: String }
+        path = "/api/add_post", input = Json, output = Json;
+
+        async fn run_body(self) -> Result<usize, ServerFnError> {
+            let Extension(pool) = match Extension::<Pool>::extract() {
+                Ok(pool) => pool,
+                Err(e) => return Err(e),
+            };
+            Ok(pool.insert(self.title))
+        }
+    }
+
+    fn request(title: &str) -> Request<Body> {
+        json_request(AddPost::PATH, format!(r#"{{"title":"{title}"}}"#))
+    }
+
+    #[tokio::test]
+    async fn handler_uses_provided_pool() {
+        let pool = Pool::default();
+        let mut service = Provide::new(pool.clone())
+            .layer(service_fn(AddPost::run_on_server));
+
+        for (title, rows) in [("first", "1"), ("second", "2")] {
+            call(&mut service, request(title))
+                .await
+                .assert_status(StatusCode::OK)
+                .assert_body(rows);
+        }
+        assert_eq!(*pool.0.lock().unwrap(), ["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn missing_pool_is_an_error() {
+        let res = AddPost::run_on_server(request("first")).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,291 @@
+use super::{BoxedService, Layer, RequestPath, Service, SharedService};
+use crate::{error::NoCustomError, ServerFnError};
+use dashmap::DashMap;
+use http::{header, StatusCode};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// How many requests a single key may make.
+///
+/// Each key has a bucket that holds up to `burst` tokens, and gets a new token every
+/// `period`. Every request takes one token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    /// The maximum number of tokens in a bucket.
+    pub burst: u32,
+    /// How long it takes to refill a single token.
+    pub period: Duration,
+}
+
+impl Quota {
+    /// Creates a new quota of `burst` requests, refilling one token every `period`.
+    pub fn new(burst: u32, period: Duration) -> Self {
+        Self { burst, period }
+    }
+}
+
+/// Storage for the token buckets used by [`RateLimit`].
+///
+/// The default [`InMemoryStore`] keeps buckets in memory. Implement this trait to share
+/// buckets between several servers, for example by keeping them in Redis.
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// Takes a token from the bucket for `key`.
+    ///
+    /// Returns how long the caller has to wait for the next token if the bucket is empty.
+    fn take<'a>(
+        &'a self,
+        key: &'a str,
+        quota: Quota,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Duration>> + Send + 'a>>;
+}
+
+/// A [`RateLimitStore`] that keeps token buckets in memory.
+#[derive(Debug, Default)]
+pub struct InMemoryStore {
+    buckets: DashMap<String, Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl InMemoryStore {
+    fn take_now(&self, key: &str, quota: Quota) -> Result<(), Duration> {
+        let now = Instant::now();
+        let period = quota.period.as_secs_f64();
+        let burst = f64::from(quota.burst);
+        let mut bucket =
+            self.buckets.entry(key.to_string()).or_insert(Bucket {
+                tokens: burst,
+                updated: now,
+            });
+        let refilled = if period > 0.0 {
+            (now - bucket.updated).as_secs_f64() / period
+        } else {
+            burst
+        };
+        bucket.tokens = (bucket.tokens + refilled).min(burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) * period))
+        }
+    }
+}
+
+impl RateLimitStore for InMemoryStore {
+    fn take<'a>(
+        &'a self,
+        key: &'a str,
+        quota: Quota,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Duration>> + Send + 'a>> {
+        let res = self.take_now(key, quota);
+        Box::pin(async move { res })
+    }
+}
+
+/// A middleware [`Layer`] that limits how often each client may call a server function.
+///
+/// Clients are told apart by a key, which is extracted from each request by a
+/// user-provided function, like the client IP from `X-Forwarded-For` or an API key
+/// header. Requests over the [`Quota`] are rejected with `429 Too Many Requests` and a
+/// `Retry-After` header.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(RateLimit::new(
+///     Quota::new(10, Duration::from_secs(1)),
+///     |req: &Request<Body>| client_ip(req),
+/// ))]
+/// pub async fn send_message(text: String) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+/// ```
+pub struct RateLimit<F, S = InMemoryStore> {
+    quota: Quota,
+    key: Arc<F>,
+    store: Arc<S>,
+}
+
+impl<F> RateLimit<F> {
+    /// Creates a new rate limit, keeping buckets in memory.
+    pub fn new(quota: Quota, key: F) -> Self {
+        Self {
+            quota,
+            key: Arc::new(key),
+            store: Arc::default(),
+        }
+    }
+}
+
+impl<F, S> RateLimit<F, S> {
+    /// Uses the given store for token buckets.
+    pub fn with_store<S2>(self, store: S2) -> RateLimit<F, S2> {
+        RateLimit {
+            quota: self.quota,
+            key: self.key,
+            store: Arc::new(store),
+        }
+    }
+
+    fn error() -> ServerFnError {
+        ServerFnError::ServerError("too many requests".into())
+    }
+}
+
+impl<F, S> Clone for RateLimit<F, S> {
+    fn clone(&self) -> Self {
+        Self {
+            quota: self.quota,
+            key: Arc::clone(&self.key),
+            store: Arc::clone(&self.store),
+        }
+    }
+}
+
+struct RateLimitService<F, S, Req, Res> {
+    layer: RateLimit<F, S>,
+    inner: SharedService<Req, Res>,
+}
+
+impl<F, S, Req, Res> Layer<Req, Res> for RateLimit<F, S>
+where
+    F: Fn(&Req) -> String + Send + Sync + 'static,
+    S: RateLimitStore,
+    Req: RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(RateLimitService {
+            layer: self.clone(),
+            inner: inner.into_shared(),
+        })
+    }
+}
+
+impl<F, S, Req, Res> Service<Req, Res> for RateLimitService<F, S, Req, Res>
+where
+    F: Fn(&Req) -> String + Send + Sync + 'static,
+    S: RateLimitStore,
+    Req: RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let key = (self.layer.key)(&req);
+        let quota = self.layer.quota;
+        let store = Arc::clone(&self.layer.store);
+        let mut inner = self.inner.clone();
+        Box::pin(async move {
+            match store.take(&key, quota).await {
+                Ok(()) => inner.run(req).await,
+                Err(wait) => {
+                    let mut res = Res::error_response(
+                        req.path(),
+                        &RateLimit::<F, S>::error(),
+                    );
+                    res.set_status(StatusCode::TOO_MANY_REQUESTS);
+                    let secs = wait.as_secs_f64().ceil() as u64;
+                    res.insert_header(
+                        header::RETRY_AFTER.as_str(),
+                        &secs.to_string(),
+                    );
+                    res
+                }
+            }
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{Quota, RateLimit};
+    use crate::middleware::{BoxedService, Layer, Service};
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use std::{future::Future, pin::Pin, time::Duration};
+
+    struct Accept;
+
+    impl Service<Request<Body>, Response<Body>> for Accept {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            Box::pin(async move { Response::new(Body::empty()) })
+        }
+    }
+
+    fn by_api_key(req: &Request<Body>) -> String {
+        req.headers()
+            .get("x-api-key")
+            .and_then(|key| key.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    async fn call(
+        layer: &RateLimit<fn(&Request<Body>) -> String>,
+        key: &str,
+    ) -> Response<Body> {
+        let req = Request::post("/api/send")
+            .header("x-api-key", key)
+            .body(Body::empty())
+            .unwrap();
+        layer.layer(BoxedService::new(Accept)).0.run(req).await
+    }
+
+    fn layer() -> RateLimit<fn(&Request<Body>) -> String> {
+        RateLimit::new(Quota::new(3, Duration::from_secs(10)), by_api_key)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn throttles_after_burst() {
+        let layer = layer();
+        for _ in 0..3 {
+            assert_eq!(call(&layer, "a").await.status(), StatusCode::OK);
+        }
+        let res = call(&layer, "a").await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers()[header::RETRY_AFTER], "10");
+
+        // other keys have their own bucket
+        assert_eq!(call(&layer, "b").await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn buckets_refill_over_time() {
+        let layer = layer();
+        for _ in 0..3 {
+            call(&layer, "a").await;
+        }
+        tokio::time::advance(Duration::from_secs(4)).await;
+        let res = call(&layer, "a").await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers()[header::RETRY_AFTER], "6");
+
+        tokio::time::advance(Duration::from_secs(16)).await;
+        assert_eq!(call(&layer, "a").await.status(), StatusCode::OK);
+        assert_eq!(call(&layer, "a").await.status(), StatusCode::OK);
+        assert_eq!(
+            call(&layer, "a").await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use super::{BoxedService, Layer, Service};
+use crate::{response::ResponseOptions, ServerFnError};
+use http::StatusCode;
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+thread_local! {
+    static CURRENT: RefCell<Option<RawErrorSlot>> = const { RefCell::new(None) };
+}
+
+/// A middleware [`Layer`] that returns the errors of the server function from
+/// [`Service::try_run`], instead of turning them into an error response.
+///
+/// By default, an error returned by the server function is turned into a response
+/// before any middleware sees it. With this layer, the error reaches the service
+/// outside of it as the `Err` of `try_run`. The status code it was given, like the
+/// `422 Unprocessable Entity` of arguments that fail to deserialize, is set as the
+/// [error status](ResponseOptions::error_status) of the response options that are
+/// current while `try_run` is awaited, if there are any. A `tower` layer around the
+/// server function service sees it as the error of the service, and can transform
+/// or report it. Errors that are not handled by an outer layer are still answered
+/// with an error response by `handle_server_fn`.
+///
+/// An error of a custom error type is returned as a [`ServerFnError::ServerError`]
+/// with its message. Only `try_run` returns the error: middleware outside of this
+/// layer that call [`Service::run`] get the usual error response, so this should be
+/// the last middleware of the server function. A response that is not produced by
+/// the server function, like the `429 Too Many Requests` of a rate limit, is
+/// returned as it is.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(RawErrors::new())]
+/// pub async fn transfer(amount: u64) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+///
+/// // a tower layer around the handler can now map the errors itself
+/// let service = get_server_fn_service(Transfer::PATH).unwrap();
+/// let service = ProblemDetails::layer(service);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RawErrors;
+
+impl RawErrors {
+    /// Creates a new layer that returns the errors of the server function.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Where the server function leaves its error for the [`RawErrors`] layer around
+/// it.
+#[derive(Clone, Default)]
+pub(crate) struct RawErrorSlot(Arc<Mutex<RawErrorSlotInner>>);
+
+#[derive(Default)]
+struct RawErrorSlotInner {
+    claimed: bool,
+    error: Option<(ServerFnError, Option<StatusCode>)>,
+}
+
+impl RawErrorSlot {
+    /// The slot of the [`RawErrors`] layer around the current server function, if
+    /// there is one that no server function has claimed yet.
+    ///
+    /// Only the first server function to start claims the slot, so that one it
+    /// calls through `call_local` can't leave its error in place of the response of
+    /// the caller.
+    pub(crate) fn claim() -> Option<Self> {
+        let slot = CURRENT.with(|current| current.borrow().clone())?;
+        let claimed = std::mem::replace(&mut slot.lock().claimed, true);
+        (!claimed).then_some(slot)
+    }
+
+    /// Leaves `err` for the layer, in place of an error response with `status`.
+    pub(crate) fn set<E: Display>(
+        &self,
+        err: &ServerFnError<E>,
+        status: Option<StatusCode>,
+    ) {
+        self.lock().error = Some((err.to_untyped(), status));
+    }
+
+    fn take(&self) -> Option<(ServerFnError, Option<StatusCode>)> {
+        self.lock().error.take()
+    }
+
+    fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        let prev = CURRENT.with(|current| current.replace(Some(self.clone())));
+        let res = f();
+        CURRENT.with(|current| *current.borrow_mut() = prev);
+        res
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RawErrorSlotInner> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+struct Scoped<F> {
+    slot: RawErrorSlot,
+    fut: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let slot = self.slot.clone();
+        slot.in_scope(|| self.fut.as_mut().poll(cx))
+    }
+}
+
+struct RawErrorsService<Req, Res> {
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for RawErrors
+where
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(RawErrorsService { inner })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for RawErrorsService<Req, Res>
+where
+    Res: Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        self.inner.0.run(req)
+    }
+
+    fn try_run(
+        &mut self,
+        req: Req,
+    ) -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send>>
+    where
+        Res: 'static,
+    {
+        let slot = RawErrorSlot::default();
+        // the inner services may start the server function while creating their
+        // future, or only once it is polled
+        let fut = slot.in_scope(|| self.inner.0.try_run(req));
+        let fut = Scoped {
+            slot: slot.clone(),
+            fut: Box::pin(fut),
+        };
+        Box::pin(async move {
+            let res = fut.await?;
+            match slot.take() {
+                Some((err, status)) => {
+                    if let (Some(options), Some(status)) =
+                        (ResponseOptions::current(), status)
+                    {
+                        options.set_error_status(status);
+                    }
+                    Err(err)
+                }
+                None => Ok(res),
+            }
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
@@ -0,0 +1,458 @@
+use super::{BoxedService, SharedService};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Keeps the names of files recorded in the same nanosecond apart.
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// A middleware [`Layer`](super::Layer) that records every request, so that it can
+/// be [replayed](replay) later to reproduce a bug.
+///
+/// The method, URI, headers and body of each request are saved as a
+/// [`RecordedRequest`]. The body is read in full before the inner service runs, and
+/// handed to it unchanged.
+///
+/// Recorded requests include their headers as they were sent, so credentials and
+/// cookies end up in the log as well. This layer is meant for debugging, not for
+/// production use.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(Recorder::to_dir("target/recorded"))]
+/// pub async fn create_post(title: String) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+///
+/// // later, in a test
+/// let recorded = Recorder::to_dir("target/recorded").recorded()?;
+/// let responses = replay(&mut service, recorded).await;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    sink: Sink,
+}
+
+#[derive(Debug, Clone)]
+enum Sink {
+    Dir(Arc<PathBuf>),
+    Memory(Arc<Mutex<Vec<RecordedRequest>>>),
+}
+
+impl Recorder {
+    /// Creates a recorder that writes each request to a JSON file in `dir`, which is
+    /// created if it does not exist.
+    ///
+    /// Files are named after the time they were recorded at, so they sort in the
+    /// order the requests arrived in.
+    pub fn to_dir(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            sink: Sink::Dir(Arc::new(dir.into())),
+        }
+    }
+
+    /// Creates a recorder that keeps requests in memory.
+    ///
+    /// Clones of the recorder share the same log.
+    pub fn in_memory() -> Self {
+        Self {
+            sink: Sink::Memory(Arc::default()),
+        }
+    }
+
+    /// The requests recorded so far, oldest first.
+    ///
+    /// For a recorder created with [`Recorder::to_dir`], this reads every file in the
+    /// directory, including those written by earlier runs.
+    pub fn recorded(&self) -> io::Result<Vec<RecordedRequest>> {
+        match &self.sink {
+            Sink::Memory(log) => {
+                Ok(log.lock().unwrap_or_else(PoisonError::into_inner).clone())
+            }
+            Sink::Dir(dir) => {
+                let mut files = fs::read_dir(dir.as_path())?
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<io::Result<Vec<_>>>()?;
+                files.retain(|path| {
+                    path.extension().is_some_and(|ext| ext == "json")
+                });
+                files.sort();
+                files
+                    .into_iter()
+                    .map(|path| {
+                        serde_json::from_slice(&fs::read(path)?)
+                            .map_err(io::Error::from)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn record(&self, req: RecordedRequest) {
+        match &self.sink {
+            Sink::Memory(log) => {
+                log.lock().unwrap_or_else(PoisonError::into_inner).push(req)
+            }
+            Sink::Dir(dir) => {
+                if let Err(e) = Self::write(dir, &req) {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        error = %e,
+                        uri = req.uri.as_str(),
+                        "could not record request"
+                    );
+                    #[cfg(not(feature = "tracing"))]
+                    let _ = e;
+                }
+            }
+        }
+    }
+
+    fn write(dir: &Path, req: &RecordedRequest) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+        let file = dir.join(format!("{nanos:020}-{sequence:06}.json"));
+        fs::write(file, serde_json::to_vec_pretty(req)?)
+    }
+}
+
+/// A request saved by a [`Recorder`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// The HTTP method.
+    pub method: String,
+    /// The path and query string.
+    pub uri: String,
+    /// The headers, in the order they were sent. Values that are not valid UTF-8
+    /// are converted lossily.
+    pub headers: Vec<(String, String)>,
+    /// The whole body.
+    pub body: Vec<u8>,
+}
+
+/// Sends each recorded request through `service` in turn, returning the responses
+/// in the same order.
+///
+/// # Panics
+/// Panics if a recorded method, URI or header is not valid, which can only happen
+/// if the recording has been edited by hand.
+pub async fn replay<Req, Res>(
+    service: &mut BoxedService<Req, Res>,
+    requests: impl IntoIterator<Item = RecordedRequest>,
+) -> Vec<Res>
+where
+    Req: From<RecordedRequest>,
+{
+    let mut responses = Vec::new();
+    for req in requests {
+        responses.push(service.0.run(req.into()).await);
+    }
+    responses
+}
+
+struct RecorderService<Req, Res> {
+    recorder: Recorder,
+    inner: SharedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{RecordedRequest, Recorder, RecorderService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Request, Response};
+    use http_body_util::BodyExt;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for Recorder {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(RecorderService {
+                recorder: self.clone(),
+                inner: inner.into_shared(),
+            })
+        }
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for RecorderService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let recorder = self.recorder.clone();
+            let mut inner = self.inner.clone();
+            Box::pin(async move {
+                let (parts, body) = req.into_parts();
+                let body = match body.collect().await {
+                    Ok(body) => body.to_bytes(),
+                    Err(e) => {
+                        return Response::error_response(
+                            parts.uri.path(),
+                            &ServerFnError::new(e),
+                        )
+                    }
+                };
+                recorder.record(RecordedRequest {
+                    method: parts.method.to_string(),
+                    uri: parts.uri.to_string(),
+                    headers: parts
+                        .headers
+                        .iter()
+                        .map(|(name, value)| {
+                            (
+                                name.to_string(),
+                                String::from_utf8_lossy(value.as_bytes())
+                                    .into_owned(),
+                            )
+                        })
+                        .collect(),
+                    body: body.to_vec(),
+                });
+                inner
+                    .run(Request::from_parts(parts, Body::from(body)))
+                    .await
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.poll_ready(cx)
+        }
+    }
+
+    impl From<RecordedRequest> for Request<Body> {
+        fn from(recorded: RecordedRequest) -> Self {
+            let mut req = Request::builder()
+                .method(recorded.method.as_str())
+                .uri(recorded.uri.as_str());
+            for (name, value) in &recorded.headers {
+                req = req.header(name.as_str(), value.as_str());
+            }
+            req.body(Body::from(recorded.body))
+                .expect("invalid recorded request")
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{RecordedRequest, Recorder, RecorderService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{dev, error::PayloadError, test::TestRequest};
+    use bytes::{Bytes, BytesMut};
+    use futures::{Stream, StreamExt};
+    use send_wrapper::SendWrapper;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for Recorder {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(RecorderService {
+                recorder: self.clone(),
+                inner: inner.into_shared(),
+            })
+        }
+    }
+
+    /// A payload that yields `body` in a single chunk.
+    fn payload(body: Bytes) -> dev::Payload {
+        dev::Payload::from(Box::pin(futures::stream::once(async move {
+            Ok::<_, PayloadError>(body)
+        }))
+            as Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>>)
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for RecorderService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let recorder = self.recorder.clone();
+            let mut inner = self.inner.clone();
+            let path = req.path().to_string();
+            let rewritten = req.1.clone();
+            let (http_req, mut body) = req.0.take();
+            // Actix keeps the request on a single thread, so reading the payload
+            // only needs to look `Send`
+            let read = SendWrapper::new(async move {
+                let mut buf = BytesMut::new();
+                while let Some(chunk) = body.next().await {
+                    buf.extend_from_slice(&chunk?);
+                }
+                Ok::<_, PayloadError>((http_req, buf.freeze()))
+            });
+            Box::pin(async move {
+                // the request is rebuilt in its own scope, so that the `HttpRequest`
+                // isn't held across running the inner service
+                let req = {
+                    let (http_req, body) = match read.await {
+                        Ok(read) => read,
+                        Err(e) => {
+                            return ActixResponse::error_response(
+                                &path,
+                                &ServerFnError::new(e),
+                            )
+                        }
+                    };
+                    recorder.record(RecordedRequest {
+                        method: http_req.method().to_string(),
+                        uri: http_req.uri().to_string(),
+                        headers: http_req
+                            .headers()
+                            .iter()
+                            .map(|(name, value)| {
+                                (
+                                    name.to_string(),
+                                    String::from_utf8_lossy(value.as_bytes())
+                                        .into_owned(),
+                                )
+                            })
+                            .collect(),
+                        body: body.to_vec(),
+                    });
+                    ActixRequest::from((http_req, payload(body)))
+                        .with_path(rewritten)
+                };
+                inner.run(req).await
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.poll_ready(cx)
+        }
+    }
+
+    impl From<RecordedRequest> for ActixRequest {
+        fn from(recorded: RecordedRequest) -> Self {
+            let method = recorded
+                .method
+                .parse()
+                .expect("invalid recorded request method");
+            let mut req = TestRequest::default()
+                .method(method)
+                .uri(recorded.uri.as_str());
+            for (name, value) in recorded.headers {
+                req = req.append_header((name, value));
+            }
+            let (http_req, _) = req.to_http_parts();
+            ActixRequest::from((http_req, payload(recorded.body.into())))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{replay, RecordedRequest, Recorder};
+    use crate::middleware::{service_fn, BoxedService, Layer};
+    use axum::body::Body;
+    use http::{header, Request, Response};
+    use http_body_util::BodyExt;
+
+    /// Echoes the method, URI, content type and body of the request.
+    fn echo() -> BoxedService<Request<Body>, Response<Body>> {
+        service_fn(|req: Request<Body>| async move {
+            let (parts, body) = req.into_parts();
+            let body = body.collect().await.unwrap().to_bytes();
+            Response::new(Body::from(format!(
+                "{} {} {:?} {}",
+                parts.method,
+                parts.uri,
+                parts.headers.get(header::CONTENT_TYPE),
+                String::from_utf8_lossy(&body)
+            )))
+        })
+    }
+
+    async fn text(res: Response<Body>) -> String {
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    fn request() -> Request<Body> {
+        Request::post("/api/create_post?draft=true")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"title":"hello"}"#))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn records_request_and_passes_it_on() {
+        let recorder = Recorder::in_memory();
+        let mut service = recorder.layer(echo());
+        let original = text(service.0.run(request()).await).await;
+        assert_eq!(
+            original,
+            r#"POST /api/create_post?draft=true Some("application/json") {"title":"hello"}"#
+        );
+
+        let recorded = recorder.recorded().unwrap();
+        assert_eq!(
+            recorded,
+            [RecordedRequest {
+                method: "POST".into(),
+                uri: "/api/create_post?draft=true".into(),
+                headers: vec![(
+                    "content-type".into(),
+                    "application/json".into()
+                )],
+                body: br#"{"title":"hello"}"#.to_vec(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn replays_recording_from_dir() {
+        let dir = std::env::temp_dir()
+            .join(format!("server_fn_recorder_{}", uuid::Uuid::new_v4()));
+        let mut service = Recorder::to_dir(&dir).layer(echo());
+        let original = text(service.0.run(request()).await).await;
+
+        let recorded = Recorder::to_dir(&dir).recorded().unwrap();
+        assert_eq!(recorded.len(), 1);
+        let mut replayed = replay(&mut echo(), recorded).await;
+        assert_eq!(text(replayed.remove(0)).await, original);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+use super::{
+    BoxedService, Layer, RequestExtensionsMut, RequestHeaders, Service,
+    SharedService,
+};
+use crate::{error::NoCustomError, request::RequestExtensions, ServerFnError};
+use http::HeaderName;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The longest request ID that is accepted from a client.
+const MAX_LEN: usize = 128;
+
+/// A middleware [`Layer`] that gives every request an ID, for tracing it across
+/// services.
+///
+/// The ID is taken from the `X-Request-Id` header of the request, if there is a
+/// valid one, or a new UUID is generated otherwise. It is inserted into the request
+/// extensions as a [`RequestIdValue`], and echoed in the same header of the
+/// response.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(RequestId::new().header_name("x-trace-id"))]
+/// pub async fn create_post(
+///     title: String,
+///     Extension(id): Extension<RequestIdValue>,
+/// ) -> Result<(), ServerFnError> {
+///     tracing::info!(request_id = %id, "creating post");
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestId {
+    header: HeaderName,
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self {
+            header: HeaderName::from_static("x-request-id"),
+        }
+    }
+}
+
+impl RequestId {
+    /// Creates a new layer that uses the `X-Request-Id` header.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads and echoes the request ID in the header called `name` instead.
+    ///
+    /// # Panics
+    /// Panics if `name` is not a valid header name.
+    pub fn header_name(mut self, name: &str) -> Self {
+        self.header = HeaderName::from_bytes(name.as_bytes())
+            .expect("invalid request ID header name");
+        self
+    }
+
+    fn id_for(header: Option<&str>) -> RequestIdValue {
+        match header.map(str::trim) {
+            Some(id)
+                if !id.is_empty()
+                    && id.len() <= MAX_LEN
+                    && id.bytes().all(|b| b.is_ascii_graphic()) =>
+            {
+                RequestIdValue(id.to_owned())
+            }
+            _ => RequestIdValue(uuid::Uuid::new_v4().to_string()),
+        }
+    }
+}
+
+/// The ID of a request, as set by a [`RequestId`] layer.
+///
+/// Server functions can take it as an
+/// [`Extension<RequestIdValue>`](crate::request::Extension) argument, or read it
+/// with [`RequestIdValue::current`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestIdValue(String);
+
+impl RequestIdValue {
+    /// The ID of the request currently being handled by a server function, or `None`
+    /// if it has none or if called outside of a server function.
+    pub fn current() -> Option<Self> {
+        RequestExtensions::current()?.get()
+    }
+
+    /// The ID as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RequestIdValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+struct RequestIdService<Req, Res> {
+    header: HeaderName,
+    inner: SharedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for RequestId
+where
+    Req: RequestHeaders + RequestExtensionsMut + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(RequestIdService {
+            header: self.header.clone(),
+            inner: inner.into_shared(),
+        })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for RequestIdService<Req, Res>
+where
+    Req: RequestHeaders + RequestExtensionsMut + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        mut req: Req,
+    ) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let id = RequestId::id_for(req.header(self.header.as_str()));
+        req.insert_extension(id.clone());
+        let header = self.header.clone();
+        let inner = self.inner.run(req);
+        Box::pin(async move {
+            let mut res = inner.await;
+            res.insert_header(header.as_str(), id.as_str());
+            res
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{RequestId, RequestIdValue};
+    use crate::middleware::{service_fn, Layer};
+    use axum::body::Body;
+    use http::{Request, Response};
+    use http_body_util::BodyExt;
+
+    /// Runs a request through the layer, returning the ID seen by the handler and
+    /// the response header.
+    async fn run(
+        layer: RequestId,
+        header: Option<(&str, &str)>,
+    ) -> (String, Option<String>) {
+        let name = layer.header.clone();
+        let mut service =
+            layer.layer(service_fn(|req: Request<Body>| async move {
+                let id = req
+                    .extensions()
+                    .get::<RequestIdValue>()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                Response::new(Body::from(id))
+            }));
+        let mut req = Request::post("/api/create_post");
+        if let Some((name, value)) = header {
+            req = req.header(name, value);
+        }
+        let res = service.0.run(req.body(Body::empty()).unwrap()).await;
+        let echoed = res
+            .headers()
+            .get(name)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (String::from_utf8(body.to_vec()).unwrap(), echoed)
+    }
+
+    #[tokio::test]
+    async fn generates_id_when_absent() {
+        let (first, echoed) = run(RequestId::new(), None).await;
+        assert!(uuid::Uuid::parse_str(&first).is_ok(), "{first}");
+        assert_eq!(echoed.as_deref(), Some(first.as_str()));
+
+        let (second, _) = run(RequestId::new(), None).await;
+        assert_ne!(first, second);
+
+        let (invalid, _) =
+            run(RequestId::new(), Some(("x-request-id", "  "))).await;
+        assert!(uuid::Uuid::parse_str(&invalid).is_ok(), "{invalid}");
+    }
+
+    #[tokio::test]
+    async fn propagates_id_when_present() {
+        let (id, echoed) =
+            run(RequestId::new(), Some(("x-request-id", "abc-123"))).await;
+        assert_eq!(id, "abc-123");
+        assert_eq!(echoed.as_deref(), Some("abc-123"));
+    }
+
+    #[tokio::test]
+    async fn uses_custom_header_name() {
+        let layer = RequestId::new().header_name("X-Trace-Id");
+        let (id, echoed) =
+            run(layer.clone(), Some(("x-request-id", "ignored"))).await;
+        assert_ne!(id, "ignored");
+        assert_eq!(echoed.as_deref(), Some(id.as_str()));
+
+        let (id, echoed) = run(layer, Some(("x-trace-id", "trace-1"))).await;
+        assert_eq!(id, "trace-1");
+        assert_eq!(echoed.as_deref(), Some("trace-1"));
+    }
+}
@@ -0,0 +1,120 @@
+use super::{BoxedService, Layer, RequestPath, Service};
+use crate::{
+    error::NoCustomError, registry, request::Req, ServerFnError,
+    ServerFnMetadata,
+};
+use http::{Method, StatusCode};
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The server functions registered by the `#[server]` macro, by path.
+static REGISTRY: Lazy<HashMap<&'static str, &'static ServerFnMetadata>> =
+    Lazy::new(|| {
+        registry()
+            .map(|metadata| (metadata.path, metadata))
+            .collect()
+    });
+
+/// A middleware [`Layer`] that rejects requests whose `Content-Type` is not the one
+/// of the input encoding of the server function, with `415 Unsupported Media Type`.
+///
+/// The expected content type is looked up in the [`registry`] by the path of the
+/// request, and compared without its parameters, like the `boundary` of multipart
+/// data. Requests to server functions that take their arguments in the query
+/// string, like [`GetUrl`](crate::codec::GetUrl), are not checked, and neither are
+/// requests to paths that aren't in the registry, like those of server functions
+/// only registered with `register_explicit`.
+///
+/// ```rust,ignore
+/// #[server(input = Json)]
+/// #[middleware(RequireContentType::new())]
+/// pub async fn create_post(title: String) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RequireContentType;
+
+impl RequireContentType {
+    /// Creates a new layer that checks requests against the registry.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// The content type that requests to `path` must have, if they are checked.
+    fn expected(path: &str) -> Option<&'static str> {
+        REGISTRY
+            .get(path)
+            .filter(|metadata| metadata.method != Method::GET)
+            .map(|metadata| metadata.input_encoding)
+    }
+
+    /// Whether a `Content-Type` header matches `expected`, ignoring parameters.
+    fn matches(content_type: Option<&str>, expected: &str) -> bool {
+        content_type.is_some_and(|content_type| {
+            essence(content_type).eq_ignore_ascii_case(essence(expected))
+        })
+    }
+
+    fn error(expected: &str) -> ServerFnError {
+        ServerFnError::ServerError(format!(
+            "unsupported content type, expected `{expected}`"
+        ))
+    }
+}
+
+/// A media type without its parameters, like `text/plain` for
+/// `text/plain; charset=utf-8`.
+fn essence(value: &str) -> &str {
+    value.split(';').next().unwrap_or("").trim()
+}
+
+struct RequireContentTypeService<Req, Res> {
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Rq, Rs> Layer<Rq, Rs> for RequireContentType
+where
+    Rq: Req<NoCustomError> + RequestPath + Send + 'static,
+    Rs: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Rq, Rs>) -> BoxedService<Rq, Rs> {
+        BoxedService::new(RequireContentTypeService { inner })
+    }
+}
+
+impl<Rq, Rs> Service<Rq, Rs> for RequireContentTypeService<Rq, Rs>
+where
+    Rq: Req<NoCustomError> + RequestPath,
+    Rs: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(&mut self, req: Rq) -> Pin<Box<dyn Future<Output = Rs> + Send>> {
+        let Some(expected) = RequireContentType::expected(req.path()) else {
+            return self.inner.0.run(req);
+        };
+        if RequireContentType::matches(
+            req.to_content_type().as_deref(),
+            expected,
+        ) {
+            return self.inner.0.run(req);
+        }
+        let mut res = Rs::error_response(
+            req.path(),
+            &RequireContentType::error(expected),
+        );
+        res.set_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        Box::pin(async move { res })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
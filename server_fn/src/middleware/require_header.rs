@@ -0,0 +1,154 @@
+use super::{
+    constant_time_eq, BoxedService, Layer, RequestHeaders, RequestPath, Service,
+};
+use crate::{error::NoCustomError, ServerFnError};
+use http::StatusCode;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// A middleware [`Layer`] that rejects requests without a valid value for a header,
+/// with `403 Forbidden`.
+///
+/// This suits server functions that are only meant to be called by other services,
+/// which share a secret with the server. The value is either compared to an expected
+/// one, in time that doesn't depend on where they differ, or checked by a validator.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(RequireHeader::new("x-internal-token", env!("INTERNAL_TOKEN")))]
+/// pub async fn rebuild_index() -> Result<(), ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RequireHeader {
+    name: Arc<str>,
+    validate: Arc<dyn Fn(&str) -> bool + Send + Sync>,
+}
+
+impl RequireHeader {
+    /// Creates a new layer that requires the header `name` to have the value
+    /// `expected`.
+    pub fn new(name: impl Into<String>, expected: impl Into<String>) -> Self {
+        let expected = expected.into();
+        Self::validate(name, move |value| {
+            constant_time_eq(value.as_bytes(), expected.as_bytes())
+        })
+    }
+
+    /// Creates a new layer that requires the header `name` to have a value that
+    /// `validate` accepts.
+    pub fn validate(
+        name: impl Into<String>,
+        validate: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into().into(),
+            validate: Arc::new(validate),
+        }
+    }
+
+    fn missing(&self) -> ServerFnError {
+        ServerFnError::ServerError(format!("missing header `{}`", self.name))
+    }
+
+    fn invalid(&self) -> ServerFnError {
+        ServerFnError::ServerError(format!("invalid header `{}`", self.name))
+    }
+}
+
+struct RequireHeaderService<Req, Res> {
+    require: RequireHeader,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Rq, Rs> Layer<Rq, Rs> for RequireHeader
+where
+    Rq: RequestHeaders + RequestPath + Send + 'static,
+    Rs: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Rq, Rs>) -> BoxedService<Rq, Rs> {
+        BoxedService::new(RequireHeaderService {
+            require: self.clone(),
+            inner,
+        })
+    }
+}
+
+impl<Rq, Rs> Service<Rq, Rs> for RequireHeaderService<Rq, Rs>
+where
+    Rq: RequestHeaders + RequestPath,
+    Rs: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(&mut self, req: Rq) -> Pin<Box<dyn Future<Output = Rs> + Send>> {
+        let err = match req.header(&self.require.name) {
+            Some(value) if (self.require.validate)(value) => {
+                return self.inner.0.run(req);
+            }
+            Some(_) => self.require.invalid(),
+            None => self.require.missing(),
+        };
+        let mut res = Rs::error_response(req.path(), &err);
+        res.set_status(StatusCode::FORBIDDEN);
+        Box::pin(async move { res })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::RequireHeader;
+    use crate::middleware::{service_fn, Layer};
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+
+    async fn call(require: RequireHeader, token: Option<&str>) -> StatusCode {
+        let mut service =
+            require.layer(service_fn(|_req: Request<Body>| async move {
+                Response::new(Body::empty())
+            }));
+        let mut req = Request::post("/api/rebuild_index");
+        if let Some(token) = token {
+            req = req.header("x-internal-token", token);
+        }
+        service
+            .0
+            .run(req.body(Body::empty()).unwrap())
+            .await
+            .status()
+    }
+
+    #[tokio::test]
+    async fn allows_the_expected_value() {
+        let require = RequireHeader::new("X-Internal-Token", "secret");
+        assert_eq!(call(require, Some("secret")).await, StatusCode::OK);
+        let require = RequireHeader::validate("x-internal-token", |token| {
+            token.starts_with("svc-")
+        });
+        assert_eq!(call(require, Some("svc-search")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rejects_other_values() {
+        let require = RequireHeader::new("x-internal-token", "secret");
+        assert_eq!(call(require, Some("secreT")).await, StatusCode::FORBIDDEN);
+        let require = RequireHeader::new("x-internal-token", "secret");
+        assert_eq!(call(require, Some("")).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn rejects_requests_without_the_header() {
+        let require = RequireHeader::new("x-internal-token", "secret");
+        assert_eq!(call(require, None).await, StatusCode::FORBIDDEN);
+    }
+}
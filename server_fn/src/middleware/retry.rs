@@ -0,0 +1,379 @@
+use super::{BoxedService, SharedService};
+use crate::ServerFnError;
+use std::time::Duration;
+
+/// The default maximum request body size buffered by [`Retry`] (2 MiB).
+pub const DEFAULT_RETRY_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// A middleware [`Layer`](super::Layer) that re-runs a server function when it fails.
+///
+/// A response counts as a failure if it has a `5xx` status, which includes every
+/// [`ServerFnError`] response. The request body is buffered up front so that a fresh
+/// request can be built for each attempt; bodies larger than the configured limit are
+/// rejected with `413 Payload Too Large`.
+///
+/// Only idempotent requests are retried: by default, this means `GET` requests. Server
+/// functions using another method can opt in with [`Retry::assume_idempotent`]. Other
+/// requests are passed through to the inner service unchanged.
+///
+/// ```rust,ignore
+/// #[server(input = GetUrl)]
+/// #[middleware(Retry::new(3).with_backoff(Duration::from_millis(50)))]
+/// pub async fn flaky_lookup(id: u32) -> Result<Item, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    max_attempts: usize,
+    backoff: Duration,
+    max_body_size: usize,
+    idempotent: bool,
+}
+
+impl Retry {
+    /// Creates a new retry layer that runs the inner service at most `max_attempts`
+    /// times in total.
+    pub fn new(max_attempts: usize) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            backoff: Duration::ZERO,
+            max_body_size: DEFAULT_RETRY_BODY_LIMIT,
+            idempotent: false,
+        }
+    }
+
+    /// Waits before each retry. The delay doubles after every failed attempt.
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the maximum size of the request body that will be buffered.
+    pub fn with_max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Retries requests regardless of their HTTP method.
+    ///
+    /// Only use this for server functions that are safe to run more than once.
+    pub fn assume_idempotent(mut self) -> Self {
+        self.idempotent = true;
+        self
+    }
+
+    fn delay(&self, attempt: usize) -> Duration {
+        self.backoff
+            .saturating_mul(1 << (attempt - 1).min(u32::BITS as usize - 1))
+    }
+
+    fn too_large() -> ServerFnError {
+        ServerFnError::ServerError("request body too large".into())
+    }
+}
+
+struct RetryService<Req, Res> {
+    config: Retry,
+    inner: SharedService<Req, Res>,
+}
+
+impl<Req, Res> RetryService<Req, Res> {
+    fn new(config: Retry, inner: BoxedService<Req, Res>) -> Self {
+        Self {
+            config,
+            inner: inner.into_shared(),
+        }
+    }
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{Retry, RetryService};
+    use crate::{
+        middleware::{BoxedService, Layer, RequestPath, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Method, Request, Response, StatusCode};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for Retry {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(RetryService::new(*self, inner))
+        }
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for RetryService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let config = self.config;
+            let mut inner = self.inner.clone();
+            if !config.idempotent && req.method() != Method::GET {
+                return inner.run(req);
+            }
+
+            Box::pin(async move {
+                let path = req.path().to_string();
+                let (parts, body) = req.into_parts();
+                let body = match axum::body::to_bytes(
+                    body,
+                    config.max_body_size,
+                )
+                .await
+                {
+                    Ok(body) => body,
+                    Err(_) => {
+                        let mut res = Response::<Body>::error_response(
+                            &path,
+                            &Retry::too_large(),
+                        );
+                        *res.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+                        return res;
+                    }
+                };
+
+                let mut attempt = 1;
+                loop {
+                    let req =
+                        Request::from_parts(parts.clone(), body.clone().into());
+                    let res = inner.run(req).await;
+                    if !res.status().is_server_error()
+                        || attempt >= config.max_attempts
+                    {
+                        return res;
+                    }
+                    tokio::time::sleep(config.delay(attempt)).await;
+                    attempt += 1;
+                }
+            })
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{Retry, RetryService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{
+        dev,
+        http::{Method, StatusCode},
+    };
+    use send_wrapper::SendWrapper;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for Retry {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(RetryService::new(*self, inner))
+        }
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for RetryService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let config = self.config;
+            let mut inner = self.inner.clone();
+            if !config.idempotent && req.0 .0.method() != Method::GET {
+                return inner.run(req);
+            }
+
+            let path = req.path().to_string();
+            let rewritten = req.1.clone();
+            Box::pin(SendWrapper::new(async move {
+                let (req, payload) = req.0.take();
+                let body = match payload
+                    .to_bytes_limited(config.max_body_size)
+                    .await
+                {
+                    Ok(Ok(body)) => body,
+                    Ok(Err(e)) => {
+                        let err: ServerFnError =
+                            ServerFnError::Deserialization(e.to_string());
+                        return ActixResponse::error_response(&path, &err);
+                    }
+                    Err(_) => {
+                        let mut res = ActixResponse::error_response(
+                            &path,
+                            &Retry::too_large(),
+                        );
+                        *res.0.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+                        return res;
+                    }
+                };
+
+                let mut attempt = 1;
+                loop {
+                    let req = ActixRequest::from((
+                        req.clone(),
+                        dev::Payload::from(body.clone()),
+                    ))
+                    .with_path(rewritten.clone());
+                    let res = inner.run(req).await;
+                    if !res.0.status().is_server_error()
+                        || attempt >= config.max_attempts
+                    {
+                        return res;
+                    }
+                    tokio::time::sleep(config.delay(attempt)).await;
+                    attempt += 1;
+                }
+            }))
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Retry;
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    /// Fails the first `failures` calls, then echoes the request body.
+    struct Flaky {
+        failures: usize,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Service<Request<Body>, Response<Body>> for Flaky {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let failures = self.failures;
+            Box::pin(async move {
+                if call < failures {
+                    let err: ServerFnError =
+                        ServerFnError::ServerError("unavailable".into());
+                    Response::<Body>::error_response("/api/flaky", &err)
+                } else {
+                    Response::new(req.into_body())
+                }
+            })
+        }
+    }
+
+    async fn run(
+        retry: Retry,
+        failures: usize,
+        req: Request<Body>,
+    ) -> (StatusCode, String, usize) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut service = retry.layer(BoxedService::new(Flaky {
+            failures,
+            calls: calls.clone(),
+        }));
+        let res = service.0.run(req).await;
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (
+            status,
+            String::from_utf8(body.to_vec()).unwrap(),
+            calls.load(Ordering::SeqCst),
+        )
+    }
+
+    fn get() -> Request<Body> {
+        Request::get("/api/flaky").body(Body::from("ping")).unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn eventually_succeeds() {
+        let retry = Retry::new(3).with_backoff(Duration::from_millis(100));
+        let (status, body, calls) = run(retry, 2, get()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "ping");
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_max_attempts() {
+        let (status, body, calls) = run(Retry::new(3), 5, get()).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body, "ServerError|unavailable");
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn post_is_only_retried_when_opted_in() {
+        let post = || {
+            Request::post("/api/flaky")
+                .body(Body::from("ping"))
+                .unwrap()
+        };
+        let (status, _, calls) = run(Retry::new(3), 1, post()).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(calls, 1);
+
+        let retry = Retry::new(3).assume_idempotent();
+        let (status, body, calls) = run(retry, 1, post()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "ping");
+        assert_eq!(calls, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rejects_oversized_body() {
+        let retry = Retry::new(3).with_max_body_size(2);
+        let (status, _, calls) = run(retry, 0, get()).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(calls, 0);
+    }
+}
@@ -0,0 +1,189 @@
+use super::{
+    BoxedService, Layer, RequestExtensionsMut, Service, SharedService,
+};
+use crate::{error::NoCustomError, request::RequestExtensions, ServerFnError};
+use std::{
+    fmt::Write,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// A middleware [`Layer`] that reports the durations recorded by a server function
+/// in the `Server-Timing` header of its response, where browser devtools show them.
+///
+/// Every request gets a new [`ServerTiming`] in its extensions, which the server
+/// function records durations into. The header is only added if something was
+/// recorded.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(ServerTimingHeader)]
+/// pub async fn list_posts() -> Result<Vec<Post>, ServerFnError> {
+///     let start = Instant::now();
+///     let posts = load_posts().await?;
+///     if let Some(timing) = ServerTiming::current() {
+///         timing.record("db", start.elapsed());
+///     }
+///     Ok(posts)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ServerTimingHeader;
+
+/// The durations recorded for a request, as provided by a [`ServerTimingHeader`]
+/// layer.
+///
+/// Server functions can take it as an
+/// [`Extension<ServerTiming>`](crate::request::Extension) argument, or read it with
+/// [`ServerTiming::current`].
+#[derive(Debug, Clone, Default)]
+pub struct ServerTiming(Arc<Mutex<Vec<(String, Duration)>>>);
+
+impl ServerTiming {
+    /// The timings of the request currently being handled by a server function, or
+    /// `None` if it has no [`ServerTimingHeader`] layer or if called outside of a
+    /// server function.
+    pub fn current() -> Option<Self> {
+        RequestExtensions::current()?.get()
+    }
+
+    /// Records that the step called `name` took `duration`.
+    ///
+    /// The name should be a short token, like `db` or `render`. Names with
+    /// characters that are not allowed in a token, like spaces, are ignored.
+    pub fn record(&self, name: &str, duration: Duration) {
+        if name.is_empty() || !name.bytes().all(is_token_char) {
+            return;
+        }
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((name.to_owned(), duration));
+    }
+
+    /// The value of the `Server-Timing` header, with each duration in
+    /// milliseconds, or `None` if nothing was recorded.
+    fn header_value(&self) -> Option<String> {
+        let timings = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut value = String::new();
+        for (name, duration) in timings.iter() {
+            if !value.is_empty() {
+                value.push_str(", ");
+            }
+            let millis = duration.as_micros() as f64 / 1000.0;
+            _ = write!(value, "{name};dur={millis}");
+        }
+        (!value.is_empty()).then_some(value)
+    }
+}
+
+/// Whether `b` may appear in a token, as defined by RFC 9110.
+fn is_token_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+struct ServerTimingService<Req, Res> {
+    inner: SharedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for ServerTimingHeader
+where
+    Req: RequestExtensionsMut + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(ServerTimingService {
+            inner: inner.into_shared(),
+        })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for ServerTimingService<Req, Res>
+where
+    Req: RequestExtensionsMut + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        mut req: Req,
+    ) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let timing = ServerTiming::default();
+        req.insert_extension(timing.clone());
+        let inner = self.inner.run(req);
+        Box::pin(async move {
+            let mut res = inner.await;
+            if let Some(value) = timing.header_value() {
+                res.append_header("server-timing", &value);
+            }
+            res
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{ServerTiming, ServerTimingHeader};
+    use crate::middleware::{service_fn, Layer};
+    use axum::body::Body;
+    use http::{Request, Response};
+    use std::time::Duration;
+
+    /// Runs a request through the layer with a handler that records `timings`,
+    /// returning the `Server-Timing` header of the response.
+    async fn run(timings: Vec<(&'static str, Duration)>) -> Option<String> {
+        let mut service =
+            ServerTimingHeader.layer(service_fn(move |req: Request<Body>| {
+                let timings = timings.clone();
+                async move {
+                    let timing =
+                        req.extensions().get::<ServerTiming>().unwrap();
+                    for (name, duration) in timings {
+                        timing.record(name, duration);
+                    }
+                    Response::new(Body::empty())
+                }
+            }));
+        let req = Request::post("/api/list_posts")
+            .body(Body::empty())
+            .unwrap();
+        let res = service.0.run(req).await;
+        res.headers()
+            .get("server-timing")
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn reports_recorded_timings() {
+        let header = run(vec![
+            ("db", Duration::from_micros(12_500)),
+            ("render", Duration::from_millis(3)),
+            ("cache", Duration::from_micros(300)),
+        ])
+        .await;
+        assert_eq!(
+            header.as_deref(),
+            Some("db;dur=12.5, render;dur=3, cache;dur=0.3")
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_invalid_names_and_empty_timings() {
+        let header = run(vec![
+            ("db query", Duration::from_millis(1)),
+            ("db", Duration::ZERO),
+        ])
+        .await;
+        assert_eq!(header.as_deref(), Some("db;dur=0"));
+        assert_eq!(run(Vec::new()).await, None);
+    }
+}
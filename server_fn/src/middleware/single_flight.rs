@@ -0,0 +1,501 @@
+use super::SharedService;
+use crate::ServerFnError;
+use bytes::Bytes;
+use futures::{
+    channel::oneshot,
+    future::{FutureExt, Shared},
+};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// A middleware [`Layer`](super::Layer) that coalesces identical `GET` requests
+/// that are in flight at the same time, running the inner service only once.
+///
+/// Requests are identical if they have the same path and query. The first one runs
+/// as usual, and its response is buffered; requests that arrive while it is still
+/// running wait for it, and all of them get a copy of its response. Once it has
+/// finished, the next request runs again: unlike a [`Cache`](super::Cache), nothing
+/// is kept. If the first request is cancelled, the requests waiting for it run on
+/// their own.
+///
+/// Like a [`Cache`](super::Cache), this is only useful for server functions whose
+/// result depends on nothing but their arguments, and which use a `GET` input
+/// encoding like [`GetUrl`](crate::codec::GetUrl). In particular, every waiting
+/// request gets the headers of the first response, including any cookies it sets.
+///
+/// ```rust,ignore
+/// #[server(input = GetUrl)]
+/// #[middleware(SingleFlight::new())]
+/// pub async fn leaderboard(season: u32) -> Result<Vec<Score>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct SingleFlight {
+    in_flight: Arc<Mutex<HashMap<String, Flight>>>,
+}
+
+/// A buffered response, shared independently of the framework.
+#[derive(Debug, Clone)]
+struct SharedResponse {
+    status: u16,
+    headers: Vec<(String, Bytes)>,
+    body: Bytes,
+}
+
+/// The response of the request that is running, for the requests waiting for it.
+type Flight = Shared<oneshot::Receiver<Result<SharedResponse, ServerFnError>>>;
+
+/// What a request does when it arrives.
+enum Role {
+    /// Runs, and shares its response.
+    Leader(Leader),
+    /// Waits for the response of the request that is already running.
+    Waiter(Flight),
+}
+
+/// The request that runs for all the identical requests in flight.
+///
+/// It stops accepting waiters when it is dropped, whether or not it has shared its
+/// response.
+struct Leader {
+    in_flight: Arc<Mutex<HashMap<String, Flight>>>,
+    key: String,
+    share: Option<oneshot::Sender<Result<SharedResponse, ServerFnError>>>,
+}
+
+impl Leader {
+    fn share(mut self, res: Result<SharedResponse, ServerFnError>) {
+        if let Some(share) = self.share.take() {
+            _ = share.send(res);
+        }
+    }
+}
+
+impl Drop for Leader {
+    fn drop(&mut self) {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.key);
+    }
+}
+
+impl SingleFlight {
+    /// Creates a new layer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The key that identical requests share, or `None` if the request should not
+    /// be coalesced.
+    fn key(method: &str, path: &str, query: Option<&str>) -> Option<String> {
+        if method != "GET" {
+            return None;
+        }
+        let mut key = path.to_string();
+        if let Some(query) = query {
+            key.push('?');
+            key.push_str(query);
+        }
+        Some(key)
+    }
+
+    /// Joins the request in flight for `key`, or becomes it.
+    fn join(&self, key: String) -> Role {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(flight) = in_flight.get(&key) {
+            return Role::Waiter(flight.clone());
+        }
+        let (share, flight) = oneshot::channel();
+        in_flight.insert(key.clone(), flight.shared());
+        Role::Leader(Leader {
+            in_flight: Arc::clone(&self.in_flight),
+            key,
+            share: Some(share),
+        })
+    }
+}
+
+impl Debug for SingleFlight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SingleFlight").finish_non_exhaustive()
+    }
+}
+
+struct SingleFlightService<Req, Res> {
+    single_flight: SingleFlight,
+    inner: SharedService<Req, Res>,
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{Role, SharedResponse, SingleFlight, SingleFlightService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        response::Res,
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use http::{HeaderName, HeaderValue, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<Request<Body>, Response<Body>> for SingleFlight {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Body>, Response<Body>>,
+        ) -> BoxedService<Request<Body>, Response<Body>> {
+            BoxedService::new(SingleFlightService {
+                single_flight: self.clone(),
+                inner: inner.into_shared(),
+            })
+        }
+    }
+
+    fn to_response(
+        path: &str,
+        shared: Result<SharedResponse, ServerFnError>,
+    ) -> Response<Body> {
+        let shared = match shared {
+            Ok(shared) => shared,
+            Err(e) => return Response::error_response(path, &e),
+        };
+        let mut res = Response::new(Body::from(shared.body));
+        *res.status_mut() =
+            StatusCode::from_u16(shared.status).unwrap_or(StatusCode::OK);
+        for (name, value) in shared.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_maybe_shared(value),
+            ) {
+                res.headers_mut().append(name, value);
+            }
+        }
+        res
+    }
+
+    async fn buffer(
+        res: Response<Body>,
+    ) -> Result<SharedResponse, ServerFnError> {
+        let (parts, body) = res.into_parts();
+        let body = body.collect().await.map_err(ServerFnError::new)?;
+        let headers = parts
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                (name.to_string(), Bytes::copy_from_slice(value.as_bytes()))
+            })
+            .collect();
+        Ok(SharedResponse {
+            status: parts.status.as_u16(),
+            headers,
+            body: body.to_bytes(),
+        })
+    }
+
+    impl Service<Request<Body>, Response<Body>>
+        for SingleFlightService<Request<Body>, Response<Body>>
+    {
+        fn run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let key = SingleFlight::key(
+                req.method().as_str(),
+                req.uri().path(),
+                req.uri().query(),
+            );
+            let Some(key) = key else {
+                return self.inner.run(req);
+            };
+
+            let path = req.uri().path().to_string();
+            match self.single_flight.join(key) {
+                Role::Leader(leader) => {
+                    let inner = self.inner.run(req);
+                    Box::pin(async move {
+                        let shared = buffer(inner.await).await;
+                        leader.share(shared.clone());
+                        to_response(&path, shared)
+                    })
+                }
+                Role::Waiter(flight) => {
+                    let mut inner = self.inner.clone();
+                    Box::pin(async move {
+                        match flight.await {
+                            Ok(shared) => to_response(&path, shared),
+                            // the request that was running was cancelled
+                            Err(_) => inner.run(req).await,
+                        }
+                    })
+                }
+            }
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{Role, SharedResponse, SingleFlight, SingleFlightService};
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        request::actix::ActixRequest,
+        response::{actix::ActixResponse, Res},
+        ServerFnError,
+    };
+    use actix_web::{
+        http::{
+            header::{HeaderName, HeaderValue},
+            StatusCode,
+        },
+        HttpResponse,
+    };
+    use bytes::Bytes;
+    use send_wrapper::SendWrapper;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    impl Layer<ActixRequest, ActixResponse> for SingleFlight {
+        fn layer(
+            &self,
+            inner: BoxedService<ActixRequest, ActixResponse>,
+        ) -> BoxedService<ActixRequest, ActixResponse> {
+            BoxedService::new(SingleFlightService {
+                single_flight: self.clone(),
+                inner: inner.into_shared(),
+            })
+        }
+    }
+
+    fn to_response(
+        path: &str,
+        shared: Result<SharedResponse, ServerFnError>,
+    ) -> ActixResponse {
+        let shared = match shared {
+            Ok(shared) => shared,
+            Err(e) => return ActixResponse::error_response(path, &e),
+        };
+        let mut res = HttpResponse::build(
+            StatusCode::from_u16(shared.status).unwrap_or(StatusCode::OK),
+        );
+        for (name, value) in shared.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_maybe_shared(value),
+            ) {
+                res.append_header((name, value));
+            }
+        }
+        ActixResponse::from(res.body(shared.body))
+    }
+
+    async fn buffer(
+        res: ActixResponse,
+    ) -> Result<SharedResponse, ServerFnError> {
+        // the response head isn't `Send`, so only its parts are kept while the
+        // body is read
+        let (status, headers, body) = {
+            let (res, body) = res.take().into_parts();
+            let headers = res
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    (name.to_string(), Bytes::copy_from_slice(value.as_bytes()))
+                })
+                .collect();
+            (res.status().as_u16(), headers, body)
+        };
+        // Actix keeps the response on a single thread, so reading the body only
+        // needs to look `Send`
+        let body = SendWrapper::new(actix_web::body::to_bytes(body))
+            .await
+            .map_err(ServerFnError::new)?;
+        Ok(SharedResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    impl Service<ActixRequest, ActixResponse>
+        for SingleFlightService<ActixRequest, ActixResponse>
+    {
+        fn run(
+            &mut self,
+            req: ActixRequest,
+        ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+            let http_req = req.request();
+            let key = SingleFlight::key(
+                http_req.method().as_str(),
+                req.path(),
+                Some(http_req.query_string()).filter(|query| !query.is_empty()),
+            );
+            let Some(key) = key else {
+                return self.inner.run(req);
+            };
+
+            let path = req.path().to_string();
+            match self.single_flight.join(key) {
+                Role::Leader(leader) => {
+                    let inner = self.inner.run(req);
+                    Box::pin(async move {
+                        let shared = buffer(inner.await).await;
+                        leader.share(shared.clone());
+                        to_response(&path, shared)
+                    })
+                }
+                Role::Waiter(flight) => {
+                    let mut inner = self.inner.clone();
+                    Box::pin(async move {
+                        match flight.await {
+                            Ok(shared) => to_response(&path, shared),
+                            // the request that was running was cancelled
+                            Err(_) => inner.run(req).await,
+                        }
+                    })
+                }
+            }
+        }
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ServerFnError>> {
+            self.inner.poll_ready(cx)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::SingleFlight;
+    use crate::middleware::{service_fn, BoxedService, Layer};
+    use axum::body::Body;
+    use futures::future::join_all;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    /// Counts its calls, and responds with the count after a while, streaming the
+    /// body.
+    fn coalesced() -> (
+        BoxedService<Request<Body>, Response<Body>>,
+        Arc<AtomicUsize>,
+    ) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = SingleFlight::new().layer(service_fn({
+            let calls = Arc::clone(&calls);
+            move |_req: Request<Body>| {
+                let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                async move {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    let chunks = futures::stream::iter([
+                        Ok::<_, std::io::Error>("call ".to_string()),
+                        Ok(n.to_string()),
+                    ]);
+                    let mut res = Response::new(Body::from_stream(chunks));
+                    res.headers_mut().insert(
+                        header::CONTENT_TYPE,
+                        "text/plain".parse().unwrap(),
+                    );
+                    res
+                }
+            }
+        }));
+        (service, calls)
+    }
+
+    fn call(
+        service: &mut BoxedService<Request<Body>, Response<Body>>,
+        method: &str,
+        uri: &str,
+    ) -> Pin<Box<dyn Future<Output = (StatusCode, String)> + Send>> {
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        let res = service.0.run(req);
+        Box::pin(async move {
+            let res = res.await;
+            let status = res.status();
+            assert_eq!(res.headers()[header::CONTENT_TYPE], "text/plain");
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn runs_concurrent_identical_requests_once() {
+        let (mut service, calls) = coalesced();
+        let requests = (0..10)
+            .map(|_| call(&mut service, "GET", "/api/leaderboard?season=3"))
+            .collect::<Vec<_>>();
+        let responses = join_all(requests).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        for res in responses {
+            assert_eq!(res, (StatusCode::OK, "call 1".to_string()));
+        }
+
+        // nothing is kept once the request has finished
+        let res = call(&mut service, "GET", "/api/leaderboard?season=3").await;
+        assert_eq!(res.1, "call 2");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn runs_other_requests_separately() {
+        let (mut service, calls) = coalesced();
+        let requests = vec![
+            call(&mut service, "GET", "/api/leaderboard?season=3"),
+            call(&mut service, "GET", "/api/leaderboard?season=4"),
+            call(&mut service, "POST", "/api/leaderboard?season=3"),
+            call(&mut service, "POST", "/api/leaderboard?season=3"),
+        ];
+        join_all(requests).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waiters_run_if_the_first_request_is_cancelled() {
+        let (mut service, calls) = coalesced();
+        let first = call(&mut service, "GET", "/api/leaderboard");
+        let second = call(&mut service, "GET", "/api/leaderboard");
+        let third = call(&mut service, "GET", "/api/leaderboard");
+        drop(first);
+        let responses = join_all([second, third]).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        for (status, _) in responses {
+            assert_eq!(status, StatusCode::OK);
+        }
+    }
+}
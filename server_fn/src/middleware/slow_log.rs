@@ -0,0 +1,285 @@
+use super::{BoxedService, Layer, RequestMethod, RequestPath, Service};
+use crate::ServerFnError;
+use http::Method;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+/// A middleware [`Layer`] that logs requests that take longer than a threshold, and
+/// nothing else.
+///
+/// Each slow request is logged as a `WARN` event with the `path` and `method` of the
+/// request and its `duration_ms`. The events have the target `server_fn::slow`, so
+/// that a subscriber can write them to a log of their own. A request is measured
+/// until its response is ready, whether it succeeds, fails, or panics, or until it is
+/// cancelled.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(SlowLog::new(Duration::from_millis(500)))]
+/// pub async fn search(query: String) -> Result<Vec<Post>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SlowLog {
+    threshold: Duration,
+}
+
+impl SlowLog {
+    /// Creates a new layer that logs requests that take longer than `threshold`.
+    pub fn new(threshold: Duration) -> Self {
+        Self { threshold }
+    }
+}
+
+/// Logs the request it was created for when dropped, if it has taken too long by
+/// then.
+struct Measure {
+    threshold: Duration,
+    path: String,
+    method: Method,
+    start: Instant,
+}
+
+impl Drop for Measure {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        if duration > self.threshold {
+            tracing::warn!(
+                target: "server_fn::slow",
+                path = self.path.as_str(),
+                method = %self.method,
+                duration_ms = duration.as_millis() as u64,
+                "slow server function request"
+            );
+        }
+    }
+}
+
+struct SlowLogService<Req, Res> {
+    threshold: Duration,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> SlowLogService<Req, Res>
+where
+    Req: RequestPath + RequestMethod,
+{
+    fn measure(&self, req: &Req) -> Measure {
+        Measure {
+            threshold: self.threshold,
+            path: req.path().to_owned(),
+            method: RequestMethod::method(req),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl<Req, Res> Layer<Req, Res> for SlowLog
+where
+    Req: RequestPath + RequestMethod + Send + 'static,
+    Res: Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(SlowLogService {
+            threshold: self.threshold,
+            inner,
+        })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for SlowLogService<Req, Res>
+where
+    Req: RequestPath + RequestMethod,
+    Res: Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let measure = self.measure(&req);
+        let inner = self.inner.0.run(req);
+        Box::pin(async move {
+            let res = inner.await;
+            drop(measure);
+            res
+        })
+    }
+
+    fn try_run(
+        &mut self,
+        req: Req,
+    ) -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send>>
+    where
+        Res: 'static,
+    {
+        let measure = self.measure(&req);
+        let inner = self.inner.0.try_run(req);
+        Box::pin(async move {
+            let res = inner.await;
+            drop(measure);
+            res
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::SlowLog;
+    use crate::{
+        middleware::{BoxedService, Layer, Service},
+        ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Request, Response};
+    use std::{
+        fmt::Debug,
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+    use tracing::{
+        field::{Field, Visit},
+        Event, Level, Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, Registry};
+
+    type Fields = Vec<(&'static str, String)>;
+
+    /// The fields of the events logged by [`SlowLog`].
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<Fields>>>);
+
+    struct Record(Fields);
+
+    impl Visit for Record {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.push((field.name(), format!("{value:?}")));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push((field.name(), value.to_string()));
+        }
+    }
+
+    impl<S: Subscriber> tracing_subscriber::Layer<S> for Capture {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            if event.metadata().target() == "server_fn::slow" {
+                assert_eq!(*event.metadata().level(), Level::WARN);
+                let mut record = Record(Vec::new());
+                event.record(&mut record);
+                self.0.lock().unwrap().push(record.0);
+            }
+        }
+    }
+
+    /// Takes `delay` to respond, and then fails if `fail` is set.
+    struct Sleep {
+        delay: Duration,
+        fail: bool,
+    }
+
+    impl Service<Request<Body>, Response<Body>> for Sleep {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let delay = self.delay;
+            Box::pin(async move {
+                thread::sleep(delay);
+                Response::new(Body::empty())
+            })
+        }
+
+        fn try_run(
+            &mut self,
+            req: Request<Body>,
+        ) -> Pin<
+            Box<
+                dyn Future<Output = Result<Response<Body>, ServerFnError>>
+                    + Send,
+            >,
+        > {
+            let fail = self.fail;
+            let inner = self.run(req);
+            Box::pin(async move {
+                let res = inner.await;
+                if fail {
+                    Err(ServerFnError::new("failed"))
+                } else {
+                    Ok(res)
+                }
+            })
+        }
+    }
+
+    /// Sends a request through a slow log with `threshold` to `inner`, returning
+    /// the events that were logged.
+    fn run(threshold: Duration, inner: Sleep) -> Vec<Fields> {
+        let capture = Capture::default();
+        let subscriber = Registry::default().with(capture.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let mut service =
+                SlowLog::new(threshold).layer(BoxedService::new(inner));
+            let req = Request::post("/api/search").body(Body::empty()).unwrap();
+            _ = futures::executor::block_on(service.0.try_run(req));
+        });
+        let events = capture.0.lock().unwrap();
+        events.clone()
+    }
+
+    #[test]
+    fn logs_slow_requests() {
+        let events = run(
+            Duration::from_millis(10),
+            Sleep {
+                delay: Duration::from_millis(30),
+                fail: false,
+            },
+        );
+        assert_eq!(events.len(), 1);
+        let fields = &events[0];
+        assert!(fields.contains(&("path", "/api/search".to_string())));
+        assert!(fields.contains(&("method", "POST".to_string())));
+        let duration_ms = fields
+            .iter()
+            .find(|(name, _)| *name == "duration_ms")
+            .map(|(_, value)| value.parse::<u64>().unwrap())
+            .unwrap();
+        assert!(duration_ms >= 30, "{duration_ms}");
+    }
+
+    #[test]
+    fn logs_slow_requests_that_fail() {
+        let events = run(
+            Duration::from_millis(10),
+            Sleep {
+                delay: Duration::from_millis(30),
+                fail: true,
+            },
+        );
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn leaves_fast_requests_unlogged() {
+        let events = run(
+            Duration::from_secs(10),
+            Sleep {
+                delay: Duration::ZERO,
+                fail: false,
+            },
+        );
+        assert!(events.is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+use super::{BoxedService, Layer, Service};
+use crate::ServerFnError;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+type Hook = Arc<dyn Fn(LayerEvent) + Send + Sync>;
+
+type NamedLayer<Req, Res> = (&'static str, Box<dyn Layer<Req, Res>>);
+
+/// A request entering or leaving one of the layers of a [`LayerStack`], as reported
+/// in debug mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerEvent {
+    /// The layer with this name has been called with a request.
+    Enter(&'static str),
+    /// The layer with this name has produced its response.
+    Exit(&'static str),
+}
+
+impl fmt::Display for LayerEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayerEvent::Enter(name) => write!(f, "enter `{name}`"),
+            LayerEvent::Exit(name) => write!(f, "exit `{name}`"),
+        }
+    }
+}
+
+/// Builds a single [`Layer`] out of several named layers, so that the order they
+/// run in can be printed and, in debug mode, traced.
+///
+/// Layers are listed from the outside in: the first one pushed sees each request
+/// first and each response last.
+///
+/// ```rust,ignore
+/// fn layers() -> ComposedLayers<Request<Body>, Response<Body>> {
+///     LayerStack::new()
+///         .push("auth", Auth::bearer(verify_token))
+///         .push("trace", Trace::new())
+///         .debug()
+///         .build()
+/// }
+/// println!("{}", layers()); // auth -> trace
+///
+/// #[server]
+/// #[middleware(layers())]
+/// pub async fn delete_post(id: u32) -> Result<(), ServerFnError> {
+///     // ...
+/// }
+/// ```
+pub struct LayerStack<Req, Res> {
+    layers: Vec<NamedLayer<Req, Res>>,
+    hook: Option<Hook>,
+}
+
+impl<Req, Res> Default for LayerStack<Req, Res> {
+    fn default() -> Self {
+        Self {
+            layers: Vec::new(),
+            hook: None,
+        }
+    }
+}
+
+impl<Req, Res> LayerStack<Req, Res> {
+    /// Creates an empty stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `layer` inside the layers pushed so far.
+    pub fn push(
+        mut self,
+        name: &'static str,
+        layer: impl Layer<Req, Res>,
+    ) -> Self {
+        self.layers.push((name, Box::new(layer)));
+        self
+    }
+
+    /// Logs each request entering and leaving every layer with `tracing`.
+    ///
+    /// This does nothing unless the `tracing` feature is enabled.
+    pub fn debug(self) -> Self {
+        #[cfg(feature = "tracing")]
+        let this = self.debug_with(|event| {
+            tracing::debug!(%event, "server function layer");
+        });
+        #[cfg(not(feature = "tracing"))]
+        let this = self;
+        this
+    }
+
+    /// Calls `hook` with each request entering and leaving every layer.
+    pub fn debug_with(
+        mut self,
+        hook: impl Fn(LayerEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Composes the layers into a single [`Layer`].
+    pub fn build(self) -> ComposedLayers<Req, Res> {
+        ComposedLayers {
+            layers: self.layers.into(),
+            hook: self.hook,
+        }
+    }
+}
+
+impl<Req, Res> fmt::Display for LayerStack<Req, Res> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_order(f, &self.layers)
+    }
+}
+
+fn write_order<Req, Res>(
+    f: &mut fmt::Formatter<'_>,
+    layers: &[NamedLayer<Req, Res>],
+) -> fmt::Result {
+    for (i, (name, _)) in layers.iter().enumerate() {
+        if i > 0 {
+            f.write_str(" -> ")?;
+        }
+        f.write_str(name)?;
+    }
+    Ok(())
+}
+
+/// The layers of a [`LayerStack`], composed into a single [`Layer`].
+///
+/// Its [`Display`](fmt::Display) implementation prints the names of the layers from
+/// the outside in, like `auth -> trace`.
+pub struct ComposedLayers<Req, Res> {
+    layers: Arc<[NamedLayer<Req, Res>]>,
+    hook: Option<Hook>,
+}
+
+impl<Req, Res> ComposedLayers<Req, Res> {
+    /// The names of the layers, from the outside in.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.layers.iter().map(|(name, _)| *name)
+    }
+}
+
+impl<Req, Res> Clone for ComposedLayers<Req, Res> {
+    fn clone(&self) -> Self {
+        Self {
+            layers: Arc::clone(&self.layers),
+            hook: self.hook.clone(),
+        }
+    }
+}
+
+impl<Req, Res> fmt::Display for ComposedLayers<Req, Res> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_order(f, &self.layers)
+    }
+}
+
+impl<Req, Res> fmt::Debug for ComposedLayers<Req, Res> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl<Req, Res> Layer<Req, Res> for ComposedLayers<Req, Res>
+where
+    Req: 'static,
+    Res: Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        self.layers
+            .iter()
+            .rev()
+            .fold(inner, |service, (name, layer)| {
+                let service = layer.layer(service);
+                match &self.hook {
+                    Some(hook) => BoxedService::new(Probe {
+                        name,
+                        hook: Arc::clone(hook),
+                        inner: service,
+                    }),
+                    None => service,
+                }
+            })
+    }
+}
+
+/// Reports a request entering and leaving a layer.
+struct Probe<Req, Res> {
+    name: &'static str,
+    hook: Hook,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> Service<Req, Res> for Probe<Req, Res>
+where
+    Res: Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        (self.hook)(LayerEvent::Enter(self.name));
+        let inner = self.inner.0.run(req);
+        let (name, hook) = (self.name, Arc::clone(&self.hook));
+        Box::pin(async move {
+            let res = inner.await;
+            hook(LayerEvent::Exit(name));
+            res
+        })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{LayerEvent, LayerStack};
+    use crate::middleware::{layer_fn, service_fn, BoxedService, Layer};
+    use axum::body::Body;
+    use http::{Request, Response};
+    use std::sync::{Arc, Mutex};
+
+    /// A layer that does nothing but pass requests on.
+    fn noop() -> impl Layer<Request<Body>, Response<Body>> {
+        layer_fn(|inner: BoxedService<Request<Body>, Response<Body>>| inner)
+    }
+
+    #[test]
+    fn prints_composition_order() {
+        let stack = LayerStack::new()
+            .push("auth", noop())
+            .push("rate_limit", noop())
+            .push("trace", noop());
+        assert_eq!(stack.to_string(), "auth -> rate_limit -> trace");
+        let stack = stack.build();
+        assert_eq!(stack.to_string(), "auth -> rate_limit -> trace");
+        assert_eq!(
+            stack.names().collect::<Vec<_>>(),
+            ["auth", "rate_limit", "trace"]
+        );
+    }
+
+    #[tokio::test]
+    async fn debug_mode_logs_outermost_first() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let stack = LayerStack::new()
+            .push("auth", noop())
+            .push("trace", noop())
+            .debug_with({
+                let events = Arc::clone(&events);
+                move |event| events.lock().unwrap().push(event)
+            })
+            .build();
+        let mut service = stack.layer(service_fn(|_req| async move {
+            Response::new(Body::empty())
+        }));
+        service
+            .0
+            .run(Request::post("/api/hello").body(Body::empty()).unwrap())
+            .await;
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                LayerEvent::Enter("auth"),
+                LayerEvent::Enter("trace"),
+                LayerEvent::Exit("trace"),
+                LayerEvent::Exit("auth"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use super::{
+    deadline::run_until, BoxedService, Deadline, Layer, RequestExtensionsMut,
+    RequestPath, ResponseStatus, Service,
+};
+use crate::{error::NoCustomError, ServerFnError};
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// A middleware [`Layer`](super::Layer) that limits how long a server function may run.
+///
+/// If the inner service has not responded within the given duration, the request is
+/// cancelled and an error response is returned with a `504 Gateway Timeout` status.
+///
+/// The time limit is also set as the [`Deadline`] of the request, so the server
+/// function can see how much of it is left. If an outer layer has already set an
+/// earlier deadline, that one is kept, and the request is cancelled when it passes.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(Timeout::new(Duration::from_secs(5)))]
+/// pub async fn slow_query() -> Result<Vec<Row>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout {
+    duration: Duration,
+}
+
+impl Timeout {
+    /// Creates a new timeout layer with the given duration.
+    pub fn new(duration: Duration) -> Self {
+        Self { duration }
+    }
+
+    pub(super) fn error() -> ServerFnError {
+        ServerFnError::ServerError("timeout".into())
+    }
+}
+
+struct TimeoutService<Req, Res> {
+    duration: Duration,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for Timeout
+where
+    Req: RequestExtensionsMut + RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + ResponseStatus + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(TimeoutService {
+            duration: self.duration,
+            inner,
+        })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for TimeoutService<Req, Res>
+where
+    Req: RequestExtensionsMut + RequestPath,
+    Res: crate::response::Res<NoCustomError> + ResponseStatus + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        run_until(&mut self.inner, req, Deadline::after(self.duration))
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+/// A middleware [`Layer`](super::Layer) that limits how long a server function may
+/// run, with a separate limit for each path.
+///
+/// This works like [`Timeout`], but looks up the time limit by the path of each
+/// request, falling back to a default for paths that are not listed. That way one
+/// layer can be shared between server functions with very different needs.
+///
+/// ```rust,ignore
+/// fn timeouts() -> PerPathTimeout {
+///     PerPathTimeout::new(
+///         Duration::from_secs(5),
+///         [("/api/export_report", Duration::from_secs(120))],
+///     )
+/// }
+///
+/// #[server(endpoint = "export_report")]
+/// #[middleware(timeouts())]
+/// pub async fn export_report() -> Result<Vec<u8>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PerPathTimeout {
+    default: Duration,
+    paths: Arc<HashMap<String, Duration>>,
+}
+
+impl PerPathTimeout {
+    /// Creates a new timeout layer with a duration for each of the given paths, and
+    /// `default` for all others.
+    pub fn new<P>(
+        default: Duration,
+        paths: impl IntoIterator<Item = (P, Duration)>,
+    ) -> Self
+    where
+        P: Into<String>,
+    {
+        Self {
+            default,
+            paths: Arc::new(
+                paths
+                    .into_iter()
+                    .map(|(path, duration)| (path.into(), duration))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The time limit for requests to `path`.
+    fn duration(&self, path: &str) -> Duration {
+        self.paths.get(path).copied().unwrap_or(self.default)
+    }
+}
+
+struct PerPathTimeoutService<Req, Res> {
+    timeout: PerPathTimeout,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for PerPathTimeout
+where
+    Req: RequestExtensionsMut + RequestPath + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + ResponseStatus + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(PerPathTimeoutService {
+            timeout: self.clone(),
+            inner,
+        })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for PerPathTimeoutService<Req, Res>
+where
+    Req: RequestExtensionsMut + RequestPath,
+    Res: crate::response::Res<NoCustomError> + ResponseStatus + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let duration = self.timeout.duration(req.path());
+        run_until(&mut self.inner, req, Deadline::after(duration))
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{PerPathTimeout, Timeout};
+    use crate::middleware::{BoxedService, Layer, Service};
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::{future::Future, pin::Pin, time::Duration};
+
+    struct Sleep(Duration);
+
+    impl Service<Request<Body>, Response<Body>> for Sleep {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let duration = self.0;
+            Box::pin(async move {
+                tokio::time::sleep(duration).await;
+                Response::new(Body::from("done"))
+            })
+        }
+    }
+
+    async fn run(handler: Duration) -> (StatusCode, String) {
+        let mut service = Timeout::new(Duration::from_secs(1))
+            .layer(BoxedService::new(Sleep(handler)));
+        let req = Request::post("/api/slow").body(Body::empty()).unwrap();
+        let res = service.0.run(req).await;
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fast_handler_passes_through() {
+        let (status, body) = run(Duration::from_millis(10)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "done");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_handler_times_out() {
+        let (status, body) = run(Duration::from_secs(10)).await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(body, "ServerError|timeout");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn each_path_gets_its_own_limit() {
+        let layer = PerPathTimeout::new(
+            Duration::from_secs(1),
+            [
+                ("/api/quick", Duration::from_millis(100)),
+                ("/api/export", Duration::from_secs(30)),
+            ],
+        );
+        let mut service =
+            layer.layer(BoxedService::new(Sleep(Duration::from_secs(5))));
+        let mut call = |path: &str| {
+            let req = Request::post(path).body(Body::empty()).unwrap();
+            service.0.run(req)
+        };
+
+        assert_eq!(
+            call("/api/quick").await.status(),
+            StatusCode::GATEWAY_TIMEOUT
+        );
+        assert_eq!(call("/api/export").await.status(), StatusCode::OK);
+        // the default applies to paths that are not listed
+        assert_eq!(
+            call("/api/other").await.status(),
+            StatusCode::GATEWAY_TIMEOUT
+        );
+    }
+}
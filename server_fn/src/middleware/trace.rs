@@ -0,0 +1,234 @@
+use super::{
+    BoxedService, Layer, RequestMethod, RequestPath, ResponseStatus, Service,
+};
+use crate::ServerFnError;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Instant,
+};
+use tracing::{field, Instrument, Level};
+
+/// A middleware [`Layer`] that wraps each server function call in a [`tracing`] span.
+///
+/// The span is named `server_fn`, and records the `path` and `method` of the request.
+/// Once the inner service has responded, the response `status` and the time it took to
+/// respond (`latency_ms`) are recorded on the span and emitted as an event.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(TraceLayer::new().level(Level::DEBUG))]
+/// pub async fn load_user(id: u32) -> Result<User, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TraceLayer {
+    level: Level,
+}
+
+impl Default for TraceLayer {
+    fn default() -> Self {
+        Self { level: Level::INFO }
+    }
+}
+
+impl TraceLayer {
+    /// Creates a new trace layer, which records spans at the `INFO` level.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the level of the spans and events this layer produces.
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+// `tracing` callsites need a constant level, so we have to pick one of the
+// five macros at runtime.
+macro_rules! with_level {
+    ($level:expr, $mac:ident!($($args:tt)*)) => {
+        match $level {
+            Level::ERROR => tracing::$mac!(Level::ERROR, $($args)*),
+            Level::WARN => tracing::$mac!(Level::WARN, $($args)*),
+            Level::INFO => tracing::$mac!(Level::INFO, $($args)*),
+            Level::DEBUG => tracing::$mac!(Level::DEBUG, $($args)*),
+            _ => tracing::$mac!(Level::TRACE, $($args)*),
+        }
+    };
+}
+
+struct TraceService<Req, Res> {
+    level: Level,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for TraceLayer
+where
+    Req: RequestPath + RequestMethod + Send + 'static,
+    Res: ResponseStatus + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(TraceService {
+            level: self.level,
+            inner,
+        })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for TraceService<Req, Res>
+where
+    Req: RequestPath + RequestMethod,
+    Res: ResponseStatus + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let level = self.level;
+        let span = with_level!(
+            level,
+            span!(
+                "server_fn",
+                path = req.path(),
+                method = %RequestMethod::method(&req),
+                status = field::Empty,
+                latency_ms = field::Empty,
+            )
+        );
+        let start = Instant::now();
+        let inner = span.in_scope(|| self.inner.0.run(req));
+        let fut = {
+            let span = span.clone();
+            async move {
+                let res = inner.await;
+                let status = res.status().as_u16();
+                let latency_ms = start.elapsed().as_millis() as u64;
+                span.record("status", status);
+                span.record("latency_ms", latency_ms);
+                with_level!(
+                    level,
+                    event!(status, latency_ms, "server function finished")
+                );
+                res
+            }
+        };
+        Box::pin(fut.instrument(span))
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::TraceLayer;
+    use crate::middleware::{BoxedService, Layer, Service};
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+    use std::{
+        collections::HashMap,
+        fmt::Debug,
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Level, Subscriber,
+    };
+    use tracing_subscriber::{
+        layer::Context, prelude::*, registry::LookupSpan, Registry,
+    };
+
+    #[derive(Debug, Default)]
+    struct CapturedSpan {
+        name: &'static str,
+        level: Option<Level>,
+        fields: HashMap<&'static str, String>,
+    }
+
+    impl Visit for CapturedSpan {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.fields.insert(field.name(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.fields.insert(field.name(), value.to_string());
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<(Id, CapturedSpan)>>>);
+
+    impl<S> tracing_subscriber::Layer<S> for Capture
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &Attributes<'_>,
+            id: &Id,
+            _: Context<'_, S>,
+        ) {
+            let mut span = CapturedSpan {
+                name: attrs.metadata().name(),
+                level: Some(*attrs.metadata().level()),
+                ..Default::default()
+            };
+            attrs.record(&mut span);
+            self.0.lock().unwrap().push((id.clone(), span));
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, _: Context<'_, S>) {
+            let mut spans = self.0.lock().unwrap();
+            if let Some((_, span)) = spans.iter_mut().find(|(i, _)| i == id) {
+                values.record(span);
+            }
+        }
+    }
+
+    struct Created;
+
+    impl Service<Request<Body>, Response<Body>> for Created {
+        fn run(
+            &mut self,
+            _req: Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            Box::pin(async move {
+                let mut res = Response::new(Body::empty());
+                *res.status_mut() = StatusCode::CREATED;
+                res
+            })
+        }
+    }
+
+    #[test]
+    fn records_span_with_request_and_response_fields() {
+        let capture = Capture::default();
+        let subscriber = Registry::default().with(capture.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let mut service = TraceLayer::new()
+                .level(Level::DEBUG)
+                .layer(BoxedService::new(Created));
+            let req =
+                Request::post("/api/hello?x=1").body(Body::empty()).unwrap();
+            futures::executor::block_on(service.0.run(req));
+        });
+
+        let spans = capture.0.lock().unwrap();
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0].1;
+        assert_eq!(span.name, "server_fn");
+        assert_eq!(span.level, Some(Level::DEBUG));
+        assert_eq!(span.fields["path"], "/api/hello");
+        assert_eq!(span.fields["method"], "POST");
+        assert_eq!(span.fields["status"], "201");
+        assert!(span.fields.contains_key("latency_ms"));
+    }
+}
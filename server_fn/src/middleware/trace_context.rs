@@ -0,0 +1,386 @@
+use super::{
+    BoxedService, Layer, RequestExtensionsMut, RequestHeaders, Service,
+};
+use crate::{error::NoCustomError, request::RequestExtensions, ServerFnError};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tracing::Instrument;
+
+const TRACEPARENT: &str = "traceparent";
+const TRACESTATE: &str = "tracestate";
+
+/// The longest `tracestate` header that is propagated.
+const MAX_STATE_LEN: usize = 512;
+
+/// A middleware [`Layer`] that propagates the
+/// [W3C Trace Context](https://www.w3.org/TR/trace-context/) of each request.
+///
+/// The `traceparent` header of the request is parsed into a [`TraceParent`], which
+/// keeps its trace ID and gives this server a new span ID, with the caller's span as
+/// its parent. If there is no valid `traceparent`, a new trace is started. A
+/// `tracestate` header is kept along with a valid `traceparent`.
+///
+/// The inner service runs in a `trace_context` [`tracing`] span, a child of the
+/// current span, which records the `trace_id`, `span_id` and `parent_id`. The
+/// context is inserted into the request extensions, so that the server function can
+/// pass it on to the services it calls, and written onto the response as
+/// `traceparent` and `tracestate` headers. Both are also listed in
+/// `Access-Control-Expose-Headers`, so that a page on another origin can read them.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(TraceContext::new())]
+/// pub async fn checkout(cart: Cart) -> Result<OrderId, ServerFnError> {
+///     let context = TraceParent::current().unwrap();
+///     payments
+///         .post("/charge")
+///         .header("traceparent", context.to_string())
+///         .send()
+///         .await?;
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TraceContext;
+
+impl TraceContext {
+    /// Creates a new layer that propagates the trace context.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// The W3C trace context of a request, as set by a [`TraceContext`] layer.
+///
+/// Server functions can take it as an
+/// [`Extension<TraceParent>`](crate::request::Extension) argument, or read it with
+/// [`TraceParent::current`]. It is displayed as the `traceparent` header to send
+/// with calls made on behalf of the request, which name this server's span as their
+/// parent.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TraceParent {
+    trace_id: String,
+    span_id: String,
+    parent_id: Option<String>,
+    flags: u8,
+    state: Option<String>,
+}
+
+impl TraceParent {
+    /// The trace context of the request currently being handled by a server
+    /// function, or `None` if it has none or if called outside of a server function.
+    pub fn current() -> Option<Self> {
+        RequestExtensions::current()?.get()
+    }
+
+    /// Continues the trace of a `traceparent` header, or starts a new one if the
+    /// header is missing or invalid.
+    fn from_headers(parent: Option<&str>, state: Option<&str>) -> Self {
+        match parent.and_then(parse) {
+            Some((trace_id, parent_id, flags)) => Self {
+                trace_id: trace_id.to_owned(),
+                span_id: new_span_id(),
+                parent_id: Some(parent_id.to_owned()),
+                flags,
+                state: state
+                    .map(str::trim)
+                    .filter(|state| is_valid_state(state))
+                    .map(ToOwned::to_owned),
+            },
+            None => Self {
+                trace_id: uuid::Uuid::new_v4().simple().to_string(),
+                span_id: new_span_id(),
+                parent_id: None,
+                // a new trace is recorded here, so it is sampled
+                flags: 0x01,
+                state: None,
+            },
+        }
+    }
+
+    /// The ID of the whole trace, as 32 lowercase hex digits.
+    pub fn trace_id(&self) -> &str {
+        &self.trace_id
+    }
+
+    /// The ID of the span of this server, as 16 lowercase hex digits.
+    pub fn span_id(&self) -> &str {
+        &self.span_id
+    }
+
+    /// The ID of the caller's span, if the request continued a trace.
+    pub fn parent_id(&self) -> Option<&str> {
+        self.parent_id.as_deref()
+    }
+
+    /// Whether the caller asked for the trace to be recorded.
+    pub fn sampled(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    /// The `tracestate` header that came with the request, if any.
+    pub fn tracestate(&self) -> Option<&str> {
+        self.state.as_deref()
+    }
+}
+
+impl fmt::Display for TraceParent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "00-{}-{}-{:02x}",
+            self.trace_id, self.span_id, self.flags
+        )
+    }
+}
+
+/// Splits a `traceparent` header into its trace ID, parent ID and flags.
+fn parse(value: &str) -> Option<(&str, &str, u8)> {
+    let mut parts = value.trim().split('-');
+    let version = parts.next()?;
+    let trace_id = parts.next()?;
+    let parent_id = parts.next()?;
+    let flags = parts.next()?;
+    // later versions may add more fields, but version 00 has exactly four
+    let valid_version = is_hex(version, 2)
+        && version != "ff"
+        && (version != "00" || parts.next().is_none());
+    let valid = valid_version
+        && is_hex(trace_id, 32)
+        && is_hex(parent_id, 16)
+        && is_hex(flags, 2)
+        && !is_zero(trace_id)
+        && !is_zero(parent_id);
+    if !valid {
+        return None;
+    }
+    let flags = u8::from_str_radix(flags, 16).ok()?;
+    Some((trace_id, parent_id, flags))
+}
+
+fn is_hex(value: &str, len: usize) -> bool {
+    value.len() == len
+        && value
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn is_zero(value: &str) -> bool {
+    value.bytes().all(|b| b == b'0')
+}
+
+fn is_valid_state(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_STATE_LEN
+        && value.bytes().all(|b| b == b' ' || b.is_ascii_graphic())
+}
+
+fn new_span_id() -> String {
+    let mut id = uuid::Uuid::new_v4().simple().to_string();
+    id.truncate(16);
+    id
+}
+
+struct TraceContextService<Req, Res> {
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Req, Res> Layer<Req, Res> for TraceContext
+where
+    Req: RequestHeaders + RequestExtensionsMut + Send + 'static,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res> {
+        BoxedService::new(TraceContextService { inner })
+    }
+}
+
+impl<Req, Res> Service<Req, Res> for TraceContextService<Req, Res>
+where
+    Req: RequestHeaders + RequestExtensionsMut,
+    Res: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        mut req: Req,
+    ) -> Pin<Box<dyn Future<Output = Res> + Send>> {
+        let context = TraceParent::from_headers(
+            req.header(TRACEPARENT),
+            req.header(TRACESTATE),
+        );
+        req.insert_extension(context.clone());
+        let span = tracing::info_span!(
+            "trace_context",
+            trace_id = context.trace_id(),
+            span_id = context.span_id(),
+            parent_id = context.parent_id(),
+        );
+        let inner = span.in_scope(|| self.inner.0.run(req));
+        Box::pin(
+            async move {
+                let mut res = inner.await;
+                res.insert_header(TRACEPARENT, &context.to_string());
+                match context.tracestate() {
+                    Some(state) => res.insert_header(TRACESTATE, state),
+                    None => res.remove_header(TRACESTATE),
+                }
+                res.append_header(
+                    "access-control-expose-headers",
+                    "traceparent, tracestate",
+                );
+                res
+            }
+            .instrument(span),
+        )
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{TraceContext, TraceParent};
+    use crate::middleware::{service_fn, Layer};
+    use axum::body::Body;
+    use http::{Request, Response};
+    use std::{
+        collections::HashMap,
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id},
+        Subscriber,
+    };
+    use tracing_subscriber::{
+        layer::Context, prelude::*, registry::LookupSpan, Registry,
+    };
+
+    const INCOMING: &str =
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    /// The fields of the `trace_context` spans that were created.
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<HashMap<&'static str, String>>>>);
+
+    struct Fields<'a>(&'a mut HashMap<&'static str, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.insert(field.name(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name(), value.to_string());
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for Capture
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &Attributes<'_>,
+            _: &Id,
+            _: Context<'_, S>,
+        ) {
+            if attrs.metadata().name() == "trace_context" {
+                let mut fields = HashMap::new();
+                attrs.record(&mut Fields(&mut fields));
+                self.0.lock().unwrap().push(fields);
+            }
+        }
+    }
+
+    /// Runs a request with the given headers through the layer, returning the
+    /// fields of its span, the context seen by the handler, and the response.
+    fn run(
+        headers: &[(&str, &str)],
+    ) -> (HashMap<&'static str, String>, TraceParent, Response<Body>) {
+        let capture = Capture::default();
+        let seen = Arc::new(Mutex::new(None));
+        let subscriber = Registry::default().with(capture.clone());
+        let res = tracing::subscriber::with_default(subscriber, || {
+            let mut service = TraceContext::new().layer(service_fn({
+                let seen = Arc::clone(&seen);
+                move |req: Request<Body>| {
+                    *seen.lock().unwrap() =
+                        req.extensions().get::<TraceParent>().cloned();
+                    async move { Response::new(Body::empty()) }
+                }
+            }));
+            let mut req = Request::post("/api/checkout");
+            for (name, value) in headers {
+                req = req.header(*name, *value);
+            }
+            futures::executor::block_on(
+                service.0.run(req.body(Body::empty()).unwrap()),
+            )
+        });
+        let mut spans = capture.0.lock().unwrap();
+        assert_eq!(spans.len(), 1);
+        let context = seen.lock().unwrap().take().unwrap();
+        (spans.remove(0), context, res)
+    }
+
+    #[test]
+    fn continues_incoming_trace_and_echoes_it() {
+        let (span, context, res) =
+            run(&[("traceparent", INCOMING), ("tracestate", "vendor=abc")]);
+
+        assert_eq!(span["trace_id"], "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(span["parent_id"], "00f067aa0ba902b7");
+        assert_eq!(span["span_id"], context.span_id());
+        assert_ne!(context.span_id(), "00f067aa0ba902b7");
+        assert!(context.sampled());
+
+        let headers = res.headers();
+        assert_eq!(
+            headers["traceparent"],
+            format!(
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-{}-01",
+                context.span_id()
+            )
+        );
+        assert_eq!(headers["tracestate"], "vendor=abc");
+        assert_eq!(
+            headers["access-control-expose-headers"],
+            "traceparent, tracestate"
+        );
+    }
+
+    #[test]
+    fn starts_new_trace_without_valid_traceparent() {
+        let invalid = [
+            "garbage",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        ];
+        for traceparent in invalid {
+            let (span, context, res) = run(&[
+                ("traceparent", traceparent),
+                ("tracestate", "vendor=abc"),
+            ]);
+            assert_ne!(context.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+            assert_eq!(context.trace_id().len(), 32);
+            assert_eq!(span["trace_id"], context.trace_id());
+            assert!(!span.contains_key("parent_id"));
+            assert_eq!(context.tracestate(), None);
+            assert_eq!(res.headers()["traceparent"], context.to_string());
+            assert!(!res.headers().contains_key("tracestate"));
+        }
+    }
+}
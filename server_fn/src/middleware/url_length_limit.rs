@@ -0,0 +1,123 @@
+use super::{BoxedService, Layer, RequestPath, Service};
+use crate::{error::NoCustomError, request::Req, ServerFnError};
+use http::StatusCode;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A middleware [`Layer`] that rejects requests whose URL is longer than a limit,
+/// with `414 URI Too Long`.
+///
+/// The length is that of the path and the query string, as sent in the request
+/// line. This mostly matters for server functions that take their arguments in the
+/// query string, like those with [`GetUrl`](crate::codec::GetUrl), whose clients can
+/// send long arguments in the body instead with
+/// [`ClientConfig::with_max_url_length`](crate::client::ClientConfig::with_max_url_length).
+///
+/// ```rust,ignore
+/// #[server(input = GetUrl)]
+/// #[middleware(UrlLengthLimit::new(2048))]
+/// pub async fn search(query: String) -> Result<Vec<Post>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct UrlLengthLimit {
+    max: usize,
+}
+
+impl UrlLengthLimit {
+    /// Creates a new layer that allows URLs of up to `max` bytes.
+    pub fn new(max: usize) -> Self {
+        Self { max }
+    }
+
+    fn error(&self) -> ServerFnError {
+        ServerFnError::ServerError(format!(
+            "URI too long, the limit is {} bytes",
+            self.max
+        ))
+    }
+}
+
+struct UrlLengthLimitService<Req, Res> {
+    limit: UrlLengthLimit,
+    inner: BoxedService<Req, Res>,
+}
+
+impl<Rq, Rs> Layer<Rq, Rs> for UrlLengthLimit
+where
+    Rq: Req<NoCustomError> + RequestPath + Send + 'static,
+    Rs: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Rq, Rs>) -> BoxedService<Rq, Rs> {
+        BoxedService::new(UrlLengthLimitService {
+            limit: *self,
+            inner,
+        })
+    }
+}
+
+impl<Rq, Rs> Service<Rq, Rs> for UrlLengthLimitService<Rq, Rs>
+where
+    Rq: Req<NoCustomError> + RequestPath,
+    Rs: crate::response::Res<NoCustomError> + Send + 'static,
+{
+    fn run(&mut self, req: Rq) -> Pin<Box<dyn Future<Output = Rs> + Send>> {
+        let len = req.path().len()
+            + req.as_query().map_or(0, |query| "?".len() + query.len());
+        if len <= self.limit.max {
+            return self.inner.0.run(req);
+        }
+        let mut res = Rs::error_response(req.path(), &self.limit.error());
+        res.set_status(StatusCode::URI_TOO_LONG);
+        Box::pin(async move { res })
+    }
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ServerFnError>> {
+        self.inner.0.poll_ready(cx)
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::UrlLengthLimit;
+    use crate::middleware::{service_fn, Layer};
+    use axum::body::Body;
+    use http::{Request, Response, StatusCode};
+
+    async fn call(uri: &str) -> StatusCode {
+        let mut service = UrlLengthLimit::new(32).layer(service_fn(
+            |_req: Request<Body>| async move { Response::new(Body::empty()) },
+        ));
+        let req = Request::get(uri).body(Body::empty()).unwrap();
+        service.0.run(req).await.status()
+    }
+
+    #[tokio::test]
+    async fn allows_urls_up_to_the_limit() {
+        assert_eq!(call("/api/search").await, StatusCode::OK);
+        // exactly 32 bytes
+        assert_eq!(
+            call("/api/search?query=rusty+and+wasm").await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_longer_urls() {
+        assert_eq!(
+            call("/api/search?query=rusty+and+wasm+").await,
+            StatusCode::URI_TOO_LONG
+        );
+        assert_eq!(
+            call("/api/a/very/long/path/without/a/query").await,
+            StatusCode::URI_TOO_LONG
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Describes the registered server functions as an [OpenAPI 3.1] document.
+//!
+//! With the `openapi` feature enabled, the `#[server]` macro derives a
+//! [`JsonSchema`] for the arguments of each server function, and requires one for
+//! its return type. [`openapi`] combines them with the [`registry`](crate::registry)
+//! into a document that can be served to tools like Swagger UI.
+//!
+//! ```rust,ignore
+//! #[derive(Serialize, Deserialize, JsonSchema)]
+//! pub struct Post {
+//!     title: String,
+//!     body: String,
+//! }
+//!
+//! #[server]
+//! pub async fn get_post(id: u32) -> Result<Post, ServerFnError> {
+//!     // ...
+//! }
+//!
+//! async fn openapi_json() -> Json<serde_json::Value> {
+//!     Json(server_fn::openapi::openapi())
+//! }
+//! ```
+//!
+//! Arguments sent with a non-serde encoding, and responses that are streamed or
+//! use a binary-only format, are described by their MIME type alone.
+//!
+//! [OpenAPI 3.1]: https://spec.openapis.org/oas/v3.1.0
+
+use crate::registry;
+use http::Method;
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::Schema,
+    JsonSchema,
+};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Produces the JSON schema for a type.
+pub type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+/// The schemas describing a registered server function, as submitted by the
+/// `#[server]` macro.
+#[derive(Debug, Clone, Copy)]
+pub struct ServerFnSchema {
+    path: &'static str,
+    args: Option<SchemaFn>,
+    output: Option<SchemaFn>,
+    streaming: bool,
+}
+
+impl ServerFnSchema {
+    /// Collects the schemas of a server function.
+    pub const fn new(
+        path: &'static str,
+        args: Option<SchemaFn>,
+        output: Option<SchemaFn>,
+        streaming: bool,
+    ) -> Self {
+        Self {
+            path,
+            args,
+            output,
+            streaming,
+        }
+    }
+}
+
+inventory::collect!(ServerFnSchema);
+
+/// The schema of the arguments to a server function, inlined so that each of its
+/// fields can be listed as a query parameter.
+#[doc(hidden)]
+pub fn args_schema<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    T::json_schema(gen)
+}
+
+/// The schema of the output of a server function, referring to a shared component
+/// where possible.
+#[doc(hidden)]
+pub fn schema_for<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    gen.subschema_for::<T>()
+}
+
+/// Builds an OpenAPI 3.1 document describing every server function in the
+/// [`registry`](crate::registry).
+///
+/// Each function appears under its path, with its arguments as a request body or,
+/// for `GET` requests, as query parameters. Functions that stream their request or
+/// response are marked with `x-streaming: true`. The `info` object only contains
+/// placeholders, which can be overwritten on the returned value.
+pub fn openapi() -> Value {
+    let mut gen = SchemaSettings::draft2019_09()
+        .with(|settings| {
+            settings.definitions_path = "#/components/schemas/".into();
+            settings.meta_schema = None;
+        })
+        .into_generator();
+
+    let schemas = inventory::iter::<ServerFnSchema>
+        .into_iter()
+        .map(|schema| (schema.path, schema))
+        .collect::<HashMap<_, _>>();
+    let mut functions = registry().collect::<Vec<_>>();
+    functions.sort_by_key(|meta| meta.path);
+
+    let mut paths = Map::new();
+    for meta in functions {
+        let schema = schemas.get(meta.path);
+        let args = schema
+            .and_then(|schema| schema.args)
+            .map(|args| to_value(args(&mut gen)));
+        let output = schema
+            .and_then(|schema| schema.output)
+            .map(|output| to_value(output(&mut gen)));
+
+        let mut operation = Map::new();
+        if meta.method == Method::GET {
+            operation.insert(
+                "parameters".into(),
+                query_parameters(args.as_ref()).into(),
+            );
+        } else {
+            operation.insert(
+                "requestBody".into(),
+                json!({
+                    "required": true,
+                    "content": media_type(meta.input_encoding, args),
+                }),
+            );
+        }
+        operation.insert(
+            "responses".into(),
+            json!({
+                "200": {
+                    "description": "The output of the server function.",
+                    "content": media_type(meta.output_encoding, output),
+                },
+                "default": {
+                    "description": "An error returned by the server function.",
+                    "content": {
+                        "text/plain": { "schema": { "type": "string" } },
+                    },
+                },
+            }),
+        );
+        if schema.is_some_and(|schema| schema.streaming) {
+            operation.insert("x-streaming".into(), true.into());
+        }
+
+        let method = meta.method.as_str().to_ascii_lowercase();
+        paths
+            .entry(meta.path)
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .expect("path items are objects")
+            .insert(method, operation.into());
+    }
+
+    let components = gen
+        .take_definitions()
+        .into_iter()
+        .map(|(name, schema)| (name, to_value(schema)))
+        .collect::<Map<_, _>>();
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Server functions",
+            "version": "0.0.0",
+        },
+        "paths": paths,
+        "components": {
+            "schemas": components,
+        },
+    })
+}
+
+fn to_value(schema: Schema) -> Value {
+    serde_json::to_value(schema).expect("JSON schemas can be serialized")
+}
+
+/// A media type object for `content_type`, with `schema` if there is one.
+fn media_type(content_type: &str, schema: Option<Value>) -> Value {
+    let media_type = match schema {
+        Some(schema) => json!({ "schema": schema }),
+        None => json!({}),
+    };
+    Value::Object(Map::from_iter([(content_type.to_string(), media_type)]))
+}
+
+/// Lists each property of an object schema as a query parameter.
+fn query_parameters(schema: Option<&Value>) -> Vec<Value> {
+    let Some(schema) = schema else {
+        return Vec::new();
+    };
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    schema["properties"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, schema)| {
+            json!({
+                "name": name,
+                "in": "query",
+                "required": required.contains(&name.as_str()),
+                "schema": schema,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{args_schema, query_parameters, schema_for, to_value};
+    use schemars::{gen::SchemaGenerator, JsonSchema};
+    use serde_json::json;
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct Search {
+        query: String,
+        page: Option<String>,
+    }
+
+    #[test]
+    fn fields_become_query_parameters() {
+        let mut gen = SchemaGenerator::default();
+        let schema = to_value(args_schema::<Search>(&mut gen));
+        assert_eq!(
+            query_parameters(Some(&schema)),
+            [
+                json!({
+                    "name": "page",
+                    "in": "query",
+                    "required": false,
+                    "schema": { "type": ["string", "null"] },
+                }),
+                json!({
+                    "name": "query",
+                    "in": "query",
+                    "required": true,
+                    "schema": { "type": "string" },
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn outputs_refer_to_components() {
+        let mut gen = SchemaGenerator::default();
+        let schema = to_value(schema_for::<Search>(&mut gen));
+        assert_eq!(schema, json!({ "$ref": "#/definitions/Search" }));
+        assert!(gen.definitions().contains_key("Search"));
+    }
+}
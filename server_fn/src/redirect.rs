@@ -1,3 +1,4 @@
+use crate::response::ResponseOptions;
 use std::sync::OnceLock;
 
 /// A custom header that can be set with any value to indicate
@@ -31,3 +32,127 @@ pub fn call_redirect_hook(loc: &str) {
         hook(loc)
     }
 }
+
+/// Redirects the client to `path` once the current server function has finished.
+///
+/// `path` can be an absolute URL or a path relative to the current page, and is
+/// sent to the client unchanged in the `Location` header. If the server function
+/// was called by a plain HTML `<form>`, the response is a `302 Found`. If it was
+/// called by the server function client, the status is left as is, so that the
+/// result can still be returned, and the [`REDIRECT_HEADER`] is set instead, which
+/// makes the client call the hook set with [`set_redirect_hook`].
+///
+/// If this is called more than once, the last path is used. It does nothing when
+/// called outside of a server function.
+///
+/// ```rust,ignore
+/// #[server]
+/// pub async fn login(user: String) -> Result<(), ServerFnError> {
+///     start_session(&user).await?;
+///     redirect("/dashboard");
+///     Ok(())
+/// }
+/// ```
+pub fn redirect(path: &str) {
+    if let Some(options) = ResponseOptions::current() {
+        options.set_redirect(path);
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::{redirect, RedirectHook, REDIRECT_HEADER};
+    use crate::{
+        client::Client,
+        codec::{Encoding, IntoReq, Json},
+        error::NoCustomError,
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use bytes::Bytes;
+    use http::{header, Request, Response, StatusCode};
+    use serde::{Deserialize, Serialize};
+    use std::sync::{Arc, Mutex};
+
+    /// Sends requests straight to [`Done`] on the server.
+    struct Loopback;
+
+    impl Client<NoCustomError> for Loopback {
+        type Request = Request<Bytes>;
+        type Response = Response<Body>;
+
+        async fn send(
+            req: Self::Request,
+        ) -> Result<Self::Response, ServerFnError> {
+            Ok(Done::run_on_server(req.map(Body::from)).await)
+        }
+    }
+
+    /// Redirects to `to`, but still returns a result.
+    #[derive(Serialize, Deserialize)]
+    struct Done {
+        to: String,
+    }
+
+    impl ServerFn for Done {
+        const PATH: &'static str = "/api/done";
+
+        type Client = Loopback;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = String;
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<String, ServerFnError> {
+            redirect(&self.to);
+            Ok("done".into())
+        }
+    }
+
+    const TARGETS: [&str; 2] = ["/dashboard", "https://example.com/a?b=c"];
+
+    #[tokio::test]
+    async fn form_submission_gets_found_status() {
+        for to in TARGETS {
+            let req = Request::post(Done::PATH)
+                .header(header::ACCEPT, "text/html")
+                .header(header::CONTENT_TYPE, Json::CONTENT_TYPE)
+                .body(Body::from(format!(r#"{{"to":"{to}"}}"#)))
+                .unwrap();
+            let res = Done::run_on_server(req).await;
+            assert_eq!(res.status(), StatusCode::FOUND);
+            assert_eq!(res.headers()[header::LOCATION], to);
+            assert!(!res.headers().contains_key(REDIRECT_HEADER));
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_calls_redirect_hook() {
+        for to in TARGETS {
+            let redirected = Arc::new(Mutex::new(None));
+            let hook: RedirectHook = Box::new({
+                let redirected = Arc::clone(&redirected);
+                move |loc| *redirected.lock().unwrap() = Some(loc.to_owned())
+            });
+            let req = <Done as IntoReq<Json, _, NoCustomError>>::into_req(
+                Done { to: to.into() },
+                Done::PATH,
+                Json::CONTENT_TYPE,
+            )
+            .unwrap();
+
+            let output = Done::run_on_client_with_req(req, Some(&hook))
+                .await
+                .unwrap();
+            assert_eq!(output, "done");
+            assert_eq!(redirected.lock().unwrap().as_deref(), Some(to));
+        }
+    }
+
+    #[test]
+    fn does_nothing_outside_server_functions() {
+        redirect("/elsewhere");
+    }
+}
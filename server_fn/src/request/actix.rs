@@ -1,7 +1,10 @@
-use crate::{error::ServerFnError, request::Req};
-use actix_web::{web::Payload, HttpRequest};
+use crate::{
+    error::ServerFnError,
+    request::{Req, RequestExtensions},
+};
+use actix_web::{dev, web::Payload, FromRequest, HttpRequest};
 use bytes::Bytes;
-use futures::Stream;
+use futures::{FutureExt, Stream, StreamExt};
 use send_wrapper::SendWrapper;
 use std::{borrow::Cow, future::Future};
 
@@ -10,7 +13,14 @@ use std::{borrow::Cow, future::Future};
 /// This uses a [`SendWrapper`] that allows the Actix `HttpRequest` type to be `Send`, but panics
 /// if it it is ever sent to another thread. Actix pins request handling to a single thread, so this
 /// is necessary to be compatible with traits that require `Send` but should never panic in actual use.
-pub struct ActixRequest(pub(crate) SendWrapper<(HttpRequest, Payload)>);
+pub struct ActixRequest(
+    pub(crate) SendWrapper<(HttpRequest, Payload)>,
+    /// The path set by a [`PathRewrite`](crate::middleware::PathRewrite) layer, if any.
+    ///
+    /// An `HttpRequest` can't be changed once it has been cloned, which Actix does
+    /// when extracting it, so the rewritten path is kept here instead.
+    pub(crate) Option<String>,
+);
 
 impl ActixRequest {
     /// Returns the raw Actix request, and its body.
@@ -18,6 +28,24 @@ impl ActixRequest {
         self.0.take()
     }
 
+    /// Returns a reference to the raw Actix request.
+    pub fn request(&self) -> &HttpRequest {
+        &self.0 .0
+    }
+
+    /// The path of the request, after any rewrites by middleware.
+    ///
+    /// Note that [`request`](Self::request) and [`take`](Self::take) return the
+    /// request with its original URI.
+    pub fn path(&self) -> &str {
+        self.1.as_deref().unwrap_or_else(|| self.0 .0.path())
+    }
+
+    pub(crate) fn with_path(mut self, path: Option<String>) -> Self {
+        self.1 = path;
+        self
+    }
+
     fn header(&self, name: &str) -> Option<Cow<'_, str>> {
         self.0
              .0
@@ -29,7 +57,18 @@ impl ActixRequest {
 
 impl From<(HttpRequest, Payload)> for ActixRequest {
     fn from(value: (HttpRequest, Payload)) -> Self {
-        ActixRequest(SendWrapper::new(value))
+        ActixRequest(SendWrapper::new(value), None)
+    }
+}
+
+impl From<(HttpRequest, dev::Payload)> for ActixRequest {
+    fn from((req, mut payload): (HttpRequest, dev::Payload)) -> Self {
+        // the Payload extractor is always ready, and never fails
+        let payload = Payload::from_request(&req, &mut payload)
+            .now_or_never()
+            .and_then(Result::ok)
+            .expect("Payload extractor should resolve immediately");
+        ActixRequest(SendWrapper::new((req, payload)), None)
     }
 }
 
@@ -53,6 +92,35 @@ where
         self.header("Referer")
     }
 
+    fn cookie_header(&self) -> Option<Cow<'_, str>> {
+        self.header("Cookie")
+    }
+
+    fn range_header(&self) -> Option<Cow<'_, str>> {
+        self.header("Range")
+    }
+
+    fn if_modified_since(&self) -> Option<Cow<'_, str>> {
+        if self.header("If-None-Match").is_some() {
+            return None;
+        }
+        self.header("If-Modified-Since")
+    }
+
+    fn host(&self) -> Option<Cow<'_, str>> {
+        self.header("Host").or_else(|| {
+            self.0
+                 .0
+                .uri()
+                .authority()
+                .map(|authority| Cow::Borrowed(authority.as_str()))
+        })
+    }
+
+    fn to_extensions(&self) -> RequestExtensions {
+        self.request().clone().into()
+    }
+
     fn try_into_bytes(
         self,
     ) -> impl Future<Output = Result<Bytes, ServerFnError<CustErr>>> + Send
@@ -91,6 +159,11 @@ where
         impl Stream<Item = Result<Bytes, ServerFnError>> + Send,
         ServerFnError<CustErr>,
     > {
-        Ok(futures::stream::once(async { todo!() }))
+        // Actix is going to keep this on a single thread anyway so it's fine to wrap it
+        // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
+        let payload = SendWrapper::new(self.0.take().1);
+        Ok(payload.map(|chunk| {
+            chunk.map_err(|e| ServerFnError::Deserialization(e.to_string()))
+        }))
     }
 }
@@ -1,12 +1,18 @@
-use crate::{error::ServerFnError, request::Req};
+use crate::{
+    error::ServerFnError,
+    request::{ClientReq, Req, RequestExtensions},
+};
 use axum::body::{Body, Bytes};
 use futures::{Stream, StreamExt};
 use http::{
-    header::{ACCEPT, CONTENT_TYPE, REFERER},
-    Request,
+    header::{
+        ACCEPT, CONTENT_TYPE, COOKIE, HOST, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        RANGE, REFERER,
+    },
+    Method, Request,
 };
 use http_body_util::BodyExt;
-use std::borrow::Cow;
+use std::{borrow::Cow, convert::Infallible};
 
 impl<CustErr> Req<CustErr> for Request<Body>
 where
@@ -34,6 +40,42 @@ where
             .map(|h| String::from_utf8_lossy(h.as_bytes()))
     }
 
+    fn cookie_header(&self) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(COOKIE)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
+    fn range_header(&self) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(RANGE)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
+    fn if_modified_since(&self) -> Option<Cow<'_, str>> {
+        if self.headers().contains_key(IF_NONE_MATCH) {
+            return None;
+        }
+        self.headers()
+            .get(IF_MODIFIED_SINCE)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
+    fn host(&self) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(HOST)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+            .or_else(|| {
+                self.uri()
+                    .authority()
+                    .map(|authority| Cow::Borrowed(authority.as_str()))
+            })
+    }
+
+    fn to_extensions(&self) -> RequestExtensions {
+        self.extensions().clone().into()
+    }
+
     async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
         let (_parts, body) = self.into_parts();
 
@@ -60,3 +102,90 @@ where
         }))
     }
 }
+
+fn request<CustErr>(
+    method: Method,
+    uri: &str,
+    accepts: &str,
+    content_type: &str,
+    body: Body,
+) -> Result<Request<Body>, ServerFnError<CustErr>> {
+    let mut req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(ACCEPT, accepts);
+    if !content_type.is_empty() {
+        req = req.header(CONTENT_TYPE, content_type);
+    }
+    req.body(body)
+        .map_err(|e| ServerFnError::Request(e.to_string()))
+}
+
+// Lets a server build a request to a server function that it calls itself, with
+// [`call_local`](crate::axum::call_local).
+impl<CustErr> ClientReq<CustErr> for Request<Body> {
+    type FormData = ();
+
+    fn try_new_get(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        query: &str,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        let uri = if query.is_empty() {
+            path.to_string()
+        } else {
+            format!("{path}?{query}")
+        };
+        request(Method::GET, &uri, accepts, content_type, Body::empty())
+    }
+
+    fn try_new_post(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: String,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        request(Method::POST, path, accepts, content_type, body.into())
+    }
+
+    fn try_new_post_bytes(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        request(Method::POST, path, accepts, content_type, body.into())
+    }
+
+    fn try_new_post_form_data(
+        _path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(ServerFnError::Request(
+            "form data can only be sent from the browser".into(),
+        ))
+    }
+
+    fn try_new_multipart(
+        _path: &str,
+        _accepts: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(ServerFnError::Request(
+            "multipart data can only be sent from the browser".into(),
+        ))
+    }
+
+    fn try_new_streaming(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        let body = Body::from_stream(body.map(Ok::<_, Infallible>));
+        request(Method::POST, path, accepts, content_type, body)
+    }
+}
@@ -1,5 +1,5 @@
 use super::ClientReq;
-use crate::{client::get_server_url, error::ServerFnError};
+use crate::{client::server_fn_url, error::ServerFnError};
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
 pub use gloo_net::http::Request;
@@ -65,19 +65,17 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         content_type: &str,
         query: &str,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        let server_url = get_server_url();
-        let mut url = String::with_capacity(
-            server_url.len() + path.len() + 1 + query.len(),
-        );
-        url.push_str(server_url);
-        url.push_str(path);
-        url.push('?');
-        url.push_str(query);
+        let mut url = server_fn_url(path);
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(query);
+        }
+        let mut req = Request::get(&url).header("Accept", accepts);
+        if !content_type.is_empty() {
+            req = req.header("Content-Type", content_type);
+        }
         Ok(Self(SendWrapper::new(
-            Request::get(&url)
-                .header("Content-Type", content_type)
-                .header("Accept", accepts)
-                .build()
+            req.build()
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
         )))
     }
@@ -88,10 +86,7 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         content_type: &str,
         body: String,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        let server_url = get_server_url();
-        let mut url = String::with_capacity(server_url.len() + path.len());
-        url.push_str(server_url);
-        url.push_str(path);
+        let url = server_fn_url(path);
         Ok(Self(SendWrapper::new(
             Request::post(&url)
                 .header("Content-Type", content_type)
@@ -107,10 +102,7 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         content_type: &str,
         body: Bytes,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        let server_url = get_server_url();
-        let mut url = String::with_capacity(server_url.len() + path.len());
-        url.push_str(server_url);
-        url.push_str(path);
+        let url = server_fn_url(path);
         let body: &[u8] = &body;
         let body = Uint8Array::from(body).buffer();
         Ok(Self(SendWrapper::new(
@@ -127,10 +119,7 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         accepts: &str,
         body: Self::FormData,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        let server_url = get_server_url();
-        let mut url = String::with_capacity(server_url.len() + path.len());
-        url.push_str(server_url);
-        url.push_str(path);
+        let url = server_fn_url(path);
         Ok(Self(SendWrapper::new(
             Request::post(&url)
                 .header("Accept", accepts)
@@ -157,7 +146,7 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                     ))
                 })?;
         Ok(Self(SendWrapper::new(
-            Request::post(path)
+            Request::post(&server_fn_url(path))
                 .header("Content-Type", content_type)
                 .header("Accept", accepts)
                 .body(url_params)
@@ -201,6 +190,7 @@ fn streaming_request(
         &JsValue::from_str("duplex"),
         &JsValue::from_str("half"),
     )?;
-    let req = web_sys::Request::new_with_str_and_init(path, &init)?;
+    let req =
+        web_sys::Request::new_with_str_and_init(&server_fn_url(path), &init)?;
     Ok(Request::from(req))
 }
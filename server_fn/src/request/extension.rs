@@ -0,0 +1,257 @@
+use crate::error::ServerFnError;
+use std::{
+    any::{type_name, Any},
+    cell::RefCell,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+thread_local! {
+    static CURRENT: RefCell<Option<RequestExtensions>> = const { RefCell::new(None) };
+}
+
+/// The extensions of the request that a server function is handling.
+///
+/// These are captured before the request body is read, so values inserted by
+/// middleware (for example, the claims inserted by an
+/// [`Auth`](crate::middleware::Auth) layer) can still be read once the server
+/// function runs, using [`Extension`].
+#[derive(Clone)]
+pub struct RequestExtensions(Arc<dyn Any + Send + Sync>);
+
+impl RequestExtensions {
+    /// The extensions of the request currently being handled by a server function,
+    /// or `None` if called outside of a server function.
+    pub fn current() -> Option<Self> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Returns a clone of the extension of type `T`, if there is one.
+    pub fn get<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        if let Some(extensions) = self.0.downcast_ref::<http::Extensions>() {
+            return extensions.get::<T>().cloned();
+        }
+        #[cfg(feature = "actix")]
+        if let Some(req) = self
+            .0
+            .downcast_ref::<send_wrapper::SendWrapper<actix_web::HttpRequest>>()
+        {
+            use actix_web::HttpMessage;
+            return req.extensions().get::<T>().cloned();
+        }
+        None
+    }
+
+    /// Makes these extensions available from [`RequestExtensions::current`] while
+    /// `fut` runs.
+    pub fn scope<F: Future>(self, fut: F) -> impl Future<Output = F::Output> {
+        Scoped {
+            extensions: self,
+            fut: Box::pin(fut),
+        }
+    }
+
+    /// Makes these extensions available from [`RequestExtensions::current`] while
+    /// `f` runs.
+    pub(crate) fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        let prev = CURRENT.with(|current| current.replace(Some(self.clone())));
+        let res = f();
+        CURRENT.with(|current| *current.borrow_mut() = prev);
+        res
+    }
+}
+
+impl Default for RequestExtensions {
+    fn default() -> Self {
+        Self::from(http::Extensions::new())
+    }
+}
+
+impl fmt::Debug for RequestExtensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestExtensions").finish_non_exhaustive()
+    }
+}
+
+impl From<http::Extensions> for RequestExtensions {
+    fn from(extensions: http::Extensions) -> Self {
+        Self(Arc::new(extensions))
+    }
+}
+
+#[cfg(feature = "actix")]
+impl From<actix_web::HttpRequest> for RequestExtensions {
+    fn from(req: actix_web::HttpRequest) -> Self {
+        // the extensions of an `HttpRequest` are shared between its clones
+        Self(Arc::new(send_wrapper::SendWrapper::new(req)))
+    }
+}
+
+struct Scoped<F> {
+    extensions: RequestExtensions,
+    fut: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let prev = CURRENT
+            .with(|current| current.replace(Some(self.extensions.clone())));
+        let res = self.fut.as_mut().poll(cx);
+        CURRENT.with(|current| *current.borrow_mut() = prev);
+        res
+    }
+}
+
+/// Extracts a value of type `T` from the extensions of the request that a server
+/// function is handling.
+///
+/// Arguments of this type are not sent by the client: the `#[server]` macro leaves
+/// them out of the arguments struct and of the function that the client calls, and
+/// extracts them on the server instead. If the request has no extension of type
+/// `T`, the server function returns a [`ServerFnError::MissingArg`] without running.
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(Auth::bearer(verify_token))]
+/// pub async fn whoami(
+///     Extension(user): Extension<User>,
+/// ) -> Result<String, ServerFnError> {
+///     Ok(user.name)
+/// }
+///
+/// // on the client, `whoami` takes no arguments
+/// let name = whoami().await?;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Extension<T>(pub T);
+
+impl<T: Clone + Send + Sync + 'static> Extension<T> {
+    /// Extracts the extension of type `T` from the request currently being handled.
+    pub fn extract<CustErr>() -> Result<Self, ServerFnError<CustErr>> {
+        RequestExtensions::current()
+            .and_then(|extensions| extensions.get::<T>())
+            .map(Extension)
+            .ok_or_else(|| {
+                ServerFnError::MissingArg(format!(
+                    "request extension `{}`",
+                    type_name::<T>()
+                ))
+            })
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::Extension;
+    use crate::{
+        codec::{test_client::ServerOnly, Json},
+        error::NoCustomError,
+        middleware::{service_fn, Auth, Layer},
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone)]
+    struct User {
+        name: String,
+    }
+
+    async fn validate(token: String) -> Result<User, &'static str> {
+        match token.as_str() {
+            "secret" => Ok(User {
+                name: "alice".into(),
+            }),
+            _ => Err("unknown token"),
+        }
+    }
+
+    /// What the `#[server]` macro generates for
+    /// `async fn whoami(Extension(user): Extension<User>)`.
+    #[derive(Serialize, Deserialize)]
+    struct Whoami {}
+
+    impl ServerFn for Whoami {
+        const PATH: &'static str = "/api/whoami";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = String;
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<String, ServerFnError> {
+            let Extension(user) = match Extension::<User>::extract() {
+                Ok(user) => user,
+                Err(e) => return Err(e),
+            };
+            Ok(user.name)
+        }
+    }
+
+    fn request() -> http::request::Builder {
+        Request::post(Whoami::PATH)
+            .header(header::CONTENT_TYPE, "application/json")
+    }
+
+    async fn body(res: Response<Body>) -> String {
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn extracts_extension_inserted_by_auth_layer() {
+        let mut service = Auth::bearer(validate)
+            .layer(service_fn(|req: Request<Body>| Whoami::run_on_server(req)));
+        let req = request()
+            .header(header::AUTHORIZATION, "Bearer secret")
+            .body(Body::from("{}"))
+            .unwrap();
+        let res = service.0.run(req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body(res).await, r#""alice""#);
+    }
+
+    #[tokio::test]
+    async fn missing_extension_is_an_error() {
+        let res =
+            Whoami::run_on_server(request().body(Body::from("{}")).unwrap())
+                .await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            body(res).await,
+            format!(
+                "MissingArg|request extension `{}`",
+                std::any::type_name::<User>()
+            )
+        );
+        assert!(Extension::<User>::extract::<NoCustomError>().is_err());
+    }
+}
+
+#[cfg(all(test, feature = "actix"))]
+mod actix_tests {
+    use super::RequestExtensions;
+    use actix_web::{test::TestRequest, HttpMessage};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct User(&'static str);
+
+    #[test]
+    fn reads_extensions_inserted_after_capture() {
+        let req = TestRequest::default().to_http_request();
+        let extensions = RequestExtensions::from(req.clone());
+        assert_eq!(extensions.get::<User>(), None);
+        req.extensions_mut().insert(User("alice"));
+        assert_eq!(extensions.get::<User>(), Some(User("alice")));
+    }
+}
@@ -1,8 +1,11 @@
-use crate::error::ServerFnError;
+use crate::error::{NoCustomError, ServerFnError};
 use bytes::Bytes;
 use futures::Stream;
 use std::{borrow::Cow, future::Future};
 
+mod extension;
+pub use extension::{Extension, RequestExtensions};
+
 /// Request types for Actix.
 #[cfg(feature = "actix")]
 pub mod actix;
@@ -25,6 +28,9 @@ where
     type FormData;
 
     /// Attempts to construct a new `GET` request.
+    ///
+    /// An empty `content_type` leaves out the `Content-Type` header, and an empty
+    /// `query` leaves out the query string.
     fn try_new_get(
         path: &str,
         content_type: &str,
@@ -89,6 +95,26 @@ where
     /// Returns the `Referer` header, if any.
     fn referer(&self) -> Option<Cow<'_, str>>;
 
+    /// Returns the `Cookie` header, if any.
+    fn cookie_header(&self) -> Option<Cow<'_, str>>;
+
+    /// Returns the `Range` header, if any.
+    fn range_header(&self) -> Option<Cow<'_, str>>;
+
+    /// Returns the `If-Modified-Since` header, if any.
+    ///
+    /// This is `None` if the request also has an `If-None-Match` header, which
+    /// takes precedence over it.
+    fn if_modified_since(&self) -> Option<Cow<'_, str>>;
+
+    /// Returns the host the request was sent to, from the `Host` header or the
+    /// authority of the URI, if any. This may include a port.
+    fn host(&self) -> Option<Cow<'_, str>>;
+
+    /// Returns a handle to the extensions of the request, which can still be read
+    /// after the body has been consumed.
+    fn to_extensions(&self) -> RequestExtensions;
+
     /// Attempts to extract the body of the request into [`Bytes`].
     fn try_into_bytes(
         self,
@@ -108,6 +134,31 @@ where
     >;
 }
 
+/// Whether a request was made by a plain HTML `<form>`, without JavaScript, rather
+/// than by a server function client.
+///
+/// The clients always send an `Accept` header naming the encoding they expect,
+/// which is never HTML, while browsers submitting a form ask for a page to show.
+/// So this is `true` for a request that accepts `text/html` and, if it has a body,
+/// sends form data. Server functions answer these requests by redirecting back to
+/// the page the form was on, and middleware can use this to do the same.
+///
+/// ```rust,ignore
+/// if is_progressive_enhancement(&req) {
+///     // send the user somewhere to see the result
+/// }
+/// ```
+pub fn is_progressive_enhancement(req: &impl Req<NoCustomError>) -> bool {
+    let accepts_html = req
+        .accepts()
+        .is_some_and(|accepts| accepts.contains("text/html"));
+    let sends_form = req.to_content_type().map_or(true, |content_type| {
+        content_type.starts_with("application/x-www-form-urlencoded")
+            || content_type.starts_with("multipart/form-data")
+    });
+    accepts_html && sends_form
+}
+
 /// A mocked request type that can be used in place of the actual server request,
 /// when compiling for the browser.
 pub struct BrowserMockReq;
@@ -131,6 +182,27 @@ where
     fn referer(&self) -> Option<Cow<'_, str>> {
         unreachable!()
     }
+
+    fn cookie_header(&self) -> Option<Cow<'_, str>> {
+        unreachable!()
+    }
+
+    fn range_header(&self) -> Option<Cow<'_, str>> {
+        unreachable!()
+    }
+
+    fn if_modified_since(&self) -> Option<Cow<'_, str>> {
+        unreachable!()
+    }
+
+    fn host(&self) -> Option<Cow<'_, str>> {
+        unreachable!()
+    }
+
+    fn to_extensions(&self) -> RequestExtensions {
+        unreachable!()
+    }
+
     async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
         unreachable!()
     }
@@ -148,3 +220,50 @@ where
         Ok(futures::stream::once(async { unreachable!() }))
     }
 }
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::is_progressive_enhancement;
+    use axum::body::Body;
+    use http::{header, Request};
+
+    const BROWSER_ACCEPT: &str =
+        "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+
+    fn post(content_type: &str, accept: &str) -> Request<Body> {
+        Request::post("/api/add_todo")
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::ACCEPT, accept)
+            .body(Body::from("title=milk"))
+            .unwrap()
+    }
+
+    #[test]
+    fn detects_form_posts() {
+        let form = "application/x-www-form-urlencoded";
+        assert!(is_progressive_enhancement(&post(form, BROWSER_ACCEPT)));
+        let multipart = "multipart/form-data; boundary=x";
+        assert!(is_progressive_enhancement(&post(multipart, BROWSER_ACCEPT)));
+        let get = Request::get("/api/list_todos?page=2")
+            .header(header::ACCEPT, BROWSER_ACCEPT)
+            .body(Body::empty())
+            .unwrap();
+        assert!(is_progressive_enhancement(&get));
+    }
+
+    #[test]
+    fn ignores_server_fn_clients() {
+        // the client asks for the encoding of the output
+        let form = "application/x-www-form-urlencoded";
+        assert!(!is_progressive_enhancement(&post(form, "application/json")));
+        assert!(!is_progressive_enhancement(&post(
+            "application/json",
+            "application/json"
+        )));
+        // and only an HTML form sends form data while asking for HTML
+        assert!(!is_progressive_enhancement(&post(
+            "application/json",
+            BROWSER_ACCEPT
+        )));
+    }
+}
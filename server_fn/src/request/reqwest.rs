@@ -1,12 +1,23 @@
 use super::ClientReq;
-use crate::{client::get_server_url, error::ServerFnError};
+use crate::{client::server_fn_url, error::ServerFnError};
 use bytes::Bytes;
 use futures::Stream;
 use once_cell::sync::Lazy;
 use reqwest::header::{ACCEPT, CONTENT_TYPE};
 pub use reqwest::{multipart::Form, Client, Method, Request, Url};
+use std::sync::{PoisonError, RwLock};
 
-pub(crate) static CLIENT: Lazy<Client> = Lazy::new(Client::new);
+pub(crate) static CLIENT: Lazy<RwLock<Client>> =
+    Lazy::new(|| RwLock::new(Client::new()));
+
+/// The client that builds and sends every request, as set by
+/// [`set_client`](crate::client::reqwest::set_client).
+pub(crate) fn client() -> Client {
+    CLIENT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
 
 impl<CustErr> ClientReq<CustErr> for Request {
     type FormData = Form;
@@ -17,17 +28,16 @@ impl<CustErr> ClientReq<CustErr> for Request {
         content_type: &str,
         query: &str,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        let url = format!("{}{}", get_server_url(), path);
+        let url = server_fn_url(path);
         let mut url = Url::try_from(url.as_str())
             .map_err(|e| ServerFnError::Request(e.to_string()))?;
-        url.set_query(Some(query));
-        let req = CLIENT
-            .get(url)
-            .header(CONTENT_TYPE, content_type)
-            .header(ACCEPT, accepts)
-            .build()
-            .map_err(|e| ServerFnError::Request(e.to_string()))?;
-        Ok(req)
+        url.set_query((!query.is_empty()).then_some(query));
+        let mut req = client().get(url).header(ACCEPT, accepts);
+        if !content_type.is_empty() {
+            req = req.header(CONTENT_TYPE, content_type);
+        }
+        req.build()
+            .map_err(|e| ServerFnError::Request(e.to_string()))
     }
 
     fn try_new_post(
@@ -36,8 +46,8 @@ impl<CustErr> ClientReq<CustErr> for Request {
         content_type: &str,
         body: String,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        let url = format!("{}{}", get_server_url(), path);
-        CLIENT
+        let url = server_fn_url(path);
+        client()
             .post(url)
             .header(CONTENT_TYPE, content_type)
             .header(ACCEPT, accepts)
@@ -52,8 +62,8 @@ impl<CustErr> ClientReq<CustErr> for Request {
         content_type: &str,
         body: Bytes,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        let url = format!("{}{}", get_server_url(), path);
-        CLIENT
+        let url = server_fn_url(path);
+        client()
             .post(url)
             .header(CONTENT_TYPE, content_type)
             .header(ACCEPT, accepts)
@@ -67,8 +77,8 @@ impl<CustErr> ClientReq<CustErr> for Request {
         accepts: &str,
         body: Self::FormData,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        CLIENT
-            .post(path)
+        client()
+            .post(server_fn_url(path))
             .header(ACCEPT, accepts)
             .multipart(body)
             .build()
@@ -81,8 +91,8 @@ impl<CustErr> ClientReq<CustErr> for Request {
         content_type: &str,
         body: Self::FormData,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        CLIENT
-            .post(path)
+        client()
+            .post(server_fn_url(path))
             .header(CONTENT_TYPE, content_type)
             .header(ACCEPT, accepts)
             .multipart(body)
@@ -102,11 +112,11 @@ impl<CustErr> ClientReq<CustErr> for Request {
         // That means the streaming types need to be wrappers over Sync streams
         // However, Axum BodyDataStream is !Sync, so we can't use the same wrapper type there
 
-        /*        let url = format!("{}{}", get_server_url(), path);
+        /*        let url = server_fn_url(path);
             let body = Body::wrap_stream(
                 body.map(|chunk| Ok(chunk) as Result<Bytes, ServerFnErrorErr>),
             );
-            CLIENT
+            client()
                 .post(url)
                 .header(CONTENT_TYPE, content_type)
                 .header(ACCEPT, accepts)
@@ -1,8 +1,14 @@
-use crate::{error::ServerFnError, request::Req};
+use crate::{
+    error::ServerFnError,
+    request::{Req, RequestExtensions},
+};
 use axum::body::{Body, Bytes};
 use futures::{Stream, StreamExt};
 use http::{
-    header::{ACCEPT, CONTENT_TYPE, REFERER},
+    header::{
+        ACCEPT, CONTENT_TYPE, COOKIE, HOST, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        RANGE, REFERER,
+    },
     Request,
 };
 use http_body_util::BodyExt;
@@ -34,6 +40,42 @@ where
             .map(|h| String::from_utf8_lossy(h.as_bytes()))
     }
 
+    fn cookie_header(&self) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(COOKIE)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
+    fn range_header(&self) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(RANGE)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
+    fn if_modified_since(&self) -> Option<Cow<'_, str>> {
+        if self.headers().contains_key(IF_NONE_MATCH) {
+            return None;
+        }
+        self.headers()
+            .get(IF_MODIFIED_SINCE)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
+    fn host(&self) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(HOST)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+            .or_else(|| {
+                self.uri()
+                    .authority()
+                    .map(|authority| Cow::Borrowed(authority.as_str()))
+            })
+    }
+
+    fn to_extensions(&self) -> RequestExtensions {
+        RequestExtensions::default()
+    }
+
     async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
         let (_parts, body) = self.into_parts();
 
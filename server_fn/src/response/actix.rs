@@ -1,11 +1,12 @@
 use super::Res;
-use crate::error::{
-    ServerFnError, ServerFnErrorErr, ServerFnErrorSerde, SERVER_FN_ERROR_HEADER,
+use crate::{
+    codec::encode_error,
+    error::{ServerFnError, ServerFnErrorErr, SERVER_FN_ERROR_HEADER},
 };
 use actix_web::{
     http::{
         header,
-        header::{HeaderValue, LOCATION},
+        header::{HeaderName, HeaderValue, LOCATION},
         StatusCode,
     },
     HttpResponse,
@@ -81,11 +82,14 @@ where
     }
 
     fn error_response(path: &str, err: &ServerFnError<CustErr>) -> Self {
-        ActixResponse(SendWrapper::new(
-            HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR)
-                .append_header((SERVER_FN_ERROR_HEADER, path))
-                .body(err.ser().unwrap_or_else(|_| err.to_string())),
-        ))
+        let (content_type, body) = encode_error(path, err);
+        let mut builder =
+            HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR);
+        builder.append_header((SERVER_FN_ERROR_HEADER, path));
+        if let Some(content_type) = content_type {
+            builder.append_header((header::CONTENT_TYPE, content_type));
+        }
+        ActixResponse(SendWrapper::new(builder.body(body)))
     }
 
     fn redirect(&mut self, path: &str) {
@@ -94,4 +98,33 @@ where
             self.0.headers_mut().insert(LOCATION, path);
         }
     }
+
+    fn insert_header(&mut self, name: &str, value: &str) {
+        if let (Ok(name), Ok(value)) =
+            (HeaderName::from_str(name), HeaderValue::from_str(value))
+        {
+            self.0.headers_mut().insert(name, value);
+        }
+    }
+
+    fn append_header(&mut self, name: &str, value: &str) {
+        if let (Ok(name), Ok(value)) =
+            (HeaderName::from_str(name), HeaderValue::from_str(value))
+        {
+            self.0.headers_mut().append(name, value);
+        }
+    }
+
+    fn remove_header(&mut self, name: &str) {
+        if let Ok(name) = HeaderName::from_str(name) {
+            self.0.headers_mut().remove(name);
+        }
+    }
+
+    fn set_status(&mut self, status: http::StatusCode) {
+        // Actix still uses `http` 0.2
+        if let Ok(status) = StatusCode::from_u16(status.as_u16()) {
+            *self.0.status_mut() = status;
+        }
+    }
 }
@@ -1,7 +1,7 @@
 use super::ClientRes;
 use crate::{error::ServerFnError, redirect::REDIRECT_HEADER};
 use bytes::Bytes;
-use futures::{Stream, StreamExt};
+use futures::{stream, Stream, StreamExt};
 pub use gloo_net::http::Response;
 use js_sys::Uint8Array;
 use send_wrapper::SendWrapper;
@@ -12,6 +12,12 @@ use wasm_streams::ReadableStream;
 /// The response to a `fetch` request made in the browser.
 pub struct BrowserResponse(pub(crate) SendWrapper<Response>);
 
+impl From<Response> for BrowserResponse {
+    fn from(res: Response) -> Self {
+        Self(SendWrapper::new(res))
+    }
+}
+
 impl<CustErr> ClientRes<CustErr> for BrowserResponse {
     fn try_into_string(
         self,
@@ -42,22 +48,27 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
         })
     }
 
+    /// Yields the body in chunks as the browser receives them, by reading the
+    /// `ReadableStream` of the response, so that a streaming encoding can decode
+    /// items before the response is complete.
     fn try_into_stream(
         self,
     ) -> Result<
         impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
         ServerFnError<CustErr>,
     > {
-        let stream = ReadableStream::from_raw(self.0.body().unwrap())
-            .into_stream()
-            .map(|data| {
-                let data = data.unwrap().unchecked_into::<Uint8Array>();
-                let mut buf = Vec::new();
-                let length = data.length();
-                buf.resize(length as usize, 0);
-                data.copy_to(&mut buf);
-                Ok(Bytes::from(buf))
-            });
+        // a response without a body, like a `204 No Content`, has no stream
+        let body = self
+            .0
+            .body()
+            .map(|body| ReadableStream::from_raw(body).into_stream());
+        let stream = stream::iter(body).flatten().map(|chunk| {
+            chunk
+                .map(|chunk| {
+                    Bytes::from(chunk.unchecked_into::<Uint8Array>().to_vec())
+                })
+                .map_err(|e| ServerFnError::Deserialization(format!("{e:?}")))
+        });
         Ok(SendWrapper::new(stream))
     }
 
@@ -79,4 +90,8 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
     fn has_redirect(&self) -> bool {
         self.0.headers().get(REDIRECT_HEADER).is_some()
     }
+
+    fn content_type(&self) -> Option<String> {
+        self.0.headers().get("Content-Type")
+    }
 }
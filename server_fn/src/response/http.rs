@@ -1,14 +1,19 @@
-use super::Res;
-use crate::error::{
-    ServerFnError, ServerFnErrorErr, ServerFnErrorSerde, SERVER_FN_ERROR_HEADER,
+use super::{ClientRes, Res};
+use crate::{
+    codec::encode_error,
+    error::{ServerFnError, ServerFnErrorErr, SERVER_FN_ERROR_HEADER},
 };
 use axum::body::Body;
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
-use http::{header, HeaderValue, Response, StatusCode};
+use http::{header, HeaderName, HeaderValue, Response, StatusCode};
+use http_body_util::BodyExt;
 use std::{
     fmt::{Debug, Display},
+    pin::Pin,
     str::FromStr,
+    sync::Mutex,
+    task::{Context, Poll},
 };
 
 impl<CustErr> Res<CustErr> for Response<Body>
@@ -56,11 +61,14 @@ where
     }
 
     fn error_response(path: &str, err: &ServerFnError<CustErr>) -> Self {
-        Response::builder()
+        let (content_type, body) = encode_error(path, err);
+        let mut builder = Response::builder()
             .status(http::StatusCode::INTERNAL_SERVER_ERROR)
-            .header(SERVER_FN_ERROR_HEADER, path)
-            .body(err.ser().unwrap_or_else(|_| err.to_string()).into())
-            .unwrap()
+            .header(SERVER_FN_ERROR_HEADER, path);
+        if let Some(content_type) = content_type {
+            builder = builder.header(header::CONTENT_TYPE, content_type);
+        }
+        builder.body(body.into()).unwrap()
     }
 
     fn redirect(&mut self, path: &str) {
@@ -69,4 +77,99 @@ where
             *self.status_mut() = StatusCode::FOUND;
         }
     }
+
+    fn insert_header(&mut self, name: &str, value: &str) {
+        if let (Ok(name), Ok(value)) =
+            (HeaderName::from_str(name), HeaderValue::from_str(value))
+        {
+            self.headers_mut().insert(name, value);
+        }
+    }
+
+    fn append_header(&mut self, name: &str, value: &str) {
+        if let (Ok(name), Ok(value)) =
+            (HeaderName::from_str(name), HeaderValue::from_str(value))
+        {
+            self.headers_mut().append(name, value);
+        }
+    }
+
+    fn remove_header(&mut self, name: &str) {
+        self.headers_mut().remove(name);
+    }
+
+    fn set_status(&mut self, status: StatusCode) {
+        *self.status_mut() = status;
+    }
+}
+
+type BoxedStream =
+    Pin<Box<dyn Stream<Item = Result<Bytes, ServerFnError>> + Send>>;
+
+/// Makes a response body stream `Sync`, as [`ClientRes`] requires.
+struct SyncStream(Mutex<BoxedStream>);
+
+impl Stream for SyncStream {
+    type Item = Result<Bytes, ServerFnError>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.get_mut().0.get_mut().unwrap().as_mut().poll_next(cx)
+    }
+}
+
+// Lets a server read the response of a server function that it called itself, with
+// [`call_local`](crate::axum::call_local).
+impl<CustErr> ClientRes<CustErr> for Response<Body> {
+    async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
+        let bytes = ClientRes::<CustErr>::try_into_bytes(self).await?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+
+    async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
+        self.into_body()
+            .collect()
+            .await
+            .map(|body| body.to_bytes())
+            .map_err(|e| ServerFnError::Response(e.to_string()))
+    }
+
+    fn try_into_stream(
+        self,
+    ) -> Result<
+        impl Stream<Item = Result<Bytes, ServerFnError>> + Send + Sync + 'static,
+        ServerFnError<CustErr>,
+    > {
+        let stream = self.into_body().into_data_stream().map(|chunk| {
+            chunk.map_err(|e| ServerFnError::Response(e.to_string()))
+        });
+        Ok(SyncStream(Mutex::new(Box::pin(stream))))
+    }
+    fn status(&self) -> u16 {
+        self.status().as_u16()
+    }
+
+    fn status_text(&self) -> String {
+        self.status().to_string()
+    }
+
+    fn location(&self) -> String {
+        self.headers()
+            .get(header::LOCATION)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string())
+            .unwrap_or_default()
+    }
+
+    fn has_redirect(&self) -> bool {
+        self.headers().contains_key(header::LOCATION)
+    }
+
+    fn content_type(&self) -> Option<String> {
+        self.headers()
+            .get(header::CONTENT_TYPE)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string())
+    }
 }
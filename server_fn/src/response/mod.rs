@@ -11,9 +11,13 @@ pub mod http;
 #[cfg(feature = "reqwest")]
 pub mod reqwest;
 
+mod options;
+
 use crate::error::ServerFnError;
+use ::http::StatusCode;
 use bytes::Bytes;
 use futures::Stream;
+pub use options::ResponseOptions;
 use std::future::Future;
 
 /// Represents the response as created by the server;
@@ -41,11 +45,53 @@ where
             + 'static,
     ) -> Result<Self, ServerFnError<CustErr>>;
 
-    /// Converts an error into a response, with a `500` status code and the error text as its body.
+    /// Converts an error into a response, with a `500` status code and the error as its body.
+    ///
+    /// While a server function runs, the error is encoded as the `Accept` header of its
+    /// request prefers, as described for [`ErrorEncoder`](crate::codec::ErrorEncoder).
     fn error_response(path: &str, err: &ServerFnError<CustErr>) -> Self;
 
     /// Redirect the response by setting a 302 code and Location header.
     fn redirect(&mut self, path: &str);
+
+    /// Sets a header on the response, replacing any existing values.
+    ///
+    /// Invalid header names or values are ignored. By default, this does nothing, so
+    /// that response types written before it was added keep compiling; they should
+    /// override it.
+    fn insert_header(&mut self, _name: &str, _value: &str) {}
+
+    /// Adds a header to the response, keeping any existing values.
+    ///
+    /// Invalid header names or values are ignored. By default, this does nothing;
+    /// see [`Res::insert_header`].
+    fn append_header(&mut self, _name: &str, _value: &str) {}
+
+    /// Removes every value of a header from the response.
+    ///
+    /// By default, this does nothing; see [`Res::insert_header`].
+    fn remove_header(&mut self, _name: &str) {}
+
+    /// Sets the status code of the response.
+    ///
+    /// By default, this does nothing, so that response types written before it was
+    /// added keep compiling; they should override it.
+    fn set_status(&mut self, _status: StatusCode) {}
+}
+
+/// Converts an error into a response like [`Res::error_response`], but with
+/// `status`, if any, which usually comes from
+/// [`ResponseOptions::error_status`].
+pub(crate) fn error_response_with_status<CustErr, R: Res<CustErr>>(
+    path: &str,
+    err: &ServerFnError<CustErr>,
+    status: Option<StatusCode>,
+) -> R {
+    let mut res = R::error_response(path, err);
+    if let Some(status) = status {
+        res.set_status(status);
+    }
+    res
 }
 
 /// Represents the response as received by the client.
@@ -79,6 +125,11 @@ pub trait ClientRes<CustErr> {
 
     /// Whether the response has the [`REDIRECT_HEADER`](crate::redirect::REDIRECT_HEADER) set.
     fn has_redirect(&self) -> bool;
+
+    /// The `Content-Type` header of the response, if any.
+    fn content_type(&self) -> Option<String> {
+        None
+    }
 }
 
 /// A mocked response type that can be used in place of the actual server response,
@@ -118,4 +169,12 @@ impl<CustErr> Res<CustErr> for BrowserMockRes {
     fn redirect(&mut self, _path: &str) {
         unreachable!()
     }
+
+    fn insert_header(&mut self, _name: &str, _value: &str) {
+        unreachable!()
+    }
+
+    fn append_header(&mut self, _name: &str, _value: &str) {
+        unreachable!()
+    }
 }
@@ -0,0 +1,384 @@
+use super::Res;
+use http::StatusCode;
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::SystemTime,
+};
+
+thread_local! {
+    static CURRENT: RefCell<Option<ResponseOptions>> = const { RefCell::new(None) };
+}
+
+/// Changes to the response of the server function that is currently running.
+///
+/// While a server function runs, its options are available from
+/// [`ResponseOptions::current`], and are applied to its response once it returns,
+/// whichever framework it is served with. This works for streaming responses as
+/// well, as long as the options are set before the server function returns.
+///
+/// ```rust,ignore
+/// #[server]
+/// pub async fn create_post(title: String) -> Result<u32, ServerFnError> {
+///     let id = insert_post(&title).await?;
+///     if let Some(options) = ResponseOptions::current() {
+///         options.set_status(StatusCode::CREATED);
+///     }
+///     Ok(id)
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ResponseOptions(Arc<Mutex<ResponseOptionsInner>>);
+
+#[derive(Debug, Default)]
+struct ResponseOptionsInner {
+    status: Option<StatusCode>,
+    error_status: Option<StatusCode>,
+    redirect: Option<String>,
+    last_modified: Option<SystemTime>,
+}
+
+impl ResponseOptions {
+    /// The options of the server function that is currently running, or `None` if
+    /// called outside of a server function.
+    pub fn current() -> Option<Self> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Sets the status code of a successful response.
+    ///
+    /// If the server function returns an error, the error response uses the status
+    /// set with [`set_error_status`](ResponseOptions::set_error_status) instead. Note
+    /// that the server function client reads any status of `400` or above as an
+    /// error.
+    pub fn set_status(&self, status: StatusCode) {
+        self.lock().status = Some(status);
+    }
+
+    /// The status code set with [`set_status`](ResponseOptions::set_status), if any.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.lock().status
+    }
+
+    /// Sets the status code of the response if the server function returns an
+    /// error, instead of `500 Internal Server Error`.
+    ///
+    /// The caller receives it as the [`status`](crate::ServerFnError::status) of the
+    /// error.
+    pub fn set_error_status(&self, status: StatusCode) {
+        self.lock().error_status = Some(status);
+    }
+
+    /// The status code set with
+    /// [`set_error_status`](ResponseOptions::set_error_status), if any.
+    pub fn error_status(&self) -> Option<StatusCode> {
+        self.lock().error_status
+    }
+
+    /// Sets the error status of the [current](ResponseOptions::current) options, if
+    /// there are any.
+    pub(crate) fn set_current_error_status(status: u16) {
+        if let (Some(options), Ok(status)) =
+            (Self::current(), StatusCode::from_u16(status))
+        {
+            options.set_error_status(status);
+        }
+    }
+
+    /// Sets the `Last-Modified` header of a successful response.
+    ///
+    /// For a server function called with `GET`, a request whose `If-Modified-Since`
+    /// header is no older than this time is answered with `304 Not Modified` and an
+    /// empty body instead, so the client can use the copy it already has. The time
+    /// is sent with a precision of one second.
+    pub fn set_last_modified(&self, time: SystemTime) {
+        self.lock().last_modified = Some(time);
+    }
+
+    /// The time set with [`set_last_modified`](ResponseOptions::set_last_modified),
+    /// if any.
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        self.lock().last_modified
+    }
+
+    /// Whether a client that sent `if_modified_since` already has the current
+    /// version, according to the time set with
+    /// [`set_last_modified`](ResponseOptions::set_last_modified).
+    pub(crate) fn is_not_modified(&self, if_modified_since: &str) -> bool {
+        let (Some(last_modified), Ok(since)) = (
+            self.last_modified(),
+            httpdate::parse_http_date(if_modified_since),
+        ) else {
+            return false;
+        };
+        // the header only has whole seconds, so the fraction is ignored
+        let last_modified = httpdate::HttpDate::from(last_modified);
+        last_modified <= httpdate::HttpDate::from(since)
+    }
+
+    pub(crate) fn set_redirect(&self, path: &str) {
+        self.lock().redirect = Some(path.to_owned());
+    }
+
+    pub(crate) fn redirect(&self) -> Option<String> {
+        self.lock().redirect.clone()
+    }
+
+    /// Sets the status code and `Last-Modified` header of `res`, if they have been
+    /// set.
+    pub fn apply<CustErr, R: Res<CustErr>>(&self, res: &mut R) {
+        if let Some(status) = self.status() {
+            res.set_status(status);
+        }
+        if let Some(last_modified) = self.last_modified() {
+            res.insert_header(
+                "last-modified",
+                &httpdate::fmt_http_date(last_modified),
+            );
+        }
+    }
+
+    /// Makes these options available from [`ResponseOptions::current`] while
+    /// `fut` runs.
+    pub fn scope<F: Future>(self, fut: F) -> impl Future<Output = F::Output> {
+        Scoped {
+            options: self,
+            fut: Box::pin(fut),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ResponseOptionsInner> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+struct Scoped<F> {
+    options: ResponseOptions,
+    fut: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let prev =
+            CURRENT.with(|current| current.replace(Some(self.options.clone())));
+        let res = self.fut.as_mut().poll(cx);
+        CURRENT.with(|current| *current.borrow_mut() = prev);
+        res
+    }
+}
+
+#[cfg(all(test, feature = "axum-no-default"))]
+mod tests {
+    use super::ResponseOptions;
+    use crate::{
+        codec::{test_client::ServerOnly, ByteStream, Json, Streaming},
+        error::NoCustomError,
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{header, Request, Response, StatusCode};
+    use http_body_util::BodyExt;
+    use serde::{Deserialize, Serialize};
+
+    /// Returns `201 Created`, unless it fails.
+    #[derive(Serialize, Deserialize)]
+    struct Create {
+        fail: bool,
+    }
+
+    impl ServerFn for Create {
+        const PATH: &'static str = "/api/create";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = u32;
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<u32, ServerFnError> {
+            ResponseOptions::current()
+                .unwrap()
+                .set_status(StatusCode::CREATED);
+            if self.fail {
+                Err(ServerFnError::new("failed"))
+            } else {
+                Ok(1)
+            }
+        }
+    }
+
+    /// Streams its response with `202 Accepted`.
+    #[derive(Serialize, Deserialize)]
+    struct Accept {}
+
+    impl ServerFn for Accept {
+        const PATH: &'static str = "/api/accept";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = ByteStream;
+        type InputEncoding = Json;
+        type OutputEncoding = Streaming;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<ByteStream, ServerFnError> {
+            ResponseOptions::current()
+                .unwrap()
+                .set_status(StatusCode::ACCEPTED);
+            Ok(ByteStream::from(futures::stream::iter(["a", "b"])))
+        }
+    }
+
+    /// Reads a post that was last modified at [`modified`].
+    #[cfg(feature = "url")]
+    #[derive(Serialize, Deserialize)]
+    struct ReadPost {}
+
+    #[cfg(feature = "url")]
+    impl ServerFn for ReadPost {
+        const PATH: &'static str = "/api/read_post";
+
+        type Client = ServerOnly;
+        type ServerRequest = Request<Body>;
+        type ServerResponse = Response<Body>;
+        type Output = String;
+        type InputEncoding = crate::codec::GetUrl;
+        type OutputEncoding = Json;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<String, ServerFnError> {
+            ResponseOptions::current()
+                .unwrap()
+                .set_last_modified(modified());
+            Ok("post".into())
+        }
+    }
+
+    #[cfg(feature = "url")]
+    fn modified() -> std::time::SystemTime {
+        std::time::UNIX_EPOCH
+            + std::time::Duration::from_millis(1_700_000_000_500)
+    }
+
+    fn request(path: &str, body: &'static str) -> Request<Body> {
+        Request::post(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    async fn body(res: Response<Body>) -> String {
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn sets_status_of_buffered_response() {
+        let res =
+            Create::run_on_server(request(Create::PATH, r#"{"fail":false}"#))
+                .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(body(res).await, "1");
+    }
+
+    #[tokio::test]
+    async fn errors_keep_their_status() {
+        let res =
+            Create::run_on_server(request(Create::PATH, r#"{"fail":true}"#))
+                .await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn sets_status_of_streaming_response() {
+        let res = Accept::run_on_server(request(Accept::PATH, "{}")).await;
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert_eq!(body(res).await, "ab");
+    }
+
+    #[cfg(feature = "url")]
+    #[tokio::test]
+    async fn answers_not_modified_since_last_modified() {
+        let read = |if_modified_since: Option<&str>| {
+            let mut req = Request::get(ReadPost::PATH);
+            if let Some(since) = if_modified_since {
+                req = req.header(header::IF_MODIFIED_SINCE, since);
+            }
+            ReadPost::run_on_server(req.body(Body::empty()).unwrap())
+        };
+        let last_modified = "Tue, 14 Nov 2023 22:13:20 GMT";
+
+        let res = read(None).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[header::LAST_MODIFIED], last_modified);
+        assert_eq!(body(res).await, "\"post\"");
+
+        // newer than the last change, and the same second as it
+        for since in ["Wed, 15 Nov 2023 08:00:00 GMT", last_modified] {
+            let res = read(Some(since)).await;
+            assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+            assert_eq!(res.headers()[header::LAST_MODIFIED], last_modified);
+            assert_eq!(body(res).await, "");
+        }
+
+        let res = read(Some("Mon, 13 Nov 2023 08:00:00 GMT")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body(res).await, "\"post\"");
+
+        // an `If-None-Match` header takes precedence
+        let req = Request::get(ReadPost::PATH)
+            .header(header::IF_MODIFIED_SINCE, last_modified)
+            .header(header::IF_NONE_MATCH, "\"stale\"")
+            .body(Body::empty())
+            .unwrap();
+        let res = ReadPost::run_on_server(req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
+
+#[cfg(all(test, feature = "actix"))]
+mod actix_tests {
+    use super::ResponseOptions;
+    use crate::{
+        error::NoCustomError,
+        response::{actix::ActixResponse, Res},
+    };
+    use bytes::Bytes;
+    use http::StatusCode;
+
+    fn created() -> ResponseOptions {
+        let options = ResponseOptions::default();
+        options.set_status(StatusCode::CREATED);
+        options
+    }
+
+    #[test]
+    fn sets_status_of_buffered_response() {
+        let mut res = <ActixResponse as Res<NoCustomError>>::try_from_string(
+            "application/json",
+            "1".into(),
+        )
+        .unwrap();
+        created().apply::<NoCustomError, _>(&mut res);
+        assert_eq!(res.take().status().as_u16(), 201);
+    }
+
+    #[test]
+    fn sets_status_of_streaming_response() {
+        let mut res = <ActixResponse as Res<NoCustomError>>::try_from_stream(
+            "application/octet-stream",
+            futures::stream::once(async { Ok(Bytes::from("a")) }),
+        )
+        .unwrap();
+        created().apply::<NoCustomError, _>(&mut res);
+        assert_eq!(res.take().status().as_u16(), 201);
+    }
+}
@@ -1,20 +1,34 @@
 use super::ClientRes;
 use crate::error::ServerFnError;
+#[cfg(feature = "debug-wire")]
+use crate::inspect::Wire;
 use bytes::Bytes;
 use futures::{Stream, TryStreamExt};
 use reqwest::Response;
 
 impl<CustErr> ClientRes<CustErr> for Response {
     async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
-        self.text()
+        #[cfg(feature = "debug-wire")]
+        let wire = wire(&self);
+        let text = self
+            .text()
             .await
-            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+        #[cfg(feature = "debug-wire")]
+        wire.log(text.as_bytes());
+        Ok(text)
     }
 
     async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
-        self.bytes()
+        #[cfg(feature = "debug-wire")]
+        let wire = wire(&self);
+        let bytes = self
+            .bytes()
             .await
-            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+        #[cfg(feature = "debug-wire")]
+        wire.log(&bytes);
+        Ok(bytes)
     }
 
     fn try_into_stream(
@@ -23,9 +37,12 @@ impl<CustErr> ClientRes<CustErr> for Response {
         impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
         ServerFnError<CustErr>,
     > {
-        Ok(self
-            .bytes_stream()
-            .map_err(|e| ServerFnError::Response(e.to_string())))
+        #[cfg(feature = "debug-wire")]
+        let wire = wire(&self);
+        let stream = self.bytes_stream();
+        #[cfg(feature = "debug-wire")]
+        let stream = wire.tee(stream);
+        Ok(stream.map_err(|e| ServerFnError::Response(e.to_string())))
     }
 
     fn status(&self) -> u16 {
@@ -46,4 +63,19 @@ impl<CustErr> ClientRes<CustErr> for Response {
     fn has_redirect(&self) -> bool {
         self.headers().get("Location").is_some()
     }
+
+    fn content_type(&self) -> Option<String> {
+        self.headers()
+            .get("Content-Type")
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string())
+    }
+}
+
+#[cfg(feature = "debug-wire")]
+fn wire(res: &Response) -> Wire {
+    let content_type = res
+        .headers()
+        .get("Content-Type")
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string());
+    Wire::client_response(res.url().path(), content_type)
 }
@@ -0,0 +1,322 @@
+//! A [`Layer`](crate::middleware::Layer) is tested by wrapping a [`MockService`],
+//! sending it a request built with [`request`] or [`json_request`], and checking
+//! the [`TestResponse`] returned by [`call`].
+//!
+//! ```rust,ignore
+//! #[tokio::test(start_paused = true)]
+//! async fn slow_handler_times_out() {
+//!     let mut service = Timeout::new(Duration::from_secs(1))
+//!         .layer(MockService::sleep(Duration::from_secs(10), "done"));
+//!     call(&mut service, request(Method::POST, "/api/slow"))
+//!         .await
+//!         .assert_status(StatusCode::GATEWAY_TIMEOUT)
+//!         .assert_body("ServerError|timeout");
+//! }
+//! ```
+
+use crate::middleware::{BoxedService, Service};
+use axum::body::Body;
+use bytes::Bytes;
+use http::{header, HeaderMap, Method, Request, Response, StatusCode};
+use http_body_util::BodyExt;
+use std::{future::Future, pin::Pin, time::Duration};
+
+/// An inner service for middleware tests, which answers every request with a
+/// function.
+pub struct MockService<F>(F);
+
+impl MockService<()> {
+    /// Creates a service that turns each request into a response with `f`.
+    ///
+    /// ```rust,ignore
+    /// let inner = MockService::new(|req: Request<Body>| async move {
+    ///     Response::new(Body::from(req.uri().path().to_string()))
+    /// });
+    /// ```
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<F, Fut>(f: F) -> BoxedService<Request<Body>, Response<Body>>
+    where
+        F: FnMut(Request<Body>) -> Fut + Send + 'static,
+        Fut: Future<Output = Response<Body>> + Send + 'static,
+    {
+        BoxedService::new(MockService(f))
+    }
+
+    /// Creates a service that answers every request with `200 OK` and `body`.
+    pub fn ok(
+        body: impl Into<Bytes>,
+    ) -> BoxedService<Request<Body>, Response<Body>> {
+        let body = body.into();
+        Self::new(move |_req| {
+            let body = body.clone();
+            async move { Response::new(Body::from(body)) }
+        })
+    }
+
+    /// Creates a service that waits for `duration` before answering with `200 OK`
+    /// and `body`.
+    ///
+    /// This uses the Tokio timer, so it can be combined with a paused clock.
+    pub fn sleep(
+        duration: Duration,
+        body: impl Into<Bytes>,
+    ) -> BoxedService<Request<Body>, Response<Body>> {
+        let body = body.into();
+        Self::new(move |_req| {
+            let body = body.clone();
+            async move {
+                tokio::time::sleep(duration).await;
+                Response::new(Body::from(body))
+            }
+        })
+    }
+}
+
+impl<F, Fut> Service<Request<Body>, Response<Body>> for MockService<F>
+where
+    F: FnMut(Request<Body>) -> Fut,
+    Fut: Future<Output = Response<Body>> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        req: Request<Body>,
+    ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+        Box::pin((self.0)(req))
+    }
+}
+
+/// Builds a request with an empty body.
+pub fn request(method: Method, path: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(path)
+        .body(Body::empty())
+        .expect("invalid test request")
+}
+
+/// Builds a `POST` request with a JSON body, as sent by the
+/// [`Json`](crate::codec::Json) encoding.
+pub fn json_request(path: &str, body: impl Into<Bytes>) -> Request<Body> {
+    Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.into()))
+        .expect("invalid test request")
+}
+
+/// Runs `req` through `service`, and reads the whole response.
+pub async fn call(
+    service: &mut BoxedService<Request<Body>, Response<Body>>,
+    req: Request<Body>,
+) -> TestResponse {
+    TestResponse::read(service.0.run(req).await).await
+}
+
+/// A response whose body has been read, for making assertions on.
+#[derive(Debug, Clone)]
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    /// Reads the whole body of `res`.
+    ///
+    /// # Panics
+    /// Panics if the body cannot be read.
+    pub async fn read(res: Response<Body>) -> Self {
+        let (parts, body) = res.into_parts();
+        let body = body
+            .collect()
+            .await
+            .expect("could not read response body")
+            .to_bytes();
+        Self {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        }
+    }
+
+    /// The status of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The value of the header called `name`, if it is present and valid UTF-8.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    /// The headers of the response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The body of the response.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The body of the response as text.
+    ///
+    /// # Panics
+    /// Panics if the body is not valid UTF-8.
+    pub fn text(&self) -> &str {
+        std::str::from_utf8(&self.body).expect("response body is not UTF-8")
+    }
+
+    /// Asserts that the response has the given status.
+    #[track_caller]
+    pub fn assert_status(&self, status: StatusCode) -> &Self {
+        assert_eq!(self.status, status, "unexpected status: {}", self.text());
+        self
+    }
+
+    /// Asserts that the header called `name` has the given value.
+    #[track_caller]
+    pub fn assert_header(&self, name: &str, value: &str) -> &Self {
+        assert_eq!(self.header(name), Some(value), "header `{name}`");
+        self
+    }
+
+    /// Asserts that the response has no header called `name`.
+    #[track_caller]
+    pub fn assert_no_header(&self, name: &str) -> &Self {
+        assert_eq!(self.header(name), None, "header `{name}`");
+        self
+    }
+
+    /// Asserts that the body of the response is `body`.
+    #[track_caller]
+    pub fn assert_body(&self, body: &str) -> &Self {
+        assert_eq!(self.text(), body);
+        self
+    }
+}
+
+/// Declares a server function the way the `#[server]` macro would, for tests inside
+/// this crate, which cannot expand the macro itself.
+///
+/// The server function is only ever run on the server, with an Axum request and
+/// response. Any other [`ServerFn`](crate::ServerFn) items, like `middlewares`, can
+/// follow `run_body`.
+///
+/// ```rust,ignore
+/// server_fn_fixture! {
+///     /// `#[server(input = GetUrl)] async fn greet(name: String)`
+///     struct Greet { name: String }
+///     path = "/api/greet", input = GetUrl, output = Json;
+///
+///     async fn run_body(self) -> Result<String, ServerFnError> {
+///         Ok(format!("Hello, {}!", self.name))
+///     }
+/// }
+/// ```
+#[cfg(test)]
+macro_rules! server_fn_fixture {
+    (
+        $(#[$meta:meta])*
+        struct $name:ident { $($fields:tt)* }
+        path = $path:literal, input = $input:ty, output = $output:ty;
+
+        async fn run_body($self:ident) -> Result<$ret:ty, $err:ty>
+            $body:block
+
+        $($items:tt)*
+    ) => {
+        $(#[$meta])*
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct $name { $($fields)* }
+
+        impl $crate::ServerFn for $name {
+            const PATH: &'static str = $path;
+
+            type Client = $crate::codec::test_client::ServerOnly;
+            type ServerRequest = http::Request<axum::body::Body>;
+            type ServerResponse = http::Response<axum::body::Body>;
+            type Output = $ret;
+            type InputEncoding = $input;
+            type OutputEncoding = $output;
+            type Error = $crate::error::NoCustomError;
+
+            async fn run_body($self) -> Result<$ret, $err>
+                $body
+
+            $($items)*
+        }
+    };
+}
+
+#[cfg(test)]
+pub(crate) use server_fn_fixture;
+
+#[cfg(test)]
+mod tests {
+    use super::{call, json_request, request, MockService};
+    use crate::{
+        codec::Json,
+        middleware::{Layer, Timeout},
+        ServerFn, ServerFnError,
+    };
+    use axum::body::Body;
+    use http::{Method, Request, Response, StatusCode};
+    use std::time::Duration;
+
+    super::server_fn_fixture! {
+        /// `#[server] async fn greet(name: String)`
+        struct Greet { name: String }
+        path = "/api/greet", input = Json, output = Json;
+
+        async fn run_body(self) -> Result<String, ServerFnError> {
+            Ok(format!("Hello, {}!", self.name))
+        }
+    }
+
+    #[tokio::test]
+    async fn mock_service_answers_with_function() {
+        let mut service = MockService::new(|req: Request<Body>| async move {
+            Response::new(Body::from(format!(
+                "{} {}",
+                req.method(),
+                req.uri().path()
+            )))
+        });
+        call(&mut service, json_request("/api/echo", "{}"))
+            .await
+            .assert_status(StatusCode::OK)
+            .assert_body("POST /api/echo");
+    }
+
+    #[tokio::test]
+    async fn fixture_runs_on_server() {
+        let mut service = MockService::new(Greet::run_on_server);
+        call(
+            &mut service,
+            json_request(Greet::PATH, r#"{"name":"Ferris"}"#),
+        )
+        .await
+        .assert_status(StatusCode::OK)
+        .assert_body(r#""Hello, Ferris!""#);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn timeout_lets_fast_mock_through() {
+        let mut service = Timeout::new(Duration::from_secs(1))
+            .layer(MockService::sleep(Duration::from_millis(10), "done"));
+        call(&mut service, request(Method::POST, "/api/slow"))
+            .await
+            .assert_status(StatusCode::OK)
+            .assert_body("done");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn timeout_cancels_slow_mock() {
+        let mut service = Timeout::new(Duration::from_secs(1))
+            .layer(MockService::sleep(Duration::from_secs(10), "done"));
+        call(&mut service, request(Method::POST, "/api/slow"))
+            .await
+            .assert_status(StatusCode::GATEWAY_TIMEOUT)
+            .assert_body("ServerError|timeout");
+    }
+}
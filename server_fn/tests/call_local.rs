@@ -0,0 +1,91 @@
+#![cfg(all(feature = "reqwest", feature = "axum-no-default"))]
+
+use axum::body::Body;
+use http::{header, Request, StatusCode};
+use http_body_util::BodyExt;
+use server_fn::{
+    axum::{call_local, handle_server_fn},
+    client::reqwest::ReqwestClient,
+    codec::Json,
+    middleware::Auth,
+    response::ResponseOptions,
+    ServerFn, ServerFnError,
+};
+use server_fn_macro_default::server;
+// the path the `#[server]` macro expects to find this crate at
+use server_fn as server_fns;
+
+async fn validate(_token: String) -> Result<(), &'static str> {
+    Err("only other servers may call this")
+}
+
+#[server(
+    endpoint = "price",
+    input = Json,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn price(item: String, quantity: u32) -> Result<u32, ServerFnError> {
+    Ok(item.len() as u32 * quantity)
+}
+
+#[server(
+    endpoint = "checkout",
+    input = Json,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn checkout(item: String) -> Result<String, ServerFnError> {
+    let total = call_local(Price { item, quantity: 3 }).await?;
+    Ok(format!("total: {total}"))
+}
+
+#[server(
+    endpoint = "audit_log",
+    input = Json,
+    output = Json,
+    client = ReqwestClient,
+    middleware = [Auth::bearer(validate)]
+)]
+pub async fn audit_log() -> Result<Vec<String>, ServerFnError> {
+    Ok(vec!["checkout".to_string()])
+}
+
+#[tokio::test]
+async fn server_fn_calls_another_in_process() {
+    // the reqwest client has no server to send to, so this only works in-process
+    let req = Request::post(Checkout::PATH)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCEPT, "application/json")
+        .body(Body::from(r#"{"item":"tea"}"#))
+        .unwrap();
+    let res = handle_server_fn(req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, r#""total: 9""#);
+}
+
+#[tokio::test]
+async fn in_process_calls_run_middleware() {
+    assert_eq!(
+        call_local(Price {
+            item: "coffee".to_string(),
+            quantity: 2
+        })
+        .await,
+        Ok(12)
+    );
+    let options = ResponseOptions::default();
+    let err = options
+        .clone()
+        .scope(call_local(AuditLog {}))
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(StatusCode::UNAUTHORIZED.as_u16()));
+    assert_eq!(
+        err.without_status(),
+        ServerFnError::ServerError("missing bearer token".to_string())
+    );
+    // the response of the calling server function is left as it is
+    assert_eq!(options.error_status(), None);
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "reqwest")]
+
+use server_fn::{
+    client::{
+        abortable, is_aborted, reqwest::ReqwestClient, set_server_url, Client,
+    },
+    error::{NoCustomError, ServerFnError},
+    request::{reqwest::Request, ClientReq},
+    response::ClientRes,
+};
+use std::time::Duration;
+use tokio::{
+    io::AsyncReadExt,
+    net::TcpListener,
+    sync::mpsc::{self, UnboundedReceiver},
+    time::timeout,
+};
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Received,
+    Closed,
+}
+
+/// Reads each request and never answers it, reporting when the request arrives and
+/// when the client closes the connection.
+async fn start_server() -> (String, UnboundedReceiver<Event>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let mut buf = [0; 1024];
+                _ = conn.read(&mut buf).await;
+                _ = tx.send(Event::Received);
+                while let Ok(len) = conn.read(&mut buf).await {
+                    if len == 0 {
+                        break;
+                    }
+                }
+                _ = tx.send(Event::Closed);
+            });
+        }
+    });
+    (format!("http://{addr}"), rx)
+}
+
+async fn call() -> Result<String, ServerFnError> {
+    let req = <Request as ClientReq<NoCustomError>>::try_new_post(
+        "/hang",
+        "application/json",
+        "application/json",
+        "{}".to_string(),
+    )?;
+    let res = <ReqwestClient as Client<NoCustomError>>::send(req).await?;
+    res.try_into_string().await
+}
+
+async fn next_event(events: &mut UnboundedReceiver<Event>) -> Event {
+    timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("no event from the server")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn cancelling_a_call_closes_the_connection() {
+    let (url, mut events) = start_server().await;
+    set_server_url(Box::leak(url.into_boxed_str()));
+
+    // aborted through the handle
+    let (call_fut, handle) = abortable(call());
+    let call_task = tokio::spawn(call_fut);
+    assert_eq!(next_event(&mut events).await, Event::Received);
+    handle.abort();
+    let err = call_task.await.unwrap().unwrap_err();
+    assert_eq!(err, ServerFnError::Request("aborted".to_string()));
+    assert!(is_aborted(&err));
+    assert_eq!(next_event(&mut events).await, Event::Closed);
+
+    // dropped without aborting
+    let call_task = tokio::spawn(call());
+    assert_eq!(next_event(&mut events).await, Event::Received);
+    call_task.abort();
+    assert_eq!(next_event(&mut events).await, Event::Closed);
+}
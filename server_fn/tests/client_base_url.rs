@@ -0,0 +1,58 @@
+#![cfg(feature = "reqwest")]
+
+use server_fn::{
+    client::{server_fn_url, set_client_config, set_server_url, ClientConfig},
+    error::NoCustomError,
+    request::{reqwest::Request, ClientReq},
+};
+
+fn post(path: &str) -> Request {
+    <Request as ClientReq<NoCustomError>>::try_new_post(
+        path,
+        "application/json",
+        "application/json",
+        "{}".to_string(),
+    )
+    .unwrap()
+}
+
+fn get(path: &str, query: &str) -> Request {
+    <Request as ClientReq<NoCustomError>>::try_new_get(
+        path,
+        "application/json",
+        "application/x-www-form-urlencoded",
+        query,
+    )
+    .unwrap()
+}
+
+// a single test, because the configuration is global to the process
+#[test]
+fn base_url_replaces_server_url() {
+    set_server_url("http://localhost:3000");
+    assert_eq!(
+        post("/api/my_fn").url().as_str(),
+        "http://localhost:3000/api/my_fn"
+    );
+
+    set_client_config(
+        ClientConfig::new().with_base_url("https://api.example.com"),
+    );
+    assert_eq!(
+        post("/api/my_fn").url().as_str(),
+        "https://api.example.com/api/my_fn"
+    );
+    assert_eq!(
+        get("/api/my_fn", "id=1").url().as_str(),
+        "https://api.example.com/api/my_fn?id=1"
+    );
+
+    // a trailing slash on the base URL doesn't double up
+    set_client_config(
+        ClientConfig::new().with_base_url("https://api.example.com/"),
+    );
+    assert_eq!(
+        server_fn_url("/api/my_fn"),
+        "https://api.example.com/api/my_fn"
+    );
+}
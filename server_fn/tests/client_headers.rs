@@ -0,0 +1,86 @@
+#![cfg(feature = "reqwest")]
+
+use http::{HeaderName, HeaderValue};
+use server_fn::{
+    client::{
+        reqwest::ReqwestClient, set_client_config, set_headers_hook,
+        set_server_url, Client, ClientConfig,
+    },
+    error::NoCustomError,
+    request::{reqwest::Request, ClientReq},
+    response::ClientRes,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+/// Responds with the `authorization` and `x-request-id` headers it received.
+async fn start_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut buf = [0; 1024];
+                let len = conn.read(&mut buf).await.unwrap();
+                let req = String::from_utf8_lossy(&buf[..len]);
+                let body = req
+                    .lines()
+                    .filter(|line| {
+                        line.starts_with("authorization:")
+                            || line.starts_with("x-request-id:")
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let res = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: \
+                     close\r\n\r\n{body}",
+                    body.len()
+                );
+                _ = conn.write_all(res.as_bytes()).await;
+            });
+        }
+    });
+    format!("http://{addr}")
+}
+
+async fn call() -> String {
+    let req = <Request as ClientReq<NoCustomError>>::try_new_post(
+        "/headers",
+        "text/plain",
+        "application/json",
+        "{}".to_string(),
+    )
+    .unwrap();
+    let res = <ReqwestClient as Client<NoCustomError>>::send(req)
+        .await
+        .unwrap();
+    ClientRes::<NoCustomError>::try_into_string(res)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn static_and_per_call_headers_reach_server() {
+    let url = start_server().await;
+    set_server_url(Box::leak(url.into_boxed_str()));
+    set_client_config(ClientConfig::new().with_header(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_static("Bearer token"),
+    ));
+    static REQUEST_ID: AtomicUsize = AtomicUsize::new(0);
+    set_headers_hook(|headers| {
+        let id = REQUEST_ID.fetch_add(1, Ordering::SeqCst);
+        headers.insert(
+            HeaderName::from_static("x-request-id"),
+            HeaderValue::from(id),
+        );
+    })
+    .unwrap_or_else(|_| panic!("headers hook already set"));
+
+    assert_eq!(call().await, "authorization: Bearer token\nx-request-id: 0");
+    assert_eq!(call().await, "authorization: Bearer token\nx-request-id: 1");
+}
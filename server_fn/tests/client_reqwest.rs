@@ -0,0 +1,84 @@
+#![cfg(all(feature = "reqwest", feature = "axum-no-default"))]
+
+use axum::body::Body;
+use http::{Request, Response};
+use serde::{Deserialize, Serialize};
+use server_fn::{
+    client::{
+        reqwest::{set_client, ReqwestClient},
+        set_server_url,
+    },
+    codec::Json,
+    error::NoCustomError,
+    ServerFn, ServerFnError,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+#[derive(Serialize, Deserialize)]
+struct Visit;
+
+impl ServerFn for Visit {
+    const PATH: &'static str = "/visit";
+
+    type Client = ReqwestClient;
+    type ServerRequest = Request<Body>;
+    type ServerResponse = Response<Body>;
+    type Output = String;
+    type InputEncoding = Json;
+    type OutputEncoding = Json;
+    type Error = NoCustomError;
+
+    async fn run_body(self) -> Result<String, ServerFnError> {
+        unreachable!("only called on the client")
+    }
+}
+
+/// Starts a session on the first visit, and recognizes it on later ones.
+async fn start_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut buf = [0; 4096];
+                let len = conn.read(&mut buf).await.unwrap();
+                let req = String::from_utf8_lossy(&buf[..len]).to_lowercase();
+                let res: &[u8] = if req.contains("cookie: session=abc123") {
+                    b"HTTP/1.1 200 OK\r\n\
+                      content-type: application/json\r\n\
+                      content-length: 11\r\n\
+                      \r\n\
+                      \"returning\""
+                } else {
+                    b"HTTP/1.1 200 OK\r\n\
+                      content-type: application/json\r\n\
+                      set-cookie: session=abc123; Path=/\r\n\
+                      content-length: 5\r\n\
+                      \r\n\
+                      \"new\""
+                };
+                _ = conn.write_all(res).await;
+            });
+        }
+    });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn custom_client_keeps_cookies_between_calls() {
+    let url = start_server().await;
+    set_server_url(Box::leak(url.into_boxed_str()));
+    set_client(
+        reqwest::Client::builder()
+            .cookie_store(true)
+            .build()
+            .unwrap(),
+    );
+
+    assert_eq!(Visit.run_on_client().await.unwrap(), "new");
+    assert_eq!(Visit.run_on_client().await.unwrap(), "returning");
+}
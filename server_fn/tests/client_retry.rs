@@ -0,0 +1,106 @@
+#![cfg(feature = "reqwest")]
+
+use server_fn::{
+    client::{
+        reqwest::ReqwestClient, set_client_config, set_server_url, Client,
+        ClientConfig, RetryPolicy,
+    },
+    error::{NoCustomError, ServerFnError},
+    request::{reqwest::Request, ClientReq},
+    response::ClientRes,
+};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+static GET_CALLS: AtomicUsize = AtomicUsize::new(0);
+static POST_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Drops the first two connections for each method without responding, then
+/// answers normally.
+async fn start_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut buf = [0; 1024];
+                let len = conn.read(&mut buf).await.unwrap();
+                let calls = if buf[..len].starts_with(b"GET ") {
+                    &GET_CALLS
+                } else {
+                    &POST_CALLS
+                };
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    return;
+                }
+                _ = conn
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\n\
+                          content-type: application/json\r\n\
+                          content-length: 2\r\n\
+                          connection: close\r\n\
+                          \r\n\
+                          {}",
+                    )
+                    .await;
+            });
+        }
+    });
+    format!("http://{addr}")
+}
+
+async fn send(req: Request) -> Result<String, ServerFnError> {
+    let res = <ReqwestClient as Client<NoCustomError>>::send(req).await?;
+    res.try_into_string().await
+}
+
+#[tokio::test]
+async fn retries_idempotent_requests_only() {
+    let url = start_server().await;
+    set_server_url(Box::leak(url.into_boxed_str()));
+    set_client_config(ClientConfig::new().with_retry(
+        RetryPolicy::new(2).with_base_delay(Duration::from_millis(10)),
+    ));
+
+    // two failures, then success
+    let req = <Request as ClientReq<NoCustomError>>::try_new_get(
+        "/retry",
+        "application/json",
+        "application/x-www-form-urlencoded",
+        "",
+    )
+    .unwrap();
+    assert_eq!(send(req).await.unwrap(), "{}");
+    assert_eq!(GET_CALLS.load(Ordering::SeqCst), 3);
+
+    // a POST is sent only once
+    let req = <Request as ClientReq<NoCustomError>>::try_new_post(
+        "/retry",
+        "application/json",
+        "application/json",
+        "{}".to_string(),
+    )
+    .unwrap();
+    assert!(matches!(send(req).await, Err(ServerFnError::Request(_))));
+    assert_eq!(POST_CALLS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn backoff_grows_up_to_max_delay() {
+    let policy = RetryPolicy::new(10)
+        .with_base_delay(Duration::from_millis(100))
+        .with_max_delay(Duration::from_secs(1));
+
+    for (retry, full) in [(0, 100), (1, 200), (2, 400), (3, 800), (8, 1000)] {
+        let delay = policy.backoff(retry);
+        let full = Duration::from_millis(full);
+        assert!(delay <= full && delay >= full / 2, "{retry}: {delay:?}");
+    }
+}
@@ -0,0 +1,80 @@
+#![cfg(all(feature = "reqwest", feature = "axum-no-default"))]
+
+use axum::body::Body;
+use http::{Request, Response};
+use serde::{Deserialize, Serialize};
+use server_fn::{
+    client::{reqwest::ReqwestClient, set_server_url},
+    codec::Json,
+    error::NoCustomError,
+    response::ResponseOptions,
+    ServerFn, ServerFnError,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+#[derive(Serialize, Deserialize)]
+struct Forbidden;
+
+impl ServerFn for Forbidden {
+    const PATH: &'static str = "/forbidden";
+
+    type Client = ReqwestClient;
+    type ServerRequest = Request<Body>;
+    type ServerResponse = Response<Body>;
+    type Output = String;
+    type InputEncoding = Json;
+    type OutputEncoding = Json;
+    type Error = NoCustomError;
+
+    async fn run_body(self) -> Result<String, ServerFnError> {
+        unreachable!("only called on the client")
+    }
+}
+
+/// Rejects every request with a `403 Forbidden`.
+async fn start_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut buf = [0; 1024];
+                _ = conn.read(&mut buf).await.unwrap();
+                _ = conn
+                    .write_all(
+                        b"HTTP/1.1 403 Forbidden\r\n\
+                          content-length: 21\r\n\
+                          \r\n\
+                          ServerError|forbidden",
+                    )
+                    .await;
+            });
+        }
+    });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn error_response_keeps_status() {
+    let url = start_server().await;
+    set_server_url(Box::leak(url.into_boxed_str()));
+
+    let options = ResponseOptions::default();
+    let err = options
+        .clone()
+        .scope(Forbidden.run_on_client())
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(403));
+    assert_eq!(err.to_string(), "error running server function: forbidden");
+    assert_eq!(
+        err.without_status(),
+        ServerFnError::ServerError("forbidden".to_string())
+    );
+    // the response of the calling server function is left as it is
+    assert_eq!(options.error_status(), None);
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "reqwest")]
+
+use server_fn::{
+    client::{
+        reqwest::ReqwestClient, set_client_config, set_server_url, Client,
+        ClientConfig,
+    },
+    error::{NoCustomError, ServerFnError},
+    request::{reqwest::Request, ClientReq},
+    response::ClientRes,
+};
+use std::time::Duration;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+/// Serves `/slow` after a second and everything else right away.
+async fn start_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut buf = [0; 1024];
+                let len = conn.read(&mut buf).await.unwrap();
+                if buf[..len].starts_with(b"POST /slow ") {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+                _ = conn
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\n\
+                          content-type: application/json\r\n\
+                          content-length: 2\r\n\
+                          \r\n\
+                          {}",
+                    )
+                    .await;
+            });
+        }
+    });
+    format!("http://{addr}")
+}
+
+async fn call(path: &str) -> Result<String, ServerFnError> {
+    let req = <Request as ClientReq<NoCustomError>>::try_new_post(
+        path,
+        "application/json",
+        "application/json",
+        "{}".to_string(),
+    )?;
+    let res = <ReqwestClient as Client<NoCustomError>>::send(req).await?;
+    res.try_into_string().await
+}
+
+#[tokio::test]
+async fn delayed_endpoint_times_out() {
+    let url = start_server().await;
+    set_server_url(Box::leak(url.into_boxed_str()));
+    set_client_config(
+        ClientConfig::new().with_timeout(Duration::from_millis(100)),
+    );
+
+    assert_eq!(call("/fast").await.unwrap(), "{}");
+    assert_eq!(
+        call("/slow").await,
+        Err(ServerFnError::Request("timeout".to_string()))
+    );
+}
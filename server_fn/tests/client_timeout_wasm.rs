@@ -0,0 +1,36 @@
+#![cfg(all(target_arch = "wasm32", feature = "browser"))]
+
+use server_fn::{
+    client::{
+        browser::BrowserClient, set_client_config, set_server_url, Client,
+        ClientConfig,
+    },
+    error::{NoCustomError, ServerFnError},
+    request::{browser::BrowserRequest, ClientReq},
+};
+use std::time::Duration;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+async fn delayed_endpoint_times_out() {
+    // a non-routable address, so the connection never completes
+    set_server_url("http://10.255.255.1");
+    set_client_config(
+        ClientConfig::new().with_timeout(Duration::from_millis(100)),
+    );
+
+    let req = <BrowserRequest as ClientReq<NoCustomError>>::try_new_post(
+        "/slow",
+        "application/json",
+        "application/json",
+        "{}".to_string(),
+    )
+    .unwrap();
+    let res = <BrowserClient as Client<NoCustomError>>::send(req).await;
+    assert!(matches!(
+        res,
+        Err(ServerFnError::Request(msg)) if msg == "timeout"
+    ));
+}
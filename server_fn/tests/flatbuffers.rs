@@ -0,0 +1,115 @@
+#![cfg(all(feature = "flatbuffers", feature = "axum-no-default"))]
+
+use axum::body::Body;
+use flatbuffers::{
+    FlatBufferBuilder, Follow, ForwardsUOffset, InvalidFlatbuffer, Table,
+    VOffsetT, Verifiable, Verifier, WIPOffset,
+};
+use http::{header, Request, Response};
+use http_body_util::BodyExt;
+use server_fn::{
+    codec::{Encoding, Flatbuffer, Flatbuffers, FromReq, IntoRes},
+    error::NoCustomError,
+};
+
+// What `flatc --rust` generates for this schema, trimmed down:
+//
+// table Greeting {
+//   text: string;
+//   times: uint32 = 1;
+// }
+// root_type Greeting;
+struct Greeting<'a> {
+    _tab: Table<'a>,
+}
+
+impl<'a> Follow<'a> for Greeting<'a> {
+    type Inner = Greeting<'a>;
+
+    unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: Table::new(buf, loc),
+        }
+    }
+}
+
+impl<'a> Greeting<'a> {
+    const VT_TEXT: VOffsetT = 4;
+    const VT_TIMES: VOffsetT = 6;
+
+    fn create(
+        builder: &mut FlatBufferBuilder<'a>,
+        text: &str,
+        times: u32,
+    ) -> WIPOffset<Greeting<'a>> {
+        let text = builder.create_string(text);
+        let start = builder.start_table();
+        builder.push_slot::<u32>(Self::VT_TIMES, times, 1);
+        builder.push_slot_always(Self::VT_TEXT, text);
+        WIPOffset::new(builder.end_table(start).value())
+    }
+
+    fn text(&self) -> Option<&'a str> {
+        unsafe { self._tab.get::<ForwardsUOffset<&str>>(Self::VT_TEXT, None) }
+    }
+
+    fn times(&self) -> u32 {
+        unsafe { self._tab.get::<u32>(Self::VT_TIMES, Some(1)).unwrap() }
+    }
+}
+
+impl Verifiable for Greeting<'_> {
+    fn run_verifier(
+        v: &mut Verifier,
+        pos: usize,
+    ) -> Result<(), InvalidFlatbuffer> {
+        v.visit_table(pos)?
+            .visit_field::<ForwardsUOffset<&str>>("text", Self::VT_TEXT, false)?
+            .visit_field::<u32>("times", Self::VT_TIMES, false)?
+            .finish();
+        Ok(())
+    }
+}
+
+fn greeting(text: &str, times: u32) -> Flatbuffer {
+    let mut builder = FlatBufferBuilder::new();
+    let greeting = Greeting::create(&mut builder, text, times);
+    builder.finish(greeting, None);
+    Flatbuffer::from_builder(builder)
+}
+
+#[tokio::test]
+async fn round_trips_a_message() {
+    let req = Request::post("/api/greet")
+        .header(header::CONTENT_TYPE, Flatbuffers::CONTENT_TYPE)
+        .body(Body::from(greeting("hello", 3).into_bytes()))
+        .unwrap();
+    let arg =
+        <Flatbuffer as FromReq<Flatbuffers, _, NoCustomError>>::from_req(req)
+            .await
+            .unwrap();
+    let received = arg.root::<Greeting>().unwrap();
+    assert_eq!(received.text(), Some("hello"));
+    assert_eq!(received.times(), 3);
+
+    let reply = greeting(&received.text().unwrap().to_uppercase(), 1);
+    let res: Response<Body> =
+        <Flatbuffer as IntoRes<Flatbuffers, _, NoCustomError>>::into_res(reply)
+            .await
+            .unwrap();
+    assert_eq!(
+        res.headers()[header::CONTENT_TYPE],
+        "application/x-flatbuffers"
+    );
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    let reply = Flatbuffer::new(body);
+    let reply = reply.root::<Greeting>().unwrap();
+    assert_eq!(reply.text(), Some("HELLO"));
+    assert_eq!(reply.times(), 1);
+}
+
+#[test]
+fn rejects_invalid_buffers() {
+    let buffer = Flatbuffer::new(vec![0xff; 3]);
+    assert!(buffer.root::<Greeting>().is_err());
+}
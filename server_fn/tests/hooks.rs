@@ -0,0 +1,62 @@
+#![cfg(all(feature = "reqwest", feature = "axum-no-default"))]
+
+use axum::body::Body;
+use http::{header, Request, Response, StatusCode};
+use server_fn::{
+    axum::handle_server_fn, client::reqwest::ReqwestClient, codec::Json,
+    ServerFn, ServerFnError,
+};
+use server_fn_macro_default::server;
+use std::sync::{Arc, Mutex};
+// the path the `#[server]` macro expects to find this crate at
+use server_fn as server_fns;
+
+#[server(
+    endpoint = "add_tag",
+    input = Json,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn add_tag(tag: String) -> Result<String, ServerFnError> {
+    Ok(tag)
+}
+
+#[tokio::test]
+async fn hooks_run_in_registration_order() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    for name in ["first", "second"] {
+        let seen = Arc::clone(&seen);
+        server_fn::on_request(move |req: &Request<Body>| {
+            seen.lock()
+                .unwrap()
+                .push(format!("{name} request {}", req.uri().path()));
+        });
+    }
+    server_fn::on_response({
+        let seen = Arc::clone(&seen);
+        move |res: &Response<Body>| {
+            seen.lock()
+                .unwrap()
+                .push(format!("response {}", res.status()));
+        }
+    });
+    // hooks for the request type of another framework never run
+    server_fn::on_request(|_: &String| unreachable!());
+
+    let req = Request::post(AddTag::PATH)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCEPT, "application/json")
+        .body(Body::from(r#"{"tag":"rust"}"#))
+        .unwrap();
+    let res = handle_server_fn(req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            "first request /api/add_tag",
+            "second request /api/add_tag",
+            "response 200 OK",
+        ]
+    );
+}
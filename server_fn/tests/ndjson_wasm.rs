@@ -0,0 +1,49 @@
+#![cfg(all(target_arch = "wasm32", feature = "browser", feature = "json"))]
+
+use futures::{channel::mpsc, StreamExt};
+use js_sys::Uint8Array;
+use serde::Deserialize;
+use server_fn::{
+    codec::{FromRes, JsonStream, NdJson},
+    error::NoCustomError,
+    response::browser::{BrowserResponse, Response},
+};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+use wasm_streams::ReadableStream;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Order {
+    id: u32,
+}
+
+fn chunk(text: &str) -> Result<JsValue, JsValue> {
+    Ok(Uint8Array::from(text.as_bytes()).into())
+}
+
+#[wasm_bindgen_test]
+async fn ndjson_yields_items_before_response_completes() {
+    // the body is only written to as the test goes on
+    let (tx, rx) = mpsc::unbounded();
+    let body = ReadableStream::from_stream(rx).into_raw();
+    let res =
+        web_sys::Response::new_with_opt_readable_stream(Some(&body)).unwrap();
+    let res = BrowserResponse::from(Response::from(res));
+
+    let mut orders =
+        <JsonStream<Order> as FromRes<NdJson, _, NoCustomError>>::from_res(res)
+            .await
+            .unwrap()
+            .into_inner();
+
+    tx.unbounded_send(chunk("{\"id\":1}\n{\"id\"")).unwrap();
+    assert_eq!(orders.next().await.unwrap().unwrap(), Order { id: 1 });
+
+    tx.unbounded_send(chunk(":2}\n")).unwrap();
+    assert_eq!(orders.next().await.unwrap().unwrap(), Order { id: 2 });
+
+    drop(tx);
+    assert!(orders.next().await.is_none());
+}
@@ -0,0 +1,74 @@
+#![cfg(all(feature = "reqwest", feature = "axum-no-default", feature = "url"))]
+
+use axum::body::Body;
+use http::{header, Request, StatusCode};
+use http_body_util::BodyExt;
+use server_fn::{
+    axum::handle_server_fn,
+    client::{reqwest::ReqwestClient, set_server_url},
+    codec::{GetNoArgs, GetUrl, IntoReq, Json},
+    error::NoCustomError,
+    request::reqwest::{Method, Request as ClientRequest},
+    ServerFn, ServerFnError,
+};
+use server_fn_macro_default::server;
+use std::any::TypeId;
+// the path the `#[server]` macro expects to find this crate at
+use server_fn as server_fns;
+
+#[server(
+    endpoint = "count_posts",
+    input = GetUrl,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn count_posts() -> Result<u32, ServerFnError> {
+    Ok(3)
+}
+
+#[server(
+    endpoint = "find_post",
+    input = GetUrl,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn find_post(id: u32) -> Result<u32, ServerFnError> {
+    Ok(id)
+}
+
+#[test]
+fn only_functions_without_arguments_skip_encoding() {
+    assert_eq!(
+        TypeId::of::<<CountPosts as ServerFn>::InputEncoding>(),
+        TypeId::of::<GetNoArgs>()
+    );
+    assert_eq!(
+        TypeId::of::<<FindPost as ServerFn>::InputEncoding>(),
+        TypeId::of::<GetUrl>()
+    );
+}
+
+#[tokio::test]
+async fn sends_a_bare_get_that_the_server_answers() {
+    set_server_url("http://localhost:3000");
+    let req: ClientRequest = IntoReq::<GetNoArgs, _, NoCustomError>::into_req(
+        CountPosts {},
+        CountPosts::PATH,
+        "application/json",
+    )
+    .unwrap();
+    assert_eq!(*req.method(), Method::GET);
+    assert_eq!(req.url().as_str(), "http://localhost:3000/api/count_posts");
+    assert!(!req.headers().contains_key("content-type"));
+    assert!(req.body().is_none());
+
+    // the same request, as the server receives it
+    let req = Request::get(CountPosts::PATH)
+        .header(header::ACCEPT, "application/json")
+        .body(Body::empty())
+        .unwrap();
+    let res = handle_server_fn(req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "3");
+}
@@ -0,0 +1,69 @@
+#![cfg(all(
+    feature = "openapi",
+    feature = "reqwest",
+    feature = "axum-no-default"
+))]
+
+use serde_json::json;
+use server_fn::{
+    client::reqwest::ReqwestClient,
+    codec::{ByteStream, Json, Streaming},
+    openapi::openapi,
+    ServerFnError,
+};
+use server_fn_macro_default::server;
+// the path the `#[server]` macro expects to find this crate at
+use server_fn as server_fns;
+
+#[server(
+    prefix = "/openapi",
+    endpoint = "search_posts",
+    input = Json,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn search_posts(
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<String>, ServerFnError> {
+    Ok(vec![query; limit.unwrap_or(1) as usize])
+}
+
+#[server(
+    prefix = "/openapi",
+    endpoint = "download",
+    input = Json,
+    output = Streaming,
+    client = ReqwestClient
+)]
+pub async fn download(name: String) -> Result<ByteStream, ServerFnError> {
+    Ok(ByteStream::new(futures::stream::once(
+        async move { Ok(name) },
+    )))
+}
+
+#[test]
+fn document_describes_server_functions() {
+    let doc = openapi();
+    assert_eq!(doc["openapi"], "3.1.0");
+
+    let search = &doc["paths"]["/openapi/search_posts"]["post"];
+    let schema =
+        &search["requestBody"]["content"]["application/json"]["schema"];
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["required"], json!(["query"]));
+    assert_eq!(schema["properties"]["query"], json!({ "type": "string" }));
+    assert_eq!(
+        search["responses"]["200"]["content"]["application/json"]["schema"]
+            ["items"],
+        json!({ "type": "string" })
+    );
+    assert!(search.get("x-streaming").is_none());
+
+    let download = &doc["paths"]["/openapi/download"]["post"];
+    assert_eq!(download["x-streaming"], true);
+    assert_eq!(
+        download["responses"]["200"]["content"]["application/octet-stream"],
+        json!({})
+    );
+}
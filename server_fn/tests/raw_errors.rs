@@ -0,0 +1,142 @@
+#![cfg(all(feature = "reqwest", feature = "axum-no-default"))]
+
+use axum::body::Body;
+use http::{header, Request, Response, StatusCode};
+use http_body_util::BodyExt;
+use server_fn::{
+    axum::{get_server_fn_service, handle_server_fn},
+    client::reqwest::ReqwestClient,
+    codec::Json,
+    middleware::RawErrors,
+    response::ResponseOptions,
+    ServerFn, ServerFnError,
+};
+use server_fn_macro_default::server;
+use std::{
+    convert::Infallible,
+    future::{poll_fn, Future},
+    pin::Pin,
+    task::{Context, Poll},
+};
+// the path the `#[server]` macro expects to find this crate at
+use server_fn as server_fns;
+
+#[server(
+    endpoint = "withdraw",
+    input = Json,
+    output = Json,
+    client = ReqwestClient,
+    middleware = [RawErrors::new()]
+)]
+pub async fn withdraw(amount: u64) -> Result<u64, ServerFnError> {
+    if amount > 100 {
+        return Err(ServerFnError::ServerError("insufficient funds".into()));
+    }
+    Ok(100 - amount)
+}
+
+/// A `tower` layer that answers the errors of the inner service itself, with a
+/// `402 Payment Required` that names the error it saw.
+struct ObserveErrors;
+
+impl<S> tower::Layer<S> for ObserveErrors {
+    type Service = Observe<S>;
+
+    fn layer(&self, inner: S) -> Observe<S> {
+        Observe(inner)
+    }
+}
+
+struct Observe<S>(S);
+
+impl<S> tower::Service<Request<Body>> for Observe<S>
+where
+    S: tower::Service<
+        Request<Body>,
+        Response = Response<Body>,
+        Error = ServerFnError,
+    >,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = Pin<
+        Box<dyn Future<Output = Result<Response<Body>, Infallible>> + Send>,
+    >;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Infallible>> {
+        self.0.poll_ready(cx).map(|_| Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // the status of the error is left in the current response options
+        let options = ResponseOptions::default();
+        let inner = options.clone().scope(self.0.call(req));
+        Box::pin(async move {
+            Ok(inner.await.unwrap_or_else(|err| {
+                let status =
+                    options.error_status().map(|status| status.as_u16());
+                Response::builder()
+                    .status(StatusCode::PAYMENT_REQUIRED)
+                    .body(Body::from(format!("{status:?} {err:?}")))
+                    .unwrap()
+            }))
+        })
+    }
+}
+
+fn withdraw_req(body: &str) -> Request<Body> {
+    Request::post(Withdraw::PATH)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCEPT, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+async fn call_observed(req: Request<Body>) -> (StatusCode, String) {
+    let service = get_server_fn_service(Withdraw::PATH).unwrap();
+    let mut service = tower::Layer::layer(&ObserveErrors, service);
+    poll_fn(|cx| tower::Service::poll_ready(&mut service, cx))
+        .await
+        .unwrap();
+    let res = tower::Service::call(&mut service, req).await.unwrap();
+    let status = res.status();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn tower_layer_sees_original_error() {
+    let (status, body) = call_observed(withdraw_req(r#"{"amount":500}"#)).await;
+    assert_eq!(status, StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(body, r#"None ServerError("insufficient funds")"#);
+}
+
+#[tokio::test]
+async fn tower_layer_sees_status_of_error() {
+    let (status, body) =
+        call_observed(withdraw_req(r#"{"amount":"all"}"#)).await;
+    assert_eq!(status, StatusCode::PAYMENT_REQUIRED);
+    assert!(body.starts_with("Some(422) Deserialization"), "{body}");
+    assert!(body.contains("Deserialization"), "{body}");
+}
+
+#[tokio::test]
+async fn successful_calls_are_untouched() {
+    let (status, body) = call_observed(withdraw_req(r#"{"amount":30}"#)).await;
+    assert_eq!((status, body.as_str()), (StatusCode::OK, "70"));
+}
+
+#[tokio::test]
+async fn handler_still_answers_with_error_response() {
+    let res = handle_server_fn(withdraw_req(r#"{"amount":500}"#)).await;
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "ServerError|insufficient funds");
+
+    let res = handle_server_fn(withdraw_req(r#"{"amount":"all"}"#)).await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
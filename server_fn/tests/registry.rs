@@ -0,0 +1,64 @@
+#![cfg(all(
+    feature = "reqwest",
+    feature = "axum-no-default",
+    feature = "cbor"
+))]
+
+use http::Method;
+use server_fn::{
+    client::reqwest::ReqwestClient,
+    codec::{Cbor, Json},
+    registry, ServerFnError, ServerFnMetadata,
+};
+use server_fn_macro_default::server;
+// the path the `#[server]` macro expects to find this crate at
+use server_fn as server_fns;
+
+#[server(
+    prefix = "/registry",
+    endpoint = "get_user",
+    input = Json,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn get_user(id: u32) -> Result<String, ServerFnError> {
+    Ok(id.to_string())
+}
+
+#[server(
+    prefix = "/registry",
+    endpoint = "save_user",
+    input = Cbor,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn save_user(name: String) -> Result<u32, ServerFnError> {
+    Ok(name.len() as u32)
+}
+
+#[test]
+fn registry_lists_server_functions() {
+    let mut registered = registry()
+        .filter(|meta| meta.path.starts_with("/registry/"))
+        .cloned()
+        .collect::<Vec<_>>();
+    registered.sort_by_key(|meta| meta.path);
+
+    assert_eq!(
+        registered,
+        [
+            ServerFnMetadata::new(
+                "/registry/get_user",
+                Method::POST,
+                "application/json",
+                "application/json",
+            ),
+            ServerFnMetadata::new(
+                "/registry/save_user",
+                Method::POST,
+                "application/cbor",
+                "application/json",
+            ),
+        ]
+    );
+}
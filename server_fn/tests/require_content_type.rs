@@ -0,0 +1,89 @@
+#![cfg(all(feature = "reqwest", feature = "axum-no-default", feature = "url"))]
+
+use axum::body::Body;
+use http::{header, Request, StatusCode};
+use http_body_util::BodyExt;
+use server_fn::{
+    axum::handle_server_fn,
+    client::reqwest::ReqwestClient,
+    codec::{GetUrl, Json},
+    middleware::RequireContentType,
+    ServerFn, ServerFnError,
+};
+use server_fn_macro_default::server;
+// the path the `#[server]` macro expects to find this crate at
+use server_fn as server_fns;
+
+#[server(
+    endpoint = "rename_post",
+    input = Json,
+    output = Json,
+    client = ReqwestClient,
+    middleware = [RequireContentType::new()]
+)]
+pub async fn rename_post(title: String) -> Result<String, ServerFnError> {
+    Ok(title)
+}
+
+#[server(
+    endpoint = "count_posts",
+    input = GetUrl,
+    output = Json,
+    client = ReqwestClient,
+    middleware = [RequireContentType::new()]
+)]
+pub async fn count_posts() -> Result<u32, ServerFnError> {
+    Ok(3)
+}
+
+async fn call(req: Request<Body>) -> (StatusCode, String) {
+    let res = handle_server_fn(req).await;
+    let status = res.status();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+fn rename(content_type: &str, body: &str) -> Request<Body> {
+    Request::post(RenamePost::PATH)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::ACCEPT, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn rejects_other_content_types() {
+    let (status, body) =
+        call(rename("application/xml", "<title>Hello</title>")).await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(
+        body,
+        "ServerError|unsupported content type, expected `application/json`"
+    );
+
+    let req = Request::post(RenamePost::PATH)
+        .body(Body::from(r#"{"title":"Hello"}"#))
+        .unwrap();
+    assert_eq!(call(req).await.0, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn accepts_expected_content_type() {
+    let (status, body) =
+        call(rename("application/json", r#"{"title":"Hello"}"#)).await;
+    assert_eq!((status, body.as_str()), (StatusCode::OK, r#""Hello""#));
+
+    // parameters are ignored
+    let (status, _) = call(rename(
+        "application/json; charset=utf-8",
+        r#"{"title":"Hello"}"#,
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn skips_query_string_arguments() {
+    let req = Request::get(CountPosts::PATH).body(Body::empty()).unwrap();
+    assert_eq!(call(req).await, (StatusCode::OK, "3".to_string()));
+}
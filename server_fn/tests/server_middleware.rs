@@ -0,0 +1,78 @@
+#![cfg(all(feature = "reqwest", feature = "axum-no-default"))]
+
+use axum::body::Body;
+use http::{header, Request, StatusCode};
+use http_body_util::BodyExt;
+use server_fn::{
+    axum::handle_server_fn, client::reqwest::ReqwestClient, codec::Json,
+    middleware::Auth, ServerFn, ServerFnError,
+};
+use server_fn_macro_default::server;
+// the path the `#[server]` macro expects to find this crate at
+use server_fn as server_fns;
+
+async fn validate(token: String) -> Result<(), &'static str> {
+    if token == "let-me-in" {
+        Ok(())
+    } else {
+        Err("unknown token")
+    }
+}
+
+#[server(
+    endpoint = "delete_post",
+    input = Json,
+    output = Json,
+    client = ReqwestClient,
+    middleware = [Auth::bearer(validate)]
+)]
+pub async fn delete_post(id: u32) -> Result<u32, ServerFnError> {
+    Ok(id)
+}
+
+#[server(
+    endpoint = "list_posts",
+    input = Json,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn list_posts() -> Result<Vec<u32>, ServerFnError> {
+    Ok(vec![1, 2, 3])
+}
+
+fn request(path: &str, body: &str, token: Option<&str>) -> Request<Body> {
+    let mut req = Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCEPT, "application/json");
+    if let Some(token) = token {
+        req = req.header(header::AUTHORIZATION, format!("Bearer {token}"));
+    }
+    req.body(Body::from(body.to_string())).unwrap()
+}
+
+async fn call(req: Request<Body>) -> (StatusCode, String) {
+    let res = handle_server_fn(req).await;
+    let status = res.status();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn middleware_argument_only_wraps_its_own_function() {
+    assert_eq!(<DeletePost as ServerFn>::PATH, "/api/delete_post");
+
+    let (status, body) =
+        call(request(DeletePost::PATH, r#"{"id":7}"#, None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body, "ServerError|missing bearer token");
+
+    let (status, body) =
+        call(request(DeletePost::PATH, r#"{"id":7}"#, Some("let-me-in"))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "7");
+
+    // the sibling function has no middleware, so needs no token
+    let (status, body) = call(request(ListPosts::PATH, "{}", None)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "[1,2,3]");
+}
@@ -0,0 +1,71 @@
+#![cfg(all(feature = "reqwest", feature = "axum-no-default", feature = "url"))]
+
+use axum::body::Body;
+use http::{header, Request, StatusCode};
+use http_body_util::BodyExt;
+use server_fn::{
+    axum::handle_server_fn,
+    client::{
+        reqwest::ReqwestClient, set_client_config, set_server_url, ClientConfig,
+    },
+    codec::{GetUrl, IntoReq, Json},
+    error::NoCustomError,
+    request::reqwest::{Method, Request as ClientRequest},
+    ServerFn, ServerFnError,
+};
+use server_fn_macro_default::server;
+// the path the `#[server]` macro expects to find this crate at
+use server_fn as server_fns;
+
+#[server(
+    endpoint = "search_posts",
+    input = GetUrl,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn search_posts(query: String) -> Result<usize, ServerFnError> {
+    Ok(query.len())
+}
+
+fn into_req(query: String) -> ClientRequest {
+    IntoReq::<GetUrl, _, NoCustomError>::into_req(
+        SearchPosts { query },
+        SearchPosts::PATH,
+        "application/json",
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn long_arguments_are_sent_in_the_body() {
+    set_server_url("http://localhost:3000");
+    set_client_config(ClientConfig::new().with_max_url_length(80));
+
+    let req = into_req("rust".to_string());
+    assert_eq!(*req.method(), Method::GET);
+    assert_eq!(
+        req.url().as_str(),
+        "http://localhost:3000/api/search_posts?query=rust"
+    );
+
+    let req = into_req("a".repeat(100));
+    assert_eq!(*req.method(), Method::POST);
+    assert_eq!(req.url().as_str(), "http://localhost:3000/api/search_posts");
+    assert_eq!(
+        req.headers()["content-type"],
+        "application/x-www-form-urlencoded"
+    );
+    let body = req.body().unwrap().as_bytes().unwrap().to_vec();
+    assert_eq!(body, format!("query={}", "a".repeat(100)).into_bytes());
+
+    // the same request, as the server receives it
+    let req = Request::post(SearchPosts::PATH)
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(header::ACCEPT, "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let res = handle_server_fn(req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "100");
+}
@@ -0,0 +1,85 @@
+#![cfg(all(feature = "reqwest", feature = "axum-no-default"))]
+
+use axum::body::Body;
+use http::{header, Request, StatusCode};
+use http_body_util::BodyExt;
+use server_fn::{
+    axum::handle_server_fn, client::reqwest::ReqwestClient, codec::Json,
+    ServerFn, ServerFnError,
+};
+use server_fn_macro_default::server;
+// the path the `#[server]` macro expects to find this crate at
+use server_fn as server_fns;
+
+#[server(
+    endpoint = "get_user",
+    version = "v1",
+    input = Json,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn get_user_v1(id: u32) -> Result<String, ServerFnError> {
+    Ok(format!("user {id}"))
+}
+
+#[server(
+    endpoint = "get_user",
+    version = "v2",
+    input = Json,
+    output = Json,
+    client = ReqwestClient
+)]
+pub async fn get_user_v2(id: u32) -> Result<(u32, String), ServerFnError> {
+    Ok((id, "user".to_string()))
+}
+
+async fn call(path: &str, version: Option<&str>) -> (StatusCode, String) {
+    let mut req = Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCEPT, "application/json");
+    if let Some(version) = version {
+        req = req.header("accept-version", version);
+    }
+    let res =
+        handle_server_fn(req.body(Body::from(r#"{"id":7}"#)).unwrap()).await;
+    let status = res.status();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[test]
+fn versions_have_their_own_paths() {
+    assert_eq!(GetUserV1::PATH, "/api/v1/get_user");
+    assert_eq!(GetUserV1::VERSION, Some("v1"));
+    assert_eq!(GetUserV2::PATH, "/api/v2/get_user");
+    assert_eq!(GetUserV2::VERSION, Some("v2"));
+}
+
+#[tokio::test]
+async fn versions_are_called_by_path() {
+    assert_eq!(
+        call("/api/v1/get_user", None).await,
+        (StatusCode::OK, r#""user 7""#.to_string())
+    );
+    assert_eq!(
+        call("/api/v2/get_user", None).await,
+        (StatusCode::OK, r#"[7,"user"]"#.to_string())
+    );
+}
+
+#[tokio::test]
+async fn versions_are_selected_by_header() {
+    assert_eq!(
+        call("/api/get_user", Some("v1")).await,
+        (StatusCode::OK, r#""user 7""#.to_string())
+    );
+    assert_eq!(
+        call("/api/get_user", Some("v2")).await,
+        (StatusCode::OK, r#"[7,"user"]"#.to_string())
+    );
+    assert_eq!(call("/api/get_user", None).await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        call("/api/get_user", Some("v3")).await.0,
+        StatusCode::BAD_REQUEST
+    );
+}
@@ -95,13 +95,19 @@ pub fn server_macro_impl(
                 })?;
             }
             typed_arg.attrs = other_attrs;
-            if default {
-                Ok(quote! { #[serde(default)] pub #typed_arg })
+            // extensions are extracted on the server, not sent by the client
+            if is_extension(&typed_arg.ty) {
+                Ok(None)
+            } else if default {
+                Ok(Some(quote! { #[serde(default)] pub #typed_arg }))
             } else {
-                Ok(quote! { pub #typed_arg })
+                Ok(Some(quote! { pub #typed_arg }))
             }
         })
-        .collect::<Result<Vec<_>>>()?;
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
 
     let dummy = body.to_dummy_output();
     let dummy_name = body.to_dummy_ident();
@@ -114,13 +120,21 @@ pub fn server_macro_impl(
         input,
         output,
         fn_path,
+        version,
         builtin_encoding,
         req_ty,
         res_ty,
         client,
         custom_wrapper,
         impl_from,
+        middlewares: arg_middlewares,
     } = args;
+    // middleware listed in the arguments comes before any `#[middleware]` attributes,
+    // in the order it is written
+    let middlewares = arg_middlewares
+        .into_iter()
+        .chain(middlewares)
+        .collect::<Vec<_>>();
     let prefix = prefix.unwrap_or_else(|| Literal::string(default_path));
     let fn_path = fn_path.unwrap_or_else(|| Literal::string(""));
     let input_ident = match &input {
@@ -130,6 +144,13 @@ pub fn server_macro_impl(
         None => Some("PostUrl".to_string()),
         _ => None,
     };
+    let output_ident = match &output {
+        Some(Type::Path(path)) => {
+            path.path.segments.last().map(|seg| seg.ident.to_string())
+        }
+        None => Some("Json".to_string()),
+        _ => None,
+    };
     let input = input
         .map(|n| {
             if builtin_encoding {
@@ -184,7 +205,7 @@ pub fn server_macro_impl(
     let vis = body.vis;
     let attrs = body.attrs;
 
-    let fn_args = body
+    let typed_args = body
         .inputs
         .iter()
         .filter_map(|f| match f {
@@ -193,42 +214,34 @@ pub fn server_macro_impl(
         })
         .collect::<Vec<_>>();
 
-    let field_names = body
-        .inputs
+    // the arguments sent by the client, leaving out extensions
+    let fn_args = typed_args
         .iter()
-        .filter_map(|f| match f {
-            FnArg::Receiver(_) => None,
-            FnArg::Typed(t) => Some(&t.pat),
-        })
+        .copied()
+        .filter(|t| !is_extension(&t.ty))
         .collect::<Vec<_>>();
 
+    let field_names = fn_args.iter().map(|t| &t.pat).collect::<Vec<_>>();
+
     // if there's exactly one field, impl From<T> for the struct
-    let first_field = body.inputs.iter().find_map(|f| match f {
-        FnArg::Receiver(_) => None,
-        FnArg::Typed(t) => Some((&t.pat, &t.ty)),
-    });
     let impl_from = impl_from.map(|v| v.value).unwrap_or(true);
-    let from_impl = (body.inputs.len() == 1
-        && first_field.is_some()
-        && impl_from)
-        .then(|| {
-            let field = first_field.unwrap();
-            let (name, ty) = field;
-            quote! {
-                impl From<#struct_name> for #ty {
-                    fn from(value: #struct_name) -> Self {
-                        let #struct_name { #name } = value;
-                        #name
-                    }
+    let from_impl = (fn_args.len() == 1 && impl_from).then(|| {
+        let (name, ty) = (&fn_args[0].pat, &fn_args[0].ty);
+        quote! {
+            impl From<#struct_name> for #ty {
+                fn from(value: #struct_name) -> Self {
+                    let #struct_name { #name } = value;
+                    #name
                 }
+            }
 
-                impl From<#ty> for #struct_name {
-                    fn from(#name: #ty) -> Self {
-                        #struct_name { #name }
-                    }
+            impl From<#ty> for #struct_name {
+                fn from(#name: #ty) -> Self {
+                    #struct_name { #name }
                 }
             }
-        });
+        }
+    });
 
     // check output type
     let output_arrow = body.output_arrow;
@@ -236,12 +249,16 @@ pub fn server_macro_impl(
 
     let output_ty = output_type(&return_ty)?;
     let error_ty = err_type(&return_ty)?;
-    let error_ty =
-        error_ty.map(ToTokens::to_token_stream).unwrap_or_else(|| {
-            quote! {
-                #server_fn_path::error::NoCustomError
-            }
-        });
+    // whether it returns an application error type, rather than a ServerFnError
+    let app_error = matches!(error_ty, ErrType::App(_));
+    let error_ty = match error_ty {
+        ErrType::ServerFnError(Some(ty)) | ErrType::App(ty) => {
+            ty.to_token_stream()
+        }
+        ErrType::ServerFnError(None) => quote! {
+            #server_fn_path::error::NoCustomError
+        },
+    };
 
     // build server fn path
     let serde_path = server_fn_path.as_ref().map(|path| {
@@ -253,10 +270,37 @@ pub fn server_macro_impl(
         let path = path.join("::");
         format!("{path}::serde")
     });
+    let schemars_path = server_fn_path.as_ref().map(|path| {
+        let path = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>();
+        let path = path.join("::");
+        format!("{path}::schemars")
+    });
     let server_fn_path = server_fn_path
         .map(|path| quote!(#path))
         .unwrap_or_else(|| quote! { server_fn });
 
+    // a `GET` without arguments has nothing to encode, so it is sent bare and its
+    // arguments are created on the server without reading the request
+    let no_args_get = fn_args.is_empty()
+        && custom_wrapper.is_none()
+        && input_ident.as_deref() == Some("GetUrl");
+    let (input, input_used) = if no_args_get {
+        (
+            quote! { #server_fn_path::codec::GetNoArgs },
+            // still names the given encoding, so that its import isn't unused
+            quote! {
+                const _: ::core::marker::PhantomData<#input> =
+                    ::core::marker::PhantomData;
+            },
+        )
+    } else {
+        (input, quote! {})
+    };
+
     let key_env_var = match option_env!("SERVER_FN_OVERRIDE_KEY") {
         Some(_) => "SERVER_FN_OVERRIDE_KEY",
         None => "CARGO_MANIFEST_DIR",
@@ -282,20 +326,62 @@ pub fn server_macro_impl(
         quote! {
             #server_fn_path::inventory::submit! {{
                 use #server_fn_path::{ServerFn, codec::Encoding};
-                #server_fn_path::ServerFnTraitObj::new(
+                #server_fn_path::ServerFnTraitObj::new_versioned(
                     #wrapped_struct_name_turbofish::PATH,
                     <#wrapped_struct_name as ServerFn>::InputEncoding::METHOD,
+                    #wrapped_struct_name_turbofish::VERSION,
                     |req| {
                         Box::pin(#wrapped_struct_name_turbofish::run_on_server(req))
                     },
                     #wrapped_struct_name_turbofish::middlewares
                 )
             }}
+            #server_fn_path::inventory::submit! {{
+                use #server_fn_path::{ServerFn, codec::Encoding};
+                #server_fn_path::ServerFnMetadata::new(
+                    #wrapped_struct_name_turbofish::PATH,
+                    <#wrapped_struct_name as ServerFn>::InputEncoding::METHOD,
+                    <#wrapped_struct_name as ServerFn>::InputEncoding::CONTENT_TYPE,
+                    <#wrapped_struct_name as ServerFn>::OutputEncoding::CONTENT_TYPE,
+                )
+            }}
         }
     } else {
         quote! {}
     };
 
+    // the result type of the trait implementation, which always has a ServerFnError
+    let server_return_ty = if app_error {
+        quote! {
+            ::core::result::Result<#output_ty, #server_fn_path::ServerFnError<#error_ty>>
+        }
+    } else {
+        return_ty.to_token_stream()
+    };
+
+    // the arguments the server-only body is called with, extracting extensions
+    // from the request
+    let call_args = |into_app_error: bool| {
+        let convert = into_app_error.then(|| quote! { .into_app_error() });
+        typed_args
+            .iter()
+            .map(|t| {
+                if is_extension(&t.ty) {
+                    quote! {
+                        match #server_fn_path::request::Extension::extract::<#error_ty>() {
+                            Ok(extension) => extension,
+                            Err(e) => return Err(e #convert),
+                        }
+                    }
+                } else {
+                    t.pat.to_token_stream()
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    let run_body_args = call_args(false);
+    let func_args = call_args(app_error);
+
     // run_body in the trait implementation
     let run_body = if cfg!(feature = "ssr") {
         let destructure = if let Some(wrapper) = custom_wrapper.as_ref() {
@@ -316,9 +402,12 @@ pub fn server_macro_impl(
         // becomes impl Future<Output = SendWrapper<_>>
         //
         // however, SendWrapper<Future<Output = T>> impls Future<Output = T>
+        let map_err = app_error.then(|| {
+            quote! { .map_err(#server_fn_path::ServerFnError::WrappedServerError) }
+        });
         let body = quote! {
             #destructure
-            #dummy_name(#(#field_names),*).await
+            #dummy_name(#(#run_body_args),*).await #map_err
         };
         let body = if cfg!(feature = "actix") {
             quote! {
@@ -335,26 +424,29 @@ pub fn server_macro_impl(
             // we need this for Actix, for the SendWrapper to count as impl Future
             // but non-Actix will have a clippy warning otherwise
             #[allow(clippy::manual_async_fn)]
-            fn run_body(self) -> impl std::future::Future<Output = #return_ty> + Send {
+            fn run_body(self) -> impl std::future::Future<Output = #server_return_ty> + Send {
                 #body
             }
         }
     } else {
         quote! {
             #[allow(unused_variables)]
-            async fn run_body(self) -> #return_ty {
+            async fn run_body(self) -> #server_return_ty {
                 unreachable!()
             }
         }
     };
 
     // the actual function definition
+    let into_app_error = app_error.then(|| {
+        quote! { .map_err(#server_fn_path::ServerFnError::into_app_error) }
+    });
     let func = if cfg!(feature = "ssr") {
         quote! {
             #docs
             #(#attrs)*
             #vis async fn #fn_name(#(#fn_args),*) #output_arrow #return_ty {
-                #dummy_name(#(#field_names),*).await
+                #dummy_name(#(#func_args),*).await
             }
         }
     } else {
@@ -375,7 +467,7 @@ pub fn server_macro_impl(
             #vis async fn #fn_name(#(#fn_args),*) #output_arrow #return_ty {
                 use #server_fn_path::ServerFn;
                 #restructure
-                data.run_on_client().await
+                data.run_on_client().await #into_app_error
             }
         }
     };
@@ -394,8 +486,12 @@ pub fn server_macro_impl(
             },
         ),
         Some("MultipartFormData")
+        | Some("Flatbuffers")
+        | Some("Protobuf")
+        | Some("Raw")
         | Some("Streaming")
-        | Some("StreamingText") => (PathInfo::None, quote! {}),
+        | Some("StreamingText")
+        | Some("WebSocket") => (PathInfo::None, quote! {}),
         Some("SerdeLite") => (
             PathInfo::Serde,
             quote! {
@@ -409,9 +505,88 @@ pub fn server_macro_impl(
             },
         ),
     };
+
+    // with the `openapi` feature, arguments encoded with serde also describe their
+    // JSON schema, as do outputs that aren't streams or binary-only formats
+    let openapi = cfg!(feature = "openapi") && cfg!(feature = "ssr");
+    let args_schema = openapi
+        && matches!(path, PathInfo::Serde)
+        && input_ident.as_deref() != Some("SerdeLite");
+    let output_schema = openapi
+        && !matches!(
+            output_ident.as_deref(),
+            Some("Streaming")
+                | Some("StreamingText")
+                | Some("Sse")
+                | Some("NdJson")
+                | Some("MultipartResponse")
+                | Some("WebSocket")
+                | Some("Flatbuffers")
+                | Some("Protobuf")
+                | Some("Raw")
+                | Some("Rkyv")
+                | Some("SerdeLite")
+        );
+    let is_stream = |ident: &Option<String>| {
+        matches!(
+            ident.as_deref(),
+            Some("Streaming")
+                | Some("StreamingText")
+                | Some("Sse")
+                | Some("NdJson")
+                | Some("MultipartResponse")
+                | Some("WebSocket")
+        )
+    };
+    let streaming = is_stream(&input_ident) || is_stream(&output_ident);
+    let derives = if args_schema {
+        quote! { #derives, #server_fn_path::schemars::JsonSchema }
+    } else {
+        derives
+    };
+    let derives = if no_args_get {
+        quote! { #derives, Default }
+    } else {
+        derives
+    };
+    let schemars_attr = if args_schema {
+        schemars_path.map(|path| quote! { #[schemars(crate = #path)] })
+    } else {
+        None
+    };
+
+    // protobuf arguments are sent as the message that is the only argument
+    let protobuf_impl = if input_ident.as_deref() == Some("Protobuf") {
+        let [arg] = fn_args.as_slice() else {
+            return Err(syn::Error::new(
+                fn_name.span(),
+                "the `Protobuf` encoding requires exactly one argument, which \
+                 is the message that is sent",
+            ));
+        };
+        let (name, ty) = (&arg.pat, &arg.ty);
+        Some(quote! {
+            impl #server_fn_path::codec::ProtobufArgs for #struct_name {
+                type Message = #ty;
+
+                fn into_message(self) -> #ty {
+                    let #struct_name { #name } = self;
+                    #name
+                }
+
+                fn from_message(#name: #ty) -> Self {
+                    #struct_name { #name }
+                }
+            }
+        })
+    } else {
+        None
+    };
+
     let addl_path = match path {
         PathInfo::Serde => quote! {
             #[serde(crate = #serde_path)]
+            #schemars_attr
         },
         PathInfo::Rkyv => {
             let rkyv_path = format!("{server_fn_path}::rkyv");
@@ -422,6 +597,33 @@ pub fn server_macro_impl(
         PathInfo::None => quote! {},
     };
 
+    // registers the schemas used to describe this function in `openapi()`
+    let openapi_inventory = if openapi {
+        let args_schema = if args_schema {
+            quote! { Some(#server_fn_path::openapi::args_schema::<#struct_name>) }
+        } else {
+            quote! { None }
+        };
+        let output_schema = if output_schema {
+            quote! { Some(#server_fn_path::openapi::schema_for::<#output_ty>) }
+        } else {
+            quote! { None }
+        };
+        quote! {
+            #server_fn_path::inventory::submit! {{
+                use #server_fn_path::ServerFn;
+                #server_fn_path::openapi::ServerFnSchema::new(
+                    #wrapped_struct_name_turbofish::PATH,
+                    #args_schema,
+                    #output_schema,
+                    #streaming,
+                )
+            }}
+        }
+    } else {
+        quote! {}
+    };
+
     let client = if let Some(client) = client {
         client.to_token_stream()
     } else if cfg!(feature = "reqwest") {
@@ -500,10 +702,22 @@ pub fn server_macro_impl(
     } else {
         quote! { concat!("/", #fn_path) }
     };
+    // a version is its own segment, between the prefix and the rest of the path
+    let version_segment = Literal::string(
+        &version
+            .as_ref()
+            .map(|version| format!("/{}", version.value()))
+            .unwrap_or_default(),
+    );
+    let version = match &version {
+        Some(version) => quote! { Some(#version) },
+        None => quote! { None },
+    };
     let path = quote! {
         if #fn_path.is_empty() {
             #server_fn_path::const_format::concatcp!(
                 #prefix,
+                #version_segment,
                 "/",
                 #fn_name_as_str,
                 #server_fn_path::xxhash_rust::const_xxh64::xxh64(
@@ -514,6 +728,7 @@ pub fn server_macro_impl(
         } else {
             #server_fn_path::const_format::concatcp!(
                 #prefix,
+                #version_segment,
                 #fn_path
             )
         }
@@ -544,9 +759,13 @@ pub fn server_macro_impl(
 
         #from_impl
 
+        #protobuf_impl
+
         impl #server_fn_path::ServerFn for #wrapped_struct_name {
             const PATH: &'static str = #path;
 
+            const VERSION: Option<&'static str> = #version;
+
             type Client = #client;
             type ServerRequest = #req;
             type ServerResponse = #res;
@@ -555,21 +774,38 @@ pub fn server_macro_impl(
             type OutputEncoding = #output;
             type Error = #error_ty;
 
-            fn middlewares() -> Vec<std::sync::Arc<dyn #server_fn_path::middleware::Layer<#req, #res>>> {
+            fn middlewares() -> Vec<std::sync::Arc<dyn #server_fn_path::middleware::AsyncLayer<#req, #res>>> {
                 #middlewares
             }
 
             #run_body
         }
 
+        #input_used
+
         #inventory
 
+        #openapi_inventory
+
         #func
 
         #dummy
     })
 }
 
+/// Whether an argument is an `Extension<T>`, which is extracted from the request
+/// instead of being sent by the client.
+fn is_extension(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Extension"),
+        _ => false,
+    }
+}
+
 fn type_from_ident(ident: Ident) -> Type {
     let mut segments = Punctuated::new();
     segments.push(PathSegment {
@@ -621,12 +857,20 @@ fn output_type(return_ty: &Type) -> Result<&GenericArgument> {
 
     Err(syn::Error::new(
         return_ty.span(),
-        "server functions should return Result<T, ServerFnError> or Result<T, \
-         ServerFnError<E>>",
+        "server functions should return Result<T, ServerFnError>, Result<T, \
+         ServerFnError<E>>, or Result<T, E> where E: FromServerFnError",
     ))
 }
 
-fn err_type(return_ty: &Type) -> Result<Option<&GenericArgument>> {
+/// The error type of a server function's return type.
+enum ErrType<'a> {
+    /// `Result<T, ServerFnError>`, or `Result<T, ServerFnError<E>>`.
+    ServerFnError(Option<&'a GenericArgument>),
+    /// `Result<T, E>`, where `E` implements `FromServerFnError`.
+    App(&'a GenericArgument),
+}
+
+fn err_type(return_ty: &Type) -> Result<ErrType<'_>> {
     if let syn::Type::Path(pat) = &return_ty {
         if pat.path.segments[0].ident == "Result" {
             if let PathArguments::AngleBracketed(args) =
@@ -634,7 +878,7 @@ fn err_type(return_ty: &Type) -> Result<Option<&GenericArgument>> {
             {
                 // Result<T>
                 if args.args.len() == 1 {
-                    return Ok(None);
+                    return Ok(ErrType::ServerFnError(None));
                 }
                 // Result<T, _>
                 else if let GenericArgument::Type(Type::Path(pat)) =
@@ -645,15 +889,22 @@ fn err_type(return_ty: &Type) -> Result<Option<&GenericArgument>> {
                             let args = &segment.arguments;
                             match args {
                                 // Result<T, ServerFnError>
-                                PathArguments::None => return Ok(None),
+                                PathArguments::None => {
+                                    return Ok(ErrType::ServerFnError(None))
+                                }
                                 // Result<T, ServerFnError<E>>
-                                PathArguments::AngleBracketed(args) => {
-                                    if args.args.len() == 1 {
-                                        return Ok(Some(&args.args[0]));
-                                    }
+                                PathArguments::AngleBracketed(args)
+                                    if args.args.len() == 1 =>
+                                {
+                                    return Ok(ErrType::ServerFnError(Some(
+                                        &args.args[0],
+                                    )));
                                 }
                                 _ => {}
                             }
+                        } else {
+                            // Result<T, E>
+                            return Ok(ErrType::App(&args.args[1]));
                         }
                     }
                 }
@@ -663,8 +914,8 @@ fn err_type(return_ty: &Type) -> Result<Option<&GenericArgument>> {
 
     Err(syn::Error::new(
         return_ty.span(),
-        "server functions should return Result<T, ServerFnError> or Result<T, \
-         ServerFnError<E>>",
+        "server functions should return Result<T, ServerFnError>, Result<T, \
+         ServerFnError<E>>, or Result<T, E> where E: FromServerFnError",
     ))
 }
 
@@ -675,12 +926,14 @@ struct ServerFnArgs {
     input: Option<Type>,
     output: Option<Type>,
     fn_path: Option<Literal>,
+    version: Option<LitStr>,
     req_ty: Option<Type>,
     res_ty: Option<Type>,
     client: Option<Type>,
     custom_wrapper: Option<Path>,
     builtin_encoding: bool,
     impl_from: Option<LitBool>,
+    middlewares: Vec<Middleware>,
 }
 
 impl Parse for ServerFnArgs {
@@ -690,6 +943,7 @@ impl Parse for ServerFnArgs {
         let mut prefix: Option<Literal> = None;
         let mut encoding: Option<Literal> = None;
         let mut fn_path: Option<Literal> = None;
+        let mut version: Option<LitStr> = None;
 
         // new arguments: can only be keyed by name
         let mut input: Option<Type> = None;
@@ -699,6 +953,7 @@ impl Parse for ServerFnArgs {
         let mut client: Option<Type> = None;
         let mut custom_wrapper: Option<Path> = None;
         let mut impl_from: Option<LitBool> = None;
+        let mut middlewares: Option<Vec<Middleware>> = None;
 
         let mut use_key_and_value = false;
         let mut arg_pos = 0;
@@ -746,6 +1001,24 @@ impl Parse for ServerFnArgs {
                             ));
                         }
                         fn_path = Some(stream.parse()?);
+                    } else if key == "version" {
+                        if version.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `version`",
+                            ));
+                        }
+                        let value: LitStr = stream.parse()?;
+                        if value.value().is_empty()
+                            || value.value().contains('/')
+                        {
+                            return Err(syn::Error::new(
+                                value.span(),
+                                "`version` should be a single path segment, \
+                                 like \"v2\"",
+                            ));
+                        }
+                        version = Some(value);
                     } else if key == "input" {
                         if encoding.is_some() {
                             return Err(syn::Error::new(
@@ -814,6 +1087,22 @@ impl Parse for ServerFnArgs {
                             ));
                         }
                         impl_from = Some(stream.parse()?);
+                    } else if key == "middleware" {
+                        if middlewares.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `middleware`",
+                            ));
+                        }
+                        let list;
+                        syn::bracketed!(list in stream);
+                        middlewares = Some(
+                            Punctuated::<Middleware, Token![,]>::parse_terminated(
+                                &list,
+                            )?
+                            .into_iter()
+                            .collect(),
+                        );
                     } else {
                         return Err(lookahead.error());
                     }
@@ -884,11 +1173,23 @@ impl Parse for ServerFnArgs {
                     output = Some(type_from_ident(syn::parse_quote!(Cbor)));
                     builtin_encoding = true;
                 }
+                "\"flatbuffers\"" => {
+                    input =
+                        Some(type_from_ident(syn::parse_quote!(Flatbuffers)));
+                    output =
+                        Some(type_from_ident(syn::parse_quote!(Flatbuffers)));
+                    builtin_encoding = true;
+                }
                 "\"getjson\"" => {
                     input = Some(type_from_ident(syn::parse_quote!(GetUrl)));
                     output = Some(type_from_ident(syn::parse_quote!(Json)));
                     builtin_encoding = true;
                 }
+                "\"protobuf\"" => {
+                    input = Some(type_from_ident(syn::parse_quote!(Protobuf)));
+                    output = Some(type_from_ident(syn::parse_quote!(Protobuf)));
+                    builtin_encoding = true;
+                }
                 _ => {
                     return Err(syn::Error::new(
                         encoding.span(),
@@ -904,12 +1205,14 @@ impl Parse for ServerFnArgs {
             input,
             output,
             fn_path,
+            version,
             builtin_encoding,
             req_ty,
             res_ty,
             client,
             custom_wrapper,
             impl_from,
+            middlewares: middlewares.unwrap_or_default(),
         })
     }
 }